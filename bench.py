@@ -76,6 +76,7 @@ def bench(
     threads_arg=False,
     chunks_as_buffer=False,
     decompressed_sizes_arg=False,
+    rust_only=False,
 ):
     def wrapper(fn):
         if not fn.__name__.startswith(("compress_", "decompress_")):
@@ -91,6 +92,7 @@ def bench(
         fn.threads_arg = threads_arg
         fn.chunks_as_buffer = chunks_as_buffer
         fn.decompressed_sizes_arg = decompressed_sizes_arg
+        fn.rust_only = rust_only
 
         BENCHES.append(fn)
 
@@ -162,6 +164,15 @@ def compress_stream_writer_size(chunks, zparams):
             compressor.write(chunk)
 
 
+@bench("discrete", "stream_writer() w/ stable buffers", rust_only=True)
+def compress_stream_writer_stable_buffers(chunks, zparams):
+    zctx = zstd.ZstdCompressor(compression_params=zparams)
+    for chunk in chunks:
+        b = bio()
+        with zctx.stream_writer(b, stable_buffers=True) as compressor:
+            compressor.write(chunk)
+
+
 @bench("discrete", "read_to_iter()")
 def compress_read_to_iter(chunks, zparams):
     zctx = zstd.ZstdCompressor(compression_params=zparams)
@@ -362,6 +373,26 @@ def decompress_reuse(chunks, opts):
         zctx.decompress(chunk)
 
 
+@bench("discrete", "decompress() w/ max output size")
+def decompress_max_output_size(chunks, opts):
+    zctx = zstd.ZstdDecompressor(**opts)
+    for chunk in chunks:
+        zctx.decompress(chunk, max_output_size=2**31)
+
+
+@bench(
+    "discrete",
+    "decompress() w/ max output size + initial size",
+    decompressed_sizes_arg=True,
+    rust_only=True,
+)
+def decompress_max_output_size_initial_size(chunks, opts, decompressed_sizes):
+    zctx = zstd.ZstdDecompressor(**opts)
+    sizes = struct.iter_unpack("=Q", decompressed_sizes)
+    for chunk, (size,) in zip(chunks, sizes):
+        zctx.decompress(chunk, max_output_size=2**31, initial_size=size)
+
+
 @bench("discrete", "decompress()", simple=True, zlib=True)
 def decompress_zlib_decompress(chunks):
     d = zlib.decompress
@@ -598,6 +629,9 @@ def get_benches(mode, direction, zlib=False):
         if fn.zlib != zlib:
             continue
 
+        if fn.rust_only and zstd.backend != "rust":
+            continue
+
         fns.append(fn)
 
     return fns
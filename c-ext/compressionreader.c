@@ -594,7 +594,7 @@ static PyObject *compressionreader_readinto(ZstdCompressionReader *self,
     zresult = ZSTD_compressStream2(self->compressor->cctx, &output,
                                    &self->input, ZSTD_e_end);
 
-    self->bytesCompressed += self->output.pos - oldPos;
+    self->bytesCompressed += output.pos - oldPos;
 
     if (ZSTD_isError(zresult)) {
         PyErr_Format(zstd_error(zstd_state_from_obj((PyObject *)self)),
@@ -705,7 +705,7 @@ static PyObject *compressionreader_readinto1(ZstdCompressionReader *self,
     zresult = ZSTD_compressStream2(self->compressor->cctx, &output,
                                    &self->input, ZSTD_e_end);
 
-    self->bytesCompressed += self->output.pos - oldPos;
+    self->bytesCompressed += output.pos - oldPos;
 
     if (ZSTD_isError(zresult)) {
         PyErr_Format(zstd_error(zstd_state_from_obj((PyObject *)self)),
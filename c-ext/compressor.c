@@ -781,6 +781,13 @@ ZstdCompressor_chunker(ZstdCompressor *self, PyObject *args, PyObject *kwargs) {
         return NULL;
     }
 
+    /* Chunks are only emitted once the output buffer is full, so an empty
+       buffer would never make progress. */
+    if (chunkSize == 0) {
+        PyErr_SetString(PyExc_ValueError, "chunk_size must be greater than 0");
+        return NULL;
+    }
+
     ZSTD_CCtx_reset(self->cctx, ZSTD_reset_session_only);
 
     zresult = ZSTD_CCtx_setPledgedSrcSize(self->cctx, sourceSize);
@@ -427,6 +427,12 @@ static PyObject *decompressionreader_readinto(ZstdDecompressionReader *self,
     output.size = dest.len;
     output.pos = 0;
 
+    /* An empty buffer can't make progress and must not consume input. */
+    if (!dest.len) {
+        result = PyLong_FromLong(0);
+        goto finally;
+    }
+
 readinput:
 
     decompressResult = decompress_input(self, &output);
@@ -495,6 +501,12 @@ static PyObject *decompressionreader_readinto1(ZstdDecompressionReader *self,
     output.size = dest.len;
     output.pos = 0;
 
+    /* An empty buffer can't make progress and must not consume input. */
+    if (!dest.len) {
+        result = PyLong_FromLong(0);
+        goto finally;
+    }
+
     while (!self->finishedInput && !self->finishedOutput) {
         int decompressResult, readResult;
 
@@ -5,17 +5,95 @@
 // of the BSD license. See the LICENSE file for details.
 
 use {
-    crate::exceptions::ZstdError,
+    crate::{
+        exceptions::{WithZstdError, ZstdError},
+        zstd_safe::ZstdFailure,
+    },
     pyo3::{
-        buffer::PyBuffer,
         exceptions::{PyIndexError, PyTypeError, PyValueError},
         ffi::Py_buffer,
         prelude::*,
-        types::{PyBytes, PyTuple},
+        types::{PyBytes, PyIterator, PyList, PyMemoryView, PySlice, PyTuple, PyType},
         IntoPyObjectExt,
     },
+    std::{
+        os::raw::{c_char, c_int, c_void},
+        pin::Pin,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    },
 };
 
+// The buffer protocol slots only joined the limited API in Python 3.11, so
+// the buffer types can't export views under an older stable ABI.
+#[cfg(all(Py_LIMITED_API, not(Py_3_11)))]
+compile_error!("the buffer types require Python 3.11 or newer with the limited API");
+
+/// C-contiguous view of an object's memory obtained via the buffer protocol.
+///
+/// Unlike `PyBuffer<u8>`, items of any format are accepted and the memory is
+/// treated as raw bytes, so e.g. `array.array("i")` and numpy arrays are used
+/// without a copy. Views whose memory isn't contiguous, such as
+/// `memoryview(data)[::2]`, are rejected.
+pub(crate) struct DataBuffer(Pin<Box<Py_buffer>>);
+
+// As with `PyBuffer`, the buffer's shape is fixed while the view exists and
+// its contents are only accessed with the GIL held or while borrowed.
+unsafe impl Send for DataBuffer {}
+unsafe impl Sync for DataBuffer {}
+
+impl DataBuffer {
+    pub fn get(obj: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let mut view: Box<Py_buffer> = Box::new(unsafe { std::mem::zeroed() });
+
+        if unsafe {
+            pyo3::ffi::PyObject_GetBuffer(obj.as_ptr(), &mut *view, pyo3::ffi::PyBUF_FULL_RO)
+        } == -1
+        {
+            return Err(PyErr::fetch(obj.py()));
+        }
+
+        // Released on drop, including when rejected below.
+        let buffer = Self(Box::into_pin(view));
+
+        if unsafe { pyo3::ffi::PyBuffer_IsContiguous(&*buffer.0, b'C' as c_char) } == 0 {
+            return Err(PyValueError::new_err("buffer must be contiguous"));
+        }
+
+        Ok(buffer)
+    }
+
+    pub fn buf_ptr(&self) -> *mut c_void {
+        self.0.buf
+    }
+
+    pub fn len_bytes(&self) -> usize {
+        self.0.len as usize
+    }
+
+    pub fn readonly(&self) -> bool {
+        self.0.readonly != 0
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.0.buf as *const u8, self.len_bytes()) }
+    }
+}
+
+impl Drop for DataBuffer {
+    fn drop(&mut self) {
+        Python::with_gil(|_| unsafe { pyo3::ffi::PyBuffer_Release(&mut *self.0) });
+    }
+}
+
+impl<'py> FromPyObject<'py> for DataBuffer {
+    fn extract_bound(obj: &Bound<'py, PyAny>) -> PyResult<Self> {
+        Self::get(obj)
+    }
+}
+
 #[repr(C)]
 #[derive(Clone, Debug)]
 pub(crate) struct BufferSegment {
@@ -23,16 +101,78 @@ pub(crate) struct BufferSegment {
     pub length: u64,
 }
 
+/// Validate a sequence index, resolving it to an offset.
+fn check_index(key: isize, len: usize) -> PyResult<usize> {
+    // Negative indices count from the end, as with sequences.
+    let key = if key < 0 { key + len as isize } else { key };
+
+    if key < 0 {
+        return Err(PyIndexError::new_err(format!(
+            "offset must be at least -{}",
+            len
+        )));
+    }
+
+    let key = key as usize;
+
+    if key >= len {
+        return Err(PyIndexError::new_err(format!(
+            "offset must be less than {}",
+            len
+        )));
+    }
+
+    Ok(key)
+}
+
+/// Obtain the dictionary ID from the frame header at the start of a segment.
+///
+/// Returns 0 if the frame doesn't record a dictionary ID.
+fn segment_dict_id(data: &[u8], index: usize) -> PyResult<u32> {
+    let mut header = zstd_sys::ZSTD_FrameHeader {
+        frameContentSize: 0,
+        windowSize: 0,
+        blockSizeMax: 0,
+        frameType: zstd_sys::ZSTD_FrameType_e::ZSTD_frame,
+        headerSize: 0,
+        dictID: 0,
+        checksumFlag: 0,
+        _reserved1: 0,
+        _reserved2: 0,
+    };
+    let zresult = unsafe {
+        zstd_sys::ZSTD_getFrameHeader(&mut header, data.as_ptr() as *const _, data.len())
+    };
+
+    if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
+        Err(ZstdError::new_err(format!(
+            "segment {} is not a valid zstd frame: {}",
+            index,
+            zstd_safe::get_error_name(zresult)
+        ))
+        .with_zstd_error(&ZstdFailure::from_result(zresult)))
+    } else if zresult != 0 {
+        Err(ZstdError::new_err(format!(
+            "segment {} is too small for a frame header; need {} bytes",
+            index, zresult
+        )))
+    } else {
+        Ok(header.dictID)
+    }
+}
+
 #[pyclass(module = "zstandard.backend_rust", name = "BufferSegment")]
 pub struct ZstdBufferSegment {
     /// The object backing storage. For reference counting.
     _parent: PyObject,
     /// PyBuffer into parent object.
-    buffer: PyBuffer<u8>,
+    buffer: DataBuffer,
     /// Offset of segment within data.
     offset: usize,
     /// Length of segment within data.
     len: usize,
+    /// Views exported by the parent `BufferWithSegments` and its segments.
+    exports: Arc<AtomicUsize>,
 }
 
 unsafe impl Sync for ZstdBufferSegment {}
@@ -53,28 +193,41 @@ impl ZstdBufferSegment {
     }
 
     // PyBufferProtocol.
-    fn bf_getbuffer(slf: PyRefMut<Self>, view: PyObject, flags: i32) -> PyResult<()> {
-        let slice = slf.as_slice();
-
-        if unsafe {
-            pyo3::ffi::PyBuffer_FillInfo(
-                view.as_ptr() as *mut Py_buffer,
-                slf.as_ptr(),
-                slice.as_ptr() as *mut _,
-                slice.len() as _,
-                1,
-                flags,
-            )
-        } != 0
+    //
+    // `PyBuffer_FillInfo()` stores a strong reference to this instance in
+    // `view.obj`, which in turn holds the parent's buffer. So the memory
+    // stays valid until the last exported view is released, even if every
+    // other reference to this instance or its parent is dropped. The held
+    // export keeps the source from being resized or freed. Views are counted
+    // with the parent's, so the parent can't be reused as
+    // `multi_decompress_to_buffer()` output while any exist.
+    unsafe fn __getbuffer__(
+        slf: Bound<'_, Self>,
+        view: *mut Py_buffer,
+        flags: c_int,
+    ) -> PyResult<()> {
+        let segment = slf.borrow();
+        let slice = segment.as_slice();
+
+        if pyo3::ffi::PyBuffer_FillInfo(
+            view,
+            slf.as_ptr(),
+            slice.as_ptr() as *mut _,
+            slice.len() as _,
+            1,
+            flags,
+        ) != 0
         {
             Err(PyErr::fetch(slf.py()))
         } else {
+            segment.exports.fetch_add(1, Ordering::SeqCst);
             Ok(())
         }
     }
 
-    #[allow(unused_variables)]
-    fn bf_releasebuffer(slf: PyRefMut<Self>, view: PyObject) {}
+    unsafe fn __releasebuffer__(&self, _view: *mut Py_buffer) {
+        self.exports.fetch_sub(1, Ordering::SeqCst);
+    }
 
     // Our methods.
 
@@ -98,21 +251,30 @@ unsafe impl Sync for ZstdBufferSegments {}
 #[pymethods]
 impl ZstdBufferSegments {
     // PyBufferProtocol.
-    fn bf_getbuffer(slf: PyRefMut<Self>, view: PyObject, flags: i32) -> PyResult<()> {
+    //
+    // The view references this instance, which keeps the parent and its
+    // immutable segments array alive. Nothing writes to the array, so its
+    // views aren't counted.
+    unsafe fn __getbuffer__(
+        slf: Bound<'_, Self>,
+        view: *mut Py_buffer,
+        flags: c_int,
+    ) -> PyResult<()> {
         let py = slf.py();
 
-        let parent = slf.parent.downcast_bound::<ZstdBufferWithSegments>(py)?;
-
-        if unsafe {
-            pyo3::ffi::PyBuffer_FillInfo(
-                view.as_ptr() as *mut Py_buffer,
-                slf.as_ptr(),
-                parent.borrow().segments.as_ptr() as *const _ as *mut _,
-                (parent.borrow().segments.len() * std::mem::size_of::<BufferSegment>()) as isize,
-                1,
-                flags,
-            )
-        } != 0
+        let parent = slf.borrow().parent.clone_ref(py);
+        let parent = parent
+            .downcast_bound::<ZstdBufferWithSegments>(py)?
+            .borrow();
+
+        if pyo3::ffi::PyBuffer_FillInfo(
+            view,
+            slf.as_ptr(),
+            parent.segments.as_ptr() as *const _ as *mut _,
+            (parent.segments.len() * std::mem::size_of::<BufferSegment>()) as isize,
+            1,
+            flags,
+        ) != 0
         {
             Err(PyErr::fetch(py))
         } else {
@@ -120,15 +282,17 @@ impl ZstdBufferSegments {
         }
     }
 
-    #[allow(unused_variables)]
-    fn bf_releasebuffer(slf: PyRefMut<Self>, view: PyObject) {}
+    unsafe fn __releasebuffer__(&self, _view: *mut Py_buffer) {}
 }
 
 #[pyclass(module = "zstandard.backend_rust", name = "BufferWithSegments")]
 pub struct ZstdBufferWithSegments {
     source: PyObject,
-    pub(crate) buffer: PyBuffer<u8>,
+    pub(crate) buffer: DataBuffer,
     pub(crate) segments: Vec<BufferSegment>,
+    /// Views exported by this instance and its segments, shared with the
+    /// instances `with_segments()` makes over the same memory.
+    exports: Arc<AtomicUsize>,
 }
 
 unsafe impl Sync for ZstdBufferWithSegments {}
@@ -140,6 +304,33 @@ impl ZstdBufferWithSegments {
         }
     }
 
+    fn segment(&self, py: Python, i: usize) -> PyResult<ZstdBufferSegment> {
+        let segment = &self.segments[i];
+
+        Ok(ZstdBufferSegment {
+            _parent: self.source.clone_ref(py),
+            buffer: DataBuffer::get(self.source.bind(py))?,
+            offset: segment.offset as _,
+            len: segment.length as _,
+            exports: self.exports.clone(),
+        })
+    }
+
+    /// Construct an instance over the same source with different segments.
+    pub(crate) fn with_segments(&self, py: Python, segments: Vec<BufferSegment>) -> PyResult<Self> {
+        Ok(Self {
+            source: self.source.clone_ref(py),
+            buffer: DataBuffer::get(self.source.bind(py))?,
+            segments,
+            exports: self.exports.clone(),
+        })
+    }
+
+    /// Number of views of this memory currently exported.
+    pub(crate) fn exported_views(&self) -> usize {
+        self.exports.load(Ordering::SeqCst)
+    }
+
     pub fn get_segment_slice<'p>(&self, _py: Python<'p>, i: usize) -> &'p [u8] {
         let segment = &self.segments[i];
 
@@ -160,59 +351,74 @@ impl ZstdBufferWithSegments {
         self.segments.len()
     }
 
-    fn __getitem__(&self, key: isize) -> PyResult<ZstdBufferSegment> {
-        let py = unsafe { Python::assume_gil_acquired() };
+    /// Obtain a segment or, given a slice, a view over a subset of segments.
+    ///
+    /// Slices share the backing memory of this instance, so its size and
+    /// `tobytes()` still describe the entire buffer.
+    fn __getitem__(&self, py: Python, key: &Bound<'_, PyAny>) -> PyResult<PyObject> {
+        if let Ok(slice) = key.downcast::<PySlice>() {
+            let indices = slice.indices(self.segments.len() as isize)?;
 
-        if key < 0 {
-            return Err(PyIndexError::new_err("offset must be non-negative"));
+            let segments = (0..indices.slicelength as isize)
+                .map(|i| self.segments[(indices.start + i * indices.step) as usize].clone())
+                .collect();
+
+            return self.with_segments(py, segments)?.into_py_any(py);
         }
 
-        let key = key as usize;
+        let key = check_index(key.extract()?, self.segments.len())?;
 
-        if key >= self.segments.len() {
-            return Err(PyIndexError::new_err(format!(
-                "offset must be less than {}",
-                self.segments.len()
-            )));
-        }
+        self.segment(py, key)?.into_py_any(py)
+    }
 
-        let segment = &self.segments[key];
+    /// Iterate over segments as memoryviews.
+    fn __iter__<'p>(&self, py: Python<'p>) -> PyResult<Bound<'p, PyIterator>> {
+        let mut views = Vec::with_capacity(self.segments.len());
+        for i in 0..self.segments.len() {
+            views.push(PyMemoryView::from(
+                Bound::new(py, self.segment(py, i)?)?.as_any(),
+            )?);
+        }
 
-        Ok(ZstdBufferSegment {
-            _parent: self.source.clone_ref(py),
-            buffer: PyBuffer::get(self.source.downcast_bound(py)?)?,
-            offset: segment.offset as _,
-            len: segment.length as _,
-        })
+        PyList::new(py, views)?.try_iter()
     }
 
     // PyBufferProtocol.
-    fn bf_getbuffer(slf: PyRefMut<Self>, view: PyObject, flags: i32) -> PyResult<()> {
-        if unsafe {
-            pyo3::ffi::PyBuffer_FillInfo(
-                view.as_ptr() as *mut Py_buffer,
-                slf.as_ptr(),
-                slf.buffer.buf_ptr(),
-                slf.buffer.len_bytes() as _,
-                1,
-                flags,
-            )
-        } != 0
+    //
+    // As with `BufferSegment`, the view references this instance and so
+    // keeps the source's buffer exported until the view is released.
+    unsafe fn __getbuffer__(
+        slf: Bound<'_, Self>,
+        view: *mut Py_buffer,
+        flags: c_int,
+    ) -> PyResult<()> {
+        let this = slf.borrow();
+
+        if pyo3::ffi::PyBuffer_FillInfo(
+            view,
+            slf.as_ptr(),
+            this.buffer.buf_ptr(),
+            this.buffer.len_bytes() as _,
+            1,
+            flags,
+        ) != 0
         {
             Err(PyErr::fetch(slf.py()))
         } else {
+            this.exports.fetch_add(1, Ordering::SeqCst);
             Ok(())
         }
     }
 
-    #[allow(unused_variables)]
-    fn bf_releasebuffer(slf: PyRefMut<Self>, view: PyObject) {}
+    unsafe fn __releasebuffer__(&self, _view: *mut Py_buffer) {
+        self.exports.fetch_sub(1, Ordering::SeqCst);
+    }
 
     // Our methods.
 
     #[new]
-    pub fn new(py: Python, data: &Bound<'_, PyAny>, segments: PyBuffer<u8>) -> PyResult<Self> {
-        let data_buffer = PyBuffer::get(&data.as_borrowed())?;
+    pub(crate) fn new(py: Python, data: &Bound<'_, PyAny>, segments: DataBuffer) -> PyResult<Self> {
+        let data_buffer = DataBuffer::get(&data.as_borrowed())?;
 
         if segments.len_bytes() % std::mem::size_of::<BufferSegment>() != 0 {
             return Err(PyValueError::new_err(format!(
@@ -246,6 +452,7 @@ impl ZstdBufferWithSegments {
             source: data.into_py_any(py)?,
             buffer: data_buffer,
             segments,
+            exports: Arc::new(AtomicUsize::new(0)),
         })
     }
 
@@ -264,6 +471,16 @@ impl ZstdBufferWithSegments {
     fn tobytes<'p>(&self, py: Python<'p>) -> PyResult<Bound<'p, PyBytes>> {
         Ok(PyBytes::new(py, self.as_slice()))
     }
+
+    /// Obtain the dictionary ID of the zstd frame in a segment.
+    ///
+    /// Only the frame header is parsed. Returns 0 if the frame doesn't
+    /// record a dictionary ID.
+    fn dict_id(&self, py: Python, index: isize) -> PyResult<u32> {
+        let index = check_index(index, self.segments.len())?;
+
+        segment_dict_id(self.get_segment_slice(py, index), index)
+    }
 }
 
 #[pyclass(
@@ -278,30 +495,13 @@ pub struct ZstdBufferWithSegmentsCollection {
 
 unsafe impl Sync for ZstdBufferWithSegmentsCollection {}
 
-#[pymethods]
 impl ZstdBufferWithSegmentsCollection {
-    // PySequenceProtocol.
-
-    fn __len__(&self) -> usize {
-        self.first_elements.last().unwrap().clone()
-    }
-
-    fn __getitem__(&self, key: isize) -> PyResult<ZstdBufferSegment> {
-        let py = unsafe { Python::assume_gil_acquired() };
-
-        if key < 0 {
-            return Err(PyIndexError::new_err("offset must be non-negative"));
-        }
-
-        let key = key as usize;
-
-        if key >= self.__len__() {
-            return Err(PyIndexError::new_err(format!(
-                "offset must be less than {}",
-                self.__len__()
-            )));
-        }
-
+    /// Resolve a collection index to its buffer and the index within it.
+    fn resolve<'p>(
+        &self,
+        py: Python<'p>,
+        key: usize,
+    ) -> PyResult<(&Bound<'p, ZstdBufferWithSegments>, usize)> {
         let mut offset = 0;
         for (buffer_index, segment) in self.buffers.iter().enumerate() {
             if key < self.first_elements[buffer_index] {
@@ -311,7 +511,7 @@ impl ZstdBufferWithSegmentsCollection {
 
                 let item = segment.downcast_bound::<ZstdBufferWithSegments>(py)?;
 
-                return item.borrow().__getitem__((key - offset) as isize);
+                return Ok((item, key - offset));
             }
         }
 
@@ -319,6 +519,24 @@ impl ZstdBufferWithSegmentsCollection {
             "error resolving segment; this should not happen",
         ))
     }
+}
+
+#[pymethods]
+impl ZstdBufferWithSegmentsCollection {
+    // PySequenceProtocol.
+
+    fn __len__(&self) -> usize {
+        *self.first_elements.last().unwrap()
+    }
+
+    fn __getitem__(&self, key: isize) -> PyResult<ZstdBufferSegment> {
+        let py = unsafe { Python::assume_gil_acquired() };
+
+        let key = check_index(key, self.__len__())?;
+        let (item, offset) = self.resolve(py, key)?;
+
+        item.borrow().segment(py, offset)
+    }
 
     #[new]
     #[pyo3(signature = (* py_args))]
@@ -355,6 +573,75 @@ impl ZstdBufferWithSegmentsCollection {
         })
     }
 
+    /// Obtain the dictionary ID of the zstd frame in a segment.
+    ///
+    /// Only the frame header is parsed. Returns 0 if the frame doesn't
+    /// record a dictionary ID.
+    fn dict_id(&self, py: Python, index: isize) -> PyResult<u32> {
+        let index = check_index(index, self.__len__())?;
+        let (item, offset) = self.resolve(py, index)?;
+
+        segment_dict_id(item.borrow().get_segment_slice(py, offset), index)
+    }
+
+    /// Construct an instance by copying buffers into a single allocation.
+    ///
+    /// Each item becomes a segment, in order. Items may be any C-contiguous
+    /// buffer and are copied as raw bytes. Zero-length items become empty
+    /// segments.
+    #[classmethod]
+    fn from_buffers(
+        _cls: Bound<'_, PyType>,
+        py: Python,
+        items: &Bound<'_, PyAny>,
+    ) -> PyResult<Self> {
+        let mut buffers = vec![];
+        for (i, item) in items.try_iter()?.enumerate() {
+            buffers.push(DataBuffer::get(&item?).map_err(|e| {
+                if e.is_instance_of::<PyValueError>(py) {
+                    PyValueError::new_err(format!("item {} is not contiguous", i))
+                } else {
+                    PyTypeError::new_err(format!("item {} does not support the buffer protocol", i))
+                }
+            })?);
+        }
+
+        if buffers.is_empty() {
+            return Err(PyValueError::new_err("must pass at least 1 item"));
+        }
+
+        let mut segments = Vec::with_capacity(buffers.len());
+        let mut offset = 0;
+        for buffer in &buffers {
+            segments.push(BufferSegment {
+                offset: offset as _,
+                length: buffer.len_bytes() as _,
+            });
+            offset += buffer.len_bytes();
+        }
+
+        let data = PyBytes::new_with(py, offset, |dest| {
+            for (buffer, segment) in buffers.iter().zip(&segments) {
+                let start = segment.offset as usize;
+                dest[start..start + buffer.len_bytes()].copy_from_slice(buffer.as_slice());
+            }
+
+            Ok(())
+        })?;
+
+        let buffer = ZstdBufferWithSegments {
+            buffer: DataBuffer::get(&data)?,
+            source: data.into_py_any(py)?,
+            segments,
+            exports: Arc::new(AtomicUsize::new(0)),
+        };
+
+        Ok(Self {
+            first_elements: vec![buffer.segments.len()],
+            buffers: vec![buffer.into_py_any(py)?],
+        })
+    }
+
     fn size(&self, py: Python) -> PyResult<usize> {
         let mut size = 0;
 
@@ -6,7 +6,7 @@
 
 use {
     crate::{
-        exceptions::ZstdError,
+        exceptions::{WithZstdError, ZstdCompressionError},
         stream::{make_in_buffer_source, InBufferSource},
         zstd_safe::CCtx,
     },
@@ -14,6 +14,20 @@ use {
     std::sync::Arc,
 };
 
+/// Compresses data into chunks of a fixed size.
+///
+/// Iterators returned by `compress()` only yield chunks of exactly
+/// `chunk_size` bytes, so a large input may produce several chunks. Output
+/// that doesn't fill a chunk is retained until a later call. `flush()` and
+/// `finish()` emit retained output, with only the final chunk being smaller
+/// than `chunk_size`.
+///
+/// A frame is produced by any number of `compress()` and `flush()` calls
+/// followed by `finish()`. The iterator returned by each call must be
+/// exhausted before `flush()` or `finish()` is called. Once `finish()` is
+/// called, `compress()`, `flush()`, and `finish()` raise until `reset()`
+/// prepares the chunker for another frame. `reset()` may also be called part
+/// way through a frame to abandon it.
 #[pyclass(module = "zstandard.backend_rust")]
 pub struct ZstdCompressionChunker {
     cctx: Arc<CCtx<'static>>,
@@ -80,8 +94,8 @@ impl ZstdCompressionChunker {
         self.ensure_state(py);
 
         if self.finished {
-            return Err(ZstdError::new_err(
-                "cannot call compress() after compression finished",
+            return Err(ZstdCompressionError::new_err(
+                "cannot call compress() after compression finished; call reset() to start a new frame",
             ));
         }
 
@@ -107,13 +121,13 @@ impl ZstdCompressionChunker {
         self.ensure_state(py);
 
         if self.finished {
-            return Err(ZstdError::new_err(
-                "cannot call flush() after compression finished",
+            return Err(ZstdCompressionError::new_err(
+                "cannot call flush() after compression finished; call reset() to start a new frame",
             ));
         }
 
         if self.iterator.is_some() {
-            return Err(ZstdError::new_err(
+            return Err(ZstdCompressionError::new_err(
                 "cannot call flush() before consuming output from previous operation",
             ));
         }
@@ -140,19 +154,22 @@ impl ZstdCompressionChunker {
         self.ensure_state(py);
 
         if self.finished {
-            return Err(ZstdError::new_err(
-                "cannot call finish() after compression finished",
+            return Err(ZstdCompressionError::new_err(
+                "cannot call finish() after compression finished; call reset() to start a new frame",
             ));
         }
 
         if self.iterator.is_some() {
-            return Err(ZstdError::new_err(
+            return Err(ZstdCompressionError::new_err(
                 "cannot call finish() before consuming output from previous operation",
             ));
         }
 
         let source = make_in_buffer_source(py, &PyBytes::new(py, &[]), zstd_safe::CCtx::in_size())?;
 
+        // Further operations are rejected even before the output is consumed.
+        self.finished = true;
+
         let it = Bound::new(
             py,
             ZstdCompressionChunkerIterator {
@@ -168,6 +185,29 @@ impl ZstdCompressionChunker {
 
         Ok(it.unbind())
     }
+
+    /// Discard any in-progress frame and prepare to compress a new one.
+    ///
+    /// Output not yet consumed from earlier iterators is lost, and those
+    /// iterators stop producing output. `size` is the size in bytes of the
+    /// next frame's input, if known.
+    #[pyo3(signature = (size=None))]
+    fn reset(&mut self, py: Python, size: Option<u64>) -> PyResult<()> {
+        if let Some(it) = self.iterator.take() {
+            it.borrow_mut(py).finished = true;
+        }
+
+        self.partial_buffer = None;
+        self.finished = false;
+
+        self.cctx.reset();
+        self.cctx
+            .set_pledged_source_size(size.unwrap_or(zstd_safe::CONTENTSIZE_UNKNOWN))
+            .map_err(|msg| {
+                ZstdCompressionError::new_err(format!("error setting source size: {}", msg))
+                    .with_zstd_error(&msg)
+            })
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -214,7 +254,10 @@ impl ZstdCompressionChunkerIterator {
                     &mut in_buffer,
                     zstd_sys::ZSTD_EndDirective::ZSTD_e_continue,
                 )
-                .map_err(|msg| ZstdError::new_err(format!("zstd compress error: {}", msg)))?;
+                .map_err(|msg| {
+                    ZstdCompressionError::new_err(format!("zstd compress error: {}", msg))
+                        .with_zstd_error(&msg)
+                })?;
 
             slf.source.record_bytes_read(in_buffer.pos - old_pos);
 
@@ -253,7 +296,10 @@ impl ZstdCompressionChunkerIterator {
             .cctx
             .clone()
             .compress_into_vec(&mut slf.dest_buffer, &mut in_buffer, flush_mode)
-            .map_err(|msg| ZstdError::new_err(format!("zstd compress error: {}", msg)))?;
+            .map_err(|msg| {
+                ZstdCompressionError::new_err(format!("zstd compress error: {}", msg))
+                    .with_zstd_error(&msg)
+            })?;
 
         // When flushing or finishing, we always emit data in the output
         // buffer. But the operation could fill the output buffer and not be
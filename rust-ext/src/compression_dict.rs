@@ -6,17 +6,31 @@
 
 use {
     crate::{
-        compression_parameters::{get_cctx_parameter, int_to_strategy, ZstdCompressionParameters},
-        zstd_safe::{train_dictionary_fastcover, CCtx, CDict, DCtx, DDict},
+        buffers::{DataBuffer, ZstdBufferWithSegments},
+        compression_parameters::{
+            dedicated_dict_search_supported, get_cctx_parameter, int_to_strategy,
+            ZstdCompressionParameters, DEDICATED_DICT_SEARCH,
+        },
+        exceptions::WithZstdError,
+        zstd_safe::{
+            dict_header_size, finalize_dictionary, train_dictionary_cover,
+            train_dictionary_fastcover, train_dictionary_legacy, CCtx, CDict, DCtx, DDict,
+            ZstdFailure,
+        },
         ZstdError,
     },
     pyo3::{
-        buffer::PyBuffer,
-        exceptions::PyValueError,
+        exceptions::{PyTypeError, PyValueError},
+        ffi,
         prelude::*,
-        types::{PyBytes, PyList},
+        types::{PyList, PyMemoryView, PyType},
         wrap_pyfunction,
     },
+    std::{
+        hash::{DefaultHasher, Hash, Hasher},
+        os::raw::c_int,
+        sync::OnceLock,
+    },
 };
 
 #[pyclass(module = "zstandard.backend_rust")]
@@ -40,21 +54,157 @@ pub struct ZstdCompressionDict {
     /// Precomputed compression dictionary.
     cdict: Option<CDict<'static>>,
 
+    /// Compression level the compression dictionary was precomputed for.
+    ///
+    /// `None` if it wasn't precomputed or was precomputed from explicit
+    /// compression parameters.
+    cdict_level: Option<i32>,
+
     /// Precomputed decompression dictionary.
     ddict: Option<DDict<'static>>,
+
+    /// Hash of `data`, computed on first use.
+    hash: OnceLock<u64>,
 }
 
 unsafe impl Sync for ZstdCompressionDict {}
 
+/// Diagnostics describing how `train_dictionary()` produced a dictionary.
+#[pyclass(module = "zstandard.backend_rust")]
+pub struct DictionaryTrainingStats {
+    /// Size of the trained dictionary in bytes.
+    #[pyo3(get)]
+    dict_size: usize,
+
+    /// Number of samples the dictionary was trained on.
+    ///
+    /// When optimizing parameters, samples after the split point are held back
+    /// to evaluate candidates and aren't counted.
+    #[pyo3(get)]
+    samples_used: usize,
+
+    /// Segment size used by the cover algorithms.
+    #[pyo3(get)]
+    k: Option<u32>,
+
+    /// Dmer size used by the cover algorithms.
+    #[pyo3(get)]
+    d: Option<u32>,
+}
+
+#[pymethods]
+impl DictionaryTrainingStats {
+    fn __repr__(&self) -> String {
+        let fmt = |v: Option<u32>| v.map_or_else(|| "None".to_string(), |v| v.to_string());
+
+        format!(
+            "DictionaryTrainingStats(dict_size={}, samples_used={}, k={}, d={})",
+            self.dict_size,
+            self.samples_used,
+            fmt(self.k),
+            fmt(self.d)
+        )
+    }
+}
+
+/// Minimum number of training samples accepted by the cover algorithms.
+const COVER_MIN_TRAINING_SAMPLES: usize = 5;
+
+/// Size of the chunks of content standing in for samples when merging
+/// dictionaries without any.
+const MERGE_SAMPLE_SIZE: usize = 256;
+
+/// Room reserved for the header when merging dictionaries. Entropy tables
+/// take at most a few KB.
+const MERGE_HEADER_CAPACITY: usize = 32768;
+
 impl ZstdCompressionDict {
-    pub(crate) fn load_into_cctx(&self, cctx: &CCtx) -> PyResult<()> {
+    /// The dictionary's content without the header of a full dictionary.
+    fn raw_content(&self) -> PyResult<&[u8]> {
+        let full = match self.content_type {
+            zstd_sys::ZSTD_dictContentType_e::ZSTD_dct_rawContent => false,
+            zstd_sys::ZSTD_dictContentType_e::ZSTD_dct_fullDict => true,
+            _ => zstd_safe::get_dict_id(&self.data).is_some(),
+        };
+
+        if !full {
+            return Ok(&self.data);
+        }
+
+        let header_size = dict_header_size(&self.data).map_err(|msg| {
+            ZstdError::new_err(format!("invalid dictionary header: {}", msg)).with_zstd_error(&msg)
+        })?;
+
+        Ok(&self.data[header_size..])
+    }
+
+    /// Reject content zstd can't load as the declared dictionary type.
+    ///
+    /// zstd only notices a full dictionary lacking the dictionary magic
+    /// number when it first digests the dictionary, reporting a generic
+    /// error.
+    fn validate_content_type(&self) -> Result<(), ZstdFailure> {
+        if self.content_type == zstd_sys::ZSTD_dictContentType_e::ZSTD_dct_fullDict
+            && zstd_safe::get_dict_id(&self.data).is_none()
+        {
+            Err(
+                "DICT_TYPE_FULLDICT requires data beginning with the zstd dictionary magic number"
+                    .into(),
+            )
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Load the dictionary into a compression context without requiring the GIL.
+    pub(crate) fn load_into_cctx_nogil(&self, cctx: &CCtx) -> Result<(), ZstdFailure> {
         if let Some(cdict) = &self.cdict {
             cctx.load_computed_dict(cdict)
         } else {
+            self.validate_content_type()?;
             cctx.load_dict_data(&self.data, self.content_type)
         }
+    }
+
+    /// Ensure a precomputed compression dictionary matches a compression level.
+    ///
+    /// zstd compresses with the parameters a dictionary was precomputed with,
+    /// silently ignoring the level of the compression context.
+    pub(crate) fn check_precomputed_level(&self, level: i32) -> PyResult<()> {
+        match self.cdict_level {
+            Some(cdict_level) if cdict_level != level => Err(ZstdError::new_err(format!(
+                "dictionary was precomputed for compression level {}; cannot compress at level {}",
+                cdict_level, level
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn load_into_cctx(&self, cctx: &CCtx) -> PyResult<()> {
+        self.load_into_cctx_nogil(cctx).map_err(|msg| {
+            ZstdError::new_err(format!("could not load compression dictionary: {}", msg))
+                .with_zstd_error(&msg)
+        })
+    }
+
+    /// Start a raw block session on a compression context with this dictionary.
+    ///
+    /// `params` is ignored if the dictionary was precomputed, which fixes its
+    /// own parameters.
+    pub(crate) fn begin_cctx_blocks(
+        &self,
+        cctx: &CCtx,
+        params: zstd_sys::ZSTD_compressionParameters,
+    ) -> PyResult<()> {
+        if let Some(cdict) = &self.cdict {
+            cctx.begin_blocks_with_cdict(cdict)
+        } else {
+            self.validate_content_type()
+                .and_then(|_| cctx.begin_blocks(&self.data, params))
+        }
         .map_err(|msg| {
             ZstdError::new_err(format!("could not load compression dictionary: {}", msg))
+                .with_zstd_error(&msg)
         })
     }
 
@@ -64,20 +214,41 @@ impl ZstdCompressionDict {
             return Ok(());
         }
 
+        self.validate_content_type().map_err(|msg| {
+            ZstdError::new_err(format!("could not load decompression dictionary: {}", msg))
+                .with_zstd_error(&msg)
+        })?;
+
         self.ddict = Some(
             DDict::from_data(&self.data, self.content_type)
-                .map_err(|msg| ZstdError::new_err(msg))?,
+                .map_err(|msg| ZstdError::new_err(msg.clone()).with_zstd_error(&msg))?,
         );
 
         Ok(())
     }
 
+    /// The prepared decompression dictionary, once `ensure_ddict()` made it.
+    pub(crate) fn ddict(&self) -> Option<&DDict<'static>> {
+        self.ddict.as_ref()
+    }
+
+    /// Start a raw block session on a decompression context with this dictionary.
+    pub(crate) fn begin_dctx_blocks(&mut self, dctx: &DCtx) -> PyResult<()> {
+        self.ensure_ddict()?;
+
+        dctx.begin_blocks(self.ddict.as_ref()).map_err(|msg| {
+            ZstdError::new_err(format!("unable to reference prepared dictionary: {}", msg))
+                .with_zstd_error(&msg)
+        })
+    }
+
     pub(crate) fn load_into_dctx(&mut self, dctx: &DCtx) -> PyResult<()> {
         self.ensure_ddict()?;
 
         dctx.load_prepared_dict(self.ddict.as_ref().unwrap())
             .map_err(|msg| {
                 ZstdError::new_err(format!("unable to reference prepared dictionary: {}", msg))
+                    .with_zstd_error(&msg)
             })
     }
 }
@@ -86,7 +257,7 @@ impl ZstdCompressionDict {
 impl ZstdCompressionDict {
     #[new]
     #[pyo3(signature = (buffer, dict_type = None))]
-    fn new(py: Python, buffer: PyBuffer<u8>, dict_type: Option<u32>) -> PyResult<Self> {
+    fn new(buffer: DataBuffer, dict_type: Option<u32>) -> PyResult<Self> {
         let dict_type = if dict_type == Some(zstd_sys::ZSTD_dictContentType_e::ZSTD_dct_auto as u32)
         {
             Ok(zstd_sys::ZSTD_dictContentType_e::ZSTD_dct_auto)
@@ -103,7 +274,7 @@ impl ZstdCompressionDict {
             Ok(zstd_sys::ZSTD_dictContentType_e::ZSTD_dct_auto)
         }?;
 
-        let dict_data = buffer.to_vec(py)?;
+        let dict_data = buffer.as_slice().to_vec();
 
         Ok(ZstdCompressionDict {
             content_type: dict_type,
@@ -111,7 +282,9 @@ impl ZstdCompressionDict {
             d: 0,
             data: dict_data,
             cdict: None,
+            cdict_level: None,
             ddict: None,
+            hash: OnceLock::new(),
         })
     }
 
@@ -119,16 +292,163 @@ impl ZstdCompressionDict {
         self.data.len()
     }
 
-    fn as_bytes<'p>(&self, py: Python<'p>) -> PyResult<Bound<'p, PyBytes>> {
-        Ok(PyBytes::new(py, &self.data))
+    // Buffer protocol. Dictionary data is never mutated after construction,
+    // so it is safe to expose it read-only for the lifetime of the object.
+    unsafe fn __getbuffer__(
+        slf: Bound<'_, Self>,
+        view: *mut ffi::Py_buffer,
+        flags: c_int,
+    ) -> PyResult<()> {
+        let data = &slf.borrow().data;
+
+        if ffi::PyBuffer_FillInfo(
+            view,
+            slf.as_ptr(),
+            data.as_ptr() as *mut _,
+            data.len() as _,
+            1,
+            flags,
+        ) != 0
+        {
+            Err(PyErr::fetch(slf.py()))
+        } else {
+            Ok(())
+        }
     }
 
-    fn dict_id(&self) -> u32 {
+    unsafe fn __releasebuffer__(&self, _view: *mut ffi::Py_buffer) {}
+
+    /// Obtain a read-only memoryview over the raw dictionary data.
+    fn as_bytes<'p>(slf: &Bound<'p, Self>) -> PyResult<Bound<'p, PyMemoryView>> {
+        PyMemoryView::from(slf.as_any())
+    }
+
+    /// The dictionary ID, or 0 for raw content dictionaries.
+    pub(crate) fn dict_id(&self) -> u32 {
+        if self.content_type == zstd_sys::ZSTD_dictContentType_e::ZSTD_dct_rawContent {
+            return 0;
+        }
+
         zstd_safe::get_dict_id(&self.data)
             .map(u32::from)
             .unwrap_or(0)
     }
 
+    fn __repr__(&self) -> String {
+        format!(
+            "ZstdCompressionDict(dict_id={}, size={})",
+            self.dict_id(),
+            self.data.len()
+        )
+    }
+
+    /// Dictionaries compare equal if their data is identical, regardless of
+    /// their declared type or what was precomputed.
+    fn __eq__(&self, other: &Self) -> bool {
+        self.data == other.data
+    }
+
+    fn __hash__(&self) -> u64 {
+        *self.hash.get_or_init(|| {
+            let mut hasher = DefaultHasher::new();
+            self.data.hash(&mut hasher);
+            hasher.finish()
+        })
+    }
+
+    /// Combine dictionaries into a single full dictionary.
+    ///
+    /// The content of each dictionary, without the header of a full
+    /// dictionary, is concatenated in order and new entropy tables are
+    /// computed for compression `level` from `samples`. Without samples,
+    /// chunks of the combined content are used instead. zstd favors content
+    /// near the end of a dictionary, so the most valuable dictionaries should
+    /// come last.
+    ///
+    /// The result is a `DICT_TYPE_FULLDICT` dictionary with ID `dict_id`, or
+    /// an ID derived from its content if 0.
+    #[classmethod]
+    #[pyo3(signature = (dicts, level=3, dict_id=0, samples=None))]
+    fn merge(
+        _cls: &Bound<'_, PyType>,
+        dicts: &Bound<'_, PyAny>,
+        level: i32,
+        dict_id: u32,
+        samples: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Self> {
+        let mut content = vec![];
+
+        for item in dicts.try_iter()? {
+            let item = item?;
+            let dict = item.downcast::<ZstdCompressionDict>().map_err(|_| {
+                PyTypeError::new_err("dicts must only contain ZstdCompressionDict instances")
+            })?;
+
+            content.extend_from_slice(dict.borrow().raw_content()?);
+        }
+
+        if content.is_empty() {
+            return Err(PyValueError::new_err(
+                "must pass at least 1 non-empty dictionary",
+            ));
+        }
+
+        let mut samples_buffer = vec![];
+        let mut sample_sizes = vec![];
+
+        if let Some(samples) = samples {
+            for (i, sample) in samples.try_iter()?.enumerate() {
+                let buffer = DataBuffer::get(&sample?).map_err(|_| {
+                    PyTypeError::new_err(format!(
+                        "sample {} does not support the buffer protocol",
+                        i
+                    ))
+                })?;
+
+                samples_buffer.extend_from_slice(buffer.as_slice());
+                sample_sizes.push(buffer.len_bytes());
+            }
+        } else {
+            samples_buffer.extend_from_slice(&content);
+            sample_sizes.extend(content.chunks(MERGE_SAMPLE_SIZE).map(|chunk| chunk.len()));
+        }
+
+        let params = zstd_sys::ZDICT_params_t {
+            compressionLevel: level,
+            notificationLevel: 0,
+            dictID: dict_id,
+        };
+
+        let mut dict_data = Vec::with_capacity(content.len() + MERGE_HEADER_CAPACITY);
+
+        finalize_dictionary(
+            &mut dict_data,
+            &content,
+            &samples_buffer,
+            &sample_sizes,
+            params,
+        )
+        .map_err(|msg| {
+            ZstdError::new_err(format!("cannot merge dictionaries: {}", msg)).with_zstd_error(&msg)
+        })?;
+
+        Ok(ZstdCompressionDict {
+            content_type: zstd_sys::ZSTD_dictContentType_e::ZSTD_dct_fullDict,
+            k: 0,
+            d: 0,
+            data: dict_data,
+            cdict: None,
+            cdict_level: None,
+            ddict: None,
+            hash: OnceLock::new(),
+        })
+    }
+
+    /// Precompute the digested compression dictionary for reuse.
+    ///
+    /// Compressors bound to this dictionary then use the parameters it was
+    /// precomputed with. Constructing a compressor with a different
+    /// compression level raises rather than silently using those parameters.
     #[pyo3(signature = (level=None, compression_params=None))]
     fn precompute_compress(
         &mut self,
@@ -136,6 +456,25 @@ impl ZstdCompressionDict {
         level: Option<i32>,
         compression_params: Option<Py<ZstdCompressionParameters>>,
     ) -> PyResult<()> {
+        if let Some(compression_params) = &compression_params {
+            let source_params = compression_params.borrow(py).params;
+
+            // Dedicated dictionary search lays out the dictionary's tables
+            // differently, which only zstd's parameter-based constructor does.
+            if level.is_none()
+                && dedicated_dict_search_supported()
+                && get_cctx_parameter(source_params, DEDICATED_DICT_SEARCH)? != 0
+            {
+                self.cdict = Some(
+                    CDict::from_cctx_params(&self.data, self.content_type, source_params)
+                        .map_err(|msg| ZstdError::new_err(msg.clone()).with_zstd_error(&msg))?,
+                );
+                self.cdict_level = None;
+
+                return Ok(());
+            }
+        }
+
         let params = if let Some(level) = level {
             if compression_params.is_some() {
                 return Err(PyValueError::new_err(
@@ -181,8 +520,9 @@ impl ZstdCompressionDict {
 
         self.cdict = Some(
             CDict::from_data(&self.data, self.content_type, params)
-                .map_err(|msg| ZstdError::new_err(msg))?,
+                .map_err(|msg| ZstdError::new_err(msg.clone()).with_zstd_error(&msg))?,
         );
+        self.cdict_level = level;
 
         Ok(())
     }
@@ -202,10 +542,14 @@ impl ZstdCompressionDict {
     level=0,
     steps=0,
     threads=0,
+    algorithm="fastcover",
+    return_stats=false,
 ))]
-fn train_dictionary(
+#[allow(clippy::too_many_arguments)]
+fn train_dictionary<'p>(
+    py: Python<'p>,
     dict_size: usize,
-    samples: &Bound<'_, PyList>,
+    samples: &Bound<'_, PyAny>,
     k: u32,
     d: u32,
     f: u32,
@@ -216,7 +560,9 @@ fn train_dictionary(
     level: i32,
     steps: u32,
     threads: i32,
-) -> PyResult<ZstdCompressionDict> {
+    algorithm: &str,
+    return_stats: bool,
+) -> PyResult<Bound<'p, PyAny>> {
     let threads = if threads < 0 {
         num_cpus::get() as u32
     } else {
@@ -234,62 +580,219 @@ fn train_dictionary(
         (d, steps, level)
     };
 
-    let params = zstd_sys::ZDICT_fastCover_params_t {
-        k,
-        d,
-        f,
-        steps,
-        nbThreads: threads,
-        splitPoint: split_point,
-        accel,
-        shrinkDict: 0,
-        shrinkDictMaxRegression: 0,
-        zParams: zstd_sys::ZDICT_params_t {
-            compressionLevel: level,
-            notificationLevel: notifications,
-            dictID: dict_id,
-        },
+    if k != 0 && d != 0 && k <= d {
+        return Err(ZstdError::new_err(format!(
+            "segment size k ({}) must be greater than dmer size d ({})",
+            k, d
+        )));
+    }
+
+    let z_params = zstd_sys::ZDICT_params_t {
+        compressionLevel: level,
+        notificationLevel: notifications,
+        dictID: dict_id,
     };
 
-    let mut samples_len = 0;
+    let segments = samples
+        .downcast::<ZstdBufferWithSegments>()
+        .ok()
+        .map(|buffer| buffer.borrow());
+
+    let mut owned_samples: Vec<u8> = Vec::new();
+    let mut sample_sizes: Vec<libc::size_t> = Vec::new();
+
+    // zstd reads samples back to back from a single buffer, which a
+    // BufferWithSegments already is if its segments are laid out in order.
+    let samples_buffer: &[u8] = if let Some(segments) = &segments {
+        let mut offset = 0;
+
+        for (i, segment) in segments.segments.iter().enumerate() {
+            if segment.offset != offset {
+                return Err(PyValueError::new_err(format!(
+                    "segment {} starts at offset {} but must start at {}; segments must be contiguous and in order",
+                    i, segment.offset, offset
+                )));
+            }
 
-    // Figure out total size of input samples. A side-effect is all elements are
-    // validated to be PyBytes.
-    for sample in samples.iter() {
-        let bytes = sample
-            .downcast::<PyBytes>()
-            .or_else(|_| Err(PyValueError::new_err("samples must be bytes")))?;
+            offset += segment.length;
+            sample_sizes.push(segment.length as _);
+        }
 
-        samples_len += bytes.as_bytes().len();
-    }
+        if offset != segments.buffer.len_bytes() as u64 {
+            return Err(PyValueError::new_err(format!(
+                "segment sizes total {} bytes but buffer is {} bytes",
+                offset,
+                segments.buffer.len_bytes()
+            )));
+        }
 
-    let mut samples_buffer: Vec<u8> = Vec::with_capacity(samples_len);
-    let mut sample_sizes: Vec<libc::size_t> = Vec::with_capacity(samples.len());
+        segments.buffer.as_slice()
+    } else {
+        let samples = samples.downcast::<PyList>().map_err(|_| {
+            PyTypeError::new_err("samples must be a list of buffers or a BufferWithSegments")
+        })?;
+
+        // Obtain every sample up front so the total size is known before copying.
+        let mut buffers = Vec::with_capacity(samples.len());
+        for (i, sample) in samples.iter().enumerate() {
+            buffers.push(DataBuffer::get(&sample).map_err(|e| {
+                if e.is_instance_of::<PyValueError>(py) {
+                    PyValueError::new_err(format!("sample {} is not contiguous", i))
+                } else {
+                    PyValueError::new_err(format!(
+                        "sample {} does not support the buffer protocol",
+                        i
+                    ))
+                }
+            })?);
+        }
 
-    for sample in samples.iter() {
-        let bytes = sample.downcast::<PyBytes>()?;
-        let data = bytes.as_bytes();
-        sample_sizes.push(data.len());
-        samples_buffer.extend_from_slice(data);
-    }
+        owned_samples.reserve_exact(buffers.iter().map(DataBuffer::len_bytes).sum());
+        sample_sizes.reserve_exact(buffers.len());
+
+        for buffer in &buffers {
+            sample_sizes.push(buffer.len_bytes());
+            owned_samples.extend_from_slice(buffer.as_slice());
+        }
+
+        &owned_samples
+    };
 
     let mut dict_data: Vec<u8> = Vec::with_capacity(dict_size);
 
-    train_dictionary_fastcover(&mut dict_data, &samples_buffer, &sample_sizes, &params)
-        .map_err(|msg| ZstdError::new_err(format!("cannot train dict: {}", msg)))?;
-
-    Ok(ZstdCompressionDict {
-        content_type: zstd_sys::ZSTD_dictContentType_e::ZSTD_dct_fullDict,
-        k: params.k,
-        d: params.d,
-        data: dict_data,
-        cdict: None,
-        ddict: None,
-    })
+    // Parameters are searched for when k or d are not specified.
+    let optimize = k == 0 || d == 0;
+
+    // zstd only splits samples into training and test sets when optimizing,
+    // with a default that differs per algorithm.
+    let effective_split_point = match algorithm {
+        "fastcover" if optimize && split_point <= 0.0 => 0.75,
+        "cover" | "fastcover" if optimize && split_point > 0.0 => split_point,
+        _ => 1.0,
+    };
+
+    let samples_used = if effective_split_point < 1.0 {
+        (sample_sizes.len() as f64 * effective_split_point) as usize
+    } else {
+        sample_sizes.len()
+    };
+
+    // Report the sample requirements zstd enforces rather than its
+    // srcSize_wrong error code.
+    if matches!(algorithm, "cover" | "fastcover") && samples_used < COVER_MIN_TRAINING_SAMPLES {
+        return Err(ZstdError::new_err(if effective_split_point < 1.0 {
+            format!(
+                    "cannot train dict: at least {} training samples are required; split point {} leaves {} of {} samples for training",
+                    COVER_MIN_TRAINING_SAMPLES,
+                    effective_split_point,
+                    samples_used,
+                    sample_sizes.len()
+                )
+        } else {
+            format!(
+                "cannot train dict: at least {} samples are required; got {}",
+                COVER_MIN_TRAINING_SAMPLES,
+                sample_sizes.len()
+            )
+        }));
+    }
+
+    let (k, d) = match algorithm {
+        "fastcover" => {
+            let mut params = zstd_sys::ZDICT_fastCover_params_t {
+                k,
+                d,
+                f,
+                steps,
+                nbThreads: threads,
+                splitPoint: split_point,
+                accel,
+                shrinkDict: 0,
+                shrinkDictMaxRegression: 0,
+                zParams: z_params,
+            };
+
+            train_dictionary_fastcover(
+                &mut dict_data,
+                samples_buffer,
+                &sample_sizes,
+                &mut params,
+                optimize,
+            )
+            .map(|_| (params.k, params.d))
+        }
+        "cover" => {
+            let mut params = zstd_sys::ZDICT_cover_params_t {
+                k,
+                d,
+                steps,
+                nbThreads: threads,
+                splitPoint: split_point,
+                shrinkDict: 0,
+                shrinkDictMaxRegression: 0,
+                zParams: z_params,
+            };
+
+            train_dictionary_cover(
+                &mut dict_data,
+                samples_buffer,
+                &sample_sizes,
+                &mut params,
+                optimize,
+            )
+            .map(|_| (params.k, params.d))
+        }
+        "legacy" => {
+            let params = zstd_sys::ZDICT_legacy_params_t {
+                selectivityLevel: 0,
+                zParams: z_params,
+            };
+
+            train_dictionary_legacy(&mut dict_data, samples_buffer, &sample_sizes, params)
+                .map(|_| (0, 0))
+        }
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "unknown algorithm: {}; must be one of cover, fastcover, or legacy",
+                algorithm
+            )))
+        }
+    }
+    .map_err(|msg| {
+        ZstdError::new_err(format!("cannot train dict: {}", msg)).with_zstd_error(&msg)
+    })?;
+
+    let stats = DictionaryTrainingStats {
+        dict_size: dict_data.len(),
+        samples_used,
+        k: (algorithm != "legacy").then_some(k),
+        d: (algorithm != "legacy").then_some(d),
+    };
+
+    let dict = Bound::new(
+        py,
+        ZstdCompressionDict {
+            content_type: zstd_sys::ZSTD_dictContentType_e::ZSTD_dct_fullDict,
+            k,
+            d,
+            data: dict_data,
+            cdict: None,
+            cdict_level: None,
+            ddict: None,
+            hash: OnceLock::new(),
+        },
+    )?;
+
+    if return_stats {
+        Ok((dict, Bound::new(py, stats)?).into_pyobject(py)?.into_any())
+    } else {
+        Ok(dict.into_any())
+    }
 }
 
 pub(crate) fn init_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
     module.add_class::<ZstdCompressionDict>()?;
+    module.add_class::<DictionaryTrainingStats>()?;
     module.add_function(wrap_pyfunction!(train_dictionary, module)?)?;
 
     Ok(())
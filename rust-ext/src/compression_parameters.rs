@@ -5,7 +5,10 @@
 // of the BSD license. See the LICENSE file for details.
 
 use {
-    crate::ZstdError,
+    crate::{
+        constants::LDM_DEFAULT_WINDOW_LOG, exceptions::WithZstdError, zstd_safe::ZstdFailure,
+        ZstdError,
+    },
     libc::c_int,
     pyo3::{
         exceptions::{PyMemoryError, PyTypeError, PyValueError},
@@ -51,12 +54,24 @@ impl<'a> CCtxParams<'a> {
             Err(ZstdError::new_err(format!(
                 "unable to set compression context parameter: {}",
                 zstd_safe::get_error_name(zresult)
-            )))
+            ))
+            .with_zstd_error(&ZstdFailure::from_result(zresult)))
         } else {
             Ok(())
         }
     }
 
+    /// Set a parameter by its `ZSTD_cParameter` value, unknown to us or not.
+    pub fn set_raw_parameter(&self, param: c_int, value: c_int) -> Result<usize, ZstdFailure> {
+        let zresult =
+            unsafe { crate::zstd_safe::cctx_params_set_parameter_raw(self.0, param, value) };
+        if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
+            Err(ZstdFailure::from_result(zresult))
+        } else {
+            Ok(zresult)
+        }
+    }
+
     fn apply_compression_parameter(
         &self,
         py: Python,
@@ -152,11 +167,53 @@ impl<'a> CCtxParams<'a> {
             &params,
             zstd_sys::ZSTD_cParameter::ZSTD_c_ldmHashRateLog,
         )?;
+        self.apply_compression_parameter(
+            py,
+            params,
+            zstd_sys::ZSTD_cParameter::ZSTD_c_targetCBlockSize,
+        )?;
+        if dedicated_dict_search_supported() {
+            self.apply_compression_parameter(py, params, DEDICATED_DICT_SEARCH)?;
+        }
 
         Ok(())
     }
 }
 
+/// `ZSTD_c_enableDedicatedDictSearch`.
+pub(crate) const DEDICATED_DICT_SEARCH: zstd_sys::ZSTD_cParameter =
+    zstd_sys::ZSTD_cParameter::ZSTD_c_experimentalParam8;
+
+/// Whether the linked zstd library knows `ZSTD_c_enableDedicatedDictSearch`.
+///
+/// The parameter is experimental, so zstd may drop or renumber it.
+pub(crate) fn dedicated_dict_search_supported() -> bool {
+    let bounds = unsafe { zstd_sys::ZSTD_cParam_getBounds(DEDICATED_DICT_SEARCH) };
+
+    unsafe { zstd_sys::ZSTD_isError(bounds.error) == 0 }
+}
+
+/// Ensure a worker count can be honored by the linked zstd library.
+///
+/// zstd built without multi-threading support only accepts 0 workers.
+pub(crate) fn validate_threads(threads: i32) -> PyResult<()> {
+    if threads <= 0 {
+        return Ok(());
+    }
+
+    let bounds =
+        unsafe { zstd_sys::ZSTD_cParam_getBounds(zstd_sys::ZSTD_cParameter::ZSTD_c_nbWorkers) };
+
+    if unsafe { zstd_sys::ZSTD_isError(bounds.error) } != 0 || bounds.upperBound == 0 {
+        return Err(ZstdError::new_err(format!(
+            "cannot use threads={}: zstd was built without multi-threading support",
+            threads
+        )));
+    }
+
+    Ok(())
+}
+
 /// Resolve the value of a compression context parameter.
 pub(crate) fn get_cctx_parameter(
     params: *mut zstd_sys::ZSTD_CCtx_params,
@@ -171,12 +228,63 @@ pub(crate) fn get_cctx_parameter(
         Err(ZstdError::new_err(format!(
             "unable to retrieve parameter: {}",
             zstd_safe::get_error_name(zresult)
-        )))
+        ))
+        .with_zstd_error(&ZstdFailure::from_result(zresult)))
     } else {
         Ok(value)
     }
 }
 
+/// Compression parameters zstd will use for input of unknown size.
+///
+/// Parameters left at 0 take the defaults of the compression level, the
+/// same way zstd resolves them when compressing.
+pub(crate) fn effective_cparams(
+    params: *mut zstd_sys::ZSTD_CCtx_params,
+) -> PyResult<zstd_sys::ZSTD_compressionParameters> {
+    let level = get_cctx_parameter(params, zstd_sys::ZSTD_cParameter::ZSTD_c_compressionLevel)?;
+    let mut cparams = unsafe { zstd_sys::ZSTD_getCParams(level, 0, 0) };
+
+    for (param, field) in [
+        (
+            zstd_sys::ZSTD_cParameter::ZSTD_c_windowLog,
+            &mut cparams.windowLog,
+        ),
+        (
+            zstd_sys::ZSTD_cParameter::ZSTD_c_hashLog,
+            &mut cparams.hashLog,
+        ),
+        (
+            zstd_sys::ZSTD_cParameter::ZSTD_c_chainLog,
+            &mut cparams.chainLog,
+        ),
+        (
+            zstd_sys::ZSTD_cParameter::ZSTD_c_searchLog,
+            &mut cparams.searchLog,
+        ),
+        (
+            zstd_sys::ZSTD_cParameter::ZSTD_c_minMatch,
+            &mut cparams.minMatch,
+        ),
+        (
+            zstd_sys::ZSTD_cParameter::ZSTD_c_targetLength,
+            &mut cparams.targetLength,
+        ),
+    ] {
+        let value = get_cctx_parameter(params, param)?;
+        if value != 0 {
+            *field = value as _;
+        }
+    }
+
+    let strategy = get_cctx_parameter(params, zstd_sys::ZSTD_cParameter::ZSTD_c_strategy)?;
+    if strategy != 0 {
+        cparams.strategy = int_to_strategy(strategy as _)?;
+    }
+
+    Ok(unsafe { zstd_sys::ZSTD_adjustCParams(cparams, 0, 0) })
+}
+
 // Surely there is a better way...
 pub(crate) fn int_to_strategy(value: u32) -> Result<zstd_sys::ZSTD_strategy, PyErr> {
     if zstd_sys::ZSTD_strategy::ZSTD_fast as u32 == value {
@@ -202,6 +310,33 @@ pub(crate) fn int_to_strategy(value: u32) -> Result<zstd_sys::ZSTD_strategy, PyE
     }
 }
 
+/// Resolve a frame format from a `FORMAT_*` constant or its name.
+///
+/// `"zstd1"` and `"magic-less"` are accepted in addition to the integer
+/// constants.
+pub(crate) fn value_to_format(value: &Bound<'_, PyAny>) -> PyResult<zstd_sys::ZSTD_format_e> {
+    if let Ok(name) = value.extract::<&str>() {
+        match name {
+            "zstd1" => Ok(zstd_sys::ZSTD_format_e::ZSTD_f_zstd1),
+            "magic-less" | "magicless" => Ok(zstd_sys::ZSTD_format_e::ZSTD_f_zstd1_magicless),
+            _ => Err(PyValueError::new_err(format!(
+                "invalid format value: {}; must be zstd1 or magic-less",
+                name
+            ))),
+        }
+    } else {
+        let value = value.extract::<u32>()?;
+
+        if value == zstd_sys::ZSTD_format_e::ZSTD_f_zstd1 as u32 {
+            Ok(zstd_sys::ZSTD_format_e::ZSTD_f_zstd1)
+        } else if value == zstd_sys::ZSTD_format_e::ZSTD_f_zstd1_magicless as u32 {
+            Ok(zstd_sys::ZSTD_format_e::ZSTD_f_zstd1_magicless)
+        } else {
+            Err(PyValueError::new_err("invalid format value"))
+        }
+    }
+}
+
 #[pyclass(module = "zstandard.backend_rust")]
 pub struct ZstdCompressionParameters {
     pub(crate) params: *mut zstd_sys::ZSTD_CCtx_params,
@@ -231,12 +366,18 @@ impl ZstdCompressionParameters {
             return Err(ZstdError::new_err(format!(
                 "unable to retrieve parameter: {}",
                 zstd_safe::get_error_name(zresult)
-            )));
+            ))
+            .with_zstd_error(&ZstdFailure::from_result(zresult)));
         }
 
         Ok(value)
     }
 
+    /// Compression parameters zstd will use for input of unknown size.
+    fn effective_cparams(&self) -> PyResult<zstd_sys::ZSTD_compressionParameters> {
+        effective_cparams(self.params)
+    }
+
     fn set_parameter(&self, param: zstd_sys::ZSTD_cParameter, value: i32) -> PyResult<()> {
         let zresult = unsafe { zstd_sys::ZSTD_CCtxParams_setParameter(self.params, param, value) };
 
@@ -244,7 +385,8 @@ impl ZstdCompressionParameters {
             return Err(ZstdError::new_err(format!(
                 "unable to set compression context parameter: {}",
                 zstd_safe::get_error_name(zresult)
-            )));
+            ))
+            .with_zstd_error(&ZstdFailure::from_result(zresult)));
         }
 
         Ok(())
@@ -277,6 +419,8 @@ impl ZstdCompressionParameters {
         let mut ldm_bucket_size_log = 0;
         let mut ldm_hash_rate_log = -1;
         let mut threads = 0;
+        let mut target_cblock_size = 0;
+        let mut enable_dedicated_dict_search = 0;
 
         for (key, value) in kwargs.iter() {
             let key = key.extract::<String>()?;
@@ -303,6 +447,10 @@ impl ZstdCompressionParameters {
                 "ldm_bucket_size_log" => ldm_bucket_size_log = value.extract::<_>()?,
                 "ldm_hash_rate_log" => ldm_hash_rate_log = value.extract::<_>()?,
                 "threads" => threads = value.extract::<_>()?,
+                "target_cblock_size" => target_cblock_size = value.extract::<_>()?,
+                "enable_dedicated_dict_search" => {
+                    enable_dedicated_dict_search = value.extract::<_>()?
+                }
                 key => {
                     return Err(PyTypeError::new_err(format!(
                         "'{}' is an invalid keyword argument",
@@ -316,6 +464,14 @@ impl ZstdCompressionParameters {
             threads = num_cpus::get() as _;
         }
 
+        validate_threads(threads)?;
+
+        // Long distance matching only finds repeats within the window, so
+        // give it a large one unless the caller picked a size.
+        if enable_ldm != 0 && window_log == 0 {
+            window_log = LDM_DEFAULT_WINDOW_LOG;
+        }
+
         // We need to set ZSTD_c_nbWorkers before ZSTD_c_jobSize and ZSTD_c_overlapLog
         // because setting ZSTD_c_nbWorkers resets the other parameters.
         self.set_parameter(zstd_sys::ZSTD_cParameter::ZSTD_c_nbWorkers, threads)?;
@@ -339,6 +495,17 @@ impl ZstdCompressionParameters {
             strategy = 0;
         }
 
+        // 0 keeps the strategy implied by the compression level. Anything else
+        // must name one of the STRATEGY_* constants.
+        let bounds =
+            unsafe { zstd_sys::ZSTD_cParam_getBounds(zstd_sys::ZSTD_cParameter::ZSTD_c_strategy) };
+        if strategy != 0 && (strategy < bounds.lowerBound || strategy > bounds.upperBound) {
+            return Err(ZstdError::new_err(format!(
+                "invalid compression strategy {}; must be between {} and {}",
+                strategy, bounds.lowerBound, bounds.upperBound
+            )));
+        }
+
         self.set_parameter(zstd_sys::ZSTD_cParameter::ZSTD_c_strategy, strategy)?;
         self.set_parameter(
             zstd_sys::ZSTD_cParameter::ZSTD_c_contentSizeFlag,
@@ -380,6 +547,33 @@ impl ZstdCompressionParameters {
             ldm_hash_rate_log,
         )?;
 
+        // 0 disables the target. zstd silently raises smaller values to its
+        // minimum, so reject them rather than ignore what was asked for.
+        let bounds = unsafe {
+            zstd_sys::ZSTD_cParam_getBounds(zstd_sys::ZSTD_cParameter::ZSTD_c_targetCBlockSize)
+        };
+        if target_cblock_size != 0
+            && (target_cblock_size < bounds.lowerBound || target_cblock_size > bounds.upperBound)
+        {
+            return Err(ZstdError::new_err(format!(
+                "invalid target_cblock_size {}; must be 0 or between {} and {}",
+                target_cblock_size, bounds.lowerBound, bounds.upperBound
+            )));
+        }
+
+        self.set_parameter(
+            zstd_sys::ZSTD_cParameter::ZSTD_c_targetCBlockSize,
+            target_cblock_size,
+        )?;
+
+        if dedicated_dict_search_supported() {
+            self.set_parameter(DEDICATED_DICT_SEARCH, enable_dedicated_dict_search)?;
+        } else if enable_dedicated_dict_search != 0 {
+            return Err(ZstdError::new_err(
+                "enable_dedicated_dict_search is not supported by the linked zstd library",
+            ));
+        }
+
         Ok(())
     }
 }
@@ -409,6 +603,15 @@ impl ZstdCompressionParameters {
 
         let level = args.get_item(0)?.extract::<i32>()?;
 
+        // zstd silently clamps levels outside its range.
+        if level < zstd_safe::min_c_level() || level > zstd_safe::max_c_level() {
+            return Err(PyValueError::new_err(format!(
+                "level must be between {} and {}",
+                zstd_safe::min_c_level(),
+                zstd_safe::max_c_level()
+            )));
+        }
+
         let source_size = if let Some(value) = kwargs.get_item("source_size")? {
             kwargs.del_item("source_size")?;
             value.extract::<u64>()?
@@ -427,7 +630,20 @@ impl ZstdCompressionParameters {
             unsafe { zstd_sys::ZSTD_getCParams(level, source_size, dict_size) };
 
         if !kwargs.contains("window_log")? {
-            kwargs.set_item("window_log", compression_params.windowLog)?;
+            let enable_ldm = match kwargs.get_item("enable_ldm")? {
+                Some(value) => value.is_truthy()?,
+                None => false,
+            };
+
+            let window_log = if enable_ldm {
+                compression_params
+                    .windowLog
+                    .max(LDM_DEFAULT_WINDOW_LOG as _)
+            } else {
+                compression_params.windowLog
+            };
+
+            kwargs.set_item("window_log", window_log)?;
         }
         if !kwargs.contains("chain_log")? {
             kwargs.set_item("chain_log", compression_params.chainLog)?;
@@ -447,6 +663,9 @@ impl ZstdCompressionParameters {
         if !kwargs.contains("strategy")? {
             kwargs.set_item("strategy", compression_params.strategy as u32)?;
         }
+        if !kwargs.contains("compression_level")? {
+            kwargs.set_item("compression_level", level)?;
+        }
 
         Self::new(py, &PyTuple::empty(py), Some(&kwargs))
     }
@@ -488,37 +707,37 @@ impl ZstdCompressionParameters {
 
     #[getter]
     fn window_log(&self) -> PyResult<c_int> {
-        self.get_parameter(zstd_sys::ZSTD_cParameter::ZSTD_c_windowLog)
+        Ok(self.effective_cparams()?.windowLog as _)
     }
 
     #[getter]
     fn hash_log(&self) -> PyResult<c_int> {
-        self.get_parameter(zstd_sys::ZSTD_cParameter::ZSTD_c_hashLog)
+        Ok(self.effective_cparams()?.hashLog as _)
     }
 
     #[getter]
     fn chain_log(&self) -> PyResult<c_int> {
-        self.get_parameter(zstd_sys::ZSTD_cParameter::ZSTD_c_chainLog)
+        Ok(self.effective_cparams()?.chainLog as _)
     }
 
     #[getter]
     fn search_log(&self) -> PyResult<c_int> {
-        self.get_parameter(zstd_sys::ZSTD_cParameter::ZSTD_c_searchLog)
+        Ok(self.effective_cparams()?.searchLog as _)
     }
 
     #[getter]
     fn min_match(&self) -> PyResult<c_int> {
-        self.get_parameter(zstd_sys::ZSTD_cParameter::ZSTD_c_minMatch)
+        Ok(self.effective_cparams()?.minMatch as _)
     }
 
     #[getter]
     fn target_length(&self) -> PyResult<c_int> {
-        self.get_parameter(zstd_sys::ZSTD_cParameter::ZSTD_c_targetLength)
+        Ok(self.effective_cparams()?.targetLength as _)
     }
 
     #[getter]
     fn strategy(&self) -> PyResult<c_int> {
-        self.get_parameter(zstd_sys::ZSTD_cParameter::ZSTD_c_strategy)
+        Ok(self.effective_cparams()?.strategy as _)
     }
 
     #[getter]
@@ -581,6 +800,60 @@ impl ZstdCompressionParameters {
         self.get_parameter(zstd_sys::ZSTD_cParameter::ZSTD_c_jobSize)
     }
 
+    #[getter]
+    fn target_cblock_size(&self) -> PyResult<c_int> {
+        self.get_parameter(zstd_sys::ZSTD_cParameter::ZSTD_c_targetCBlockSize)
+    }
+
+    #[getter]
+    fn enable_dedicated_dict_search(&self) -> PyResult<c_int> {
+        if dedicated_dict_search_supported() {
+            self.get_parameter(DEDICATED_DICT_SEARCH)
+        } else {
+            Ok(0)
+        }
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        let fields = [
+            ("format", self.format()?),
+            ("compression_level", self.compression_level()?),
+            ("window_log", self.window_log()?),
+            ("hash_log", self.hash_log()?),
+            ("chain_log", self.chain_log()?),
+            ("search_log", self.search_log()?),
+            ("min_match", self.min_match()?),
+            ("target_length", self.target_length()?),
+            ("strategy", self.strategy()?),
+            ("write_content_size", self.write_content_size()?),
+            ("write_checksum", self.write_checksum()?),
+            ("write_dict_id", self.write_dict_id()?),
+            ("job_size", self.job_size()?),
+            ("overlap_log", self.overlap_log()?),
+            ("force_max_window", self.force_max_window()?),
+            ("enable_ldm", self.enable_ldm()?),
+            ("ldm_hash_log", self.ldm_hash_log()?),
+            ("ldm_min_match", self.ldm_min_match()?),
+            ("ldm_bucket_size_log", self.ldm_bucket_size_log()?),
+            ("ldm_hash_rate_log", self.ldm_hash_rate_log()?),
+            ("threads", self.threads()?),
+            ("target_cblock_size", self.target_cblock_size()?),
+            (
+                "enable_dedicated_dict_search",
+                self.enable_dedicated_dict_search()?,
+            ),
+        ];
+
+        Ok(format!(
+            "ZstdCompressionParameters({})",
+            fields
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+    }
+
     fn estimated_compression_context_size(&self) -> PyResult<usize> {
         let size = unsafe { zstd_sys::ZSTD_estimateCCtxSize_usingCCtxParams(self.params) };
 
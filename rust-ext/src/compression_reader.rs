@@ -6,12 +6,12 @@
 
 use {
     crate::{
-        exceptions::ZstdError,
+        buffers::DataBuffer,
+        exceptions::{WithZstdError, ZstdCompressionError},
         stream::{make_in_buffer_source, InBufferSource},
         zstd_safe::CCtx,
     },
     pyo3::{
-        buffer::PyBuffer,
         exceptions::{PyOSError, PyValueError},
         prelude::*,
         types::{PyBytes, PyList},
@@ -41,18 +41,17 @@ impl ZstdCompressionReader {
         read_size: usize,
         closefd: bool,
     ) -> PyResult<Self> {
-        let source = make_in_buffer_source(py, reader, read_size)?;
+        let mut source = make_in_buffer_source(py, reader, read_size)?;
+        source.allow_pending();
 
         let size = match source.source_size() {
             Some(size) => size as _,
             None => size,
         };
 
-        cctx.set_pledged_source_size(size).or_else(|msg| {
-            Err(ZstdError::new_err(format!(
-                "error setting source size: {}",
-                msg
-            )))
+        cctx.set_pledged_source_size(size).map_err(|msg| {
+            ZstdCompressionError::new_err(format!("error setting source size: {}", msg))
+                .with_zstd_error(&msg)
         })?;
 
         Ok(Self {
@@ -83,7 +82,10 @@ impl ZstdCompressionReader {
                     &mut in_buffer,
                     zstd_sys::ZSTD_EndDirective::ZSTD_e_continue,
                 )
-                .map_err(|msg| ZstdError::new_err(format!("zstd compress error: {}", msg)))?;
+                .map_err(|msg| {
+                    ZstdCompressionError::new_err(format!("zstd compress error: {}", msg))
+                        .with_zstd_error(&msg)
+                })?;
 
             self.bytes_compressed += out_buffer.pos - old_out_pos;
             self.source.record_bytes_read(in_buffer.pos - old_in_pos);
@@ -222,6 +224,16 @@ impl ZstdCompressionReader {
         self.closed
     }
 
+    /// Whether the source is exhausted and the end of the frame was returned.
+    ///
+    /// Reads return no data exactly when this is true, except when the source
+    /// is a non-blocking object whose `read()` returned `None` because no data
+    /// was available yet.
+    #[getter]
+    fn at_eof(&self) -> bool {
+        self.finished_output
+    }
+
     fn tell(&self) -> usize {
         self.bytes_compressed
     }
@@ -267,8 +279,9 @@ impl ZstdCompressionReader {
         let mut dest_buffer: Vec<u8> = Vec::with_capacity(size as _);
 
         while !self.source.finished() {
-            // If the output buffer is full, return its content.
-            if self.compress_into_vec(py, &mut dest_buffer)? {
+            // If the output buffer is full, return its content. Likewise if
+            // the source has no data available yet.
+            if self.compress_into_vec(py, &mut dest_buffer)? || self.source.pending() {
                 // TODO avoid buffer copy.
                 return Ok(PyBytes::new(py, &dest_buffer).into_any());
             }
@@ -292,7 +305,8 @@ impl ZstdCompressionReader {
                 zstd_sys::ZSTD_EndDirective::ZSTD_e_end,
             )
             .map_err(|msg| {
-                ZstdError::new_err(format!("error ending compression stream: {}", msg))
+                ZstdCompressionError::new_err(format!("error ending compression stream: {}", msg))
+                    .with_zstd_error(&msg)
             })?;
 
         self.bytes_compressed += dest_buffer.len() - old_pos;
@@ -341,14 +355,17 @@ impl ZstdCompressionReader {
         // Read data until we exhaust input or have output data.
         while !self.source.finished() && dest_buffer.is_empty() {
             self.compress_into_vec(py, &mut dest_buffer)?;
+
+            if self.source.pending() {
+                break;
+            }
         }
 
         // We return immediately if:
         // a) output buffer is full
-        // b) output buffer has data and input isn't exhausted.
-        if dest_buffer.len() == dest_buffer.capacity()
-            || (!dest_buffer.is_empty() && !self.source.finished())
-        {
+        // b) input isn't exhausted, so the output buffer has data or the
+        //    source has no data available yet.
+        if dest_buffer.len() == dest_buffer.capacity() || !self.source.finished() {
             // TODO avoid buffer copy.
             return Ok(PyBytes::new(py, &dest_buffer).into_any());
         }
@@ -370,7 +387,8 @@ impl ZstdCompressionReader {
                 zstd_sys::ZSTD_EndDirective::ZSTD_e_end,
             )
             .map_err(|msg| {
-                ZstdError::new_err(format!("error ending compression stream: {}", msg))
+                ZstdCompressionError::new_err(format!("error ending compression stream: {}", msg))
+                    .with_zstd_error(&msg)
             })?;
 
         self.bytes_compressed += dest_buffer.len() - old_pos;
@@ -383,7 +401,7 @@ impl ZstdCompressionReader {
         Ok(PyBytes::new(py, &dest_buffer).into_any())
     }
 
-    fn readinto(&mut self, py: Python, buffer: PyBuffer<u8>) -> PyResult<usize> {
+    fn readinto(&mut self, py: Python, buffer: DataBuffer) -> PyResult<usize> {
         if buffer.readonly() {
             return Err(PyValueError::new_err("buffer is not writable"));
         }
@@ -392,7 +410,9 @@ impl ZstdCompressionReader {
             return Err(PyValueError::new_err("stream is closed"));
         }
 
-        if self.finished_output {
+        // zstd stops consuming input once it has no room to emit output, so
+        // an empty buffer would never see the source finish.
+        if self.finished_output || buffer.len_bytes() == 0 {
             return Ok(0);
         }
 
@@ -403,7 +423,7 @@ impl ZstdCompressionReader {
         };
 
         while !self.source.finished() {
-            if self.compress_into_buffer(py, &mut out_buffer)? {
+            if self.compress_into_buffer(py, &mut out_buffer)? || self.source.pending() {
                 return Ok(out_buffer.pos);
             }
         }
@@ -425,7 +445,8 @@ impl ZstdCompressionReader {
                 zstd_sys::ZSTD_EndDirective::ZSTD_e_end,
             )
             .map_err(|msg| {
-                ZstdError::new_err(format!("error ending compression stream: {}", msg))
+                ZstdCompressionError::new_err(format!("error ending compression stream: {}", msg))
+                    .with_zstd_error(&msg)
             })?;
 
         self.bytes_compressed += out_buffer.pos - old_pos;
@@ -437,7 +458,7 @@ impl ZstdCompressionReader {
         Ok(out_buffer.pos)
     }
 
-    fn readinto1(&mut self, py: Python, buffer: PyBuffer<u8>) -> PyResult<usize> {
+    fn readinto1(&mut self, py: Python, buffer: DataBuffer) -> PyResult<usize> {
         if buffer.readonly() {
             return Err(PyValueError::new_err("buffer is not writable"));
         }
@@ -446,7 +467,9 @@ impl ZstdCompressionReader {
             return Err(PyValueError::new_err("stream is closed"));
         }
 
-        if self.finished_output {
+        // zstd stops consuming input once it has no room to emit output, so
+        // an empty buffer would never see the source finish.
+        if self.finished_output || buffer.len_bytes() == 0 {
             return Ok(0);
         }
 
@@ -459,6 +482,10 @@ impl ZstdCompressionReader {
         // Read until we get output.
         while out_buffer.pos == 0 && !self.source.finished() {
             self.compress_into_buffer(py, &mut out_buffer)?;
+
+            if self.source.pending() {
+                break;
+            }
         }
 
         // If we still have input, return immediately.
@@ -483,7 +510,8 @@ impl ZstdCompressionReader {
                 zstd_sys::ZSTD_EndDirective::ZSTD_e_end,
             )
             .map_err(|msg| {
-                ZstdError::new_err(format!("error ending compression stream: {}", msg))
+                ZstdCompressionError::new_err(format!("error ending compression stream: {}", msg))
+                    .with_zstd_error(&msg)
             })?;
 
         self.bytes_compressed += out_buffer.pos - old_pos;
@@ -5,10 +5,20 @@
 // of the BSD license. See the LICENSE file for details.
 
 use {
-    crate::{exceptions::ZstdError, zstd_safe::CCtx},
+    crate::{
+        buffers::DataBuffer,
+        compressor_iterator::compress_into,
+        constants::{
+            FLUSH_BLOCK, FLUSH_FRAME, SEEKABLE_FOOTER_SIZE, SEEKABLE_MAGIC_NUMBER,
+            SEEKABLE_SKIPPABLE_MAGIC_NUMBER,
+        },
+        exceptions::{WithZstdError, ZstdCompressionError},
+        frame_progression::FrameProgression,
+        plaintext_hash::PlaintextHasher,
+        zstd_safe::{compress_bound, CCtx, ZstdFailure},
+    },
     pyo3::{
-        buffer::PyBuffer,
-        exceptions::{PyNotImplementedError, PyOSError, PyValueError},
+        exceptions::{PyOSError, PyValueError},
         prelude::*,
         types::PyBytes,
         IntoPyObjectExt,
@@ -16,9 +26,6 @@ use {
     std::sync::Arc,
 };
 
-const FLUSH_BLOCK: usize = 0;
-const FLUSH_FRAME: usize = 1;
-
 #[pyclass(module = "zstandard.backend_rust")]
 pub struct ZstdCompressionWriter {
     cctx: Arc<CCtx<'static>>,
@@ -29,12 +36,30 @@ pub struct ZstdCompressionWriter {
     closing: bool,
     closed: bool,
     bytes_compressed: usize,
+    /// Source size pledged for the current frame, if any.
+    source_size: Option<u64>,
+    /// Input bytes written to the current frame.
+    bytes_read: u64,
+    write_size: usize,
+    /// Compressed bytes to accumulate before writing to `writer`.
+    min_flush_size: usize,
+    /// Whether `close()` calls `flush()` on `writer`.
+    flush_on_close: bool,
+    /// Whether the end of the frame was written and no input followed.
+    frame_ended: bool,
     dest_buffer: Vec<u8>,
+    /// Hashes the data written, if a hash was requested.
+    hasher: Option<PlaintextHasher>,
+    /// Digest of the data written, computed on close.
+    plaintext_digest: Option<Py<PyBytes>>,
+    /// Whether `cctx` references our input and output buffers directly.
+    stable_buffers: bool,
 }
 
 unsafe impl Sync for ZstdCompressionWriter {}
 
 impl ZstdCompressionWriter {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         py: Python,
         cctx: Arc<CCtx<'static>>,
@@ -43,9 +68,15 @@ impl ZstdCompressionWriter {
         write_size: usize,
         write_return_read: bool,
         closefd: bool,
+        min_flush_size: usize,
+        flush_on_close: bool,
+        hasher: Option<PlaintextHasher>,
+        stable_buffers: bool,
     ) -> PyResult<Self> {
-        cctx.set_pledged_source_size(source_size)
-            .map_err(|msg| ZstdError::new_err(format!("error setting source size: {}", msg)))?;
+        cctx.set_pledged_source_size(source_size).map_err(|msg| {
+            ZstdCompressionError::new_err(format!("error setting source size: {}", msg))
+                .with_zstd_error(&msg)
+        })?;
 
         Ok(Self {
             cctx,
@@ -56,9 +87,157 @@ impl ZstdCompressionWriter {
             closing: false,
             closed: false,
             bytes_compressed: 0,
+            source_size: (source_size != zstd_safe::CONTENTSIZE_UNKNOWN).then_some(source_size),
+            bytes_read: 0,
+            write_size,
+            min_flush_size,
+            flush_on_close,
+            frame_ended: false,
             dest_buffer: Vec::with_capacity(write_size),
+            hasher,
+            plaintext_digest: None,
+            stable_buffers,
         })
     }
+
+    /// Compress `in_buffer` into the output buffer, growing it as needed.
+    ///
+    /// The GIL is released while zstd runs.
+    fn compress_input(
+        &mut self,
+        py: Python,
+        in_buffer: &mut zstd_sys::ZSTD_inBuffer,
+        end_mode: zstd_sys::ZSTD_EndDirective,
+    ) -> PyResult<usize> {
+        // Output is retained across calls when min_flush_size is set, so
+        // always leave room for a full chunk.
+        self.dest_buffer.reserve(self.write_size);
+
+        let source: &[u8] = if in_buffer.pos < in_buffer.size {
+            unsafe {
+                std::slice::from_raw_parts(
+                    (in_buffer.src as *const u8).add(in_buffer.pos),
+                    in_buffer.size - in_buffer.pos,
+                )
+            }
+        } else {
+            &[]
+        };
+
+        let (cctx, dest) = (&self.cctx, &mut self.dest_buffer);
+        let (consumed, zresult) = py
+            .allow_threads(|| compress_into(cctx, dest, source, end_mode))
+            .map_err(|msg| {
+                ZstdCompressionError::new_err(format!("zstd compress error: {}", msg))
+                    .with_zstd_error(&msg)
+            })?;
+
+        in_buffer.pos += consumed;
+
+        Ok(zresult)
+    }
+
+    /// Compress `buffer` as a complete frame for a context using stable
+    /// buffers.
+    ///
+    /// zstd references both buffers directly until the frame ends, so they
+    /// must not move or change before then. The frame ends before this
+    /// returns and the output buffer is sized for the whole frame up front,
+    /// so neither is reallocated mid-frame. Writable buffers are compressed
+    /// with the GIL held so other threads can't modify them in the meantime.
+    /// Without a GIL, as on free-threaded builds, zstd compresses a private
+    /// copy of them instead.
+    fn write_frame(&mut self, py: Python, buffer: &DataBuffer) -> PyResult<usize> {
+        let copy = if !buffer.readonly() && gil_disabled(py)? {
+            Some(buffer.as_slice().to_vec())
+        } else {
+            None
+        };
+        let source = copy.as_deref().unwrap_or_else(|| buffer.as_slice());
+
+        // A frame can't span writes, so the pledged size must arrive at once.
+        if let Some(size) = self.source_size {
+            if source.len() as u64 != size {
+                return Err(ZstdCompressionError::new_err(format!(
+                    "stable_buffers requires each frame in a single write(); \
+                     pledged {} bytes but wrote {}",
+                    size,
+                    source.len()
+                )));
+            }
+        }
+
+        let bound = compress_bound(source.len())
+            .ok_or_else(|| ZstdCompressionError::new_err("input size is too large to compress"))?;
+        self.dest_buffer.reserve(bound);
+
+        let (cctx, dest) = (&self.cctx, &mut self.dest_buffer);
+        let mut compress = || {
+            let mut in_buffer = zstd_sys::ZSTD_inBuffer {
+                src: source.as_ptr() as *const _,
+                size: source.len(),
+                pos: 0,
+            };
+
+            loop {
+                let zresult = cctx.compress_into_vec(
+                    dest,
+                    &mut in_buffer,
+                    zstd_sys::ZSTD_EndDirective::ZSTD_e_end,
+                )?;
+
+                if zresult == 0 {
+                    return Ok(());
+                }
+            }
+        };
+
+        if buffer.readonly() || copy.is_some() {
+            py.allow_threads(compress)
+        } else {
+            compress()
+        }
+        .map_err(|msg: ZstdFailure| {
+            ZstdCompressionError::new_err(format!("zstd compress error: {}", msg))
+                .with_zstd_error(&msg)
+        })?;
+
+        self.source_size = None;
+        self.bytes_read = 0;
+        self.frame_ended = true;
+
+        if let Some(hasher) = &mut self.hasher {
+            hasher.update(py, source)?;
+        }
+
+        let written = self.write_output(py, false)?;
+
+        if self.write_return_read {
+            Ok(source.len())
+        } else {
+            Ok(written)
+        }
+    }
+
+    /// Write buffered compressed data to the wrapped writer.
+    ///
+    /// Unless `force` is set, nothing is written until at least
+    /// `min_flush_size` bytes are buffered. Returns the number of bytes written.
+    fn write_output(&mut self, py: Python, force: bool) -> PyResult<usize> {
+        if self.dest_buffer.is_empty() || !force && self.dest_buffer.len() < self.min_flush_size {
+            return Ok(0);
+        }
+
+        // TODO avoid buffer copy.
+        let chunk = PyBytes::new(py, &self.dest_buffer);
+        self.writer.call_method1(py, "write", (chunk,))?;
+
+        let written = self.dest_buffer.len();
+        self.bytes_compressed += written;
+        self.dest_buffer.clear();
+
+        Ok(written)
+    }
 }
 
 #[pymethods]
@@ -85,7 +264,9 @@ impl ZstdCompressionWriter {
         if slf.closed {
             Err(PyValueError::new_err("stream is closed"))
         } else if slf.entered {
-            Err(ZstdError::new_err("cannot __enter__ multiple times"))
+            Err(ZstdCompressionError::new_err(
+                "cannot __enter__ multiple times",
+            ))
         } else {
             slf.entered = true;
             Ok(slf)
@@ -100,6 +281,8 @@ impl ZstdCompressionWriter {
         _exc_tb: &Bound<'_, PyAny>,
     ) -> PyResult<bool> {
         slf.entered = false;
+        // The byte count from close() can't be returned here, since a truthy
+        // value would suppress exceptions. tell() reports the final total.
         slf.close(py)?;
 
         // TODO clear out compressor context?
@@ -121,26 +304,46 @@ impl ZstdCompressionWriter {
         }
     }
 
-    fn close(&mut self, py: Python) -> PyResult<()> {
+    /// End the frame and close the writer.
+    ///
+    /// Returns the number of compressed bytes written to finish the frame, or
+    /// 0 if the writer was already closed.
+    fn close(&mut self, py: Python) -> PyResult<usize> {
         if self.closed {
-            return Ok(());
+            return Ok(0);
+        }
+
+        // All data has been written, so the digest is final.
+        if let Some(hasher) = self.hasher.take() {
+            self.plaintext_digest = Some(hasher.digest(py)?.unbind());
         }
 
         self.closing = true;
-        let res = self.flush(py, FLUSH_FRAME);
+        let res = self.flush(py, Some(FLUSH_FRAME), false);
         self.closing = false;
         self.closed = true;
 
-        res?;
-
-        // Call close() on underlying stream as well.
-        if let Ok(close) = self.writer.getattr(py, "close") {
-            if self.closefd {
-                close.call0(py)?;
+        let res = res.and_then(|written| {
+            if self.flush_on_close {
+                if let Ok(flush) = self.writer.getattr(py, "flush") {
+                    flush.call0(py)?;
+                }
             }
-        }
 
-        Ok(())
+            Ok(written)
+        });
+
+        // Call close() on underlying stream as well. This happens even if
+        // ending the frame failed so the stream isn't leaked.
+        let close_res = match self.writer.getattr(py, "close") {
+            Ok(close) if self.closefd => close.call0(py).map(|_| ()),
+            _ => Ok(()),
+        };
+
+        let written = res?;
+        close_res?;
+
+        Ok(written)
     }
 
     #[getter]
@@ -148,6 +351,34 @@ impl ZstdCompressionWriter {
         self.closed
     }
 
+    /// Digest of all data written, as computed by the algorithm passed as
+    /// `hash` to `stream_writer()`.
+    ///
+    /// The data is hashed before compression, across every frame written.
+    /// The digest is only available once the writer is closed.
+    fn plaintext_digest(&self, py: Python) -> PyResult<Py<PyBytes>> {
+        if let Some(digest) = &self.plaintext_digest {
+            Ok(digest.clone_ref(py))
+        } else if self.hasher.is_some() {
+            Err(PyValueError::new_err(
+                "plaintext digest is not available until the writer is closed",
+            ))
+        } else {
+            Err(PyValueError::new_err(
+                "no hash algorithm was passed to stream_writer()",
+            ))
+        }
+    }
+
+    /// Whether the end of the frame was written and no data followed.
+    ///
+    /// True after `close()` or a `FLUSH_FRAME` flush until data is written
+    /// again, starting a new frame.
+    #[getter]
+    fn at_eof(&self) -> bool {
+        self.frame_ended
+    }
+
     fn isatty(&self) -> bool {
         false
     }
@@ -200,9 +431,13 @@ impl ZstdCompressionWriter {
         true
     }
 
-    #[allow(unused_variables)]
-    fn writelines(&self, lines: &Bound<'_, PyAny>) -> PyResult<()> {
-        Err(PyNotImplementedError::new_err(()))
+    /// Write each item of `lines`, consuming the iterable lazily.
+    fn writelines(&mut self, py: Python, lines: &Bound<'_, PyAny>) -> PyResult<()> {
+        for line in lines.try_iter()? {
+            self.write(py, line?.extract::<DataBuffer>()?)?;
+        }
+
+        Ok(())
     }
 
     #[pyo3(signature = (size=None))]
@@ -229,13 +464,27 @@ impl ZstdCompressionWriter {
         Err(PyErr::from_value(exc))
     }
 
-    fn write(&mut self, py: Python, buffer: PyBuffer<u8>) -> PyResult<usize> {
+    fn write(&mut self, py: Python, buffer: DataBuffer) -> PyResult<usize> {
         if self.closed {
             return Err(PyValueError::new_err("stream is closed"));
         }
 
+        if self.stable_buffers {
+            return if buffer.len_bytes() > 0 {
+                self.write_frame(py, &buffer)
+            } else {
+                Ok(0)
+            };
+        }
+
         let mut total_write = 0;
 
+        self.bytes_read += buffer.len_bytes() as u64;
+
+        if buffer.len_bytes() > 0 {
+            self.frame_ended = false;
+        }
+
         let mut in_buffer = zstd_sys::ZSTD_inBuffer {
             src: buffer.buf_ptr(),
             size: buffer.len_bytes(),
@@ -243,23 +492,41 @@ impl ZstdCompressionWriter {
         };
 
         while in_buffer.pos < in_buffer.size {
-            self.cctx
-                .compress_into_vec(
-                    &mut self.dest_buffer,
-                    &mut in_buffer,
-                    zstd_sys::ZSTD_EndDirective::ZSTD_e_continue,
-                )
-                .map_err(|msg| ZstdError::new_err(format!("zstd compress error: {}", msg)))?;
-
-            if !self.dest_buffer.is_empty() {
-                // TODO avoid buffer copy.
-                let chunk = PyBytes::new(py, &self.dest_buffer);
-                self.writer.call_method1(py, "write", (chunk,))?;
+            let (source_size, bytes_read) = (self.source_size, self.bytes_read);
+
+            self.compress_input(
+                py,
+                &mut in_buffer,
+                zstd_sys::ZSTD_EndDirective::ZSTD_e_continue,
+            )
+            .map_err(|err| match source_size {
+                // zstd rejects input beyond the pledged size without
+                // saying by how much.
+                Some(size) if bytes_read > size => {
+                    let new_err = ZstdCompressionError::new_err(format!(
+                        "{}; pledged {} bytes but wrote {}",
+                        err.value(py),
+                        size,
+                        bytes_read
+                    ));
+
+                    // Carry over the zstd error recorded on the original.
+                    for attr in ["code", "error_name"] {
+                        if let Ok(value) = err.value(py).getattr(attr) {
+                            let _ = new_err.value(py).setattr(attr, value);
+                        }
+                    }
+
+                    new_err
+                }
+                _ => err,
+            })?;
+
+            total_write += self.write_output(py, false)?;
+        }
 
-                total_write += self.dest_buffer.len();
-                self.bytes_compressed += self.dest_buffer.len();
-                self.dest_buffer.clear();
-            }
+        if let Some(hasher) = &mut self.hasher {
+            hasher.update(py, buffer.as_slice())?;
         }
 
         if self.write_return_read {
@@ -269,8 +536,23 @@ impl ZstdCompressionWriter {
         }
     }
 
-    #[pyo3(signature = (flush_mode=FLUSH_BLOCK))]
-    fn flush(&mut self, py: Python, flush_mode: usize) -> PyResult<usize> {
+    /// Flush buffered data, optionally ending the current frame.
+    ///
+    /// `end_frame=True` is equivalent to `flush_mode=FLUSH_FRAME`. Data
+    /// written afterwards starts a new frame.
+    #[pyo3(signature = (flush_mode=None, end_frame=false))]
+    fn flush(&mut self, py: Python, flush_mode: Option<usize>, end_frame: bool) -> PyResult<usize> {
+        let flush_mode = match (flush_mode, end_frame) {
+            (None | Some(FLUSH_FRAME), true) => FLUSH_FRAME,
+            (Some(flush_mode), true) => {
+                return Err(PyValueError::new_err(format!(
+                    "end_frame=True conflicts with flush_mode {}",
+                    flush_mode
+                )))
+            }
+            (flush_mode, false) => flush_mode.unwrap_or(FLUSH_BLOCK),
+        };
+
         let flush = match flush_mode {
             FLUSH_BLOCK => Ok(zstd_sys::ZSTD_EndDirective::ZSTD_e_flush),
             FLUSH_FRAME => Ok(zstd_sys::ZSTD_EndDirective::ZSTD_e_end),
@@ -284,6 +566,22 @@ impl ZstdCompressionWriter {
             return Err(PyValueError::new_err("stream is closed"));
         }
 
+        let end_frame = flush == zstd_sys::ZSTD_EndDirective::ZSTD_e_end;
+
+        // Catch short input before zstd writes a frame whose header claims
+        // more content than it holds. Excess input was already rejected by
+        // write().
+        if end_frame {
+            if let Some(size) = self.source_size {
+                if self.bytes_read < size {
+                    return Err(ZstdCompressionError::new_err(format!(
+                        "pledged {} bytes but wrote {}",
+                        size, self.bytes_read
+                    )));
+                }
+            }
+        }
+
         let mut total_write = 0;
 
         let mut in_buffer = zstd_sys::ZSTD_inBuffer {
@@ -292,25 +590,27 @@ impl ZstdCompressionWriter {
             pos: 0,
         };
 
-        loop {
-            let zresult = self
-                .cctx
-                .compress_into_vec(&mut self.dest_buffer, &mut in_buffer, flush)
-                .map_err(|msg| ZstdError::new_err(format!("zstd compress error: {}", msg)))?;
+        // With stable buffers, every write() ends its frame, leaving nothing
+        // to flush except an empty frame if nothing was written.
+        if self.stable_buffers && (self.frame_ended || !end_frame) {
+            total_write += self.write_output(py, true)?;
+        } else {
+            loop {
+                let zresult = self.compress_input(py, &mut in_buffer, flush)?;
 
-            if !self.dest_buffer.is_empty() {
-                // TODO avoid buffer copy.
-                let chunk = PyBytes::new(py, &self.dest_buffer);
-                self.writer.call_method1(py, "write", (chunk,))?;
+                total_write += self.write_output(py, true)?;
 
-                total_write += self.dest_buffer.len();
-                self.bytes_compressed += self.dest_buffer.len();
-                self.dest_buffer.clear();
+                if zresult == 0 {
+                    break;
+                }
             }
+        }
 
-            if zresult == 0 {
-                break;
-            }
+        // Later frames have no pledged size.
+        if end_frame {
+            self.source_size = None;
+            self.bytes_read = 0;
+            self.frame_ended = true;
         }
 
         if let Ok(flush) = self.writer.getattr(py, "flush") {
@@ -325,4 +625,351 @@ impl ZstdCompressionWriter {
     fn tell(&self) -> usize {
         self.bytes_compressed
     }
+
+    fn frame_progression(&self) -> PyResult<FrameProgression> {
+        if self.closed {
+            return Err(ZstdCompressionError::new_err("stream is closed"));
+        }
+
+        Ok(self.cctx.get_frame_progression().into())
+    }
+}
+
+/// Whether the interpreter is running without a GIL.
+///
+/// Free-threaded builds may disable the GIL; other builds always have one.
+fn gil_disabled(py: Python) -> PyResult<bool> {
+    let sys = py.import("sys")?;
+
+    if sys.hasattr("_is_gil_enabled")? {
+        Ok(!sys.call_method0("_is_gil_enabled")?.extract::<bool>()?)
+    } else {
+        Ok(false)
+    }
+}
+
+/// An entry in the seek table describing a single frame.
+struct SeekTableEntry {
+    compressed_size: u32,
+    decompressed_size: u32,
+    checksum: u32,
+}
+
+/// Writes data using the zstd seekable format.
+///
+/// Input is compressed into independent frames of at most `max_frame_size`
+/// decompressed bytes. A skippable frame holding the seek table is appended
+/// when the writer is closed.
+#[pyclass(module = "zstandard.backend_rust")]
+pub struct SeekableCompressionWriter {
+    cctx: Arc<CCtx<'static>>,
+    writer: PyObject,
+    closefd: bool,
+    entered: bool,
+    closed: bool,
+    max_frame_size: usize,
+    write_checksum: bool,
+    frame_decompressed_size: usize,
+    frame_compressed_size: usize,
+    /// Trailing bytes of the current frame. Holds the frame checksum once
+    /// the frame is ended.
+    frame_tail: [u8; 4],
+    entries: Vec<SeekTableEntry>,
+    bytes_compressed: usize,
+    dest_buffer: Vec<u8>,
+}
+
+unsafe impl Sync for SeekableCompressionWriter {}
+
+impl SeekableCompressionWriter {
+    pub fn new(
+        py: Python,
+        cctx: Arc<CCtx<'static>>,
+        writer: &Bound<'_, PyAny>,
+        max_frame_size: usize,
+        write_size: usize,
+        write_checksum: bool,
+        closefd: bool,
+    ) -> PyResult<Self> {
+        cctx.set_pledged_source_size(zstd_safe::CONTENTSIZE_UNKNOWN)
+            .map_err(|msg| {
+                ZstdCompressionError::new_err(format!("error setting source size: {}", msg))
+                    .with_zstd_error(&msg)
+            })?;
+
+        Ok(Self {
+            cctx,
+            writer: writer.into_py_any(py)?,
+            closefd,
+            entered: false,
+            closed: false,
+            max_frame_size,
+            write_checksum,
+            frame_decompressed_size: 0,
+            frame_compressed_size: 0,
+            frame_tail: [0; 4],
+            entries: vec![],
+            bytes_compressed: 0,
+            dest_buffer: Vec::with_capacity(write_size),
+        })
+    }
+
+    /// Send pending output to the underlying writer.
+    fn write_output(&mut self, py: Python) -> PyResult<()> {
+        if self.dest_buffer.is_empty() {
+            return Ok(());
+        }
+
+        let chunk = PyBytes::new(py, &self.dest_buffer);
+        self.writer.call_method1(py, "write", (chunk,))?;
+
+        let tail_len = self.dest_buffer.len().min(4);
+        self.frame_tail.rotate_left(tail_len);
+        self.frame_tail[4 - tail_len..]
+            .copy_from_slice(&self.dest_buffer[self.dest_buffer.len() - tail_len..]);
+
+        self.frame_compressed_size += self.dest_buffer.len();
+        self.bytes_compressed += self.dest_buffer.len();
+        self.dest_buffer.clear();
+
+        Ok(())
+    }
+
+    fn compress_remaining(
+        &mut self,
+        py: Python,
+        directive: zstd_sys::ZSTD_EndDirective,
+    ) -> PyResult<()> {
+        let mut in_buffer = zstd_sys::ZSTD_inBuffer {
+            src: std::ptr::null_mut(),
+            size: 0,
+            pos: 0,
+        };
+
+        loop {
+            let zresult = self
+                .cctx
+                .compress_into_vec(&mut self.dest_buffer, &mut in_buffer, directive)
+                .map_err(|msg| {
+                    ZstdCompressionError::new_err(format!("zstd compress error: {}", msg))
+                        .with_zstd_error(&msg)
+                })?;
+
+            self.write_output(py)?;
+
+            if zresult == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    /// End the current frame and record it in the seek table.
+    fn end_frame(&mut self, py: Python) -> PyResult<()> {
+        if self.frame_decompressed_size == 0 {
+            return Ok(());
+        }
+
+        self.compress_remaining(py, zstd_sys::ZSTD_EndDirective::ZSTD_e_end)?;
+
+        let compressed_size = u32::try_from(self.frame_compressed_size).map_err(|_| {
+            ZstdCompressionError::new_err("compressed frame too large for seekable format")
+        })?;
+
+        self.entries.push(SeekTableEntry {
+            compressed_size,
+            decompressed_size: self.frame_decompressed_size as u32,
+            checksum: u32::from_le_bytes(self.frame_tail),
+        });
+
+        self.frame_decompressed_size = 0;
+        self.frame_compressed_size = 0;
+        self.frame_tail = [0; 4];
+
+        Ok(())
+    }
+
+    fn write_seek_table(&mut self, py: Python) -> PyResult<()> {
+        let entry_size = if self.write_checksum { 12 } else { 8 };
+        let frame_size = self.entries.len() * entry_size + SEEKABLE_FOOTER_SIZE;
+        let frame_size = u32::try_from(frame_size)
+            .map_err(|_| ZstdCompressionError::new_err("too many frames for seekable format"))?;
+
+        let mut table = Vec::with_capacity(8 + frame_size as usize);
+        table.extend_from_slice(&SEEKABLE_SKIPPABLE_MAGIC_NUMBER.to_le_bytes());
+        table.extend_from_slice(&frame_size.to_le_bytes());
+
+        for entry in &self.entries {
+            table.extend_from_slice(&entry.compressed_size.to_le_bytes());
+            table.extend_from_slice(&entry.decompressed_size.to_le_bytes());
+            if self.write_checksum {
+                table.extend_from_slice(&entry.checksum.to_le_bytes());
+            }
+        }
+
+        table.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+        table.push(if self.write_checksum { 0x80 } else { 0 });
+        table.extend_from_slice(&SEEKABLE_MAGIC_NUMBER.to_le_bytes());
+
+        let chunk = PyBytes::new(py, &table);
+        self.writer.call_method1(py, "write", (chunk,))?;
+        self.bytes_compressed += table.len();
+
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl SeekableCompressionWriter {
+    fn __enter__<'p>(mut slf: PyRefMut<'p, Self>, _py: Python<'p>) -> PyResult<PyRefMut<'p, Self>> {
+        if slf.closed {
+            Err(PyValueError::new_err("stream is closed"))
+        } else if slf.entered {
+            Err(ZstdCompressionError::new_err(
+                "cannot __enter__ multiple times",
+            ))
+        } else {
+            slf.entered = true;
+            Ok(slf)
+        }
+    }
+
+    fn __exit__<'p>(
+        mut slf: PyRefMut<'p, Self>,
+        py: Python<'p>,
+        _exc_type: &Bound<'_, PyAny>,
+        _exc_value: &Bound<'_, PyAny>,
+        _exc_tb: &Bound<'_, PyAny>,
+    ) -> PyResult<bool> {
+        slf.entered = false;
+        slf.close(py)?;
+
+        Ok(false)
+    }
+
+    fn memory_size(&self) -> usize {
+        self.cctx.memory_size()
+    }
+
+    fn fileno(&self, py: Python) -> PyResult<PyObject> {
+        if let Ok(fileno) = self.writer.getattr(py, "fileno") {
+            fileno.call0(py)
+        } else {
+            Err(PyOSError::new_err(
+                "fileno not available on underlying writer",
+            ))
+        }
+    }
+
+    fn close(&mut self, py: Python) -> PyResult<()> {
+        if self.closed {
+            return Ok(());
+        }
+
+        let res = self.end_frame(py).and_then(|_| self.write_seek_table(py));
+        self.closed = true;
+
+        // Close the underlying stream even if finishing the output failed.
+        let close_res = match self.writer.getattr(py, "close") {
+            Ok(close) if self.closefd => close.call0(py).map(|_| ()),
+            _ => Ok(()),
+        };
+
+        res?;
+        close_res
+    }
+
+    #[getter]
+    fn closed(&self) -> bool {
+        self.closed
+    }
+
+    fn isatty(&self) -> bool {
+        false
+    }
+
+    fn readable(&self) -> bool {
+        false
+    }
+
+    fn seekable(&self) -> bool {
+        false
+    }
+
+    fn writable(&self) -> bool {
+        true
+    }
+
+    fn write(&mut self, py: Python, buffer: DataBuffer) -> PyResult<usize> {
+        if self.closed {
+            return Err(PyValueError::new_err("stream is closed"));
+        }
+
+        let mut in_buffer = zstd_sys::ZSTD_inBuffer {
+            src: buffer.buf_ptr(),
+            size: 0,
+            pos: 0,
+        };
+
+        while in_buffer.pos < buffer.len_bytes() {
+            // Never feed the compressor more than what fits in the current frame.
+            let frame_remaining = self.max_frame_size - self.frame_decompressed_size;
+            in_buffer.size = buffer.len_bytes().min(in_buffer.pos + frame_remaining);
+
+            let start = in_buffer.pos;
+            self.cctx
+                .compress_into_vec(
+                    &mut self.dest_buffer,
+                    &mut in_buffer,
+                    zstd_sys::ZSTD_EndDirective::ZSTD_e_continue,
+                )
+                .map_err(|msg| {
+                    ZstdCompressionError::new_err(format!("zstd compress error: {}", msg))
+                        .with_zstd_error(&msg)
+                })?;
+            self.frame_decompressed_size += in_buffer.pos - start;
+
+            self.write_output(py)?;
+
+            if self.frame_decompressed_size == self.max_frame_size {
+                self.end_frame(py)?;
+            }
+        }
+
+        Ok(in_buffer.pos)
+    }
+
+    #[pyo3(signature = (flush_mode=FLUSH_BLOCK))]
+    fn flush(&mut self, py: Python, flush_mode: usize) -> PyResult<()> {
+        if self.closed {
+            return Err(PyValueError::new_err("stream is closed"));
+        }
+
+        match flush_mode {
+            FLUSH_BLOCK => {
+                self.compress_remaining(py, zstd_sys::ZSTD_EndDirective::ZSTD_e_flush)?
+            }
+            FLUSH_FRAME => self.end_frame(py)?,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "unknown flush_mode: {}",
+                    flush_mode
+                )))
+            }
+        }
+
+        if let Ok(flush) = self.writer.getattr(py, "flush") {
+            flush.call0(py)?;
+        }
+
+        Ok(())
+    }
+
+    fn tell(&self) -> usize {
+        self.bytes_compressed
+    }
+
+    /// Number of frames written so far, excluding any frame in progress.
+    fn frame_count(&self) -> usize {
+        self.entries.len()
+    }
 }
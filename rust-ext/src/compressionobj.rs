@@ -6,26 +6,33 @@
 
 use {
     crate::{
+        buffers::DataBuffer,
         constants::{COMPRESSOBJ_FLUSH_BLOCK, COMPRESSOBJ_FLUSH_FINISH},
+        exceptions::{WithZstdError, ZstdCompressionError},
+        frame_progression::FrameProgression,
         zstd_safe::CCtx,
-        ZstdError,
     },
-    pyo3::{buffer::PyBuffer, exceptions::PyValueError, prelude::*, types::PyBytes},
+    pyo3::{exceptions::PyValueError, prelude::*, types::PyBytes},
     std::sync::Arc,
 };
 
 #[pyclass(module = "zstandard.backend_rust")]
 pub struct ZstdCompressionObj {
     cctx: Arc<CCtx<'static>>,
+    /// Source size pledged to the compressor, if any.
+    source_size: Option<u64>,
+    bytes_read: u64,
     finished: bool,
 }
 
 unsafe impl Sync for ZstdCompressionObj {}
 
 impl ZstdCompressionObj {
-    pub fn new(cctx: Arc<CCtx<'static>>) -> PyResult<Self> {
+    pub fn new(cctx: Arc<CCtx<'static>>, source_size: Option<u64>) -> PyResult<Self> {
         Ok(ZstdCompressionObj {
             cctx,
+            source_size,
+            bytes_read: 0,
             finished: false,
         })
     }
@@ -33,13 +40,15 @@ impl ZstdCompressionObj {
 
 #[pymethods]
 impl ZstdCompressionObj {
-    fn compress<'p>(&self, py: Python<'p>, buffer: PyBuffer<u8>) -> PyResult<Bound<'p, PyBytes>> {
+    fn compress<'p>(&mut self, py: Python<'p>, buffer: DataBuffer) -> PyResult<Bound<'p, PyBytes>> {
         if self.finished {
-            return Err(ZstdError::new_err(
+            return Err(ZstdCompressionError::new_err(
                 "cannot call compress() after compressor finished",
             ));
         }
 
+        self.bytes_read += buffer.len_bytes() as u64;
+
         let mut source = unsafe {
             std::slice::from_raw_parts::<u8>(buffer.buf_ptr() as *const _, buffer.len_bytes())
         };
@@ -59,7 +68,22 @@ impl ZstdCompressionObj {
                         write_size,
                     )
                 })
-                .or_else(|msg| Err(ZstdError::new_err(format!("zstd compress error: {}", msg))))?;
+                .or_else(|msg| match self.source_size {
+                    // zstd rejects input beyond the pledged size without
+                    // saying by how much.
+                    Some(size) if self.bytes_read > size => {
+                        Err(ZstdCompressionError::new_err(format!(
+                            "zstd compress error: {}; expected {} bytes but received {}",
+                            msg, size, self.bytes_read
+                        ))
+                        .with_zstd_error(&msg))
+                    }
+                    _ => Err(ZstdCompressionError::new_err(format!(
+                        "zstd compress error: {}",
+                        msg
+                    ))
+                    .with_zstd_error(&msg)),
+                })?;
 
             compressed.extend(result.0);
             source = result.1;
@@ -85,10 +109,21 @@ impl ZstdCompressionObj {
         }?;
 
         if self.finished {
-            return Err(ZstdError::new_err("compressor object already finished"));
+            return Err(ZstdCompressionError::new_err(
+                "compressor object already finished",
+            ));
         }
 
         if flush_mode == zstd_sys::ZSTD_EndDirective::ZSTD_e_end {
+            if let Some(size) = self.source_size {
+                if self.bytes_read != size {
+                    return Err(ZstdCompressionError::new_err(format!(
+                        "error ending compression stream: source size mismatch; expected {} bytes but received {}",
+                        size, self.bytes_read
+                    )));
+                }
+            }
+
             self.finished = true;
         }
 
@@ -101,11 +136,12 @@ impl ZstdCompressionObj {
         loop {
             let (chunk, _, call_again) = py
                 .allow_threads(|| cctx.compress_chunk(&[], flush_mode, write_size))
-                .or_else(|msg| {
-                    Err(ZstdError::new_err(format!(
+                .map_err(|msg| {
+                    ZstdCompressionError::new_err(format!(
                         "error ending compression stream: {}",
                         msg
-                    )))
+                    ))
+                    .with_zstd_error(&msg)
                 })?;
 
             result.extend(&chunk);
@@ -115,4 +151,14 @@ impl ZstdCompressionObj {
             }
         }
     }
+
+    fn frame_progression(&self) -> PyResult<FrameProgression> {
+        if self.finished {
+            return Err(ZstdCompressionError::new_err(
+                "compressor object already finished",
+            ));
+        }
+
+        Ok(self.cctx.get_frame_progression().into())
+    }
 }
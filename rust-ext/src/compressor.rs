@@ -6,28 +6,180 @@
 
 use {
     crate::{
-        buffers::ZstdBufferWithSegmentsCollection,
+        buffers::{DataBuffer, ZstdBufferWithSegmentsCollection},
         compression_chunker::ZstdCompressionChunker,
         compression_dict::ZstdCompressionDict,
-        compression_parameters::{CCtxParams, ZstdCompressionParameters},
+        compression_parameters::{
+            effective_cparams, get_cctx_parameter, validate_threads, value_to_format, CCtxParams,
+            ZstdCompressionParameters,
+        },
         compression_reader::ZstdCompressionReader,
-        compression_writer::ZstdCompressionWriter,
+        compression_writer::{SeekableCompressionWriter, ZstdCompressionWriter},
         compressionobj::ZstdCompressionObj,
         compressor_iterator::ZstdCompressorIterator,
         compressor_multi::multi_compress_to_buffer,
+        constants::{SEEKABLE_MAX_FRAME_SIZE, SKIPPABLE_MAGIC_VARIANT_MAX},
+        exceptions::{WithZstdError, ZstdCompressionError, ZstdError},
+        plaintext_hash::PlaintextHasher,
+        stream::{make_in_buffer_source, make_iter_source},
         zstd_safe::CCtx,
-        ZstdError,
     },
-    pyo3::{buffer::PyBuffer, exceptions::PyValueError, prelude::*, types::PyBytes},
-    std::sync::Arc,
+    libc::c_int,
+    pyo3::{
+        exceptions::{PyOverflowError, PyValueError},
+        prelude::*,
+        sync::GILOnceCell,
+        types::{PyBytes, PyDict, PyList, PyType},
+        wrap_pyfunction,
+    },
+    std::{cell::RefCell, sync::Arc},
 };
 
+/// `Sequence(offset, match_length, lit_length, rep)` named tuple type.
+static SEQUENCE_TYPE: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+
+fn sequence_type<'py>(py: Python<'py>) -> PyResult<&'py Bound<'py, PyType>> {
+    SEQUENCE_TYPE
+        .get_or_try_init(py, || {
+            let kwargs = PyDict::new(py);
+            kwargs.set_item("module", "zstandard.backend_rust")?;
+
+            let namedtuple = py.import("collections")?.getattr("namedtuple")?;
+            let sequence = namedtuple.call(
+                ("Sequence", ("offset", "match_length", "lit_length", "rep")),
+                Some(&kwargs),
+            )?;
+
+            Ok::<_, PyErr>(sequence.downcast_into::<PyType>()?.unbind())
+        })
+        .map(|t| t.bind(py))
+}
+
+thread_local! {
+    /// Context reused by the module-level `compress()` on this thread.
+    ///
+    /// Tagged with the ID of the interpreter that created it so subinterpreters
+    /// taking turns on the same OS thread never share a context.
+    static ONE_SHOT_CCTX: RefCell<Option<(i64, Arc<CCtx<'static>>)>> = const { RefCell::new(None) };
+}
+
+/// ID of the interpreter the calling thread is running in.
+pub(crate) fn interpreter_id() -> i64 {
+    unsafe { pyo3_ffi::PyInterpreterState_GetID(pyo3_ffi::PyInterpreterState_Get()) }
+}
+
+fn validate_level(level: i32) -> PyResult<()> {
+    if level > zstd_safe::max_c_level() {
+        Err(PyValueError::new_err(format!(
+            "level must be less than {}",
+            zstd_safe::max_c_level() as i32 + 1
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Build a frame storing `source` in raw blocks if it's smaller than `frame`.
+///
+/// zstd already stores incompressible blocks raw, but its frame also carries
+/// metadata like the dictionary ID that raw blocks don't need. The stored
+/// frame keeps the content size and checksum settings of `params` and, having
+/// no dictionary ID, can be decoded by any decompressor.
+fn stored_frame(params: &CCtxParams, source: &[u8], frame: &[u8]) -> PyResult<Option<Vec<u8>>> {
+    let raw_params = unsafe { params.get_raw_ptr() };
+    let content_size = get_cctx_parameter(
+        raw_params,
+        zstd_sys::ZSTD_cParameter::ZSTD_c_contentSizeFlag,
+    )? != 0;
+    let checksum =
+        get_cctx_parameter(raw_params, zstd_sys::ZSTD_cParameter::ZSTD_c_checksumFlag)? != 0;
+    // ZSTD_c_format.
+    let magicless = get_cctx_parameter(
+        raw_params,
+        zstd_sys::ZSTD_cParameter::ZSTD_c_experimentalParam2,
+    )? == zstd_sys::ZSTD_format_e::ZSTD_f_zstd1_magicless as i32;
+
+    let block_size = zstd_sys::ZSTD_BLOCKSIZE_MAX as usize;
+    let block_count = std::cmp::max(1, source.len().div_ceil(block_size));
+
+    // Frame content size flag and field length. The single segment flag
+    // allows a 1 byte field and a 2 byte field is offset by 256.
+    let (fcs_flag, fcs_len) = match source.len() {
+        _ if !content_size => (0, 0),
+        0..=255 => (0, 1),
+        256..=65791 => (1, 2),
+        len if len <= u32::MAX as usize => (2, 4),
+        _ => (3, 8),
+    };
+
+    let header_len = if magicless { 0 } else { 4 } + 1 + if content_size { 0 } else { 1 } + fcs_len;
+    let stored_len = header_len + 3 * block_count + source.len() + if checksum { 4 } else { 0 };
+
+    if stored_len >= frame.len() {
+        return Ok(None);
+    }
+
+    let mut dest = Vec::with_capacity(stored_len);
+
+    if !magicless {
+        dest.extend_from_slice(&zstd_sys::ZSTD_MAGICNUMBER.to_le_bytes());
+    }
+
+    let single_segment = if content_size { 1 << 5 } else { 0 };
+    dest.push((fcs_flag << 6) | single_segment | if checksum { 1 << 2 } else { 0 });
+
+    if content_size {
+        let len = source.len() as u64;
+        match fcs_len {
+            1 => dest.push(len as u8),
+            2 => dest.extend_from_slice(&((len - 256) as u16).to_le_bytes()),
+            4 => dest.extend_from_slice(&(len as u32).to_le_bytes()),
+            _ => dest.extend_from_slice(&len.to_le_bytes()),
+        }
+    } else {
+        // Window descriptor for a window of exactly one maximum size block.
+        dest.push(((zstd_sys::ZSTD_BLOCKSIZELOG_MAX - 10) << 3) as u8);
+    }
+
+    let mut chunks = source.chunks(block_size).peekable();
+    if chunks.peek().is_none() {
+        // A frame needs at least one block, even if it is empty.
+        dest.extend_from_slice(&[1, 0, 0]);
+    }
+    while let Some(chunk) = chunks.next() {
+        // Raw block type is 0, leaving the last block flag and the size.
+        let header = ((chunk.len() as u32) << 3) | if chunks.peek().is_none() { 1 } else { 0 };
+        dest.extend_from_slice(&header.to_le_bytes()[0..3]);
+        dest.extend_from_slice(chunk);
+    }
+
+    if checksum {
+        // The checksum only covers the content, so zstd's is still valid.
+        dest.extend_from_slice(&frame[frame.len() - 4..]);
+    }
+
+    Ok(Some(dest))
+}
+
+/// Context and history for `ZstdCompressor.compress_block()`.
+///
+/// zstd matches against the previous block in place, so a copy of it is
+/// kept alive until the next block has been compressed.
+struct BlockCompressor {
+    cctx: CCtx<'static>,
+    previous: Vec<u8>,
+}
+
 #[pyclass(module = "zstandard.backend_rust")]
 struct ZstdCompressor {
     _threads: i32,
     dict: Option<Py<ZstdCompressionDict>>,
     params: CCtxParams<'static>,
+    /// Parameters the compressor was constructed with, once replaced by
+    /// `set_compression_params()`.
+    initial_params: Option<CCtxParams<'static>>,
     cctx: Arc<CCtx<'static>>,
+    block: Option<BlockCompressor>,
 }
 
 unsafe impl Sync for ZstdCompressor {}
@@ -36,7 +188,7 @@ impl ZstdCompressor {
     pub(crate) fn setup_cctx(&self, py: Python) -> PyResult<()> {
         self.cctx
             .set_parameters(&self.params)
-            .or_else(|msg| Err(ZstdError::new_err(msg)))?;
+            .map_err(|msg| ZstdCompressionError::new_err(msg.clone()).with_zstd_error(&msg))?;
 
         if let Some(dict) = &self.dict {
             dict.borrow(py).load_into_cctx(&self.cctx)?;
@@ -44,6 +196,39 @@ impl ZstdCompressor {
 
         Ok(())
     }
+
+    /// Create a new context configured with `params` and our dictionary.
+    fn new_cctx(&self, py: Python, params: &CCtxParams) -> PyResult<CCtx<'static>> {
+        let cctx = CCtx::new().map_err(ZstdCompressionError::new_err)?;
+        cctx.set_parameters(params)
+            .map_err(|msg| ZstdCompressionError::new_err(msg.clone()).with_zstd_error(&msg))?;
+
+        if let Some(dict) = &self.dict {
+            dict.borrow(py).load_into_cctx(&cctx)?;
+        }
+
+        Ok(cctx)
+    }
+
+    /// Create a context for raw block compression configured like ours.
+    fn new_block_compressor(&self, py: Python) -> PyResult<BlockCompressor> {
+        let cctx = CCtx::new().map_err(ZstdCompressionError::new_err)?;
+        let params = effective_cparams(unsafe { self.params.get_raw_ptr() })?;
+
+        if let Some(dict) = &self.dict {
+            dict.borrow(py).begin_cctx_blocks(&cctx, params)?;
+        } else {
+            cctx.begin_blocks(&[], params).map_err(|msg| {
+                ZstdCompressionError::new_err(format!("unable to begin block compression: {}", msg))
+                    .with_zstd_error(&msg)
+            })?;
+        }
+
+        Ok(BlockCompressor {
+            cctx,
+            previous: vec![],
+        })
+    }
 }
 
 #[pymethods]
@@ -57,7 +242,9 @@ impl ZstdCompressor {
         write_content_size=None,
         write_dict_id=None,
         threads=0,
+        format=None,
     ))]
+    #[allow(clippy::too_many_arguments)]
     fn new(
         py: Python,
         level: i32,
@@ -67,13 +254,9 @@ impl ZstdCompressor {
         write_content_size: Option<bool>,
         write_dict_id: Option<bool>,
         threads: i32,
+        format: Option<&Bound<'_, PyAny>>,
     ) -> PyResult<Self> {
-        if level > zstd_safe::max_c_level() {
-            return Err(PyValueError::new_err(format!(
-                "level must be less than {}",
-                zstd_safe::max_c_level() as i32 + 1
-            )));
-        }
+        validate_level(level)?;
 
         let threads = if threads < 0 {
             num_cpus::get() as i32
@@ -81,7 +264,7 @@ impl ZstdCompressor {
             threads
         };
 
-        let cctx = Arc::new(CCtx::new().or_else(|msg| Err(PyErr::new::<ZstdError, _>(msg)))?);
+        let cctx = Arc::new(CCtx::new().map_err(ZstdCompressionError::new_err)?);
         let params = CCtxParams::create()?;
 
         if let Some(compression_params) = &compression_params {
@@ -105,11 +288,20 @@ impl ZstdCompressor {
                     "cannot define compression_params and threads",
                 ));
             }
+            if format.is_some() {
+                return Err(PyValueError::new_err(
+                    "cannot define compression_params and format",
+                ));
+            }
 
             params.apply_compression_parameters(py, compression_params)?;
 
         // TODO set parameters from CompressionParameters
         } else {
+            if let Some(dict) = &dict_data {
+                dict.borrow(py).check_precomputed_level(level)?;
+            }
+
             params.set_parameter(zstd_sys::ZSTD_cParameter::ZSTD_c_compressionLevel, level)?;
             params.set_parameter(
                 zstd_sys::ZSTD_cParameter::ZSTD_c_contentSizeFlag,
@@ -132,15 +324,25 @@ impl ZstdCompressor {
                 if write_dict_id.unwrap_or(true) { 1 } else { 0 },
             )?;
             if threads != 0 {
+                validate_threads(threads)?;
                 params.set_parameter(zstd_sys::ZSTD_cParameter::ZSTD_c_nbWorkers, threads)?;
             }
+            if let Some(format) = format {
+                // ZSTD_c_format.
+                params.set_parameter(
+                    zstd_sys::ZSTD_cParameter::ZSTD_c_experimentalParam2,
+                    value_to_format(format)? as i32,
+                )?;
+            }
         }
 
         let compressor = ZstdCompressor {
             _threads: threads,
             dict: dict_data,
             params,
+            initial_params: None,
             cctx,
+            block: None,
         };
 
         compressor.setup_cctx(py)?;
@@ -148,6 +350,84 @@ impl ZstdCompressor {
         Ok(compressor)
     }
 
+    /// Abandon any in-progress frame so the next operation starts a new one.
+    ///
+    /// By default only the session is reset. The compression level,
+    /// parameters, and loaded dictionary stay in effect. With
+    /// `session_and_parameters`, parameters changed by
+    /// `set_compression_params()` are also reverted to the ones the
+    /// compressor was constructed with.
+    #[pyo3(signature = (session_and_parameters=false))]
+    fn reset(&mut self, py: Python, session_and_parameters: bool) -> PyResult<()> {
+        if !session_and_parameters {
+            self.cctx.reset();
+            return Ok(());
+        }
+
+        if let Some(params) = self.initial_params.take() {
+            self.params = params;
+            self.block = None;
+        }
+
+        self.cctx.reset_parameters(true).map_err(|msg| {
+            ZstdError::new_err(format!("unable to reset compression context: {}", msg))
+                .with_zstd_error(&msg)
+        })?;
+
+        self.setup_cctx(py)
+    }
+
+    /// Replace the compression parameters for subsequent operations.
+    ///
+    /// zstd can't change parameters while a frame is being written, so this
+    /// raises if an operation sharing the compressor's context left a frame
+    /// unfinished. `reset()` abandons such a frame.
+    fn set_compression_params(
+        &mut self,
+        py: Python,
+        compression_params: Py<ZstdCompressionParameters>,
+    ) -> PyResult<()> {
+        let params = CCtxParams::create()?;
+        params.apply_compression_parameters(py, &compression_params)?;
+
+        self.cctx.reset_parameters(false).map_err(|msg| {
+            ZstdError::new_err("cannot change parameters mid-frame; call reset() first")
+                .with_zstd_error(&msg)
+        })?;
+
+        let previous = std::mem::replace(&mut self.params, params);
+        self.initial_params.get_or_insert(previous);
+        self.block = None;
+
+        self.setup_cctx(py)
+    }
+
+    /// Set a compression parameter by its raw `ZSTD_cParameter` value.
+    ///
+    /// This is an escape hatch for parameters added by zstd releases this
+    /// module doesn't know about yet. Nothing is validated beyond zstd
+    /// accepting the call, and the meaning of experimental parameter values
+    /// can change between zstd releases, so it's easy to produce frames other
+    /// tools can't read or to break assumptions made by the rest of this
+    /// module. Returns the result of `ZSTD_CCtx_setParameter()`.
+    ///
+    /// The parameter applies to every subsequent operation until
+    /// `set_compression_params()` replaces the parameters.
+    fn set_parameter(&self, param: c_int, value: c_int) -> PyResult<usize> {
+        let zresult = self.cctx.set_raw_parameter(param, value).map_err(|msg| {
+            ZstdError::new_err(format!("unable to set parameter {}: {}", param, msg))
+                .with_zstd_error(&msg)
+        })?;
+
+        // Operations that set up their own contexts consult the parameters.
+        // zstd validated the value above, so this doesn't fail.
+        self.params
+            .set_raw_parameter(param, value)
+            .map_err(|msg| ZstdError::new_err(msg.clone()).with_zstd_error(&msg))?;
+
+        Ok(zresult)
+    }
+
     fn memory_size(&self) -> PyResult<usize> {
         Ok(self.cctx.memory_size())
     }
@@ -162,20 +442,264 @@ impl ZstdCompressor {
         ))
     }
 
-    fn compress<'p>(&self, py: Python<'p>, buffer: PyBuffer<u8>) -> PyResult<Bound<'p, PyBytes>> {
+    /// Compress `buffer` into a single frame.
+    ///
+    /// With `allow_store`, the frame is replaced by one holding the data
+    /// uncompressed whenever that is smaller, so incompressible input never
+    /// grows by more than the frame and block headers.
+    #[pyo3(signature = (buffer, prefix=None, allow_store=false))]
+    fn compress<'p>(
+        &self,
+        py: Python<'p>,
+        buffer: DataBuffer,
+        prefix: Option<DataBuffer>,
+        allow_store: bool,
+    ) -> PyResult<Bound<'p, PyBytes>> {
         let source: &[u8] =
             unsafe { std::slice::from_raw_parts(buffer.buf_ptr() as *const _, buffer.len_bytes()) };
 
         let cctx = &self.cctx;
 
         // TODO implement 0 copy via Py_SIZE().
+        let data = if let Some(prefix) = &prefix {
+            let prefix: &[u8] = unsafe {
+                std::slice::from_raw_parts(prefix.buf_ptr() as *const _, prefix.len_bytes())
+            };
+
+            let result = py.allow_threads(|| cctx.compress_with_prefix(source, prefix));
+
+            // The prefix displaced our dictionary. Restore it for later frames.
+            if let Some(dict) = &self.dict {
+                dict.borrow(py).load_into_cctx(&self.cctx)?;
+            }
+
+            result
+        } else {
+            py.allow_threads(|| cctx.compress(source))
+        }
+        .map_err(|msg| {
+            ZstdCompressionError::new_err(format!("cannot compress: {}", msg)).with_zstd_error(&msg)
+        })?;
+
+        let data = if allow_store {
+            stored_frame(&self.params, source, &data)?.unwrap_or(data)
+        } else {
+            data
+        };
+
+        Ok(PyBytes::new(py, &data))
+    }
+
+    /// Compress `data` as a raw zstd block with no frame header.
+    ///
+    /// Successive blocks share one long-lived context and may reference
+    /// earlier blocks, so they must be decompressed in the same order by a
+    /// single `ZstdDecompressor.decompress_block()` session. `data` can't
+    /// exceed the maximum block size of 128 KiB. If it doesn't compress,
+    /// `data` is returned unchanged; the result is never larger than the
+    /// input and a result as long as the input means a raw block. The
+    /// caller must transmit both the block and the original size.
+    fn compress_block<'p>(
+        &mut self,
+        py: Python<'p>,
+        data: DataBuffer,
+    ) -> PyResult<Bound<'p, PyBytes>> {
+        if data.len_bytes() == 0 {
+            return Ok(PyBytes::new(py, &[]));
+        }
+
+        if self.block.is_none() {
+            self.block = Some(self.new_block_compressor(py)?);
+        }
+        let block = self.block.as_mut().unwrap();
+
+        let max_size = block.cctx.block_size();
+        if data.len_bytes() > max_size {
+            return Err(ZstdError::new_err(format!(
+                "block of {} bytes exceeds the maximum block size of {} bytes",
+                data.len_bytes(),
+                max_size
+            )));
+        }
+
+        // zstd reads this again when compressing the next block.
+        let source = data.as_slice().to_vec();
+        let mut dest = Vec::with_capacity(source.len());
+
+        let compressed = match py.allow_threads(|| block.cctx.compress_block(&mut dest, &source)) {
+            Ok(compressed) => compressed,
+            Err(msg) => {
+                // zstd can't resume after a failure.
+                self.block = None;
+
+                return Err(ZstdCompressionError::new_err(format!(
+                    "cannot compress block: {}",
+                    msg
+                ))
+                .with_zstd_error(&msg));
+            }
+        };
+
+        let result = PyBytes::new(py, if compressed { &dest } else { &source });
+        block.previous = source;
+
+        Ok(result)
+    }
+
+    /// Compress `src` into a single frame using explicit match sequences.
+    ///
+    /// `sequences` is an iterable of `(offset, match_length, lit_length, rep)`
+    /// tuples whose lengths must add up to the length of `src`.
+    fn compress_sequences<'p>(
+        &self,
+        py: Python<'p>,
+        sequences: &Bound<'_, PyAny>,
+        src: DataBuffer,
+    ) -> PyResult<Bound<'p, PyBytes>> {
+        let mut seqs = Vec::new();
+        let mut total: u64 = 0;
+
+        for item in sequences.try_iter()? {
+            let (offset, match_length, lit_length, rep): (u32, u32, u32, u32) = item?.extract()?;
+            total += match_length as u64 + lit_length as u64;
+
+            seqs.push(zstd_sys::ZSTD_Sequence {
+                offset,
+                litLength: lit_length,
+                matchLength: match_length,
+                rep,
+            });
+        }
+
+        if total != src.len_bytes() as u64 {
+            return Err(ZstdCompressionError::new_err(format!(
+                "sequences cover {} bytes but src is {} bytes",
+                total,
+                src.len_bytes()
+            )));
+        }
+
+        let source: &[u8] =
+            unsafe { std::slice::from_raw_parts(src.buf_ptr() as *const _, src.len_bytes()) };
+
+        let cctx = &self.cctx;
+        let seqs = &mut seqs;
+
         let data = py
-            .allow_threads(|| cctx.compress(source))
-            .or_else(|msg| Err(ZstdError::new_err(format!("cannot compress: {}", msg))))?;
+            .allow_threads(|| cctx.compress_sequences(seqs, source))
+            .map_err(|msg| {
+                ZstdCompressionError::new_err(format!("cannot compress sequences: {}", msg))
+                    .with_zstd_error(&msg)
+            })?;
 
         Ok(PyBytes::new(py, &data))
     }
 
+    /// Match sequences zstd would use to compress `data`.
+    ///
+    /// Returns a list of `Sequence` named tuples, in the form accepted by
+    /// `compress_sequences()`.
+    fn generate_sequences<'p>(
+        &self,
+        py: Python<'p>,
+        data: DataBuffer,
+    ) -> PyResult<Bound<'p, PyList>> {
+        let source: &[u8] =
+            unsafe { std::slice::from_raw_parts(data.buf_ptr() as *const _, data.len_bytes()) };
+
+        // Sequence generation spoils the context it runs on, so use a
+        // throwaway one configured like ours.
+        let cctx = self.new_cctx(py, &self.params)?;
+
+        let sequences = py
+            .allow_threads(|| cctx.generate_sequences(source))
+            .map_err(|msg| {
+                ZstdCompressionError::new_err(format!("cannot generate sequences: {}", msg))
+                    .with_zstd_error(&msg)
+            })?;
+
+        let sequence_type = sequence_type(py)?;
+        let result = PyList::empty(py);
+
+        for seq in sequences {
+            result.append(sequence_type.call1((
+                seq.offset,
+                seq.matchLength,
+                seq.litLength,
+                seq.rep,
+            ))?)?;
+        }
+
+        Ok(result)
+    }
+
+    fn compress_into(&self, py: Python, data: DataBuffer, output: DataBuffer) -> PyResult<usize> {
+        if output.readonly() {
+            return Err(PyValueError::new_err("output buffer is not writable"));
+        }
+
+        let source: &[u8] =
+            unsafe { std::slice::from_raw_parts(data.buf_ptr() as *const _, data.len_bytes()) };
+        let dest: &mut [u8] = unsafe {
+            std::slice::from_raw_parts_mut(output.buf_ptr() as *mut _, output.len_bytes())
+        };
+
+        let cctx = &self.cctx;
+
+        let written = py
+            .allow_threads(|| cctx.compress_into(source, dest))
+            .map_err(|msg| {
+                ZstdCompressionError::new_err(format!("cannot compress: {}", msg))
+                    .with_zstd_error(&msg)
+            })?;
+
+        written.ok_or_else(|| {
+            ZstdCompressionError::new_err(format!(
+                "output buffer of {} bytes is too small; use compress_bound() to size it",
+                dest.len()
+            ))
+        })
+    }
+
+    fn compress_bound(&self, input_len: usize) -> PyResult<usize> {
+        compress_bound(input_len)
+    }
+
+    /// Wrap `data` in a skippable frame, which decoders skip over.
+    ///
+    /// Skippable frames embed metadata between compressed frames. The magic
+    /// number identifying the frame is chosen from 16 variants.
+    #[pyo3(signature = (data, magic_variant=0))]
+    fn write_skippable_frame<'p>(
+        &self,
+        py: Python<'p>,
+        data: DataBuffer,
+        magic_variant: u32,
+    ) -> PyResult<Bound<'p, PyBytes>> {
+        if magic_variant > SKIPPABLE_MAGIC_VARIANT_MAX {
+            return Err(PyValueError::new_err(format!(
+                "magic_variant must be between 0 and {}",
+                SKIPPABLE_MAGIC_VARIANT_MAX
+            )));
+        }
+
+        if data.len_bytes() > u32::MAX as usize {
+            return Err(PyValueError::new_err(format!(
+                "skippable frame payload of {} bytes exceeds the maximum of {} bytes",
+                data.len_bytes(),
+                u32::MAX
+            )));
+        }
+
+        let frame = crate::zstd_safe::write_skippable_frame(data.as_slice(), magic_variant)
+            .map_err(|msg| {
+                ZstdError::new_err(format!("unable to write skippable frame: {}", msg))
+                    .with_zstd_error(&msg)
+            })?;
+
+        Ok(PyBytes::new(py, &frame))
+    }
+
     #[pyo3(signature = (size=None, chunk_size=None))]
     fn chunker(
         &self,
@@ -187,11 +711,15 @@ impl ZstdCompressor {
         let size = size.unwrap_or(zstd_safe::CONTENTSIZE_UNKNOWN);
         let chunk_size = chunk_size.unwrap_or_else(|| zstd_safe::CCtx::out_size());
 
-        self.cctx.set_pledged_source_size(size).or_else(|msg| {
-            Err(ZstdError::new_err(format!(
-                "error setting source size: {}",
-                msg
-            )))
+        // Chunks are only emitted once the output buffer is full, so an empty
+        // buffer would never make progress.
+        if chunk_size == 0 {
+            return Err(PyValueError::new_err("chunk_size must be greater than 0"));
+        }
+
+        self.cctx.set_pledged_source_size(size).map_err(|msg| {
+            ZstdCompressionError::new_err(format!("error setting source size: {}", msg))
+                .with_zstd_error(&msg)
         })?;
 
         ZstdCompressionChunker::new(self.cctx.clone(), chunk_size)
@@ -201,23 +729,28 @@ impl ZstdCompressor {
     fn compressobj(&self, size: Option<u64>) -> PyResult<ZstdCompressionObj> {
         self.cctx.reset();
 
-        let size = if let Some(size) = size {
+        let pledged_size = if let Some(size) = size {
             size
         } else {
             zstd_safe::CONTENTSIZE_UNKNOWN
         };
 
-        self.cctx.set_pledged_source_size(size).or_else(|msg| {
-            Err(ZstdError::new_err(format!(
-                "error setting source size: {}",
-                msg
-            )))
-        })?;
+        self.cctx
+            .set_pledged_source_size(pledged_size)
+            .map_err(|msg| {
+                ZstdCompressionError::new_err(format!("error setting source size: {}", msg))
+                    .with_zstd_error(&msg)
+            })?;
 
-        ZstdCompressionObj::new(self.cctx.clone())
+        ZstdCompressionObj::new(self.cctx.clone(), size)
     }
 
-    #[pyo3(signature = (ifh, ofh, size=None, read_size=None, write_size=None))]
+    /// Compress data read from `ifh` and write it to `ofh`.
+    ///
+    /// Returns the number of bytes read from `ifh` and written to `ofh`,
+    /// including the output of the final flush ending the frame.
+    #[pyo3(signature = (ifh, ofh, size=None, read_size=None, write_size=None, progress=None))]
+    #[allow(clippy::too_many_arguments)]
     fn copy_stream(
         &self,
         py: Python,
@@ -226,6 +759,7 @@ impl ZstdCompressor {
         size: Option<u64>,
         read_size: Option<usize>,
         write_size: Option<usize>,
+        progress: Option<&Bound<'_, PyAny>>,
     ) -> PyResult<(usize, usize)> {
         let source_size = if let Some(source_size) = size {
             source_size
@@ -250,16 +784,18 @@ impl ZstdCompressor {
         self.cctx.reset();
         self.cctx
             .set_pledged_source_size(source_size)
-            .or_else(|msg| {
-                Err(ZstdError::new_err(format!(
-                    "error setting source size: {}",
-                    msg
-                )))
+            .map_err(|msg| {
+                ZstdCompressionError::new_err(format!("error setting source size: {}", msg))
+                    .with_zstd_error(&msg)
             })?;
 
         let mut total_read = 0;
         let mut total_write = 0;
 
+        // An error raised by the progress callback. It is deferred until the
+        // frame is ended so no partial frame is left in the output.
+        let mut progress_err = None;
+
         loop {
             // Try to read from source stream.
             let read_object = ifh.call_method("read", (read_size,), None)?;
@@ -288,8 +824,9 @@ impl ZstdCompressor {
                             write_size,
                         )
                     })
-                    .or_else(|msg| {
-                        Err(ZstdError::new_err(format!("zstd compress error: {}", msg)))
+                    .map_err(|msg| {
+                        ZstdCompressionError::new_err(format!("zstd compress error: {}", msg))
+                            .with_zstd_error(&msg)
                     })?;
 
                 source = result.1;
@@ -303,19 +840,35 @@ impl ZstdCompressor {
                     total_write += chunk.len();
                 }
             }
+
+            if let Some(progress) = progress {
+                if let Err(err) = progress.call1((total_read, total_write)) {
+                    progress_err = Some(err);
+                    break;
+                }
+            }
         }
 
         // We've finished reading. Now flush the compressor stream.
         loop {
-            let result = self
-                .cctx
-                .compress_chunk(&[], zstd_sys::ZSTD_EndDirective::ZSTD_e_end, write_size)
-                .or_else(|msg| {
-                    Err(ZstdError::new_err(format!(
-                        "error ending compression stream: {}",
-                        msg
-                    )))
-                })?;
+            let result = match self.cctx.compress_chunk(
+                &[],
+                zstd_sys::ZSTD_EndDirective::ZSTD_e_end,
+                write_size,
+            ) {
+                Ok(result) => result,
+                // The progress error is more useful than the (likely) source
+                // size mismatch it caused.
+                Err(msg) => {
+                    return Err(progress_err.unwrap_or_else(|| {
+                        ZstdCompressionError::new_err(format!(
+                            "error ending compression stream: {}",
+                            msg
+                        ))
+                        .with_zstd_error(&msg)
+                    }))
+                }
+            };
 
             let chunk = &result.0;
 
@@ -331,17 +884,32 @@ impl ZstdCompressor {
             }
         }
 
+        if let Some(err) = progress_err {
+            return Err(err);
+        }
+
+        if let Some(progress) = progress {
+            progress.call1((total_read, total_write))?;
+        }
+
         Ok((total_read, total_write))
     }
 
-    #[pyo3(signature = (data, threads=0))]
+    /// Compress each item of `data` into its own frame on a pool of threads.
+    ///
+    /// Every item is compressed by a single worker as an independent frame
+    /// and results are returned in input order, so the output is
+    /// byte-identical to compressing each item with `compress()`, whatever
+    /// the value of `threads`.
+    #[pyo3(signature = (data, threads=0, dicts=None))]
     fn multi_compress_to_buffer(
         &self,
         py: Python,
         data: &Bound<'_, PyAny>,
         threads: isize,
+        dicts: Option<&Bound<'_, PyAny>>,
     ) -> PyResult<ZstdBufferWithSegmentsCollection> {
-        multi_compress_to_buffer(py, &self.params, &self.dict, data, threads)
+        multi_compress_to_buffer(py, &self.params, &self.dict, data, threads, dicts)
     }
 
     #[pyo3(signature = (reader, size=None, read_size=None, write_size=None))]
@@ -359,7 +927,28 @@ impl ZstdCompressor {
 
         self.cctx.reset();
 
-        ZstdCompressorIterator::new(py, self.cctx.clone(), reader, size, read_size, write_size)
+        let source = make_in_buffer_source(py, reader, read_size)?;
+
+        ZstdCompressorIterator::new(self.cctx.clone(), source, size, write_size)
+    }
+
+    /// Compress an iterable of chunks into a single frame, yielding output.
+    #[pyo3(signature = (chunks, size=None, write_size=None))]
+    fn compress_iter(
+        &self,
+        py: Python,
+        chunks: &Bound<'_, PyAny>,
+        size: Option<u64>,
+        write_size: Option<usize>,
+    ) -> PyResult<ZstdCompressorIterator> {
+        let size = size.unwrap_or(zstd_safe::CONTENTSIZE_UNKNOWN);
+        let write_size = write_size.unwrap_or_else(zstd_safe::CCtx::out_size);
+
+        let source = make_iter_source(py, chunks)?;
+
+        self.cctx.reset();
+
+        ZstdCompressorIterator::new(self.cctx.clone(), source, size, write_size)
     }
 
     #[pyo3(signature = (source, size=None, read_size=None, closefd=true))]
@@ -379,7 +968,24 @@ impl ZstdCompressor {
         ZstdCompressionReader::new(py, self.cctx.clone(), source, size, read_size, closefd)
     }
 
-    #[pyo3(signature = (writer, size=None, write_size=None, write_return_read=true, closefd=true))]
+    /// Compress data written to the returned writer into `writer`.
+    ///
+    /// `compression_params`, `job_size` and `overlap_log` apply to this
+    /// stream alone. A stream using any of them gets a context of its own, so
+    /// several can be open at once. Other streams share the compressor's
+    /// context, and only one of those may be in use at a time.
+    ///
+    /// With `hash`, data written is also hashed, saving a second pass over it
+    /// to compute `plaintext_digest()`. `"xxh64"` and any algorithm known to
+    /// `hashlib`, such as `"sha256"`, are supported.
+    ///
+    /// `stable_buffers=True` has zstd read input from and write output to
+    /// our buffers directly, skipping the copies through its own. Each
+    /// `write()` then compresses its data as one complete frame, so a frame
+    /// can't span writes and `size` must match the length of every write.
+    /// Multithreaded compression keeps its own buffers regardless.
+    #[pyo3(signature = (writer, size=None, write_size=None, write_return_read=true, closefd=true, min_flush_size=0, flush_on_close=false, job_size=None, overlap_log=None, compression_params=None, hash=None, stable_buffers=false))]
+    #[allow(clippy::too_many_arguments)]
     fn stream_writer(
         &self,
         py: Python,
@@ -388,6 +994,13 @@ impl ZstdCompressor {
         write_size: Option<usize>,
         write_return_read: bool,
         closefd: bool,
+        min_flush_size: usize,
+        flush_on_close: bool,
+        job_size: Option<i32>,
+        overlap_log: Option<i32>,
+        compression_params: Option<Py<ZstdCompressionParameters>>,
+        hash: Option<&str>,
+        stable_buffers: bool,
     ) -> PyResult<ZstdCompressionWriter> {
         if !writer.hasattr("write")? {
             return Err(PyValueError::new_err(
@@ -395,25 +1008,195 @@ impl ZstdCompressor {
             ));
         }
 
-        self.cctx.reset();
+        let hasher = hash
+            .map(|name| PlaintextHasher::new(py, name))
+            .transpose()?;
+
+        let params = if let Some(compression_params) = &compression_params {
+            let params = CCtxParams::create()?;
+            params.apply_compression_parameters(py, compression_params)?;
+            Some(params)
+        } else {
+            None
+        };
+
+        // Overrides get a context of their own so our parameters are left
+        // untouched.
+        let cctx = if params.is_some()
+            || job_size.is_some()
+            || overlap_log.is_some()
+            || stable_buffers
+        {
+            let cctx = self.new_cctx(py, params.as_ref().unwrap_or(&self.params))?;
+            let stable_buffers = stable_buffers.then_some(1);
+
+            for (name, param, value) in [
+                (
+                    "job_size",
+                    zstd_sys::ZSTD_cParameter::ZSTD_c_jobSize,
+                    job_size,
+                ),
+                (
+                    "overlap_log",
+                    zstd_sys::ZSTD_cParameter::ZSTD_c_overlapLog,
+                    overlap_log,
+                ),
+                (
+                    "stable_buffers",
+                    // ZSTD_c_stableInBuffer.
+                    zstd_sys::ZSTD_cParameter::ZSTD_c_experimentalParam9,
+                    stable_buffers,
+                ),
+                (
+                    "stable_buffers",
+                    // ZSTD_c_stableOutBuffer.
+                    zstd_sys::ZSTD_cParameter::ZSTD_c_experimentalParam10,
+                    stable_buffers,
+                ),
+            ] {
+                if let Some(value) = value {
+                    cctx.set_parameter(param, value).map_err(|msg| {
+                        ZstdCompressionError::new_err(format!("unable to set {}: {}", name, msg))
+                            .with_zstd_error(&msg)
+                    })?;
+                }
+            }
+
+            Arc::new(cctx)
+        } else {
+            self.cctx.reset();
+            self.cctx.clone()
+        };
 
         let size = size.unwrap_or(zstd_sys::ZSTD_CONTENTSIZE_UNKNOWN as _);
         let write_size = write_size.unwrap_or_else(|| unsafe { zstd_sys::ZSTD_CStreamOutSize() });
 
         ZstdCompressionWriter::new(
             py,
-            self.cctx.clone(),
+            cctx,
             writer,
             size,
             write_size,
             write_return_read,
             closefd,
+            min_flush_size,
+            flush_on_close,
+            hasher,
+            stable_buffers,
+        )
+    }
+
+    #[pyo3(signature = (writer, max_frame_size=None, write_size=None, closefd=true))]
+    fn seekable_stream_writer(
+        &self,
+        py: Python,
+        writer: &Bound<'_, PyAny>,
+        max_frame_size: Option<usize>,
+        write_size: Option<usize>,
+        closefd: bool,
+    ) -> PyResult<SeekableCompressionWriter> {
+        if !writer.hasattr("write")? {
+            return Err(PyValueError::new_err(
+                "must pass object with a write() method",
+            ));
+        }
+
+        let max_frame_size = max_frame_size.unwrap_or(SEEKABLE_MAX_FRAME_SIZE);
+        if max_frame_size == 0 || max_frame_size > SEEKABLE_MAX_FRAME_SIZE {
+            return Err(PyValueError::new_err(format!(
+                "max_frame_size must be between 1 and {}",
+                SEEKABLE_MAX_FRAME_SIZE
+            )));
+        }
+
+        let write_checksum = get_cctx_parameter(
+            unsafe { self.params.get_raw_ptr() },
+            zstd_sys::ZSTD_cParameter::ZSTD_c_checksumFlag,
+        )? != 0;
+
+        self.cctx.reset();
+
+        let write_size = write_size.unwrap_or_else(zstd_safe::CCtx::out_size);
+
+        SeekableCompressionWriter::new(
+            py,
+            self.cctx.clone(),
+            writer,
+            max_frame_size,
+            write_size,
+            write_checksum,
+            closefd,
         )
     }
 }
 
+/// Maximum compressed size of `size` bytes of input.
+///
+/// This doesn't touch any compression context, so it is safe to call from any
+/// interpreter without constructing a compressor.
+#[pyfunction]
+fn compress_bound(size: usize) -> PyResult<usize> {
+    crate::zstd_safe::compress_bound(size)
+        .ok_or_else(|| PyOverflowError::new_err("input size is too large to compress"))
+}
+
+/// Compress data as a single frame without constructing a compressor.
+///
+/// Equivalent to `ZstdCompressor(level=level, dict_data=dict).compress(data)`,
+/// except the compression context is cached per thread and reused.
+#[pyfunction]
+#[pyo3(signature = (data, level=3, dict=None))]
+pub(crate) fn compress<'p>(
+    py: Python<'p>,
+    data: DataBuffer,
+    level: i32,
+    dict: Option<Py<ZstdCompressionDict>>,
+) -> PyResult<Bound<'p, PyBytes>> {
+    validate_level(level)?;
+
+    if let Some(dict) = &dict {
+        dict.borrow(py).check_precomputed_level(level)?;
+    }
+
+    let params = CCtxParams::create()?;
+    params.set_parameter(zstd_sys::ZSTD_cParameter::ZSTD_c_compressionLevel, level)?;
+
+    // Take the context out of the cache while it is in use so a reentrant
+    // call can't share it.
+    let id = interpreter_id();
+    let cctx = match ONE_SHOT_CCTX.with(|cell| cell.borrow_mut().take()) {
+        Some((owner, cctx)) if owner == id => cctx,
+        _ => Arc::new(CCtx::new().map_err(ZstdCompressionError::new_err)?),
+    };
+
+    // A failed call may have left a frame in progress or a dictionary loaded.
+    cctx.reset();
+    cctx.clear_dict().map_err(ZstdCompressionError::new_err)?;
+
+    let compressor = ZstdCompressor {
+        _threads: 0,
+        dict,
+        params,
+        initial_params: None,
+        cctx: cctx.clone(),
+        block: None,
+    };
+
+    let res = compressor
+        .setup_cctx(py)
+        .and_then(|_| compressor.compress(py, data, None, false));
+
+    drop(compressor);
+    ONE_SHOT_CCTX.with(|cell| *cell.borrow_mut() = Some((id, cctx)));
+
+    res
+}
+
 pub(crate) fn init_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
     module.add_class::<ZstdCompressor>()?;
+    module.add("Sequence", sequence_type(module.py())?)?;
+    module.add_function(wrap_pyfunction!(compress, module)?)?;
+    module.add_function(wrap_pyfunction!(compress_bound, module)?)?;
 
     Ok(())
 }
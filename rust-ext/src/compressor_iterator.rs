@@ -6,9 +6,9 @@
 
 use {
     crate::{
-        exceptions::ZstdError,
-        stream::{make_in_buffer_source, InBufferSource},
-        zstd_safe::CCtx,
+        exceptions::{WithZstdError, ZstdCompressionError},
+        stream::InBufferSource,
+        zstd_safe::{CCtx, ZstdFailure},
     },
     pyo3::{prelude::*, types::PyBytes, IntoPyObjectExt},
     std::sync::Arc,
@@ -40,20 +40,34 @@ impl ZstdCompressorIterator {
         let py = unsafe { Python::assume_gil_acquired() };
 
         let mut dest_buffer: Vec<u8> = Vec::with_capacity(slf.write_size);
+        let cctx = slf.cctx.clone();
 
         // Feed data into the compressor until there is output data.
-        while let Some(mut in_buffer) = slf.source.input_buffer(py)? {
-            let old_pos = in_buffer.pos;
-
-            slf.cctx
-                .compress_into_vec(
-                    &mut dest_buffer,
-                    &mut in_buffer,
-                    zstd_sys::ZSTD_EndDirective::ZSTD_e_continue,
+        while let Some(in_buffer) = slf.next_input(py)? {
+            let source: &[u8] = unsafe {
+                std::slice::from_raw_parts(
+                    (in_buffer.src as *const u8).add(in_buffer.pos),
+                    in_buffer.size - in_buffer.pos,
                 )
-                .map_err(|msg| ZstdError::new_err(format!("zstd compress error: {}", msg)))?;
-
-            slf.source.record_bytes_read(in_buffer.pos - old_pos);
+            };
+
+            let dest = &mut dest_buffer;
+            let consumed = py
+                .allow_threads(|| {
+                    compress_into(
+                        &cctx,
+                        dest,
+                        source,
+                        zstd_sys::ZSTD_EndDirective::ZSTD_e_continue,
+                    )
+                })
+                .map_err(|msg| {
+                    ZstdCompressionError::new_err(format!("zstd compress error: {}", msg))
+                        .with_zstd_error(&msg)
+                })?
+                .0;
+
+            slf.source.record_bytes_read(consumed);
 
             // Emit compressed data, if available.
             if !dest_buffer.is_empty() {
@@ -69,22 +83,16 @@ impl ZstdCompressorIterator {
 
         // Input data is exhausted. End the stream and emit what remains.
 
-        let mut in_buffer = zstd_sys::ZSTD_inBuffer {
-            src: std::ptr::null_mut(),
-            size: 0,
-            pos: 0,
-        };
-
-        let zresult = slf
-            .cctx
-            .compress_into_vec(
-                &mut dest_buffer,
-                &mut in_buffer,
-                zstd_sys::ZSTD_EndDirective::ZSTD_e_end,
-            )
+        let dest = &mut dest_buffer;
+        let zresult = py
+            .allow_threads(|| {
+                compress_into(&cctx, dest, &[], zstd_sys::ZSTD_EndDirective::ZSTD_e_end)
+            })
             .map_err(|msg| {
-                ZstdError::new_err(format!("error ending compression stream: {}", msg))
-            })?;
+                ZstdCompressionError::new_err(format!("error ending compression stream: {}", msg))
+                    .with_zstd_error(&msg)
+            })?
+            .1;
 
         if zresult == 0 {
             slf.finished_output = true;
@@ -101,24 +109,42 @@ impl ZstdCompressorIterator {
     }
 }
 
+/// Compress `source` into the spare capacity of `dest`.
+///
+/// Returns the number of input bytes consumed and zstd's result.
+pub(crate) fn compress_into(
+    cctx: &CCtx,
+    dest: &mut Vec<u8>,
+    source: &[u8],
+    end_mode: zstd_sys::ZSTD_EndDirective,
+) -> Result<(usize, usize), ZstdFailure> {
+    let mut in_buffer = zstd_sys::ZSTD_inBuffer {
+        src: source.as_ptr() as *const _,
+        size: source.len(),
+        pos: 0,
+    };
+
+    let zresult = cctx.compress_into_vec(dest, &mut in_buffer, end_mode)?;
+
+    Ok((in_buffer.pos, zresult))
+}
+
 impl ZstdCompressorIterator {
     pub fn new(
-        py: Python,
         cctx: Arc<CCtx<'static>>,
-        reader: &Bound<'_, PyAny>,
+        source: Box<dyn InBufferSource + Send>,
         size: u64,
-        read_size: usize,
         write_size: usize,
     ) -> PyResult<Self> {
-        let source = make_in_buffer_source(py, reader, read_size)?;
-
         let size = match source.source_size() {
             Some(size) => size as _,
             None => size,
         };
 
-        cctx.set_pledged_source_size(size)
-            .map_err(|msg| ZstdError::new_err(format!("error setting source size: {}", msg)))?;
+        cctx.set_pledged_source_size(size).map_err(|msg| {
+            ZstdCompressionError::new_err(format!("error setting source size: {}", msg))
+                .with_zstd_error(&msg)
+        })?;
 
         Ok(Self {
             cctx,
@@ -127,4 +153,15 @@ impl ZstdCompressorIterator {
             finished_output: false,
         })
     }
+
+    /// Obtain more input, abandoning the frame if the source raises.
+    ///
+    /// The compression session is reset so nothing of the partial frame
+    /// lingers in the context, and iteration stops like a generator would.
+    fn next_input(&mut self, py: Python) -> PyResult<Option<zstd_sys::ZSTD_inBuffer>> {
+        self.source.input_buffer(py).inspect_err(|_| {
+            self.cctx.reset();
+            self.finished_output = true;
+        })
+    }
 }
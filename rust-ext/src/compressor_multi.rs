@@ -6,19 +6,21 @@
 
 use {
     crate::{
-        buffers::{BufferSegment, ZstdBufferWithSegments, ZstdBufferWithSegmentsCollection},
+        buffers::{
+            BufferSegment, DataBuffer, ZstdBufferWithSegments, ZstdBufferWithSegmentsCollection,
+        },
         compression_dict::ZstdCompressionDict,
         compression_parameters::CCtxParams,
-        exceptions::ZstdError,
-        zstd_safe::CCtx,
+        exceptions::{WithZstdError, ZstdCompressionError},
+        zstd_safe::{CCtx, ZstdFailure},
     },
     pyo3::{
-        buffer::PyBuffer,
         exceptions::{PyTypeError, PyValueError},
         prelude::*,
         types::{PyBytes, PyList, PyTuple},
     },
     rayon::prelude::*,
+    std::sync::atomic::{AtomicBool, Ordering},
 };
 
 struct DataSource<'a> {
@@ -31,6 +33,7 @@ pub fn multi_compress_to_buffer(
     dict: &Option<Py<ZstdCompressionDict>>,
     data: &Bound<'_, PyAny>,
     threads: isize,
+    dicts: Option<&Bound<'_, PyAny>>,
 ) -> PyResult<ZstdBufferWithSegmentsCollection> {
     let threads = if threads < 0 {
         num_cpus::get()
@@ -72,7 +75,7 @@ pub fn multi_compress_to_buffer(
         sources.reserve_exact(list.len());
 
         for (i, item) in list.iter().enumerate() {
-            let buffer: PyBuffer<u8> = PyBuffer::get(&item.as_borrowed())
+            let buffer: DataBuffer = DataBuffer::get(&item.as_borrowed())
                 .map_err(|_| PyTypeError::new_err(format!("item {} not a bytes like object", i)))?;
 
             let slice = unsafe {
@@ -96,13 +99,61 @@ pub fn multi_compress_to_buffer(
         return Err(PyValueError::new_err("source elements are empty"));
     }
 
-    compress_from_datasources(py, params, dict, sources, threads)
+    // Per-item dictionaries are borrowed for the duration of compression so
+    // workers can use them without holding the GIL.
+    let item_dicts = if let Some(dicts) = dicts {
+        let list = dicts
+            .downcast::<PyList>()
+            .map_err(|_| PyTypeError::new_err("dicts must be a list"))?;
+
+        if list.len() != sources.len() {
+            return Err(PyValueError::new_err(format!(
+                "dicts must have the same length as data ({} != {})",
+                list.len(),
+                sources.len()
+            )));
+        }
+
+        let mut item_dicts = Vec::with_capacity(list.len());
+
+        for (i, item) in list.iter().enumerate() {
+            let item_dict = if item.is_none() {
+                dict.as_ref().map(|dict| dict.bind(py).borrow())
+            } else {
+                Some(
+                    item.downcast::<ZstdCompressionDict>()
+                        .map_err(|_| {
+                            PyTypeError::new_err(format!(
+                                "dicts item {} is not a ZstdCompressionDict or None",
+                                i
+                            ))
+                        })?
+                        .borrow(),
+                )
+            };
+
+            item_dicts.push(item_dict);
+        }
+
+        Some(item_dicts)
+    } else {
+        None
+    };
+
+    let item_dicts = item_dicts.as_ref().map(|item_dicts| {
+        item_dicts
+            .iter()
+            .map(|item_dict| item_dict.as_deref())
+            .collect::<Vec<_>>()
+    });
+
+    compress_from_datasources(py, params, dict, item_dicts, sources, threads)
 }
 
 /// Holds results of an individual compression operation.
 struct WorkerResult {
     source_offset: usize,
-    error: Option<&'static str>,
+    error: Option<ZstdFailure>,
     data: Option<Vec<u8>>,
 }
 
@@ -110,6 +161,7 @@ fn compress_from_datasources(
     py: Python,
     params: &CCtxParams,
     dict: &Option<Py<ZstdCompressionDict>>,
+    item_dicts: Option<Vec<Option<&ZstdCompressionDict>>>,
     sources: Vec<DataSource>,
     thread_count: usize,
 ) -> PyResult<ZstdBufferWithSegmentsCollection> {
@@ -126,14 +178,17 @@ fn compress_from_datasources(
     // to the C backend.
 
     for _ in 0..thread_count {
-        let cctx = CCtx::new().map_err(|msg| ZstdError::new_err(msg))?;
+        let cctx = CCtx::new().map_err(ZstdCompressionError::new_err)?;
 
         cctx.set_parameters(params).map_err(|msg| {
-            ZstdError::new_err(format!("could not set compression parameters: {}", msg))
+            ZstdCompressionError::new_err(format!("could not set compression parameters: {}", msg))
+                .with_zstd_error(&msg)
         })?;
 
-        if let Some(dict) = dict {
-            dict.borrow(py).load_into_cctx(&cctx)?;
+        if item_dicts.is_none() {
+            if let Some(dict) = dict {
+                dict.borrow(py).load_into_cctx(&cctx)?;
+            }
         }
 
         cctxs.push(cctx);
@@ -142,31 +197,51 @@ fn compress_from_datasources(
     let pool = rayon::ThreadPoolBuilder::new()
         .num_threads(thread_count)
         .build()
-        .map_err(|err| ZstdError::new_err(format!("error initializing thread pool: {}", err)))?;
+        .map_err(|err| {
+            ZstdCompressionError::new_err(format!("error initializing thread pool: {}", err))
+        })?;
 
-    pool.install(|| {
-        sources.par_iter().enumerate().for_each(|(index, source)| {
-            let thread_index = pool.current_thread_index().unwrap();
+    // Set once any item fails so remaining items are skipped.
+    let failed = AtomicBool::new(false);
 
-            let cctx = &cctxs[thread_index];
+    py.allow_threads(|| {
+        pool.install(|| {
+            sources.par_iter().enumerate().for_each(|(index, source)| {
+                if failed.load(Ordering::Relaxed) {
+                    return;
+                }
 
-            let mut result = WorkerResult {
-                source_offset: index,
-                error: None,
-                data: None,
-            };
+                let thread_index = pool.current_thread_index().unwrap();
 
-            match cctx.compress(source.data) {
-                Ok(chunk) => {
-                    result.data = Some(chunk);
-                }
-                Err(msg) => {
-                    result.error = Some(msg);
+                let cctx = &cctxs[thread_index];
+
+                let mut result = WorkerResult {
+                    source_offset: index,
+                    error: None,
+                    data: None,
+                };
+
+                let loaded = match item_dicts.as_ref().map(|item_dicts| item_dicts[index]) {
+                    Some(Some(item_dict)) => item_dict.load_into_cctx_nogil(cctx),
+                    Some(None) => cctx.clear_dict(),
+                    None => Ok(()),
+                };
+
+                // compress() resets the session, so the frame doesn't depend on
+                // which worker produced it or what the worker compressed before.
+                match loaded.and_then(|_| cctx.compress(source.data)) {
+                    Ok(chunk) => {
+                        result.data = Some(chunk);
+                    }
+                    Err(msg) => {
+                        result.error = Some(msg);
+                        failed.store(true, Ordering::Relaxed);
+                    }
                 }
-            }
 
-            // TODO we can do better than a shared lock.
-            results.lock().unwrap().push(result);
+                // TODO we can do better than a shared lock.
+                results.lock().unwrap().push(result);
+            });
         });
     });
 
@@ -177,6 +252,23 @@ fn compress_from_datasources(
         .unwrap()
         .sort_by(|a, b| a.source_offset.cmp(&b.source_offset));
 
+    // Items after a failure may have been skipped, so report the failure
+    // before assembling output.
+    if let Some(result) = results
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|result| result.error.is_some())
+    {
+        let msg = result.error.as_ref().unwrap();
+
+        return Err(ZstdCompressionError::new_err(format!(
+            "error compressing item {}: {}",
+            result.source_offset, msg
+        ))
+        .with_zstd_error(msg));
+    }
+
     // TODO this is horribly inefficient due to memory copies.
     let els = PyTuple::new(
         py,
@@ -185,13 +277,6 @@ fn compress_from_datasources(
             .unwrap()
             .iter()
             .map(|result| {
-                if let Some(msg) = result.error {
-                    return Err(ZstdError::new_err(format!(
-                        "error compressing item {}: {}",
-                        result.source_offset, msg
-                    )));
-                }
-
                 let data = result.data.as_ref().unwrap();
                 let chunk = PyBytes::new(py, data);
                 let segments = vec![BufferSegment {
@@ -206,7 +291,7 @@ fn compress_from_datasources(
                         segments.len() * std::mem::size_of::<BufferSegment>(),
                     )
                 };
-                let segments_buffer = PyBuffer::get(&segments)?;
+                let segments_buffer = DataBuffer::get(&segments)?;
 
                 Py::new(
                     py,
@@ -4,17 +4,73 @@
 // This software may be modified and distributed under the terms
 // of the BSD license. See the LICENSE file for details.
 
-use pyo3::{prelude::*, types::PyBytes};
+use {
+    crate::exceptions::ZstdError,
+    pyo3::{prelude::*, types::PyBytes, wrap_pyfunction},
+};
 
+/// Writer flush mode emitting buffered data as a complete block (`ZSTD_e_flush`).
+///
+/// The frame stays open, so a decoder can decompress everything written so
+/// far while more data is appended to the same frame.
+pub(crate) const FLUSH_BLOCK: usize = 0;
+/// Writer flush mode ending the current frame (`ZSTD_e_end`).
+///
+/// Subsequent writes start a new frame with its own header.
+pub(crate) const FLUSH_FRAME: usize = 1;
+
+/// Equivalents of `FLUSH_FRAME` and `FLUSH_BLOCK` for `ZstdCompressionObj.flush()`.
+///
+/// The values differ from the writer constants, so the two sets aren't
+/// interchangeable.
 pub(crate) const COMPRESSOBJ_FLUSH_FINISH: i32 = 0;
 pub(crate) const COMPRESSOBJ_FLUSH_BLOCK: i32 = 1;
 
+/// Smallest possible frame header for the standard (magic prefixed) format.
+pub(crate) const FRAMEHEADERSIZE_MIN: usize = 6;
+
+/// Smallest window log a decoder will ever use (`ZSTD_WINDOWLOG_ABSOLUTEMIN`).
+pub(crate) const WINDOWLOG_ABSOLUTEMIN: u32 = 10;
+
+/// Window log used with long distance matching when none is chosen
+/// (`ZSTD_LDM_DEFAULT_WINDOW_LOG`).
+pub(crate) const LDM_DEFAULT_WINDOW_LOG: i32 = 27;
+
+/// Size of the magic number and payload length starting a skippable frame
+/// (`ZSTD_SKIPPABLEHEADERSIZE`).
+pub(crate) const SKIPPABLE_HEADER_SIZE: usize = 8;
+/// Largest magic number variant of a skippable frame.
+pub(crate) const SKIPPABLE_MAGIC_VARIANT_MAX: u32 = 15;
+
+/// Magic number of the skippable frame holding a seekable format seek table.
+pub(crate) const SEEKABLE_SKIPPABLE_MAGIC_NUMBER: u32 = 0x184D_2A5E;
+/// Magic number terminating the seek table footer.
+pub(crate) const SEEKABLE_MAGIC_NUMBER: u32 = 0x8F92_EAB1;
+/// Size in bytes of the seek table footer.
+pub(crate) const SEEKABLE_FOOTER_SIZE: usize = 9;
+/// Largest decompressed size of a single frame in the seekable format.
+pub(crate) const SEEKABLE_MAX_FRAME_SIZE: usize = 0x4000_0000;
+
+/// Version string of the zstd library linked at runtime.
+#[pyfunction]
+fn zstd_version() -> &'static str {
+    zstd_safe::version_string()
+}
+
+/// Whether the linked zstd library supports multithreaded compression.
+///
+/// Without it, compressing with `threads` other than 0 fails.
+#[pyfunction]
+fn has_multithread_support() -> PyResult<bool> {
+    crate::zstd_safe::has_multithread_support().map_err(ZstdError::new_err)
+}
+
 pub(crate) fn init_module(py: Python, module: &Bound<'_, PyModule>) -> PyResult<()> {
     module.add("__version", super::VERSION)?;
     module.add("__doc__", "Rust backend for zstandard bindings")?;
 
-    module.add("FLUSH_BLOCK", 0)?;
-    module.add("FLUSH_FRAME", 1)?;
+    module.add("FLUSH_BLOCK", FLUSH_BLOCK)?;
+    module.add("FLUSH_FRAME", FLUSH_FRAME)?;
 
     module.add("COMPRESSOBJ_FLUSH_FINISH", COMPRESSOBJ_FLUSH_FINISH)?;
     module.add("COMPRESSOBJ_FLUSH_BLOCK", COMPRESSOBJ_FLUSH_BLOCK)?;
@@ -27,12 +83,21 @@ pub(crate) fn init_module(py: Python, module: &Bound<'_, PyModule>) -> PyResult<
             zstd_safe::VERSION_RELEASE,
         ),
     )?;
+    // Unlike ZSTD_VERSION, these describe the library actually linked.
+    module.add("VERSION_NUMBER", zstd_safe::version_number())?;
+    module.add_function(wrap_pyfunction!(zstd_version, module)?)?;
+    module.add_function(wrap_pyfunction!(has_multithread_support, module)?)?;
     module.add("FRAME_HEADER", PyBytes::new(py, b"\x28\xb5\x2f\xfd"))?;
 
     module.add("CONTENTSIZE_UNKNOWN", zstd_safe::CONTENTSIZE_UNKNOWN)?;
     module.add("CONTENTSIZE_ERROR", zstd_safe::CONTENTSIZE_ERROR)?;
 
+    // Levels below 0 select the fast strategy, trading ratio for speed.
+    module.add("MIN_COMPRESSION_LEVEL", zstd_safe::min_c_level())?;
     module.add("MAX_COMPRESSION_LEVEL", zstd_safe::max_c_level())?;
+    module.add("DEFAULT_COMPRESSION_LEVEL", unsafe {
+        zstd_sys::ZSTD_defaultCLevel()
+    })?;
     module.add(
         "COMPRESSION_RECOMMENDED_INPUT_SIZE",
         zstd_safe::CCtx::in_size(),
@@ -51,6 +116,10 @@ pub(crate) fn init_module(py: Python, module: &Bound<'_, PyModule>) -> PyResult<
     )?;
 
     module.add("MAGIC_NUMBER", zstd_safe::MAGICNUMBER)?;
+    module.add("FRAMEHEADERSIZE_MIN", FRAMEHEADERSIZE_MIN)?;
+    module.add("FRAMEHEADERSIZE_MAX", zstd_safe::FRAMEHEADERSIZE_MAX)?;
+    module.add("SEEKABLE_MAGIC_NUMBER", SEEKABLE_MAGIC_NUMBER)?;
+    module.add("SEEKABLE_MAX_FRAME_SIZE", SEEKABLE_MAX_FRAME_SIZE)?;
     module.add("BLOCKSIZELOG_MAX", zstd_safe::BLOCKSIZELOG_MAX)?;
     module.add("BLOCKSIZE_MAX", zstd_safe::BLOCKSIZE_MAX)?;
     module.add("WINDOWLOG_MIN", zstd_safe::WINDOWLOG_MIN)?;
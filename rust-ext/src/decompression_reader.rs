@@ -6,12 +6,21 @@
 
 use {
     crate::{
-        exceptions::ZstdError,
-        stream::{make_in_buffer_source, InBufferSource},
-        zstd_safe::DCtx,
+        buffers::DataBuffer,
+        constants::{
+            SEEKABLE_FOOTER_SIZE, SEEKABLE_MAGIC_NUMBER, SEEKABLE_SKIPPABLE_MAGIC_NUMBER,
+            SKIPPABLE_HEADER_SIZE,
+        },
+        decompressionobj::starting_input_hint,
+        decompressor::FrameDicts,
+        exceptions::{WithZstdError, ZstdDecompressionError, ZstdTruncatedError},
+        stream::{
+            make_in_buffer_source, make_low_latency_in_buffer_source, parse_frame_header,
+            peek_frame_header, InBufferSource,
+        },
+        zstd_safe::{read_skippable_frame, DCtx, ZstdFailure},
     },
     pyo3::{
-        buffer::PyBuffer,
         exceptions::{PyOSError, PyValueError},
         prelude::*,
         types::{PyBytes, PyList},
@@ -19,73 +28,355 @@ use {
     std::{cmp::min, sync::Arc},
 };
 
+// Subclasses both OSError and ValueError, matching what io raises for
+// unsupported seeks.
+pyo3::import_exception!(io, UnsupportedOperation);
+
+/// Reject input once more than `max_frames` frames have been started.
+///
+/// A `max_frames` of 0 means there is no limit.
+pub(crate) fn check_frame_limit(frames: usize, max_frames: usize) -> PyResult<()> {
+    if max_frames != 0 && frames > max_frames {
+        Err(ZstdDecompressionError::new_err(format!(
+            "input contains more than {} frames, exceeding max_frames",
+            max_frames
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Reject a frame whose header shows it was written without a checksum.
+///
+/// Skippable frames never carry one and are let through.
+pub(crate) fn check_frame_checksum(header: &zstd_sys::ZSTD_FrameHeader) -> PyResult<()> {
+    if header.frameType == zstd_sys::ZSTD_FrameType_e::ZSTD_frame && header.checksumFlag == 0 {
+        Err(ZstdDecompressionError::new_err(
+            "frame has no content checksum, which require_checksum disallows",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Output and input buffers handed to zstd with the GIL released.
+///
+/// The raw pointers make the buffers `!Send`. They remain valid for the
+/// duration of the call: the reader is mutably borrowed and the source keeps
+/// its input alive.
+struct StreamBuffers<'a>(
+    &'a mut zstd_sys::ZSTD_outBuffer,
+    &'a mut zstd_sys::ZSTD_inBuffer,
+);
+
+unsafe impl Send for StreamBuffers<'_> {}
+
+impl StreamBuffers<'_> {
+    fn decompress(self, dctx: &DCtx) -> Result<usize, ZstdFailure> {
+        dctx.decompress_buffers(self.0, self.1)
+    }
+}
+
 #[pyclass(module = "zstandard.backend_rust")]
 pub struct ZstdDecompressionReader {
     dctx: Arc<DCtx<'static>>,
     source: Box<dyn InBufferSource + Send>,
     read_across_frames: bool,
+    /// zstd's suggested size of the first input of a frame.
+    frame_start_hint: usize,
+    /// Maximum number of frames to decompress. 0 means unlimited.
+    max_frames: usize,
+    /// Number of frames started so far.
+    frames: usize,
+    /// Maximum number of bytes to decompress across all frames. 0 means
+    /// unlimited.
+    max_output_size: usize,
+    /// Bytes decompressed so far, including any buffered by `readline()`.
+    bytes_output: usize,
+    format: zstd_sys::ZSTD_format_e,
+    /// Largest content size a frame header may declare. 0 means unlimited.
+    max_frame_content_size: u64,
+    /// Whether frames lacking a content checksum are rejected.
+    require_checksum: bool,
+    /// Dictionaries to select by frame header, if any were added.
+    dicts: Option<FrameDicts>,
     closefd: bool,
     entered: bool,
     closed: bool,
     bytes_decompressed: usize,
     finished_output: bool,
+    in_frame: bool,
+    /// Whether reads return any available output instead of waiting until
+    /// the requested amount is decompressed.
+    low_latency: bool,
+    /// Whether the last decompression filled its output buffer, in which
+    /// case zstd may hold more output.
+    output_pending: bool,
+    /// Decompressed data read ahead by `readline()` but not yet returned.
+    /// Data before `line_offset` has already been consumed.
+    line_buffer: Vec<u8>,
+    line_offset: usize,
 }
 
 unsafe impl Sync for ZstdDecompressionReader {}
 
 impl ZstdDecompressionReader {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         py: Python,
         dctx: Arc<DCtx<'static>>,
         reader: &Bound<'_, PyAny>,
         read_size: usize,
         read_across_frames: bool,
+        max_frames: usize,
         closefd: bool,
+        low_latency: bool,
+        max_output_size: usize,
+        format: zstd_sys::ZSTD_format_e,
+        max_frame_content_size: u64,
+        require_checksum: bool,
+        dicts: Option<FrameDicts>,
     ) -> PyResult<Self> {
+        let mut source = if low_latency {
+            make_low_latency_in_buffer_source(py, reader, read_size)?
+        } else {
+            make_in_buffer_source(py, reader, read_size)?
+        };
+
+        let frame_start_hint = starting_input_hint(&dctx)?;
+
+        // Input following the frame is left on the source.
+        if !read_across_frames {
+            source.bound_to_frame(py, frame_start_hint, true)?;
+        }
+
         Ok(Self {
             dctx,
-            source: make_in_buffer_source(py, reader, read_size)?,
+            source,
             read_across_frames,
+            frame_start_hint,
+            max_frames,
+            frames: 0,
+            max_output_size,
+            bytes_output: 0,
+            format,
+            max_frame_content_size,
+            require_checksum,
+            dicts,
             closefd,
             entered: false,
             closed: false,
             bytes_decompressed: 0,
             finished_output: false,
+            in_frame: false,
+            low_latency,
+            output_pending: false,
+            line_buffer: Vec::new(),
+            line_offset: 0,
         })
     }
 }
 
 impl ZstdDecompressionReader {
+    /// Reject the upcoming frame if its header declares too much content or
+    /// lacks a required checksum, and load the dictionary it names.
+    ///
+    /// Output is only ever decompressed into buffers sized by the caller, so
+    /// the declared size never causes an allocation. This lets bogus frames
+    /// be rejected before any of their content is decoded. Only the header
+    /// is peeked at, and frames whose header is malformed or truncated are
+    /// left for the decoder to report.
+    fn check_frame_header(&mut self, py: Python) -> PyResult<()> {
+        let Some(header) = peek_frame_header(py, self.source.as_mut(), self.format)? else {
+            return Ok(());
+        };
+
+        if self.require_checksum {
+            check_frame_checksum(&header)?;
+        }
+
+        if let Some(dicts) = &self.dicts {
+            dicts.load(py, &self.dctx, header.dictID)?;
+        }
+
+        if self.max_frame_content_size != 0
+            && header.frameType == zstd_sys::ZSTD_FrameType_e::ZSTD_frame
+            && header.frameContentSize != zstd_sys::ZSTD_CONTENTSIZE_UNKNOWN as u64
+            && header.frameContentSize > self.max_frame_content_size
+        {
+            return Err(ZstdDecompressionError::new_err(format!(
+                "frame declares a content size of {} bytes, exceeding \
+                max_frame_content_size of {} bytes",
+                header.frameContentSize, self.max_frame_content_size
+            )));
+        }
+
+        Ok(())
+    }
+
     fn decompress_into_buffer(
         &mut self,
         py: Python,
         out_buffer: &mut zstd_sys::ZSTD_outBuffer,
     ) -> PyResult<bool> {
-        let mut in_buffer =
-            self.source
-                .input_buffer(py)?
-                .unwrap_or_else(|| zstd_sys::ZSTD_inBuffer {
-                    src: std::ptr::null_mut(),
-                    size: 0,
-                    pos: 0,
-                });
+        let empty = zstd_sys::ZSTD_inBuffer {
+            src: std::ptr::null_mut(),
+            size: 0,
+            pos: 0,
+        };
+
+        // Obtaining input may block, so in low latency mode output zstd
+        // already holds is flushed first.
+        let mut in_buffer = if self.low_latency && self.output_pending {
+            empty
+        } else {
+            if (self.max_frame_content_size != 0 || self.require_checksum || self.dicts.is_some())
+                && !self.in_frame
+            {
+                self.check_frame_header(py)?;
+            }
+
+            self.source.input_buffer(py)?.unwrap_or(empty)
+        };
 
         let old_pos = in_buffer.pos;
+        let old_out_pos = out_buffer.pos;
+
+        // Output is capped to what max_output_size still allows. Once
+        // exhausted, a single byte of room reveals whether more would follow.
+        let out_size = out_buffer.size;
+        let allowance = if self.max_output_size != 0 {
+            let allowance = self.max_output_size - self.bytes_output;
+            out_buffer.size = min(out_size, out_buffer.pos + allowance.max(1));
+            Some(allowance)
+        } else {
+            None
+        };
 
-        let zresult = self
-            .dctx
-            .decompress_buffers(out_buffer, &mut in_buffer)
-            .map_err(|msg| ZstdError::new_err(format!("zstd decompress error: {}", msg)))?;
+        let dctx = &self.dctx;
+        let buffers = StreamBuffers(&mut *out_buffer, &mut in_buffer);
+        let zresult = py.allow_threads(move || buffers.decompress(dctx));
+        let full = out_buffer.pos == out_buffer.size;
+        out_buffer.size = out_size;
+
+        let zresult = zresult.map_err(|msg| {
+            ZstdDecompressionError::new_err(format!("zstd decompress error: {}", msg))
+                .with_zstd_error(&msg)
+        })?;
+
+        let produced = out_buffer.pos - old_out_pos;
+        if allowance.is_some_and(|allowance| produced > allowance) {
+            return Err(ZstdDecompressionError::new_err(format!(
+                "decompressed output exceeds max_output_size of {} bytes",
+                self.max_output_size
+            )));
+        }
+        self.bytes_output += produced;
 
         if in_buffer.pos - old_pos > 0 {
             self.source.record_bytes_read(in_buffer.pos - old_pos);
+
+            // Consuming input outside a frame starts a new one.
+            if !self.in_frame {
+                self.frames += 1;
+                check_frame_limit(self.frames, self.max_frames)?;
+            }
+        }
+
+        if zresult == 0 {
+            self.in_frame = false;
+        } else if in_buffer.pos > old_pos {
+            self.in_frame = true;
+        }
+
+        if !self.read_across_frames {
+            if zresult == 0 {
+                self.source
+                    .bound_to_frame(py, self.frame_start_hint, true)?;
+            } else {
+                self.source.bound_to_frame(py, zresult, false)?;
+            }
         }
 
+        self.output_pending = full;
+
         // Emit data if there is data AND either:
-        // a) output buffer is full (read amount is satisfied)
+        // a) output buffer is full (read amount is satisfied, or all output
+        //    max_output_size allows was produced)
         // b) we're at the end of a frame and not in frame spanning mode
+        // c) we're in low latency mode
         return Ok(out_buffer.pos != 0
-            && (out_buffer.pos == out_buffer.size || zresult == 0 && !self.read_across_frames));
+            && (full || zresult == 0 && !self.read_across_frames || self.low_latency));
+    }
+
+    /// Move data buffered by `readline()` into `out_buffer`.
+    ///
+    /// Returns whether `out_buffer` is full.
+    fn drain_line_buffer(&mut self, out_buffer: &mut zstd_sys::ZSTD_outBuffer) -> bool {
+        let available = &self.line_buffer[self.line_offset..];
+        let count = min(available.len(), out_buffer.size - out_buffer.pos);
+
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                available.as_ptr(),
+                (out_buffer.dst as *mut u8).add(out_buffer.pos),
+                count,
+            );
+        }
+
+        out_buffer.pos += count;
+        self.line_offset += count;
+
+        out_buffer.pos == out_buffer.size
+    }
+
+    /// Decompress the next chunk of output into the line buffer.
+    ///
+    /// Returns the number of bytes added, which is 0 once the input is exhausted.
+    fn fill_line_buffer(&mut self, py: Python) -> PyResult<usize> {
+        self.line_buffer.drain(..self.line_offset);
+        self.line_offset = 0;
+
+        let start = self.line_buffer.len();
+        self.line_buffer.reserve(zstd_safe::DCtx::out_size());
+
+        let mut out_buffer = zstd_sys::ZSTD_outBuffer {
+            dst: unsafe { self.line_buffer.as_mut_ptr().add(start) } as *mut _,
+            size: self.line_buffer.capacity() - start,
+            pos: 0,
+        };
+
+        // Unlike read(), always attempt decompression so output still buffered
+        // in the context is flushed once the source is exhausted.
+        loop {
+            self.decompress_into_buffer(py, &mut out_buffer)?;
+
+            if out_buffer.pos > 0 || self.source.finished() {
+                break;
+            }
+        }
+
+        self.check_input_complete(out_buffer.pos)?;
+
+        unsafe {
+            self.line_buffer.set_len(start + out_buffer.pos);
+        }
+
+        Ok(out_buffer.pos)
+    }
+
+    /// Reject input ending part way through a frame when spanning frames.
+    ///
+    /// Without this, trailing bytes too short to be identified as garbage
+    /// would be silently dropped.
+    fn check_input_complete(&self, output_size: usize) -> PyResult<()> {
+        if self.read_across_frames && self.in_frame && output_size == 0 && self.source.finished() {
+            Err(ZstdTruncatedError::new_err(
+                "input ended with an incomplete frame or non-frame data",
+            ))
+        } else {
+            Ok(())
+        }
     }
 }
 
@@ -93,20 +384,22 @@ impl ZstdDecompressionReader {
 impl ZstdDecompressionReader {
     // PyIterProtocol.
 
-    fn __iter__(slf: PyRef<Self>) -> PyResult<()> {
-        let py = slf.py();
-        let io = py.import("io")?;
-        let exc = io.getattr("UnsupportedOperation")?;
-
-        Err(PyErr::from_value(exc))
+    fn __iter__(slf: PyRef<Self>) -> PyResult<PyRef<Self>> {
+        if slf.closed {
+            Err(PyValueError::new_err("stream is closed"))
+        } else {
+            Ok(slf)
+        }
     }
 
-    fn __next__(slf: PyRef<Self>) -> PyResult<Option<()>> {
-        let py = slf.py();
-        let io = py.import("io")?;
-        let exc = io.getattr("UnsupportedOperation")?;
+    fn __next__<'p>(&mut self, py: Python<'p>) -> PyResult<Option<Bound<'p, PyBytes>>> {
+        let line = self.readline(py, None)?;
 
-        Err(PyErr::from_value(exc))
+        if line.as_bytes().is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(line))
+        }
     }
 
     fn __enter__<'p>(mut slf: PyRefMut<'p, Self>, _py: Python<'p>) -> PyResult<PyRefMut<'p, Self>> {
@@ -139,6 +432,61 @@ impl ZstdDecompressionReader {
         true
     }
 
+    /// Read the skippable frame that follows, if any.
+    ///
+    /// Returns `(magic_variant, payload)` and consumes the frame if the next
+    /// frame in the source is skippable, which reads would otherwise skip
+    /// over. Returns `None` without consuming anything if it isn't. Must be
+    /// called between frames, once output of the previous frame was read.
+    fn read_skippable_frame<'p>(
+        &mut self,
+        py: Python<'p>,
+    ) -> PyResult<Option<(u32, Bound<'p, PyBytes>)>> {
+        if self.closed {
+            return Err(PyValueError::new_err("stream is closed"));
+        }
+
+        if self.in_frame || self.line_offset < self.line_buffer.len() {
+            return Err(PyValueError::new_err(
+                "cannot read a skippable frame before the current frame is fully read",
+            ));
+        }
+
+        let header = self.source.peek(py, SKIPPABLE_HEADER_SIZE)?;
+        if header.len() < SKIPPABLE_HEADER_SIZE
+            || unsafe { zstd_sys::ZSTD_isSkippableFrame(header.as_ptr() as *const _, header.len()) }
+                == 0
+        {
+            return Ok(None);
+        }
+
+        let payload_size = u32::from_le_bytes(header[4..8].try_into().unwrap()) as usize;
+        let frame = self.source.peek(py, SKIPPABLE_HEADER_SIZE + payload_size)?;
+        if frame.len() < SKIPPABLE_HEADER_SIZE + payload_size {
+            return Err(ZstdTruncatedError::new_err(format!(
+                "skippable frame of {} bytes is truncated after {} bytes",
+                SKIPPABLE_HEADER_SIZE + payload_size,
+                frame.len()
+            )));
+        }
+
+        let (magic_variant, payload) = read_skippable_frame(&frame).map_err(|msg| {
+            ZstdDecompressionError::new_err(format!("unable to read skippable frame: {}", msg))
+                .with_zstd_error(&msg)
+        })?;
+
+        self.source.record_bytes_read(frame.len());
+        self.frames += 1;
+        check_frame_limit(self.frames, self.max_frames)?;
+
+        if !self.read_across_frames {
+            self.source
+                .bound_to_frame(py, self.frame_start_hint, true)?;
+        }
+
+        Ok(Some((magic_variant, PyBytes::new(py, &payload))))
+    }
+
     fn writable(&self) -> bool {
         false
     }
@@ -147,22 +495,78 @@ impl ZstdDecompressionReader {
         false
     }
 
+    /// Read up to and including the next `\n`, or up to `size` bytes.
+    ///
+    /// The final line is returned without a trailing newline if the stream
+    /// doesn't end with one.
     #[pyo3(signature = (size=None))]
-    #[allow(unused_variables)]
-    fn readline(&self, py: Python, size: Option<&Bound<'_, PyAny>>) -> PyResult<()> {
-        let io = py.import("io")?;
-        let exc = io.getattr("UnsupportedOperation")?;
+    fn readline<'p>(
+        &mut self,
+        py: Python<'p>,
+        size: Option<isize>,
+    ) -> PyResult<Bound<'p, PyBytes>> {
+        if self.closed {
+            return Err(PyValueError::new_err("stream is closed"));
+        }
 
-        Err(PyErr::from_value(exc))
+        let limit = match size {
+            Some(size) if size >= 0 => size as usize,
+            _ => usize::MAX,
+        };
+
+        // Offset into the unconsumed data already scanned for a newline.
+        let mut scanned = 0;
+
+        let length = loop {
+            let available = &self.line_buffer[self.line_offset..];
+
+            if let Some(index) = available[scanned..].iter().position(|b| *b == b'\n') {
+                break min(scanned + index + 1, limit);
+            }
+
+            if available.len() >= limit {
+                break limit;
+            }
+
+            scanned = available.len();
+
+            if self.fill_line_buffer(py)? == 0 {
+                break scanned;
+            }
+        };
+
+        let start = self.line_offset;
+        self.line_offset += length;
+        self.bytes_decompressed += length;
+
+        Ok(PyBytes::new(py, &self.line_buffer[start..start + length]))
     }
 
     #[pyo3(signature = (hint=None))]
-    #[allow(unused_variables)]
-    fn readlines(&self, py: Python, hint: Option<&Bound<'_, PyAny>>) -> PyResult<()> {
-        let io = py.import("io")?;
-        let exc = io.getattr("UnsupportedOperation")?;
+    fn readlines<'p>(
+        &mut self,
+        py: Python<'p>,
+        hint: Option<isize>,
+    ) -> PyResult<Bound<'p, PyList>> {
+        let hint = hint.unwrap_or(-1);
+        let lines = PyList::empty(py);
+        let mut total = 0;
 
-        Err(PyErr::from_value(exc))
+        loop {
+            let line = self.readline(py, None)?;
+            if line.as_bytes().is_empty() {
+                break;
+            }
+
+            total += line.as_bytes().len();
+            lines.append(line)?;
+
+            if hint > 0 && total >= hint as usize {
+                break;
+            }
+        }
+
+        Ok(lines)
     }
 
     #[pyo3(signature = (data))]
@@ -262,7 +666,9 @@ impl ZstdDecompressionReader {
             pos: 0,
         };
 
-        if self.decompress_into_buffer(py, &mut out_buffer)? {
+        if self.drain_line_buffer(&mut out_buffer)
+            || self.decompress_into_buffer(py, &mut out_buffer)?
+        {
             self.bytes_decompressed += out_buffer.pos;
             unsafe {
                 dest_buffer.set_len(out_buffer.pos);
@@ -286,6 +692,8 @@ impl ZstdDecompressionReader {
             }
         }
 
+        self.check_input_complete(out_buffer.pos)?;
+
         self.bytes_decompressed += out_buffer.pos;
         unsafe {
             dest_buffer.set_len(out_buffer.pos);
@@ -296,7 +704,7 @@ impl ZstdDecompressionReader {
         return Ok(chunk.into_any());
     }
 
-    fn readinto(&mut self, py: Python, buffer: PyBuffer<u8>) -> PyResult<usize> {
+    fn readinto(&mut self, py: Python, buffer: DataBuffer) -> PyResult<usize> {
         if buffer.readonly() {
             return Err(PyValueError::new_err("buffer is not writable"));
         }
@@ -305,7 +713,8 @@ impl ZstdDecompressionReader {
             return Err(PyValueError::new_err("stream is closed"));
         }
 
-        if self.finished_output {
+        // An empty buffer can't make progress and must not consume input.
+        if self.finished_output || buffer.len_bytes() == 0 {
             return Ok(0);
         }
 
@@ -315,7 +724,9 @@ impl ZstdDecompressionReader {
             pos: 0,
         };
 
-        if self.decompress_into_buffer(py, &mut out_buffer)? {
+        if self.drain_line_buffer(&mut out_buffer)
+            || self.decompress_into_buffer(py, &mut out_buffer)?
+        {
             self.bytes_decompressed += out_buffer.pos;
 
             return Ok(out_buffer.pos);
@@ -329,6 +740,8 @@ impl ZstdDecompressionReader {
             }
         }
 
+        self.check_input_complete(out_buffer.pos)?;
+
         self.bytes_decompressed += out_buffer.pos;
 
         Ok(out_buffer.pos)
@@ -365,11 +778,13 @@ impl ZstdDecompressionReader {
             pos: 0,
         };
 
+        self.drain_line_buffer(&mut out_buffer);
+
         // read1() dictates that we can perform at most 1 call to underlying
         // stream to get input. However, we can't satisfy this restriction with
         // decompression because not all input generates output. So we allow
         // multiple read(). But unlike read(), we stop once we have any output.
-        while !self.source.finished() {
+        while out_buffer.pos == 0 && !self.source.finished() {
             self.decompress_into_buffer(py, &mut out_buffer)?;
 
             if out_buffer.pos > 0 {
@@ -377,6 +792,8 @@ impl ZstdDecompressionReader {
             }
         }
 
+        self.check_input_complete(out_buffer.pos)?;
+
         unsafe {
             dest_buffer.set_len(out_buffer.pos);
         }
@@ -387,7 +804,7 @@ impl ZstdDecompressionReader {
         Ok(chunk.into_any())
     }
 
-    fn readinto1(&mut self, py: Python, buffer: PyBuffer<u8>) -> PyResult<usize> {
+    fn readinto1(&mut self, py: Python, buffer: DataBuffer) -> PyResult<usize> {
         if buffer.readonly() {
             return Err(PyValueError::new_err("buffer is not writable"));
         }
@@ -396,7 +813,8 @@ impl ZstdDecompressionReader {
             return Err(PyValueError::new_err("stream is closed"));
         }
 
-        if self.finished_output {
+        // An empty buffer can't make progress and must not consume input.
+        if self.finished_output || buffer.len_bytes() == 0 {
             return Ok(0);
         }
 
@@ -406,7 +824,9 @@ impl ZstdDecompressionReader {
             pos: 0,
         };
 
-        while !self.source.finished() && !self.finished_output {
+        self.drain_line_buffer(&mut out_buffer);
+
+        while out_buffer.pos == 0 && !self.source.finished() && !self.finished_output {
             self.decompress_into_buffer(py, &mut out_buffer)?;
 
             if out_buffer.pos > 0 {
@@ -414,6 +834,8 @@ impl ZstdDecompressionReader {
             }
         }
 
+        self.check_input_complete(out_buffer.pos)?;
+
         self.bytes_decompressed += out_buffer.pos;
 
         Ok(out_buffer.pos)
@@ -435,13 +857,13 @@ impl ZstdDecompressionReader {
 
         let mut read_amount = if whence == seek_set {
             if pos < 0 {
-                return Err(PyOSError::new_err(
+                return Err(UnsupportedOperation::new_err(
                     "cannot seek to negative position with SEEK_SET",
                 ));
             }
 
             if pos < self.bytes_decompressed as isize {
-                return Err(PyOSError::new_err(
+                return Err(UnsupportedOperation::new_err(
                     "cannot seek zstd decompression stream backwards",
                 ));
             }
@@ -449,14 +871,14 @@ impl ZstdDecompressionReader {
             pos as usize - self.bytes_decompressed
         } else if whence == seek_cur {
             if pos < 0 {
-                return Err(PyOSError::new_err(
+                return Err(UnsupportedOperation::new_err(
                     "cannot seek zstd decompression stream backwards",
                 ));
             }
 
             pos as usize
         } else if whence == seek_end {
-            return Err(PyOSError::new_err(
+            return Err(UnsupportedOperation::new_err(
                 "zstd decompression streams cannot be seeked with SEEK_END",
             ));
         } else {
@@ -476,3 +898,443 @@ impl ZstdDecompressionReader {
         Ok(self.bytes_decompressed)
     }
 }
+
+/// Location of a single frame within a seekable stream.
+struct SeekableFrame {
+    compressed_offset: u64,
+    compressed_size: usize,
+    decompressed_offset: u64,
+    decompressed_size: usize,
+}
+
+/// Provides random access reads over data in the zstd seekable format.
+///
+/// The seek table is parsed from the end of the source when the reader is
+/// created. Reads only decompress the frames covering the requested range.
+#[pyclass(module = "zstandard.backend_rust")]
+pub struct SeekableDecompressionReader {
+    dctx: Arc<DCtx<'static>>,
+    source: PyObject,
+    /// Set when the source conforms to the buffer protocol.
+    buffer: Option<DataBuffer>,
+    format: zstd_sys::ZSTD_format_e,
+    require_checksum: bool,
+    /// Dictionaries to select by frame header, if any were added.
+    dicts: Option<FrameDicts>,
+    closefd: bool,
+    entered: bool,
+    closed: bool,
+    frames: Vec<SeekableFrame>,
+    decompressed_size: u64,
+    position: u64,
+    /// Index and decompressed content of the most recently decoded frame.
+    current_frame: Option<(usize, Vec<u8>)>,
+}
+
+unsafe impl Sync for SeekableDecompressionReader {}
+
+impl SeekableDecompressionReader {
+    pub fn new(
+        py: Python,
+        dctx: Arc<DCtx<'static>>,
+        source: &Bound<'_, PyAny>,
+        format: zstd_sys::ZSTD_format_e,
+        require_checksum: bool,
+        dicts: Option<FrameDicts>,
+        closefd: bool,
+    ) -> PyResult<Self> {
+        let buffer = if let Ok(buffer) = DataBuffer::get(source) {
+            Some(buffer)
+        } else if source.hasattr("read")? && source.hasattr("seek")? {
+            None
+        } else {
+            return Err(PyValueError::new_err(
+                "must pass an object with read() and seek() methods or that conforms to the buffer protocol",
+            ));
+        };
+
+        let mut reader = Self {
+            dctx,
+            source: source.clone().unbind(),
+            buffer,
+            format,
+            require_checksum,
+            dicts,
+            closefd,
+            entered: false,
+            closed: false,
+            frames: vec![],
+            decompressed_size: 0,
+            position: 0,
+            current_frame: None,
+        };
+
+        reader.load_seek_table(py)?;
+
+        Ok(reader)
+    }
+
+    fn source_size(&self, py: Python) -> PyResult<u64> {
+        if let Some(buffer) = &self.buffer {
+            Ok(buffer.len_bytes() as u64)
+        } else {
+            self.source.call_method1(py, "seek", (0, 2))?.extract(py)
+        }
+    }
+
+    /// Read exactly `size` bytes of the source starting at `offset`.
+    fn read_source(&self, py: Python, offset: u64, size: usize) -> PyResult<Vec<u8>> {
+        if let Some(buffer) = &self.buffer {
+            let data: &[u8] = unsafe {
+                std::slice::from_raw_parts(buffer.buf_ptr() as *const _, buffer.len_bytes())
+            };
+
+            let start = offset as usize;
+
+            return data
+                .get(start..start.saturating_add(size))
+                .map(|chunk| chunk.to_vec())
+                .ok_or_else(|| ZstdDecompressionError::new_err("seekable source is truncated"));
+        }
+
+        self.source.call_method1(py, "seek", (offset,))?;
+        let data = self.source.call_method1(py, "read", (size,))?;
+        let buffer = DataBuffer::get(data.bind(py))?;
+
+        if buffer.len_bytes() != size {
+            return Err(ZstdDecompressionError::new_err(
+                "seekable source is truncated",
+            ));
+        }
+
+        Ok(buffer.as_slice().to_vec())
+    }
+
+    fn load_seek_table(&mut self, py: Python) -> PyResult<()> {
+        let source_size = self.source_size(py)?;
+
+        if source_size < (8 + SEEKABLE_FOOTER_SIZE) as u64 {
+            return Err(ZstdDecompressionError::new_err(
+                "source lacks a valid seek table footer",
+            ));
+        }
+
+        let footer = self.read_source(
+            py,
+            source_size - SEEKABLE_FOOTER_SIZE as u64,
+            SEEKABLE_FOOTER_SIZE,
+        )?;
+
+        let frame_count = u32::from_le_bytes(footer[0..4].try_into().unwrap()) as usize;
+        let descriptor = footer[4];
+        let magic = u32::from_le_bytes(footer[5..9].try_into().unwrap());
+
+        // Bits 2-6 of the descriptor are reserved and must be zero.
+        if magic != SEEKABLE_MAGIC_NUMBER || descriptor & 0x7c != 0 {
+            return Err(ZstdDecompressionError::new_err(
+                "source lacks a valid seek table footer",
+            ));
+        }
+
+        let entry_size = if descriptor & 0x80 != 0 { 12 } else { 8 };
+        let table_size = (8 + frame_count * entry_size + SEEKABLE_FOOTER_SIZE) as u64;
+
+        if table_size > source_size {
+            return Err(ZstdDecompressionError::new_err(
+                "seek table is larger than source",
+            ));
+        }
+
+        let table_offset = source_size - table_size;
+        let table = self.read_source(py, table_offset, table_size as usize)?;
+
+        let skippable_magic = u32::from_le_bytes(table[0..4].try_into().unwrap());
+        let frame_size = u32::from_le_bytes(table[4..8].try_into().unwrap()) as u64;
+
+        if skippable_magic != SEEKABLE_SKIPPABLE_MAGIC_NUMBER || frame_size != table_size - 8 {
+            return Err(ZstdDecompressionError::new_err("invalid seek table header"));
+        }
+
+        let mut compressed_offset = 0u64;
+        let mut decompressed_offset = 0u64;
+
+        for entry in table[8..8 + frame_count * entry_size].chunks_exact(entry_size) {
+            let compressed_size = u32::from_le_bytes(entry[0..4].try_into().unwrap()) as usize;
+            let decompressed_size = u32::from_le_bytes(entry[4..8].try_into().unwrap()) as usize;
+
+            self.frames.push(SeekableFrame {
+                compressed_offset,
+                compressed_size,
+                decompressed_offset,
+                decompressed_size,
+            });
+
+            compressed_offset += compressed_size as u64;
+            decompressed_offset += decompressed_size as u64;
+        }
+
+        if compressed_offset > table_offset {
+            return Err(ZstdDecompressionError::new_err(
+                "seek table references data beyond the end of the source",
+            ));
+        }
+
+        self.decompressed_size = decompressed_offset;
+
+        Ok(())
+    }
+
+    /// Decompress the frame at `index` unless it is already cached.
+    fn load_frame(&mut self, py: Python, index: usize) -> PyResult<&[u8]> {
+        if !matches!(&self.current_frame, Some((current, _)) if *current == index) {
+            let frame = &self.frames[index];
+            let compressed =
+                self.read_source(py, frame.compressed_offset, frame.compressed_size)?;
+
+            let mut dest_buffer = Vec::with_capacity(frame.decompressed_size);
+
+            self.dctx.reset().map_err(|msg| {
+                ZstdDecompressionError::new_err(format!(
+                    "unable to reset decompression context: {}",
+                    msg
+                ))
+                .with_zstd_error(&msg)
+            })?;
+
+            if let Ok((header, 0)) = parse_frame_header(&compressed, self.format) {
+                if self.require_checksum {
+                    check_frame_checksum(&header)?;
+                }
+
+                if let Some(dicts) = &self.dicts {
+                    dicts.load(py, &self.dctx, header.dictID)?;
+                }
+            }
+
+            let mut in_buffer = zstd_sys::ZSTD_inBuffer {
+                src: compressed.as_ptr() as *const _,
+                size: compressed.len(),
+                pos: 0,
+            };
+
+            loop {
+                let zresult = self
+                    .dctx
+                    .decompress_into_vec(&mut dest_buffer, &mut in_buffer)
+                    .map_err(|msg| {
+                        ZstdDecompressionError::new_err(format!("zstd decompress error: {}", msg))
+                            .with_zstd_error(&msg)
+                    })?;
+
+                if zresult == 0 {
+                    break;
+                } else if in_buffer.pos == in_buffer.size
+                    || dest_buffer.len() == dest_buffer.capacity()
+                {
+                    return Err(ZstdDecompressionError::new_err(format!(
+                        "frame {} does not match seek table",
+                        index
+                    )));
+                }
+            }
+
+            if in_buffer.pos != in_buffer.size || dest_buffer.len() != frame.decompressed_size {
+                return Err(ZstdDecompressionError::new_err(format!(
+                    "frame {} does not match seek table",
+                    index
+                )));
+            }
+
+            self.current_frame = Some((index, dest_buffer));
+        }
+
+        Ok(&self.current_frame.as_ref().unwrap().1)
+    }
+
+    /// Copy decompressed data at the current position into `dest`.
+    fn read_into_slice(&mut self, py: Python, dest: &mut [u8]) -> PyResult<usize> {
+        let mut written = 0;
+
+        while written < dest.len() && self.position < self.decompressed_size {
+            // Find the first frame ending beyond the current position. This
+            // skips over empty frames when landing on a frame boundary.
+            let position = self.position;
+            let index = self.frames.partition_point(|f| {
+                f.decompressed_offset + f.decompressed_size as u64 <= position
+            });
+            let frame_offset = (position - self.frames[index].decompressed_offset) as usize;
+
+            let data = &self.load_frame(py, index)?[frame_offset..];
+            let count = min(data.len(), dest.len() - written);
+            dest[written..written + count].copy_from_slice(&data[..count]);
+
+            written += count;
+            self.position += count as u64;
+        }
+
+        Ok(written)
+    }
+}
+
+#[pymethods]
+impl SeekableDecompressionReader {
+    fn __enter__<'p>(mut slf: PyRefMut<'p, Self>, _py: Python<'p>) -> PyResult<PyRefMut<'p, Self>> {
+        if slf.entered {
+            Err(PyValueError::new_err("cannot __enter__ multiple times"))
+        } else if slf.closed {
+            Err(PyValueError::new_err("stream is closed"))
+        } else {
+            slf.entered = true;
+            Ok(slf)
+        }
+    }
+
+    fn __exit__<'p>(
+        mut slf: PyRefMut<'p, Self>,
+        py: Python<'p>,
+        _exc_type: &Bound<'_, PyAny>,
+        _exc_value: &Bound<'_, PyAny>,
+        _exc_tb: &Bound<'_, PyAny>,
+    ) -> PyResult<bool> {
+        slf.entered = false;
+        slf.close(py)?;
+
+        Ok(false)
+    }
+
+    fn readable(&self) -> bool {
+        true
+    }
+
+    fn writable(&self) -> bool {
+        false
+    }
+
+    fn seekable(&self) -> bool {
+        true
+    }
+
+    fn isatty(&self) -> bool {
+        false
+    }
+
+    fn flush(&self) -> PyResult<()> {
+        Ok(())
+    }
+
+    fn close(&mut self, py: Python) -> PyResult<()> {
+        if self.closed {
+            return Ok(());
+        }
+
+        self.closed = true;
+        self.current_frame = None;
+
+        if let Ok(close) = self.source.getattr(py, "close") {
+            if self.closefd {
+                close.call0(py)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    #[getter]
+    fn closed(&self) -> bool {
+        self.closed
+    }
+
+    fn tell(&self) -> u64 {
+        self.position
+    }
+
+    /// Number of frames described by the seek table.
+    fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    fn readall<'p>(&mut self, py: Python<'p>) -> PyResult<Bound<'p, PyBytes>> {
+        self.read(py, Some(-1))
+    }
+
+    #[pyo3(signature = (size=None))]
+    fn read<'p>(&mut self, py: Python<'p>, size: Option<isize>) -> PyResult<Bound<'p, PyBytes>> {
+        if self.closed {
+            return Err(PyValueError::new_err("stream is closed"));
+        }
+
+        let size = size.unwrap_or(-1);
+
+        if size < -1 {
+            return Err(PyValueError::new_err(
+                "cannot read negative amounts less than -1",
+            ));
+        }
+
+        let remaining = self.decompressed_size.saturating_sub(self.position) as usize;
+        let size = if size == -1 {
+            remaining
+        } else {
+            min(size as usize, remaining)
+        };
+
+        let mut dest_buffer = vec![0; size];
+        let count = self.read_into_slice(py, &mut dest_buffer)?;
+        dest_buffer.truncate(count);
+
+        Ok(PyBytes::new(py, &dest_buffer))
+    }
+
+    fn readinto(&mut self, py: Python, buffer: DataBuffer) -> PyResult<usize> {
+        if buffer.readonly() {
+            return Err(PyValueError::new_err("buffer is not writable"));
+        }
+
+        if self.closed {
+            return Err(PyValueError::new_err("stream is closed"));
+        }
+
+        let dest: &mut [u8] = unsafe {
+            std::slice::from_raw_parts_mut(buffer.buf_ptr() as *mut _, buffer.len_bytes())
+        };
+
+        self.read_into_slice(py, dest)
+    }
+
+    #[pyo3(signature = (pos, whence=None))]
+    fn seek(&mut self, py: Python, pos: i64, whence: Option<i32>) -> PyResult<u64> {
+        if self.closed {
+            return Err(PyValueError::new_err("stream is closed"));
+        }
+
+        let os = py.import("os")?;
+
+        let seek_set = os.getattr("SEEK_SET")?.extract::<i32>()?;
+        let seek_cur = os.getattr("SEEK_CUR")?.extract::<i32>()?;
+        let seek_end = os.getattr("SEEK_END")?.extract::<i32>()?;
+
+        let whence = whence.unwrap_or(seek_set);
+
+        let base = if whence == seek_set {
+            0
+        } else if whence == seek_cur {
+            self.position as i64
+        } else if whence == seek_end {
+            self.decompressed_size as i64
+        } else {
+            return Err(PyValueError::new_err(format!(
+                "invalid whence value: {}",
+                whence
+            )));
+        };
+
+        let position = base
+            .checked_add(pos)
+            .filter(|position| *position >= 0)
+            .ok_or_else(|| PyOSError::new_err("cannot seek to negative position"))?;
+
+        self.position = position as u64;
+
+        Ok(self.position)
+    }
+}
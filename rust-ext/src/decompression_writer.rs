@@ -5,9 +5,15 @@
 // of the BSD license. See the LICENSE file for details.
 
 use {
-    crate::{exceptions::ZstdError, zstd_safe::DCtx},
+    crate::{
+        buffers::DataBuffer,
+        decompression_reader::check_frame_checksum,
+        decompressor::FrameDicts,
+        exceptions::{WithZstdError, ZstdDecompressionError},
+        stream::parse_frame_header,
+        zstd_safe::DCtx,
+    },
     pyo3::{
-        buffer::PyBuffer,
         exceptions::{PyOSError, PyValueError},
         prelude::*,
         types::PyBytes,
@@ -22,21 +28,34 @@ pub struct ZstdDecompressionWriter {
     writer: PyObject,
     write_size: usize,
     write_return_read: bool,
+    format: zstd_sys::ZSTD_format_e,
+    require_checksum: bool,
+    /// Dictionaries to select by frame header, if any were added.
+    dicts: Option<FrameDicts>,
     closefd: bool,
     entered: bool,
     closing: bool,
     closed: bool,
+    /// Whether zstd has consumed input of the current frame.
+    in_frame: bool,
+    /// Start of a frame whose header is incomplete, held back so the header
+    /// can be inspected before any of the frame is decoded.
+    header_input: Vec<u8>,
 }
 
 unsafe impl Sync for ZstdDecompressionWriter {}
 
 impl ZstdDecompressionWriter {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         py: Python,
         dctx: Arc<DCtx<'static>>,
         writer: &Bound<'_, PyAny>,
         write_size: usize,
         write_return_read: bool,
+        format: zstd_sys::ZSTD_format_e,
+        require_checksum: bool,
+        dicts: Option<FrameDicts>,
         closefd: bool,
     ) -> PyResult<Self> {
         Ok(Self {
@@ -44,10 +63,15 @@ impl ZstdDecompressionWriter {
             writer: writer.into_py_any(py)?,
             write_size,
             write_return_read,
+            format,
+            require_checksum,
+            dicts,
             closefd,
             entered: false,
             closing: false,
             closed: false,
+            in_frame: false,
+            header_input: vec![],
         })
     }
 }
@@ -76,7 +100,9 @@ impl ZstdDecompressionWriter {
         if slf.closed {
             Err(PyValueError::new_err("stream is closed"))
         } else if slf.entered {
-            Err(ZstdError::new_err("cannot __enter__ multiple times"))
+            Err(ZstdDecompressionError::new_err(
+                "cannot __enter__ multiple times",
+            ))
         } else {
             slf.entered = true;
             Ok(slf)
@@ -113,15 +139,15 @@ impl ZstdDecompressionWriter {
         self.closing = false;
         self.closed = true;
 
-        res?;
-
-        if let Ok(close) = self.writer.getattr(py, "close") {
-            if self.closefd {
-                close.call0(py)?;
-            }
-        }
+        // Close the underlying stream even if flushing failed so it isn't
+        // leaked.
+        let close_res = match self.writer.getattr(py, "close") {
+            Ok(close) if self.closefd => close.call0(py).map(|_| ()),
+            _ => Ok(()),
+        };
 
-        Ok(())
+        res?;
+        close_res
     }
 
     #[getter]
@@ -262,37 +288,83 @@ impl ZstdDecompressionWriter {
         Err(PyErr::from_value(exc))
     }
 
-    fn write(&self, py: Python, buffer: PyBuffer<u8>) -> PyResult<usize> {
+    fn write(&mut self, py: Python, buffer: DataBuffer) -> PyResult<usize> {
         if self.closed {
             return Err(PyValueError::new_err("stream is closed"));
         }
 
         let mut total_write = 0;
 
-        let mut in_buffer = zstd_sys::ZSTD_inBuffer {
-            src: buffer.buf_ptr(),
-            size: buffer.len_bytes(),
-            pos: 0,
+        let held;
+        let mut input = if self.header_input.is_empty() {
+            buffer.as_slice()
+        } else {
+            self.header_input.extend_from_slice(buffer.as_slice());
+            held = std::mem::take(&mut self.header_input);
+            held.as_slice()
         };
 
         let mut dest_buffer = Vec::with_capacity(self.write_size);
 
-        while in_buffer.pos < in_buffer.size {
-            self.dctx
-                .decompress_into_vec(&mut dest_buffer, &mut in_buffer)
-                .map_err(|msg| ZstdError::new_err(format!("zstd decompress error: {}", msg)))?;
-
-            if !dest_buffer.is_empty() {
-                // TODO avoid buffer copy.
-                let chunk = PyBytes::new(py, &dest_buffer);
-                self.writer.call_method1(py, "write", (chunk,))?;
-                total_write += dest_buffer.len();
-                dest_buffer.clear();
+        while !input.is_empty() {
+            if (self.require_checksum || self.dicts.is_some()) && !self.in_frame {
+                match parse_frame_header(input, self.format) {
+                    Ok((header, 0)) => {
+                        if self.require_checksum {
+                            check_frame_checksum(&header)?;
+                        }
+
+                        if let Some(dicts) = &self.dicts {
+                            dicts.load(py, &self.dctx, header.dictID)?;
+                        }
+                    }
+                    Ok(_) => {
+                        self.header_input = input.to_vec();
+                        break;
+                    }
+                    // Malformed headers are left for zstd to report.
+                    Err(_) => {}
+                }
             }
+
+            let mut in_buffer = zstd_sys::ZSTD_inBuffer {
+                src: input.as_ptr() as *const _,
+                size: input.len(),
+                pos: 0,
+            };
+
+            // Stop at the end of a frame so the next one's header is checked.
+            loop {
+                let zresult = self
+                    .dctx
+                    .decompress_into_vec(&mut dest_buffer, &mut in_buffer)
+                    .map_err(|msg| {
+                        ZstdDecompressionError::new_err(format!("zstd decompress error: {}", msg))
+                            .with_zstd_error(&msg)
+                    })?;
+
+                self.in_frame = zresult != 0;
+
+                if !dest_buffer.is_empty() {
+                    // TODO avoid buffer copy.
+                    let chunk = PyBytes::new(py, &dest_buffer);
+                    self.writer.call_method1(py, "write", (chunk,))?;
+                    total_write += dest_buffer.len();
+                    dest_buffer.clear();
+                }
+
+                if zresult == 0 || in_buffer.pos == in_buffer.size {
+                    break;
+                }
+            }
+
+            input = &input[in_buffer.pos..];
         }
 
+        // All input is consumed, with an incomplete header held until the
+        // next write.
         if self.write_return_read {
-            Ok(in_buffer.pos)
+            Ok(buffer.len_bytes())
         } else {
             Ok(total_write)
         }
@@ -5,9 +5,15 @@
 // of the BSD license. See the LICENSE file for details.
 
 use {
-    crate::{exceptions::ZstdError, zstd_safe::DCtx},
+    crate::{
+        buffers::DataBuffer,
+        decompression_reader::check_frame_checksum,
+        decompressor::FrameDicts,
+        exceptions::{WithZstdError, ZstdDecompressionError},
+        stream::parse_frame_header,
+        zstd_safe::DCtx,
+    },
     pyo3::{
-        buffer::PyBuffer,
         prelude::*,
         types::{PyBytes, PyList},
     },
@@ -19,8 +25,18 @@ pub struct ZstdDecompressionObj {
     dctx: Arc<DCtx<'static>>,
     write_size: usize,
     read_across_frames: bool,
+    format: zstd_sys::ZSTD_format_e,
+    require_checksum: bool,
+    /// Dictionaries to select by frame header, if any were added.
+    dicts: Option<FrameDicts>,
     finished: bool,
     unused_data: Vec<u8>,
+    next_input_hint: usize,
+    /// Whether zstd has consumed input of the current frame.
+    in_frame: bool,
+    /// Start of a frame whose header is incomplete, held back so the header
+    /// can be inspected before any of the frame is decoded.
+    header_input: Vec<u8>,
 }
 
 unsafe impl Sync for ZstdDecompressionObj {}
@@ -30,22 +46,72 @@ impl ZstdDecompressionObj {
         dctx: Arc<DCtx<'static>>,
         write_size: usize,
         read_across_frames: bool,
+        format: zstd_sys::ZSTD_format_e,
+        require_checksum: bool,
+        dicts: Option<FrameDicts>,
     ) -> PyResult<Self> {
+        let next_input_hint = starting_input_hint(&dctx)?;
+
         Ok(ZstdDecompressionObj {
             dctx,
             write_size,
             read_across_frames,
+            format,
+            require_checksum,
+            dicts,
             finished: false,
             unused_data: vec![],
+            next_input_hint,
+            in_frame: false,
+            header_input: vec![],
         })
     }
 }
 
+/// Obtain zstd's input size hint for a context that hasn't seen any input.
+///
+/// Feeding no input consumes nothing but still yields the hint. It does move
+/// zstd past the stage where dictionaries may be referenced, so the session
+/// is reset afterwards.
+pub(crate) fn starting_input_hint(dctx: &DCtx) -> PyResult<usize> {
+    let mut in_buffer = zstd_sys::ZSTD_inBuffer {
+        src: std::ptr::null(),
+        size: 0,
+        pos: 0,
+    };
+    let mut out_buffer = zstd_sys::ZSTD_outBuffer {
+        dst: std::ptr::null_mut(),
+        size: 0,
+        pos: 0,
+    };
+
+    let hint = dctx
+        .decompress_buffers(&mut out_buffer, &mut in_buffer)
+        .map_err(|msg| {
+            ZstdDecompressionError::new_err(format!("zstd decompress error: {}", msg))
+                .with_zstd_error(&msg)
+        })?;
+
+    dctx.reset().map_err(|msg| {
+        ZstdDecompressionError::new_err(format!("unable to reset decompression context: {}", msg))
+            .with_zstd_error(&msg)
+    })?;
+
+    Ok(hint)
+}
+
 #[pymethods]
 impl ZstdDecompressionObj {
-    fn decompress<'p>(&mut self, py: Python<'p>, data: PyBuffer<u8>) -> PyResult<Bound<'p, PyAny>> {
+    /// Decompress `data`, returning all output it allows.
+    ///
+    /// Input may be split anywhere, even within a frame header or block.
+    /// Input that doesn't complete a block is retained by the context and
+    /// decompressed once later calls supply the rest, so the output doesn't
+    /// depend on how a frame is split. No output is held back: once a call
+    /// returns, `needs_input` tells whether more input is required.
+    fn decompress<'p>(&mut self, py: Python<'p>, data: DataBuffer) -> PyResult<Bound<'p, PyAny>> {
         if self.finished {
-            return Err(ZstdError::new_err(
+            return Err(ZstdDecompressionError::new_err(
                 "cannot use a decompressobj multiple times",
             ));
         }
@@ -54,49 +120,88 @@ impl ZstdDecompressionObj {
             return Ok(PyBytes::new(py, &[]).into_any());
         }
 
-        let mut in_buffer = zstd_sys::ZSTD_inBuffer {
-            src: data.buf_ptr(),
-            size: data.len_bytes(),
-            pos: 0,
+        let held;
+        let mut input = if self.header_input.is_empty() {
+            data.as_slice()
+        } else {
+            self.header_input.extend_from_slice(data.as_slice());
+            held = std::mem::take(&mut self.header_input);
+            held.as_slice()
         };
 
         let mut dest_buffer: Vec<u8> = Vec::with_capacity(self.write_size);
 
         let chunks = PyList::empty(py);
 
-        loop {
-            let zresult = self
-                .dctx
-                .decompress_into_vec(&mut dest_buffer, &mut in_buffer)
-                .map_err(|msg| ZstdError::new_err(format!("zstd decompress error: {}", msg)))?;
+        'frames: loop {
+            if (self.require_checksum || self.dicts.is_some()) && !self.in_frame {
+                match parse_frame_header(input, self.format) {
+                    Ok((header, 0)) => {
+                        if self.require_checksum {
+                            check_frame_checksum(&header)?;
+                        }
 
-            if !dest_buffer.is_empty() {
-                // TODO avoid buffer copy.
-                let chunk = PyBytes::new(py, &dest_buffer);
-                chunks.append(chunk)?;
+                        if let Some(dicts) = &self.dicts {
+                            dicts.load(py, &self.dctx, header.dictID)?;
+                        }
+                    }
+                    Ok((_, needed)) => {
+                        self.next_input_hint = needed - input.len();
+                        self.header_input = input.to_vec();
+                        break;
+                    }
+                    // Malformed headers are left for zstd to report.
+                    Err(_) => {}
+                }
             }
 
-            if zresult == 0 && !self.read_across_frames {
-                self.finished = true;
-                // TODO clear out decompressor?
+            let mut in_buffer = zstd_sys::ZSTD_inBuffer {
+                src: input.as_ptr() as *const _,
+                size: input.len(),
+                pos: 0,
+            };
+
+            loop {
+                let zresult = self
+                    .dctx
+                    .decompress_into_vec(&mut dest_buffer, &mut in_buffer)
+                    .map_err(|msg| {
+                        ZstdDecompressionError::new_err(format!("zstd decompress error: {}", msg))
+                            .with_zstd_error(&msg)
+                    })?;
 
-                if let Some(data) = data.as_slice(py) {
-                    let unused = &data[in_buffer.pos..in_buffer.size];
-                    self.unused_data = unused.iter().map(|x| x.get()).collect::<Vec<_>>();
+                self.next_input_hint = zresult;
+                self.in_frame = zresult != 0;
+
+                if !dest_buffer.is_empty() {
+                    // TODO avoid buffer copy.
+                    let chunk = PyBytes::new(py, &dest_buffer);
+                    chunks.append(chunk)?;
                 }
 
-                break;
-            } else if zresult == 0 && self.read_across_frames {
-                if in_buffer.pos == in_buffer.size {
-                    break;
+                if zresult == 0 {
+                    let remaining = &input[in_buffer.pos..];
+
+                    if !self.read_across_frames {
+                        self.finished = true;
+                        // TODO clear out decompressor?
+                        self.unused_data = remaining.to_vec();
+                        break 'frames;
+                    } else if remaining.is_empty() {
+                        break 'frames;
+                    }
+
+                    // The next frame's header is checked before decoding it.
+                    dest_buffer.clear();
+                    input = remaining;
+                    continue 'frames;
+                } else if in_buffer.pos == in_buffer.size
+                    && dest_buffer.len() < dest_buffer.capacity()
+                {
+                    break 'frames;
                 } else {
                     dest_buffer.clear();
                 }
-            } else if in_buffer.pos == in_buffer.size && dest_buffer.len() < dest_buffer.capacity()
-            {
-                break;
-            } else {
-                dest_buffer.clear();
             }
         }
 
@@ -104,12 +209,44 @@ impl ZstdDecompressionObj {
         empty.call_method1("join", (chunks,))
     }
 
+    /// Discard any in-progress frame so the object can decompress a new one.
+    ///
+    /// Only session state is reset: the dictionary and decompression
+    /// parameters configured by the decompressor remain in effect.
+    fn reset(&mut self) -> PyResult<()> {
+        self.dctx.reset().map_err(|msg| {
+            ZstdDecompressionError::new_err(format!(
+                "unable to reset decompression context: {}",
+                msg
+            ))
+            .with_zstd_error(&msg)
+        })?;
+
+        self.finished = false;
+        self.unused_data.clear();
+        self.in_frame = false;
+        self.header_input.clear();
+        self.next_input_hint = starting_input_hint(&self.dctx)?;
+
+        Ok(())
+    }
+
     #[pyo3(signature = (length=None))]
     #[allow(unused_variables)]
     fn flush<'p>(&self, py: Python<'p>, length: Option<usize>) -> PyResult<Bound<'p, PyBytes>> {
         Ok(PyBytes::new(py, &[]))
     }
 
+    /// Suggested number of input bytes to pass to the next `decompress()`.
+    ///
+    /// This is zstd's hint from the most recent call and is `0` once the
+    /// frame is complete. It is advisory: passing more or less input still
+    /// decompresses correctly, but reading exactly this much from a socket
+    /// avoids buffering partial blocks.
+    fn next_input_hint(&self) -> usize {
+        self.next_input_hint
+    }
+
     #[getter]
     fn unused_data<'p>(&self, py: Python<'p>) -> Bound<'p, PyBytes> {
         PyBytes::new(py, &self.unused_data)
@@ -124,4 +261,14 @@ impl ZstdDecompressionObj {
     fn eof(&self) -> bool {
         self.finished
     }
+
+    /// Whether a frame is incomplete and needs more input to finish.
+    ///
+    /// This is true until the first frame's input is complete. With
+    /// `read_across_frames`, it becomes true again once input starts another
+    /// frame, so it is false when input fed so far ends on a frame boundary.
+    #[getter]
+    fn needs_input(&self) -> bool {
+        !self.finished && self.next_input_hint != 0
+    }
 }
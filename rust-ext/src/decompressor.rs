@@ -6,49 +6,194 @@
 
 use {
     crate::{
-        buffers::ZstdBufferWithSegmentsCollection, compression_dict::ZstdCompressionDict,
-        decompression_reader::ZstdDecompressionReader,
-        decompression_writer::ZstdDecompressionWriter, decompressionobj::ZstdDecompressionObj,
+        buffers::{
+            BufferSegment, DataBuffer, ZstdBufferWithSegments, ZstdBufferWithSegmentsCollection,
+        },
+        compression_dict::ZstdCompressionDict,
+        compression_parameters::value_to_format,
+        compressor::interpreter_id,
+        decompression_reader::{
+            check_frame_checksum, SeekableDecompressionReader, ZstdDecompressionReader,
+        },
+        decompression_writer::ZstdDecompressionWriter,
+        decompressionobj::ZstdDecompressionObj,
         decompressor_iterator::ZstdDecompressorIterator,
-        decompressor_multi::multi_decompress_to_buffer, exceptions::ZstdError, zstd_safe::DCtx,
+        decompressor_multi::{multi_decompress_to_buffer, resolve_decompressed_sizes},
+        exceptions::{WithZstdError, ZstdDecompressionError, ZstdError, ZstdTruncatedError},
+        frame_parameters::{find_frames, frame_block_count},
+        stream::{make_in_buffer_source, parse_frame_header, peek_frame_header, InBufferSource},
+        zstd_safe::{window_too_large_error, DCtx},
     },
+    libc::c_int,
     pyo3::{
-        buffer::PyBuffer,
         exceptions::{PyMemoryError, PyValueError},
         prelude::*,
         types::{PyBytes, PyList},
         wrap_pyfunction,
     },
-    std::{ffi::c_ulonglong, sync::Arc},
+    std::{cell::RefCell, collections::HashMap, ffi::c_ulonglong, sync::Arc},
 };
 
+thread_local! {
+    /// Context reused by the module-level `decompress()` on this thread.
+    ///
+    /// Tagged with the owning interpreter's ID like the compression context.
+    static ONE_SHOT_DCTX: RefCell<Option<(i64, Arc<DCtx<'static>>)>> = const { RefCell::new(None) };
+}
+
+/// Default limit on the output `decompress_to_buffer()` allocates up front.
+const DECOMPRESS_TO_BUFFER_MAX_OUTPUT_SIZE: usize = 1 << 30;
+
+/// Context and history for `ZstdDecompressor.decompress_block()`.
+///
+/// Like the compressing side, the previous block stays alive so the next
+/// block can reference it.
+struct BlockDecompressor {
+    dctx: DCtx<'static>,
+    previous: Vec<u8>,
+}
+
 #[pyclass(module = "zstandard.backend_rust")]
 struct ZstdDecompressor {
     dict_data: Option<Py<ZstdCompressionDict>>,
     max_window_size: usize,
+    max_window_log: i32,
     format: zstd_sys::ZSTD_format_e,
+    verify_checksum: bool,
+    /// Whether frames lacking a content checksum are rejected.
+    require_checksum: bool,
+    /// Dictionaries added with `add_dictionary()`, keyed by dictionary ID.
+    dictionaries: HashMap<u32, Py<ZstdCompressionDict>>,
     dctx: Arc<DCtx<'static>>,
+    block: Option<BlockDecompressor>,
 }
 
 unsafe impl Sync for ZstdDecompressor {}
 
+/// Construct the error for a frame left incomplete by a decompression call.
+///
+/// zstd asking for more input after consuming all of it means the input
+/// ended part way through the frame.
+fn incomplete_frame_error(msg: String, in_buffer: &zstd_sys::ZSTD_inBuffer) -> PyErr {
+    if in_buffer.pos == in_buffer.size {
+        ZstdTruncatedError::new_err(msg)
+    } else {
+        ZstdDecompressionError::new_err(msg)
+    }
+}
+
+/// Find the dictionary to decompress a frame naming `dict_id` with.
+///
+/// Frames without a dictionary ID, and every frame when no dictionaries
+/// were added, use `dict_data`. Other frames use the added dictionary with
+/// their ID, or `dict_data` if it has that ID.
+fn select_frame_dict<'a>(
+    py: Python,
+    dict_data: Option<&'a Py<ZstdCompressionDict>>,
+    dictionaries: &'a HashMap<u32, Py<ZstdCompressionDict>>,
+    dict_id: u32,
+) -> PyResult<Option<&'a Py<ZstdCompressionDict>>> {
+    if dict_id == 0 || dictionaries.is_empty() {
+        return Ok(dict_data);
+    }
+
+    dictionaries
+        .get(&dict_id)
+        .or_else(|| dict_data.filter(|dict| dict.borrow(py).dict_id() == dict_id))
+        .map(Some)
+        .ok_or_else(|| {
+            ZstdDecompressionError::new_err(format!(
+                "frame requires dictionary {}, which has not been added",
+                dict_id
+            ))
+        })
+}
+
+/// Decompress subsequent frames on `dctx` with `dict`, or without a
+/// dictionary.
+fn load_dict_into(py: Python, dctx: &DCtx, dict: Option<&Py<ZstdCompressionDict>>) -> PyResult<()> {
+    if let Some(dict) = dict {
+        dict.try_borrow_mut(py)?.load_into_dctx(dctx)
+    } else {
+        dctx.clear_dict().map_err(|msg| {
+            ZstdDecompressionError::new_err(format!("unable to clear dictionary: {}", msg))
+                .with_zstd_error(&msg)
+        })
+    }
+}
+
+/// Dictionaries a stream selects from by the dictionary ID in frame headers.
+pub struct FrameDicts {
+    dict_data: Option<Py<ZstdCompressionDict>>,
+    dictionaries: HashMap<u32, Py<ZstdCompressionDict>>,
+}
+
+impl FrameDicts {
+    /// Find the dictionary to decompress a frame naming `dict_id` with.
+    pub(crate) fn select(
+        &self,
+        py: Python,
+        dict_id: u32,
+    ) -> PyResult<Option<&Py<ZstdCompressionDict>>> {
+        select_frame_dict(py, self.dict_data.as_ref(), &self.dictionaries, dict_id)
+    }
+
+    /// Load the dictionary for a frame naming `dict_id` into `dctx`.
+    ///
+    /// zstd only accepts this between frames, before the next one's input.
+    pub(crate) fn load(&self, py: Python, dctx: &DCtx, dict_id: u32) -> PyResult<()> {
+        load_dict_into(py, dctx, self.select(py, dict_id)?)
+    }
+}
+
 impl ZstdDecompressor {
     fn setup_dctx(&self, py: Python, load_dict: bool) -> PyResult<()> {
         self.dctx.reset().map_err(|msg| {
-            ZstdError::new_err(format!("unable to reset decompression context: {}", msg))
+            ZstdDecompressionError::new_err(format!(
+                "unable to reset decompression context: {}",
+                msg
+            ))
+            .with_zstd_error(&msg)
         })?;
 
         if self.max_window_size != 0 {
             self.dctx
                 .set_max_window_size(self.max_window_size)
                 .map_err(|msg| {
-                    ZstdError::new_err(format!("unable to set max window size: {}", msg))
+                    ZstdDecompressionError::new_err(format!(
+                        "unable to set max window size: {}",
+                        msg
+                    ))
+                    .with_zstd_error(&msg)
                 })?;
         }
 
+        if self.max_window_log != 0 {
+            self.dctx
+                .set_window_log_max(self.max_window_log)
+                .map_err(|msg| {
+                    ZstdDecompressionError::new_err(format!(
+                        "unable to set max window log: {}",
+                        msg
+                    ))
+                    .with_zstd_error(&msg)
+                })?;
+        }
+
+        self.dctx.set_format(self.format).map_err(|msg| {
+            ZstdDecompressionError::new_err(format!("unable to set decoding format: {}", msg))
+                .with_zstd_error(&msg)
+        })?;
+
         self.dctx
-            .set_format(self.format)
-            .map_err(|msg| ZstdError::new_err(format!("unable to set decoding format: {}", msg)))?;
+            .set_verify_checksum(self.verify_checksum)
+            .map_err(|msg| {
+                ZstdDecompressionError::new_err(format!(
+                    "unable to set checksum verification: {}",
+                    msg
+                ))
+                .with_zstd_error(&msg)
+            })?;
 
         if let Some(dict_data) = &self.dict_data {
             if load_dict {
@@ -58,23 +203,272 @@ impl ZstdDecompressor {
 
         Ok(())
     }
+
+    /// Parse the header of the frame at the start of `buffer`.
+    ///
+    /// Frames with a known content size are decompressed in a single pass,
+    /// which zstd doesn't subject to the window limit. So the limit is
+    /// enforced here so max_window_size and max_window_log cap memory in every
+    /// mode.
+    fn read_frame_header(&self, buffer: &DataBuffer) -> PyResult<zstd_sys::ZSTD_FrameHeader> {
+        let mut header: zstd_sys::ZSTD_FrameHeader = unsafe { std::mem::zeroed() };
+        let zresult = unsafe {
+            zstd_sys::ZSTD_getFrameHeader_advanced(
+                &mut header,
+                buffer.buf_ptr(),
+                buffer.len_bytes(),
+                self.format,
+            )
+        };
+
+        if zresult != 0 {
+            return Err(ZstdDecompressionError::new_err(
+                "error determining content size from frame header",
+            ));
+        }
+
+        let limit = self.dctx.max_window_size();
+
+        if (self.max_window_log != 0 || self.max_window_size != 0)
+            && header.frameType != zstd_sys::ZSTD_FrameType_e::ZSTD_skippableFrame
+            && header.windowSize > limit
+        {
+            let msg = window_too_large_error(header.windowSize, limit);
+            return Err(
+                ZstdDecompressionError::new_err(format!("decompression error: {}", msg))
+                    .with_zstd_error(&msg),
+            );
+        }
+
+        if self.require_checksum {
+            check_frame_checksum(&header)?;
+        }
+
+        Ok(header)
+    }
+
+    /// Reject the frame at the start of `data` if it lacks a checksum and
+    /// `require_checksum` is set.
+    ///
+    /// Headers that are malformed or truncated are left for the decoder to
+    /// report.
+    fn check_require_checksum(&self, data: &[u8]) -> PyResult<()> {
+        if self.require_checksum {
+            if let Ok((header, 0)) = parse_frame_header(data, self.format) {
+                check_frame_checksum(&header)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Find the dictionary to decompress a frame naming `dict_id` with.
+    fn frame_dict(&self, py: Python, dict_id: u32) -> PyResult<Option<&Py<ZstdCompressionDict>>> {
+        select_frame_dict(py, self.dict_data.as_ref(), &self.dictionaries, dict_id)
+    }
+
+    /// Decompress subsequent frames with `dict`, or without a dictionary.
+    fn load_dict(&self, py: Python, dict: Option<&Py<ZstdCompressionDict>>) -> PyResult<()> {
+        load_dict_into(py, &self.dctx, dict)
+    }
+
+    /// Load the dictionary for the frame at the start of `data`.
+    ///
+    /// Headers that are malformed or truncated are left for the decoder to
+    /// report, with `dict_data` loaded.
+    fn load_frame_dict(&self, py: Python, data: &[u8]) -> PyResult<()> {
+        let dict_id = match parse_frame_header(data, self.format) {
+            Ok((header, 0)) => header.dictID,
+            _ => 0,
+        };
+
+        self.load_dict(py, self.frame_dict(py, dict_id)?)
+    }
+
+    /// Copy the dictionaries for streams to select from by frame header.
+    ///
+    /// This is `None` unless dictionaries were added, in which case streams
+    /// load the dictionary each frame names as it starts. Dictionaries added
+    /// later don't affect streams already created.
+    fn frame_dicts(&self, py: Python) -> Option<FrameDicts> {
+        if self.dictionaries.is_empty() {
+            return None;
+        }
+
+        Some(FrameDicts {
+            dict_data: self.dict_data.as_ref().map(|dict| dict.clone_ref(py)),
+            dictionaries: self
+                .dictionaries
+                .iter()
+                .map(|(dict_id, dict)| (*dict_id, dict.clone_ref(py)))
+                .collect(),
+        })
+    }
+
+    /// Decompress input from `source` and write it to `dest`.
+    ///
+    /// Returns the number of compressed bytes consumed, the number of
+    /// decompressed bytes written and whether input ended inside a frame.
+    fn decompress_source(
+        &self,
+        py: Python,
+        source: &mut (dyn InBufferSource + Send),
+        dest: &Bound<'_, PyAny>,
+        write_size: usize,
+        read_across_frames: bool,
+    ) -> PyResult<(usize, usize, bool)> {
+        let mut dest_buffer: Vec<u8> = Vec::with_capacity(write_size);
+        let mut total_read = 0;
+        let mut total_write = 0;
+        let mut in_frame = false;
+
+        // Only an empty read() ends the input, so short reads are fine.
+        loop {
+            // Peeking may replace the input buffer, so it happens first.
+            if (self.require_checksum || !self.dictionaries.is_empty()) && !in_frame {
+                if let Some(header) = peek_frame_header(py, source, self.format)? {
+                    if self.require_checksum {
+                        check_frame_checksum(&header)?;
+                    }
+
+                    if !self.dictionaries.is_empty() {
+                        self.load_dict(py, self.frame_dict(py, header.dictID)?)?;
+                    }
+                }
+            }
+
+            let Some(mut in_buffer) = source.input_buffer(py)? else {
+                break;
+            };
+
+            let old_pos = in_buffer.pos;
+
+            // Keep going while zstd fills the output buffer, as it may hold
+            // more output even once the input is consumed.
+            let zresult = loop {
+                let zresult = self
+                    .dctx
+                    .decompress_into_vec(&mut dest_buffer, &mut in_buffer)
+                    .map_err(|msg| {
+                        ZstdDecompressionError::new_err(format!("zstd decompress error: {}", msg))
+                            .with_zstd_error(&msg)
+                    })?;
+
+                let full = dest_buffer.len() == dest_buffer.capacity();
+
+                if !dest_buffer.is_empty() {
+                    // TODO avoid buffer copy.
+                    dest.call_method1("write", (PyBytes::new(py, &dest_buffer),))?;
+                    total_write += dest_buffer.len();
+                    dest_buffer.clear();
+                }
+
+                if zresult == 0 || (!full && in_buffer.pos == in_buffer.size) {
+                    break zresult;
+                }
+            };
+
+            source.record_bytes_read(in_buffer.pos - old_pos);
+            total_read += in_buffer.pos - old_pos;
+            in_frame = zresult != 0;
+
+            if zresult == 0 && !read_across_frames {
+                break;
+            }
+        }
+
+        Ok((total_read, total_write, in_frame))
+    }
+
+    /// Read the header of a frame from a file object, one read at a time.
+    ///
+    /// zstd reports how many bytes it needs to make progress, so nothing past
+    /// the end of the header is read.
+    fn read_source_frame_header(
+        &self,
+        source: &Bound<'_, PyAny>,
+    ) -> PyResult<zstd_sys::ZSTD_FrameHeader> {
+        let mut data = Vec::new();
+
+        loop {
+            let (header, needed) = parse_frame_header(&data, self.format)?;
+            if needed == 0 {
+                return Ok(header);
+            }
+
+            while data.len() < needed {
+                let chunk = source.call_method1("read", (needed - data.len(),))?;
+                let chunk = DataBuffer::get(&chunk)?;
+
+                if chunk.len_bytes() == 0 {
+                    return Err(ZstdTruncatedError::new_err(format!(
+                        "source ended after {} bytes, before the end of the frame header",
+                        data.len()
+                    )));
+                }
+
+                data.extend_from_slice(chunk.as_slice());
+            }
+        }
+    }
+
+    /// Create a context for raw block decompression using our dictionary.
+    fn new_block_decompressor(&self, py: Python) -> PyResult<BlockDecompressor> {
+        let dctx = DCtx::new().map_err(|_| PyMemoryError::new_err(()))?;
+
+        if let Some(dict) = &self.dict_data {
+            dict.borrow_mut(py).begin_dctx_blocks(&dctx)?;
+        } else {
+            dctx.begin_blocks(None).map_err(|msg| {
+                ZstdDecompressionError::new_err(format!(
+                    "unable to begin block decompression: {}",
+                    msg
+                ))
+                .with_zstd_error(&msg)
+            })?;
+        }
+
+        Ok(BlockDecompressor {
+            dctx,
+            previous: vec![],
+        })
+    }
 }
 
 #[pymethods]
 impl ZstdDecompressor {
     #[new]
-    #[pyo3(signature = (dict_data=None, max_window_size=0, format=0))]
+    #[pyo3(signature = (dict_data=None, max_window_size=0, format=None, max_window_log=0, verify_checksum=true, require_checksum=false))]
     fn new(
         dict_data: Option<Py<ZstdCompressionDict>>,
         max_window_size: usize,
-        format: u32,
+        format: Option<&Bound<'_, PyAny>>,
+        max_window_log: i32,
+        verify_checksum: bool,
+        require_checksum: bool,
     ) -> PyResult<Self> {
-        let format = if format == zstd_sys::ZSTD_format_e::ZSTD_f_zstd1 as u32 {
-            zstd_sys::ZSTD_format_e::ZSTD_f_zstd1
-        } else if format == zstd_sys::ZSTD_format_e::ZSTD_f_zstd1_magicless as u32 {
-            zstd_sys::ZSTD_format_e::ZSTD_f_zstd1_magicless
+        if max_window_log != 0 {
+            if max_window_size != 0 {
+                return Err(PyValueError::new_err(
+                    "cannot specify both max_window_size and max_window_log",
+                ));
+            }
+
+            let bounds = unsafe {
+                zstd_sys::ZSTD_dParam_getBounds(zstd_sys::ZSTD_dParameter::ZSTD_d_windowLogMax)
+            };
+            if max_window_log < bounds.lowerBound || max_window_log > bounds.upperBound {
+                return Err(PyValueError::new_err(format!(
+                    "max_window_log must be between {} and {}",
+                    bounds.lowerBound, bounds.upperBound
+                )));
+            }
+        }
+
+        let format = if let Some(format) = format {
+            value_to_format(format)?
         } else {
-            return Err(PyValueError::new_err(format!("invalid format value")));
+            zstd_sys::ZSTD_format_e::ZSTD_f_zstd1
         };
 
         let dctx = Arc::new(DCtx::new().map_err(|_| PyMemoryError::new_err(()))?);
@@ -82,11 +476,19 @@ impl ZstdDecompressor {
         Ok(Self {
             dict_data,
             max_window_size,
+            max_window_log,
             format,
+            verify_checksum,
+            require_checksum,
+            dictionaries: HashMap::new(),
             dctx,
+            block: None,
         })
     }
 
+    /// Decompress data read from `ifh` and write it to `ofh`.
+    ///
+    /// Returns the number of bytes read from `ifh` and written to `ofh`.
     #[pyo3(signature = (ifh, ofh, read_size=None, write_size=None))]
     fn copy_stream(
         &self,
@@ -111,99 +513,108 @@ impl ZstdDecompressor {
             ));
         }
 
-        self.setup_dctx(py, true)?;
-
-        let mut dest_buffer: Vec<u8> = Vec::with_capacity(write_size);
+        let mut source = make_in_buffer_source(py, ifh, read_size)?;
 
-        let mut in_buffer = zstd_sys::ZSTD_inBuffer {
-            src: std::ptr::null(),
-            size: 0,
-            pos: 0,
-        };
+        self.setup_dctx(py, true)?;
 
-        let mut total_read = 0;
-        let mut total_write = 0;
+        let (total_read, total_write, _) =
+            self.decompress_source(py, source.as_mut(), ofh, write_size, true)?;
 
-        // Read all available input.
-        loop {
-            let read_object = ifh.call_method1("read", (read_size,))?;
-            let read_bytes = read_object.downcast::<PyBytes>()?;
-            let read_data = read_bytes.as_bytes();
+        Ok((total_read, total_write))
+    }
 
-            if read_data.len() == 0 {
-                break;
-            }
+    /// Decompress data from `source` and write it to `dest`.
+    ///
+    /// Returns the number of compressed bytes consumed and decompressed
+    /// bytes written. Only the first frame is decompressed unless
+    /// `read_across_frames` is set.
+    #[pyo3(signature = (source, dest, read_size=None, write_size=None, read_across_frames=false))]
+    fn decompress_stream(
+        &self,
+        py: Python,
+        source: &Bound<'_, PyAny>,
+        dest: &Bound<'_, PyAny>,
+        read_size: Option<usize>,
+        write_size: Option<usize>,
+        read_across_frames: bool,
+    ) -> PyResult<(usize, usize)> {
+        let read_size = read_size.unwrap_or_else(zstd_safe::DCtx::in_size);
+        let write_size = write_size.unwrap_or_else(zstd_safe::DCtx::out_size);
 
-            total_read += read_data.len();
+        if !dest.hasattr("write")? {
+            return Err(PyValueError::new_err(
+                "second argument must have a write() method",
+            ));
+        }
 
-            in_buffer.src = read_data.as_ptr() as *const _;
-            in_buffer.size = read_data.len();
-            in_buffer.pos = 0;
+        let mut source = make_in_buffer_source(py, source, read_size)?;
 
-            // Flush all read data to output.
-            while in_buffer.pos < in_buffer.size {
-                self.dctx
-                    .decompress_into_vec(&mut dest_buffer, &mut in_buffer)
-                    .map_err(|msg| ZstdError::new_err(format!("zstd decompress error: {}", msg)))?;
+        self.setup_dctx(py, true)?;
 
-                if !dest_buffer.is_empty() {
-                    // TODO avoid buffer copy.
-                    let data = PyBytes::new(py, &dest_buffer);
+        let (total_read, total_write, in_frame) =
+            self.decompress_source(py, source.as_mut(), dest, write_size, read_across_frames)?;
 
-                    ofh.call_method1("write", (data,))?;
-                    total_write += dest_buffer.len();
-                    dest_buffer.clear();
-                }
-            }
-            // Continue loop to keep reading.
+        if in_frame {
+            return Err(ZstdTruncatedError::new_err(
+                "input ended with an incomplete frame",
+            ));
         }
 
         Ok((total_read, total_write))
     }
 
-    #[pyo3(signature = (buffer, max_output_size=0, read_across_frames=false, allow_extra_data=true))]
+    #[pyo3(signature = (buffer, max_output_size=0, read_across_frames=false, allow_extra_data=true, prefix=None, initial_size=0))]
+    #[allow(clippy::too_many_arguments)]
     fn decompress<'p>(
         &mut self,
         py: Python<'p>,
-        buffer: PyBuffer<u8>,
+        buffer: DataBuffer,
         max_output_size: usize,
         read_across_frames: bool,
         allow_extra_data: bool,
+        prefix: Option<DataBuffer>,
+        initial_size: usize,
     ) -> PyResult<Bound<'p, PyBytes>> {
         if read_across_frames {
-            return Err(ZstdError::new_err(
+            return Err(ZstdDecompressionError::new_err(
                 "ZstdDecompressor.read_across_frames=True is not yet implemented",
             ));
         }
 
-        self.setup_dctx(py, true)?;
-
-        let mut header: zstd_sys::ZSTD_FrameHeader = unsafe { std::mem::zeroed() };
-        let zresult = unsafe {
-            zstd_sys::ZSTD_getFrameHeader_advanced(
-                &mut header,
-                buffer.buf_ptr(),
-                buffer.len_bytes(),
-                self.format,
-            )
-        };
-
-        if zresult != 0 {
-            return Err(ZstdError::new_err(
-                "error determining content size from frame header",
+        // Magic-less frames can't be told apart from garbage, so require the
+        // caller to bound the output explicitly.
+        if self.format == zstd_sys::ZSTD_format_e::ZSTD_f_zstd1_magicless && max_output_size == 0 {
+            return Err(ZstdDecompressionError::new_err(
+                "max_output_size must be specified when decompressing magic-less frames",
             ));
         }
 
+        self.setup_dctx(py, false)?;
+
+        let header = self.read_frame_header(&buffer)?;
+        self.load_dict(py, self.frame_dict(py, header.dictID)?)?;
+
+        // Frames without a content size are decompressed into a buffer that
+        // grows as needed, so a generous max_output_size doesn't allocate that
+        // much up front. The buffer may grow one byte past the limit so output
+        // exceeding it can be told apart from a truncated frame. initial_size
+        // lets callers expecting large output skip the early growth steps.
         let (output_buffer_size, output_size) = if header.frameContentSize == 0 {
             return Ok(PyBytes::new(py, &[]));
         } else if header.frameContentSize == zstd_sys::ZSTD_CONTENTSIZE_UNKNOWN as c_ulonglong {
             if max_output_size == 0 {
-                return Err(ZstdError::new_err(
+                return Err(ZstdDecompressionError::new_err(
                     "could not determine content size in frame header",
                 ));
             }
 
-            (max_output_size, 0)
+            let initial_size = if initial_size == 0 {
+                zstd_safe::DCtx::out_size()
+            } else {
+                initial_size
+            };
+
+            (max_output_size.min(initial_size), 0)
         } else {
             (header.frameContentSize as _, header.frameContentSize)
         };
@@ -219,22 +630,61 @@ impl ZstdDecompressor {
             pos: 0,
         };
 
-        let zresult = self
-            .dctx
-            .decompress_into_vec(&mut dest_buffer, &mut in_buffer)
-            .map_err(|msg| ZstdError::new_err(format!("decompression error: {}", msg)))?;
+        // The prefix is only consulted for the frame decompressed below.
+        if let Some(prefix) = &prefix {
+            let prefix: &[u8] = unsafe {
+                std::slice::from_raw_parts(prefix.buf_ptr() as *const _, prefix.len_bytes())
+            };
 
-        if zresult != 0 {
-            Err(ZstdError::new_err(
-                "decompression error: did not decompress full frame",
+            self.dctx.ref_prefix(prefix).map_err(|msg| {
+                ZstdDecompressionError::new_err(format!("unable to reference prefix: {}", msg))
+                    .with_zstd_error(&msg)
+            })?;
+        }
+
+        let limit = max_output_size.saturating_add(1);
+
+        let zresult = loop {
+            let zresult = self
+                .dctx
+                .decompress_into_vec(&mut dest_buffer, &mut in_buffer)
+                .map_err(|msg| {
+                    ZstdDecompressionError::new_err(format!("decompression error: {}", msg))
+                        .with_zstd_error(&msg)
+                })?;
+
+            // Stop unless zstd ran out of room in a growable buffer.
+            if zresult == 0
+                || output_size != 0
+                || dest_buffer.len() < dest_buffer.capacity()
+                || dest_buffer.len() >= limit
+            {
+                break zresult;
+            }
+
+            let capacity = dest_buffer.capacity().saturating_mul(2).min(limit);
+            dest_buffer
+                .try_reserve_exact(capacity - dest_buffer.len())
+                .map_err(|_| PyMemoryError::new_err(()))?;
+        };
+
+        if output_size == 0 && dest_buffer.len() > max_output_size {
+            Err(ZstdDecompressionError::new_err(format!(
+                "decompression error: did not decompress full frame; output exceeds max_output_size of {} bytes",
+                max_output_size
+            )))
+        } else if zresult != 0 {
+            Err(incomplete_frame_error(
+                "decompression error: did not decompress full frame".to_string(),
+                &in_buffer,
             ))
         } else if output_size != 0 && dest_buffer.len() != output_size as usize {
-            Err(ZstdError::new_err(format!(
+            Err(ZstdDecompressionError::new_err(format!(
                 "decompression error: decompressed {} bytes; expected {}",
                 zresult, output_size
             )))
         } else if !allow_extra_data && in_buffer.pos < in_buffer.size {
-            Err(ZstdError::new_err(format!(
+            Err(ZstdDecompressionError::new_err(format!(
                 "compressed input contains {} bytes of unused data, which is disallowed",
                 in_buffer.size - in_buffer.pos
             )))
@@ -244,6 +694,91 @@ impl ZstdDecompressor {
         }
     }
 
+    #[pyo3(signature = (data, output, max_output_size=0))]
+    fn decompress_into(
+        &mut self,
+        py: Python,
+        data: DataBuffer,
+        output: DataBuffer,
+        max_output_size: usize,
+    ) -> PyResult<usize> {
+        if output.readonly() {
+            return Err(PyValueError::new_err("output buffer is not writable"));
+        }
+
+        self.setup_dctx(py, false)?;
+
+        let header = self.read_frame_header(&data)?;
+        self.load_dict(py, self.frame_dict(py, header.dictID)?)?;
+
+        let output_len = output.len_bytes();
+
+        let (limit, expected_size) =
+            if header.frameContentSize == zstd_sys::ZSTD_CONTENTSIZE_UNKNOWN as c_ulonglong {
+                let limit = if max_output_size != 0 {
+                    max_output_size.min(output_len)
+                } else {
+                    output_len
+                };
+
+                (limit, None)
+            } else if header.frameContentSize > output_len as c_ulonglong {
+                return Err(ZstdDecompressionError::new_err(format!(
+                    "decompressed size of {} bytes exceeds output buffer size of {} bytes",
+                    header.frameContentSize, output_len
+                )));
+            } else {
+                (
+                    header.frameContentSize as usize,
+                    Some(header.frameContentSize),
+                )
+            };
+
+        if expected_size == Some(0) {
+            return Ok(0);
+        }
+
+        let mut in_buffer = zstd_sys::ZSTD_inBuffer {
+            src: data.buf_ptr(),
+            size: data.len_bytes(),
+            pos: 0,
+        };
+        let mut out_buffer = zstd_sys::ZSTD_outBuffer {
+            dst: output.buf_ptr(),
+            size: limit,
+            pos: 0,
+        };
+
+        let zresult = self
+            .dctx
+            .decompress_buffers(&mut out_buffer, &mut in_buffer)
+            .map_err(|msg| {
+                ZstdDecompressionError::new_err(format!("decompression error: {}", msg))
+                    .with_zstd_error(&msg)
+            })?;
+
+        if zresult != 0 {
+            if out_buffer.pos == out_buffer.size {
+                Err(ZstdDecompressionError::new_err(format!(
+                    "decompressed data exceeds output limit of {} bytes",
+                    limit
+                )))
+            } else {
+                Err(incomplete_frame_error(
+                    "decompression error: did not decompress full frame".to_string(),
+                    &in_buffer,
+                ))
+            }
+        } else if expected_size.is_some_and(|size| size != out_buffer.pos as c_ulonglong) {
+            Err(ZstdDecompressionError::new_err(format!(
+                "decompression error: decompressed {} bytes; expected {}",
+                out_buffer.pos, header.frameContentSize
+            )))
+        } else {
+            Ok(out_buffer.pos)
+        }
+    }
+
     fn decompress_content_dict_chain<'p>(
         &self,
         py: Python<'p>,
@@ -260,7 +795,7 @@ impl ZstdDecompressor {
             return Err(PyValueError::new_err("chunk 0 must be bytes"));
         }
 
-        let chunk_buffer: PyBuffer<u8> = PyBuffer::get(&chunk.as_borrowed())?;
+        let chunk_buffer: DataBuffer = DataBuffer::get(&chunk.as_borrowed())?;
         let mut params = zstd_sys::ZSTD_FrameHeader {
             frameContentSize: 0,
             windowSize: 0,
@@ -293,6 +828,17 @@ impl ZstdDecompressor {
             ));
         }
 
+        if self.require_checksum {
+            check_frame_checksum(&params)?;
+        }
+
+        if params.dictID != 0 {
+            return Err(ZstdDecompressionError::new_err(format!(
+                "chunk 0 requires dictionary {}; the first chunk must not use a dictionary",
+                params.dictID
+            )));
+        }
+
         self.setup_dctx(py, false)?;
 
         let mut last_buffer: Vec<u8> = Vec::with_capacity(params.frameContentSize as _);
@@ -306,10 +852,16 @@ impl ZstdDecompressor {
         let zresult = self
             .dctx
             .decompress_into_vec(&mut last_buffer, &mut in_buffer)
-            .map_err(|msg| ZstdError::new_err(format!("could not decompress chunk 0: {}", msg)))?;
+            .map_err(|msg| {
+                ZstdDecompressionError::new_err(format!("could not decompress chunk 0: {}", msg))
+                    .with_zstd_error(&msg)
+            })?;
 
         if zresult != 0 {
-            return Err(ZstdError::new_err("chunk 0 did not decompress full frame"));
+            return Err(incomplete_frame_error(
+                "chunk 0 did not decompress full frame".to_string(),
+                &in_buffer,
+            ));
         }
 
         // Special case of chain length 1.
@@ -324,7 +876,7 @@ impl ZstdDecompressor {
                 return Err(PyValueError::new_err(format!("chunk {} must be bytes", i)));
             }
 
-            let chunk_buffer: PyBuffer<u8> = PyBuffer::get(&chunk.as_borrowed())?;
+            let chunk_buffer: DataBuffer = DataBuffer::get(&chunk.as_borrowed())?;
 
             let zresult = unsafe {
                 zstd_sys::ZSTD_getFrameHeader(
@@ -352,6 +904,28 @@ impl ZstdDecompressor {
                 )));
             }
 
+            if self.require_checksum {
+                check_frame_checksum(&params)?;
+            }
+
+            // The previous output is referenced as raw content, which can't
+            // satisfy a frame compressed with a structured dictionary.
+            if params.dictID != 0 {
+                return Err(ZstdDecompressionError::new_err(format!(
+                    "chunk {} requires dictionary {}; content dictionary chains only support raw content dictionaries",
+                    i, params.dictID
+                )));
+            }
+
+            self.dctx.ref_prefix(&last_buffer).map_err(|msg| {
+                ZstdDecompressionError::new_err(format!(
+                    "unable to reference chunk {} output as dictionary: {}",
+                    i - 1,
+                    msg
+                ))
+                .with_zstd_error(&msg)
+            })?;
+
             let mut dest_buffer: Vec<u8> = Vec::with_capacity(params.frameContentSize as _);
 
             let mut in_buffer = zstd_sys::ZSTD_inBuffer {
@@ -364,14 +938,18 @@ impl ZstdDecompressor {
                 .dctx
                 .decompress_into_vec(&mut dest_buffer, &mut in_buffer)
                 .map_err(|msg| {
-                    ZstdError::new_err(format!("could not decompress chunk {}: {}", i, msg))
+                    ZstdDecompressionError::new_err(format!(
+                        "could not decompress chunk {}: {}",
+                        i, msg
+                    ))
+                    .with_zstd_error(&msg)
                 })?;
 
             if zresult != 0 {
-                return Err(ZstdError::new_err(format!(
-                    "chunk {} did not decompress full frame",
-                    i
-                )));
+                return Err(incomplete_frame_error(
+                    format!("chunk {} did not decompress full frame", i),
+                    &in_buffer,
+                ));
             }
 
             last_buffer = dest_buffer;
@@ -398,14 +976,295 @@ impl ZstdDecompressor {
 
         self.setup_dctx(py, true)?;
 
-        ZstdDecompressionObj::new(self.dctx.clone(), write_size, read_across_frames)
+        ZstdDecompressionObj::new(
+            self.dctx.clone(),
+            write_size,
+            read_across_frames,
+            self.format,
+            self.require_checksum,
+            self.frame_dicts(py),
+        )
+    }
+
+    /// Decompress a raw block produced by `ZstdCompressor.compress_block()`.
+    ///
+    /// `original_size` is the size of the block before compression, which
+    /// the caller must transmit alongside it. Blocks must be passed in the
+    /// order they were compressed, since later blocks reference earlier
+    /// ones through a single long-lived context. A block as long as
+    /// `original_size` is taken to be uncompressed and is returned as is.
+    fn decompress_block<'p>(
+        &mut self,
+        py: Python<'p>,
+        data: DataBuffer,
+        original_size: usize,
+    ) -> PyResult<Bound<'p, PyBytes>> {
+        let max_size = zstd_sys::ZSTD_BLOCKSIZE_MAX as usize;
+        if original_size > max_size {
+            return Err(ZstdError::new_err(format!(
+                "original_size of {} bytes exceeds the maximum block size of {} bytes",
+                original_size, max_size
+            )));
+        }
+
+        if data.len_bytes() > original_size {
+            return Err(ZstdDecompressionError::new_err(format!(
+                "block of {} bytes is larger than original_size of {} bytes",
+                data.len_bytes(),
+                original_size
+            )));
+        }
+
+        if original_size == 0 {
+            return Ok(PyBytes::new(py, &[]));
+        }
+
+        if self.block.is_none() {
+            self.block = Some(self.new_block_decompressor(py)?);
+        }
+        let block = self.block.as_mut().unwrap();
+
+        if data.len_bytes() == original_size {
+            let raw = data.as_slice().to_vec();
+            block.dctx.insert_block(&raw);
+
+            let result = PyBytes::new(py, &raw);
+            block.previous = raw;
+
+            return Ok(result);
+        }
+
+        let source = data.as_slice();
+        let mut dest = Vec::with_capacity(original_size);
+
+        let res = py
+            .allow_threads(|| block.dctx.decompress_block(&mut dest, source))
+            .map_err(|msg| {
+                ZstdDecompressionError::new_err(format!("cannot decompress block: {}", msg))
+                    .with_zstd_error(&msg)
+            })
+            .and_then(|_| {
+                if dest.len() == original_size {
+                    Ok(())
+                } else {
+                    Err(ZstdDecompressionError::new_err(format!(
+                        "block decompressed to {} bytes; expected {}",
+                        dest.len(),
+                        original_size
+                    )))
+                }
+            });
+
+        if let Err(err) = res {
+            // The context can't continue from a failed block.
+            self.block = None;
+
+            return Err(err);
+        }
+
+        let result = PyBytes::new(py, &dest);
+        block.previous = dest;
+
+        Ok(result)
+    }
+
+    fn frame_bound(&self, data: DataBuffer) -> PyResult<c_ulonglong> {
+        let bound = unsafe { zstd_sys::ZSTD_decompressBound(data.buf_ptr(), data.len_bytes()) };
+
+        if bound == zstd_sys::ZSTD_CONTENTSIZE_ERROR as c_ulonglong {
+            Err(ZstdDecompressionError::new_err(
+                "error determining decompressed size bound; input must be a complete sequence of zstd frames",
+            ))
+        } else {
+            Ok(bound)
+        }
+    }
+
+    /// Obtain the content size declared by the frame at the start of `source`.
+    ///
+    /// `source` is a buffer or an object with a `read()` method. Only the
+    /// frame header is read from the latter. Seekable file objects are then
+    /// returned to their original position; from others, the header bytes are
+    /// consumed.
+    fn frame_content_size(&self, source: &Bound<'_, PyAny>) -> PyResult<u64> {
+        let header = if source.hasattr("read")? {
+            let position =
+                if source.hasattr("seekable")? && source.call_method0("seekable")?.is_truthy()? {
+                    Some(source.call_method0("tell")?)
+                } else {
+                    None
+                };
+
+            let header = self.read_source_frame_header(source);
+
+            if let Some(position) = position {
+                source.call_method1("seek", (position,))?;
+            }
+
+            header?
+        } else {
+            let buffer = DataBuffer::get(source).map_err(|err| {
+                if err.is_instance_of::<PyValueError>(source.py()) {
+                    err
+                } else {
+                    PyValueError::new_err(
+                        "must pass an object with a read() method or conforms to buffer protocol",
+                    )
+                }
+            })?;
+
+            let (header, needed) = parse_frame_header(buffer.as_slice(), self.format)?;
+            if needed != 0 {
+                return Err(ZstdTruncatedError::new_err(format!(
+                    "need at least {} bytes to parse the frame header but got {}",
+                    needed,
+                    buffer.len_bytes()
+                )));
+            }
+
+            header
+        };
+
+        if header.frameType == zstd_sys::ZSTD_FrameType_e::ZSTD_skippableFrame {
+            Err(ZstdDecompressionError::new_err(
+                "source begins with a skippable frame, which has no content size",
+            ))
+        } else if header.frameContentSize == zstd_sys::ZSTD_CONTENTSIZE_UNKNOWN as c_ulonglong {
+            Err(ZstdDecompressionError::new_err(
+                "frame header does not declare the content size",
+            ))
+        } else {
+            Ok(header.frameContentSize)
+        }
+    }
+
+    /// Verify that `data` is a sequence of intact frames.
+    ///
+    /// Frames are decompressed into a fixed-size scratch buffer that is
+    /// discarded, so memory use doesn't depend on the content size. Checksums
+    /// are verified when present, regardless of `verify_checksum`.
+    fn validate(&self, py: Python, data: DataBuffer) -> PyResult<bool> {
+        if data.len_bytes() == 0 {
+            return Err(ZstdDecompressionError::new_err(
+                "cannot validate empty input; no frames found",
+            ));
+        }
+
+        self.setup_dctx(py, false)?;
+
+        self.dctx.set_verify_checksum(true).map_err(|msg| {
+            ZstdDecompressionError::new_err(format!("unable to set checksum verification: {}", msg))
+                .with_zstd_error(&msg)
+        })?;
+
+        let checksum_wrong =
+            0usize.wrapping_sub(zstd_sys::ZSTD_ErrorCode::ZSTD_error_checksum_wrong as usize);
+
+        let mut scratch: Vec<u8> = Vec::with_capacity(zstd_safe::DCtx::out_size());
+
+        let mut in_buffer = zstd_sys::ZSTD_inBuffer {
+            src: data.buf_ptr(),
+            size: data.len_bytes(),
+            pos: 0,
+        };
+        let mut frame_offset = 0;
+        self.check_require_checksum(data.as_slice())?;
+        self.load_frame_dict(py, data.as_slice())?;
+
+        loop {
+            let mut out_buffer = zstd_sys::ZSTD_outBuffer {
+                dst: scratch.as_mut_ptr() as *mut _,
+                size: scratch.capacity(),
+                pos: 0,
+            };
+
+            let zresult = self
+                .dctx
+                .decompress_buffers(&mut out_buffer, &mut in_buffer)
+                .map_err(|msg| {
+                    let err = if msg.code() == Some(checksum_wrong) {
+                        ZstdDecompressionError::new_err(format!(
+                            "frame at offset {} failed checksum verification: {}",
+                            frame_offset, msg
+                        ))
+                    } else {
+                        ZstdDecompressionError::new_err(format!(
+                            "frame at offset {} failed validation: {}",
+                            frame_offset, msg
+                        ))
+                    };
+
+                    err.with_zstd_error(&msg)
+                })?;
+
+            if zresult == 0 {
+                if in_buffer.pos == in_buffer.size {
+                    return Ok(true);
+                }
+
+                frame_offset = in_buffer.pos;
+                self.check_require_checksum(&data.as_slice()[frame_offset..])?;
+                self.load_frame_dict(py, &data.as_slice()[frame_offset..])?;
+            } else if in_buffer.pos == in_buffer.size && out_buffer.pos < out_buffer.size {
+                // zstd wants more input but there is none left.
+                return Err(ZstdTruncatedError::new_err(format!(
+                    "frame at offset {} is truncated",
+                    frame_offset
+                )));
+            }
+        }
     }
 
     fn memory_size(&self) -> usize {
         self.dctx.memory_size()
     }
 
-    #[pyo3(signature = (frames, decompressed_sizes=None, threads=0))]
+    /// Set a decompression parameter by its raw `ZSTD_dParameter` value.
+    ///
+    /// This is an escape hatch for parameters added by zstd releases this
+    /// module doesn't know about yet. Nothing is validated beyond zstd
+    /// accepting the call, and experimental parameters can change meaning
+    /// between zstd releases. Returns the result of `ZSTD_DCtx_setParameter()`.
+    ///
+    /// Operations reapply the settings the decompressor was constructed with,
+    /// such as `format`, over values set this way. Operations using contexts
+    /// of their own, like `multi_decompress_to_buffer()`, don't see them.
+    fn set_parameter(&self, param: c_int, value: c_int) -> PyResult<usize> {
+        self.dctx.set_raw_parameter(param, value).map_err(|msg| {
+            ZstdError::new_err(format!("unable to set parameter {}: {}", param, msg))
+                .with_zstd_error(&msg)
+        })
+    }
+
+    /// Add a dictionary to select by the dictionary ID in frame headers.
+    ///
+    /// Frames naming the ID of an added dictionary are decompressed with it,
+    /// saving callers from parsing headers to pick one. Adding a dictionary
+    /// with the ID of an earlier one replaces it. Streams select from the
+    /// dictionaries added before they were created.
+    fn add_dictionary(&mut self, py: Python, dict: Py<ZstdCompressionDict>) -> PyResult<()> {
+        let dict_id = dict.borrow(py).dict_id();
+
+        if dict_id == 0 {
+            return Err(PyValueError::new_err(
+                "dictionary has no dictionary ID; only dictionaries with one can be added",
+            ));
+        }
+
+        self.dictionaries.insert(dict_id, dict);
+
+        Ok(())
+    }
+
+    /// Decompress multiple frames, optionally in parallel.
+    ///
+    /// With `output`, a `BufferWithSegments` over writable memory, frames are
+    /// decompressed into it back to back instead of into new allocations, and
+    /// the result has a single buffer sharing that memory. Reusing the same
+    /// `output` across calls recycles the memory but overwrites the results
+    /// of earlier calls. Every frame's decompressed size must be known.
+    /// `output` must not be read or written until the call returns.
+    #[pyo3(signature = (frames, decompressed_sizes=None, threads=0, output=None))]
     #[allow(unused_variables)]
     fn multi_decompress_to_buffer(
         &self,
@@ -413,19 +1272,189 @@ impl ZstdDecompressor {
         frames: &Bound<'_, PyAny>,
         decompressed_sizes: Option<&Bound<'_, PyAny>>,
         threads: isize,
+        output: Option<&Bound<'_, ZstdBufferWithSegments>>,
     ) -> PyResult<ZstdBufferWithSegmentsCollection> {
         self.setup_dctx(py, true)?;
 
         multi_decompress_to_buffer(
             py,
             self.dict_data.as_ref(),
+            self.format,
+            self.verify_checksum,
+            self.require_checksum,
+            self.frame_dicts(py).as_ref(),
             frames,
             decompressed_sizes,
             threads,
+            output,
         )
     }
 
-    #[pyo3(signature = (reader, read_size=None, write_size=None, skip_bytes=None))]
+    /// Decompress every frame in `data` into a single allocation.
+    ///
+    /// The result has one segment per frame, skippable frames excluded.
+    /// Sizes come from frame headers. Frames written without a content size
+    /// need an entry in `decompressed_sizes`, where 0 defers to the header.
+    ///
+    /// The output is allocated before any frame is decompressed, so sizes a
+    /// frame's blocks can't produce are rejected, and the total may not
+    /// exceed `max_output_size` (1 GiB by default, 0 for no limit).
+    #[pyo3(signature = (data, decompressed_sizes=None, max_output_size=DECOMPRESS_TO_BUFFER_MAX_OUTPUT_SIZE))]
+    fn decompress_to_buffer(
+        &self,
+        py: Python,
+        data: DataBuffer,
+        decompressed_sizes: Option<&Bound<'_, PyAny>>,
+        max_output_size: usize,
+    ) -> PyResult<ZstdBufferWithSegments> {
+        let raw_data = unsafe {
+            std::slice::from_raw_parts::<u8>(data.buf_ptr() as *const _, data.len_bytes())
+        };
+
+        let frames = find_frames(raw_data)?
+            .into_iter()
+            .filter(|(_, _, skippable)| !skippable)
+            .map(|(offset, length, _)| &raw_data[offset..offset + length])
+            .collect::<Vec<_>>();
+
+        let sizes = if let Some(decompressed_sizes) = decompressed_sizes {
+            let sizes = resolve_decompressed_sizes(decompressed_sizes)?;
+
+            if sizes.len() != frames.len() {
+                return Err(PyValueError::new_err(format!(
+                    "decompressed_sizes size mismatch; expected {}, got {}",
+                    frames.len(),
+                    sizes.len()
+                )));
+            }
+
+            sizes
+        } else {
+            vec![0; frames.len()]
+        };
+
+        let mut segments = Vec::with_capacity(frames.len());
+        let mut dicts = Vec::with_capacity(frames.len());
+        let mut total_size: usize = 0;
+
+        for (i, (frame, size)) in frames.iter().zip(sizes).enumerate() {
+            self.check_require_checksum(frame)?;
+            let (header, _) = parse_frame_header(frame, self.format)?;
+            dicts.push(self.frame_dict(py, header.dictID)?);
+
+            let size = if size == 0 {
+                match zstd_safe::get_frame_content_size(frame) {
+                    Ok(Some(size)) => size,
+                    _ => {
+                        return Err(ZstdError::new_err(format!(
+                            "could not determine decompressed size of frame {}; pass \
+                            decompressed_sizes",
+                            i
+                        )))
+                    }
+                }
+            } else {
+                size
+            };
+
+            let bound = frame_block_count(frame, header.headerSize as usize) as u64
+                * header.blockSizeMax as u64;
+            if size > bound {
+                return Err(ZstdDecompressionError::new_err(format!(
+                    "frame {} can't decompress to {} bytes; its blocks produce at most {} bytes",
+                    i, size, bound
+                )));
+            }
+
+            segments.push(BufferSegment {
+                offset: total_size as _,
+                length: size,
+            });
+
+            total_size = usize::try_from(size)
+                .ok()
+                .and_then(|size| total_size.checked_add(size))
+                .ok_or_else(|| {
+                    PyMemoryError::new_err("decompressed output exceeds addressable memory")
+                })?;
+
+            if max_output_size != 0 && total_size > max_output_size {
+                return Err(ZstdDecompressionError::new_err(format!(
+                    "frame {} brings the decompressed size to {} bytes, exceeding \
+                    max_output_size of {} bytes",
+                    i, total_size, max_output_size
+                )));
+            }
+        }
+
+        self.setup_dctx(py, false)?;
+
+        let output = PyBytes::new_with(py, total_size, |dest| {
+            for (i, ((frame, segment), dict)) in frames.iter().zip(&segments).zip(dicts).enumerate()
+            {
+                self.load_dict(py, dict)?;
+
+                let region =
+                    &mut dest[segment.offset as usize..(segment.offset + segment.length) as usize];
+
+                let mut out_buffer = zstd_sys::ZSTD_outBuffer {
+                    dst: region.as_mut_ptr() as *mut _,
+                    size: region.len(),
+                    pos: 0,
+                };
+                let mut in_buffer = zstd_sys::ZSTD_inBuffer {
+                    src: frame.as_ptr() as *const _,
+                    size: frame.len(),
+                    pos: 0,
+                };
+
+                match self
+                    .dctx
+                    .decompress_buffers(&mut out_buffer, &mut in_buffer)
+                {
+                    Ok(0) if out_buffer.pos == region.len() => {}
+                    Ok(0) => {
+                        return Err(ZstdDecompressionError::new_err(format!(
+                            "error decompressing frame {}: decompressed {} bytes; expected {}",
+                            i,
+                            out_buffer.pos,
+                            region.len()
+                        )))
+                    }
+                    Ok(_) => {
+                        return Err(ZstdDecompressionError::new_err(format!(
+                            "error decompressing frame {}: decompressed more than expected {} \
+                            bytes",
+                            i,
+                            region.len()
+                        )))
+                    }
+                    Err(msg) => {
+                        return Err(ZstdDecompressionError::new_err(format!(
+                            "error decompressing frame {}: {}",
+                            i, msg
+                        ))
+                        .with_zstd_error(&msg))
+                    }
+                }
+            }
+
+            Ok(())
+        })?;
+
+        let segments = unsafe {
+            PyBytes::from_ptr(
+                py,
+                segments.as_ptr() as *const _,
+                segments.len() * std::mem::size_of::<BufferSegment>(),
+            )
+        };
+
+        ZstdBufferWithSegments::new(py, &output, DataBuffer::get(&segments)?)
+    }
+
+    #[pyo3(signature = (reader, read_size=None, write_size=None, skip_bytes=None, read_across_frames=false, frame_boundaries=false, max_frames=0))]
+    #[allow(clippy::too_many_arguments)]
     fn read_to_iter(
         &self,
         py: Python,
@@ -433,6 +1462,9 @@ impl ZstdDecompressor {
         read_size: Option<usize>,
         write_size: Option<usize>,
         skip_bytes: Option<usize>,
+        read_across_frames: bool,
+        frame_boundaries: bool,
+        max_frames: usize,
     ) -> PyResult<ZstdDecompressorIterator> {
         let read_size = read_size.unwrap_or_else(|| zstd_safe::DCtx::in_size());
         let write_size = write_size.unwrap_or_else(|| zstd_safe::DCtx::out_size());
@@ -459,10 +1491,17 @@ impl ZstdDecompressor {
             read_size,
             write_size,
             skip_bytes,
+            read_across_frames,
+            max_frames,
+            frame_boundaries,
+            self.format,
+            self.require_checksum,
+            self.frame_dicts(py),
         )
     }
 
-    #[pyo3(signature = (source, read_size=None, read_across_frames=false, closefd=true))]
+    #[pyo3(signature = (source, read_size=None, read_across_frames=false, closefd=true, max_frames=0, low_latency=false, max_output_size=0, max_frame_content_size=0))]
+    #[allow(clippy::too_many_arguments)]
     fn stream_reader(
         &self,
         py: Python,
@@ -470,6 +1509,10 @@ impl ZstdDecompressor {
         read_size: Option<usize>,
         read_across_frames: bool,
         closefd: bool,
+        max_frames: usize,
+        low_latency: bool,
+        max_output_size: usize,
+        max_frame_content_size: u64,
     ) -> PyResult<ZstdDecompressionReader> {
         let read_size = read_size.unwrap_or_else(|| zstd_safe::DCtx::in_size());
 
@@ -481,6 +1524,33 @@ impl ZstdDecompressor {
             source,
             read_size,
             read_across_frames,
+            max_frames,
+            closefd,
+            low_latency,
+            max_output_size,
+            self.format,
+            max_frame_content_size,
+            self.require_checksum,
+            self.frame_dicts(py),
+        )
+    }
+
+    #[pyo3(signature = (source, closefd=true))]
+    fn seekable_stream_reader(
+        &self,
+        py: Python,
+        source: &Bound<'_, PyAny>,
+        closefd: bool,
+    ) -> PyResult<SeekableDecompressionReader> {
+        self.setup_dctx(py, true)?;
+
+        SeekableDecompressionReader::new(
+            py,
+            self.dctx.clone(),
+            source,
+            self.format,
+            self.require_checksum,
+            self.frame_dicts(py),
             closefd,
         )
     }
@@ -504,6 +1574,9 @@ impl ZstdDecompressor {
             writer,
             write_size,
             write_return_read,
+            self.format,
+            self.require_checksum,
+            self.frame_dicts(py),
             closefd,
         )
     }
@@ -514,8 +1587,60 @@ fn estimate_decompression_context_size() -> usize {
     unsafe { zstd_sys::ZSTD_estimateDCtxSize() }
 }
 
+/// Decompress a zstd frame without constructing a decompressor.
+///
+/// Equivalent to `ZstdDecompressor(dict_data=dict).decompress(data,
+/// max_output_size=max_output_size)`, except the decompression context is
+/// cached per thread and reused.
+#[pyfunction]
+#[pyo3(signature = (data, max_output_size=0, dict=None))]
+pub(crate) fn decompress<'p>(
+    py: Python<'p>,
+    data: DataBuffer,
+    max_output_size: usize,
+    dict: Option<Py<ZstdCompressionDict>>,
+) -> PyResult<Bound<'p, PyBytes>> {
+    let id = interpreter_id();
+    let dctx = match ONE_SHOT_DCTX.with(|cell| cell.borrow_mut().take()) {
+        Some((owner, dctx)) if owner == id => dctx,
+        _ => Arc::new(DCtx::new().map_err(ZstdDecompressionError::new_err)?),
+    };
+
+    let mut decompressor = ZstdDecompressor {
+        dict_data: dict,
+        max_window_size: 0,
+        max_window_log: 0,
+        format: zstd_sys::ZSTD_format_e::ZSTD_f_zstd1,
+        verify_checksum: true,
+        require_checksum: false,
+        dictionaries: HashMap::new(),
+        dctx: dctx.clone(),
+        block: None,
+    };
+
+    // Drop any dictionary loaded by a previous call before setup_dctx()
+    // loads ours.
+    let res = dctx
+        .reset()
+        .and_then(|_| dctx.clear_dict())
+        .map_err(|msg| {
+            ZstdDecompressionError::new_err(format!(
+                "unable to reset decompression context: {}",
+                msg
+            ))
+            .with_zstd_error(&msg)
+        })
+        .and_then(|_| decompressor.decompress(py, data, max_output_size, false, true, None, 0));
+
+    drop(decompressor);
+    ONE_SHOT_DCTX.with(|cell| *cell.borrow_mut() = Some((id, dctx)));
+
+    res
+}
+
 pub(crate) fn init_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
     module.add_class::<ZstdDecompressor>()?;
+    module.add_function(wrap_pyfunction!(decompress, module)?)?;
     module.add_function(wrap_pyfunction!(
         estimate_decompression_context_size,
         module
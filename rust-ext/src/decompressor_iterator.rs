@@ -6,8 +6,10 @@
 
 use {
     crate::{
-        exceptions::ZstdError,
-        stream::{make_in_buffer_source, InBufferSource},
+        decompression_reader::{check_frame_checksum, check_frame_limit},
+        decompressor::FrameDicts,
+        exceptions::{WithZstdError, ZstdDecompressionError, ZstdTruncatedError},
+        stream::{make_in_buffer_source, peek_frame_header, InBufferSource},
         zstd_safe::DCtx,
     },
     pyo3::{exceptions::PyValueError, prelude::*, types::PyBytes, IntoPyObjectExt},
@@ -19,7 +21,20 @@ pub struct ZstdDecompressorIterator {
     dctx: Arc<DCtx<'static>>,
     source: Box<dyn InBufferSource + Send>,
     write_size: usize,
+    read_across_frames: bool,
+    /// Maximum number of frames to decompress. 0 means unlimited.
+    max_frames: usize,
+    /// Number of frames started so far.
+    frames: usize,
+    /// Whether to yield `(chunk, is_frame_end)` tuples.
+    frame_boundaries: bool,
+    format: zstd_sys::ZSTD_format_e,
+    /// Whether frames lacking a content checksum are rejected.
+    require_checksum: bool,
+    /// Dictionaries to select by frame header, if any were added.
+    dicts: Option<FrameDicts>,
     finished_output: bool,
+    in_frame: bool,
 }
 
 unsafe impl Sync for ZstdDecompressorIterator {}
@@ -33,52 +48,116 @@ impl ZstdDecompressorIterator {
     }
 
     fn __next__(mut slf: PyRefMut<Self>) -> PyResult<Option<PyObject>> {
-        if slf.finished_output {
-            return Ok(None);
+        let py = slf.py();
+        let result = slf.next_chunk(py);
+
+        // Once exhausted or failed, the source is never read again.
+        if !matches!(result, Ok(Some(_))) {
+            slf.finished_output = true;
         }
 
-        let py = unsafe { Python::assume_gil_acquired() };
+        result
+    }
+}
+
+impl ZstdDecompressorIterator {
+    /// Decompress the next chunk, returning `None` at the end of output.
+    fn next_chunk(&mut self, py: Python) -> PyResult<Option<PyObject>> {
+        if self.finished_output {
+            return Ok(None);
+        }
 
-        let mut dest_buffer: Vec<u8> = Vec::with_capacity(slf.write_size);
+        let mut dest_buffer: Vec<u8> = Vec::with_capacity(self.write_size);
 
         // While input is available.
-        while let Some(mut in_buffer) = slf.source.input_buffer(py)? {
+        loop {
+            // Peeking may replace the input buffer, so it happens first.
+            if (self.require_checksum || self.dicts.is_some()) && !self.in_frame {
+                if let Some(header) = peek_frame_header(py, self.source.as_mut(), self.format)? {
+                    if self.require_checksum {
+                        check_frame_checksum(&header)?;
+                    }
+
+                    if let Some(dicts) = &self.dicts {
+                        dicts.load(py, &self.dctx, header.dictID)?;
+                    }
+                }
+            }
+
+            let Some(mut in_buffer) = self.source.input_buffer(py)? else {
+                break;
+            };
+
             let old_pos = in_buffer.pos;
 
-            let zresult = slf
+            let zresult = self
                 .dctx
                 .decompress_into_vec(&mut dest_buffer, &mut in_buffer)
-                .map_err(|msg| ZstdError::new_err(format!("zstd decompress error: {}", msg)))?;
+                .map_err(|msg| {
+                    ZstdDecompressionError::new_err(format!("zstd decompress error: {}", msg))
+                        .with_zstd_error(&msg)
+                })?;
 
-            slf.source.record_bytes_read(in_buffer.pos - old_pos);
+            self.source.record_bytes_read(in_buffer.pos - old_pos);
+
+            // Consuming input outside a frame starts a new one.
+            if !self.in_frame && in_buffer.pos > old_pos {
+                self.frames += 1;
+                check_frame_limit(self.frames, self.max_frames)?;
+            }
 
             if zresult == 0 {
-                slf.finished_output = true;
+                self.in_frame = false;
+
+                // Stop at the end of the frame instead of decompressing
+                // whatever follows it.
+                if !self.read_across_frames {
+                    self.finished_output = true;
+                }
+
+                // zstd returns at the end of every frame, so the output
+                // belongs to this frame alone. Frames without any output
+                // still get a marker.
+                if self.frame_boundaries {
+                    return Ok(Some(self.make_chunk(py, &dest_buffer, true)?));
+                }
+
+                if self.finished_output {
+                    break;
+                }
+            } else if in_buffer.pos > old_pos {
+                self.in_frame = true;
             }
 
             // Emit chunk if output buffer has data.
             if !dest_buffer.is_empty() {
-                // TODO avoid buffer copy.
-                let chunk = PyBytes::new(py, &dest_buffer);
-                return Ok(Some(chunk.into_py_any(py)?));
+                return Ok(Some(self.make_chunk(py, &dest_buffer, false)?));
             }
 
             // Repeat loop to collect more input data.
             continue;
         }
 
+        // The source is exhausted, so this is the last chunk.
+        self.finished_output = true;
+
+        // Trailing bytes too short to be identified as garbage would
+        // otherwise be silently dropped.
+        if self.read_across_frames && self.in_frame && dest_buffer.is_empty() {
+            return Err(ZstdTruncatedError::new_err(
+                "input ended with an incomplete frame or non-frame data",
+            ));
+        }
+
         // Input is exhausted. Emit what we have or finish.
         if !dest_buffer.is_empty() {
-            // TODO avoid buffer copy.
-            let chunk = PyBytes::new(py, &dest_buffer);
-            Ok(Some(chunk.into_py_any(py)?))
+            Ok(Some(self.make_chunk(py, &dest_buffer, false)?))
         } else {
             Ok(None)
         }
     }
-}
 
-impl ZstdDecompressorIterator {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         py: Python,
         dctx: Arc<DCtx<'static>>,
@@ -86,6 +165,12 @@ impl ZstdDecompressorIterator {
         read_size: usize,
         write_size: usize,
         skip_bytes: usize,
+        read_across_frames: bool,
+        max_frames: usize,
+        frame_boundaries: bool,
+        format: zstd_sys::ZSTD_format_e,
+        require_checksum: bool,
+        dicts: Option<FrameDicts>,
     ) -> PyResult<Self> {
         let mut source = make_in_buffer_source(py, reader, read_size)?;
 
@@ -104,7 +189,27 @@ impl ZstdDecompressorIterator {
             dctx,
             source,
             write_size,
+            read_across_frames,
+            max_frames,
+            frames: 0,
+            frame_boundaries,
+            format,
+            require_checksum,
+            dicts,
             finished_output: false,
+            in_frame: false,
         })
     }
+
+    /// Convert decompressed output into the object yielded by the iterator.
+    fn make_chunk(&self, py: Python, data: &[u8], frame_end: bool) -> PyResult<PyObject> {
+        // TODO avoid buffer copy.
+        let chunk = PyBytes::new(py, data);
+
+        if self.frame_boundaries {
+            (chunk, frame_end).into_py_any(py)
+        } else {
+            chunk.into_py_any(py)
+        }
+    }
 }
@@ -6,13 +6,17 @@
 
 use {
     crate::{
-        buffers::{BufferSegment, ZstdBufferWithSegments, ZstdBufferWithSegmentsCollection},
+        buffers::{
+            BufferSegment, DataBuffer, ZstdBufferWithSegments, ZstdBufferWithSegmentsCollection,
+        },
         compression_dict::ZstdCompressionDict,
-        exceptions::ZstdError,
-        zstd_safe::DCtx,
+        decompression_reader::check_frame_checksum,
+        decompressor::FrameDicts,
+        exceptions::{WithZstdError, ZstdDecompressionError, ZstdError},
+        stream::parse_frame_header,
+        zstd_safe::{DCtx, DDict, ZstdFailure},
     },
     pyo3::{
-        buffer::PyBuffer,
         exceptions::{PyTypeError, PyValueError},
         prelude::*,
         types::{PyBytes, PyList, PyTuple},
@@ -22,15 +26,48 @@ use {
 
 struct DataSource<'a> {
     data: &'a [u8],
+    /// Decompressed size given by the caller, if any.
     decompressed_size: usize,
 }
 
+/// Resolve `decompressed_sizes` from a list of ints or a buffer of u64s.
+pub(crate) fn resolve_decompressed_sizes(
+    decompressed_sizes: &Bound<'_, PyAny>,
+) -> PyResult<Vec<u64>> {
+    if let Ok(list) = decompressed_sizes.downcast::<PyList>() {
+        return list.extract::<Vec<u64>>().map_err(|_| {
+            PyTypeError::new_err("decompressed_sizes must only contain non-negative integers")
+        });
+    }
+
+    let buffer: DataBuffer = DataBuffer::get(&decompressed_sizes.as_borrowed())?;
+    let data =
+        unsafe { std::slice::from_raw_parts(buffer.buf_ptr() as *const u8, buffer.len_bytes()) };
+
+    if data.len() % 8 != 0 {
+        return Err(PyValueError::new_err(
+            "decompressed_sizes buffer length must be a multiple of 8",
+        ));
+    }
+
+    Ok(data
+        .chunks_exact(8)
+        .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+        .collect())
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn multi_decompress_to_buffer(
     py: Python,
     dict_data: Option<&Py<ZstdCompressionDict>>,
+    format: zstd_sys::ZSTD_format_e,
+    verify_checksum: bool,
+    require_checksum: bool,
+    dicts: Option<&FrameDicts>,
     frames: &Bound<'_, PyAny>,
     decompressed_sizes: Option<&Bound<'_, PyAny>>,
     threads: isize,
+    output: Option<&Bound<'_, ZstdBufferWithSegments>>,
 ) -> PyResult<ZstdBufferWithSegmentsCollection> {
     let threads = if threads < 0 {
         num_cpus::get()
@@ -40,11 +77,10 @@ pub fn multi_decompress_to_buffer(
         threads as _
     };
 
-    let frame_sizes: &[u64] = if let Some(frames_sizes) = decompressed_sizes {
-        let buffer: PyBuffer<u8> = PyBuffer::get(&frames_sizes.as_borrowed())?;
-        unsafe { std::slice::from_raw_parts(buffer.buf_ptr() as *const _, buffer.len_bytes() / 8) }
+    let frame_sizes = if let Some(decompressed_sizes) = decompressed_sizes {
+        resolve_decompressed_sizes(decompressed_sizes)?
     } else {
-        &[]
+        vec![]
     };
 
     let mut sources = vec![];
@@ -111,7 +147,7 @@ pub fn multi_decompress_to_buffer(
         sources.reserve_exact(list.len());
 
         for (i, item) in list.iter().enumerate() {
-            let buffer: PyBuffer<u8> = PyBuffer::get(&item.as_borrowed())
+            let buffer: DataBuffer = DataBuffer::get(&item.as_borrowed())
                 .map_err(|_| PyTypeError::new_err(format!("item {} not a bytes like object", i)))?;
 
             let slice = unsafe {
@@ -129,14 +165,143 @@ pub fn multi_decompress_to_buffer(
         ));
     }
 
-    decompress_from_datasources(py, dict_data, sources, threads)
+    // Headers are inspected before any frame is decoded.
+    let mut frame_dicts = vec![];
+
+    if require_checksum || dicts.is_some() {
+        for (i, source) in sources.iter().enumerate() {
+            let item_error = |err: PyErr| {
+                ZstdDecompressionError::new_err(format!(
+                    "error decompressing item {}: {}",
+                    i,
+                    err.value(py)
+                ))
+            };
+
+            let header = match parse_frame_header(source.data, format) {
+                Ok((header, 0)) => Some(header),
+                _ => None,
+            };
+
+            if let (true, Some(header)) = (require_checksum, &header) {
+                check_frame_checksum(header).map_err(item_error)?;
+            }
+
+            if let Some(dicts) = dicts {
+                let dict_id = header.as_ref().map_or(0, |header| header.dictID);
+                frame_dicts.push(dicts.select(py, dict_id).map_err(item_error)?);
+            }
+        }
+    }
+
+    decompress_from_datasources(
+        py,
+        dict_data,
+        dicts.map(|_| frame_dicts),
+        format,
+        verify_checksum,
+        sources,
+        threads,
+        output.map(|output| output.borrow()).as_deref(),
+    )
+}
+
+/// Carve the caller's output buffer into one region per frame.
+///
+/// Every frame's decompressed size must be known up front so the regions can
+/// be laid out before any worker starts. The regions are disjoint, so workers
+/// write to them in parallel without further synchronization.
+#[allow(clippy::mut_from_ref)]
+fn output_regions<'o>(
+    output: &'o ZstdBufferWithSegments,
+    sources: &[DataSource],
+) -> PyResult<(Vec<BufferSegment>, Vec<&'o mut [u8]>)> {
+    let buffer = &output.buffer;
+
+    if buffer.readonly() {
+        return Err(PyValueError::new_err("output buffer must be writable"));
+    }
+
+    // Decompressing would change memory exported views see.
+    if output.exported_views() != 0 {
+        return Err(PyValueError::new_err(format!(
+            "output buffer has {} exported views; release them before reusing it",
+            output.exported_views()
+        )));
+    }
+
+    let mut segments = Vec::with_capacity(sources.len());
+    let mut offset: usize = 0;
+
+    for (i, source) in sources.iter().enumerate() {
+        let size = if source.decompressed_size == 0 {
+            match zstd_safe::get_frame_content_size(source.data) {
+                Ok(Some(size)) => size as usize,
+                _ => {
+                    return Err(PyValueError::new_err(format!(
+                        "could not determine decompressed size of item {}",
+                        i
+                    )))
+                }
+            }
+        } else {
+            source.decompressed_size
+        };
+
+        segments.push(BufferSegment {
+            offset: offset as _,
+            length: size as _,
+        });
+        offset = offset.saturating_add(size);
+    }
+
+    if offset > buffer.len_bytes() {
+        return Err(ZstdError::new_err(format!(
+            "output buffer too small; need {} bytes, got {}",
+            offset,
+            buffer.len_bytes()
+        )));
+    }
+
+    let start = buffer.buf_ptr() as usize;
+    let end = start + buffer.len_bytes();
+
+    if let Some(i) = sources.iter().position(|source| {
+        let data = source.data.as_ptr() as usize;
+        data < end && start < data + source.data.len()
+    }) {
+        return Err(PyValueError::new_err(format!(
+            "item {} overlaps the output buffer",
+            i
+        )));
+    }
+
+    // No input aliases the output and the regions don't overlap. Holding the
+    // GIL doesn't keep other threads away from the memory, as code may
+    // release it and free-threaded builds don't have one, so callers must
+    // not access `output` until the call returns.
+    let mut remaining =
+        unsafe { std::slice::from_raw_parts_mut(buffer.buf_ptr() as *mut u8, offset) };
+    let mut regions = Vec::with_capacity(segments.len());
+
+    for segment in &segments {
+        let (region, rest) = std::mem::take(&mut remaining).split_at_mut(segment.length as usize);
+        regions.push(region);
+        remaining = rest;
+    }
+
+    Ok((segments, regions))
 }
 
 #[derive(Debug, PartialEq)]
 enum WorkerError {
     None,
     NoSize,
-    Zstd(&'static str),
+    Zstd(ZstdFailure),
+    /// Frame decompressed to a size other than the one given by the caller.
+    /// Holds the expected size and the actual size, if the frame was able
+    /// to finish within the expected size.
+    SizeMismatch(usize, Option<usize>),
 }
 
 /// Holds results of an individual compression operation.
@@ -146,15 +311,27 @@ struct WorkerResult {
     data: Option<Vec<u8>>,
 }
 
+#[allow(clippy::too_many_arguments)]
 fn decompress_from_datasources(
     py: Python,
     dict_data: Option<&Py<ZstdCompressionDict>>,
+    frame_dicts: Option<Vec<Option<&Py<ZstdCompressionDict>>>>,
+    format: zstd_sys::ZSTD_format_e,
+    verify_checksum: bool,
     sources: Vec<DataSource>,
     thread_count: usize,
+    output: Option<&ZstdBufferWithSegments>,
 ) -> PyResult<ZstdBufferWithSegmentsCollection> {
     // More threads than inputs makes no sense.
     let thread_count = std::cmp::min(thread_count, sources.len());
 
+    let (output_segments, dests) = if let Some(output) = output {
+        let (segments, regions) = output_regions(output, &sources)?;
+        (Some(segments), regions.into_iter().map(Some).collect())
+    } else {
+        (None, sources.iter().map(|_| None).collect::<Vec<_>>())
+    };
+
     // TODO lower thread count when input size is too small and threads
     // would add overhead.
 
@@ -165,7 +342,17 @@ fn decompress_from_datasources(
     // to the C backend.
 
     for _ in 0..thread_count {
-        let dctx = DCtx::new().map_err(ZstdError::new_err)?;
+        let dctx = DCtx::new().map_err(ZstdDecompressionError::new_err)?;
+
+        dctx.set_format(format).map_err(|msg| {
+            ZstdDecompressionError::new_err(format!("unable to set decoding format: {}", msg))
+                .with_zstd_error(&msg)
+        })?;
+
+        dctx.set_verify_checksum(verify_checksum).map_err(|msg| {
+            ZstdDecompressionError::new_err(format!("unable to set checksum verification: {}", msg))
+                .with_zstd_error(&msg)
+        })?;
 
         if let Some(dict_data) = dict_data {
             dict_data.borrow_mut(py).load_into_dctx(&dctx)?;
@@ -174,16 +361,40 @@ fn decompress_from_datasources(
         dctxs.push(dctx);
     }
 
+    // With added dictionaries, each frame is decompressed with the one its
+    // header names. They are prepared here so workers only reference them.
+    let frame_dicts = match frame_dicts {
+        Some(dicts) => {
+            for dict in dicts.iter().flatten() {
+                dict.try_borrow_mut(py)?.ensure_ddict()?;
+            }
+
+            Some(
+                dicts
+                    .iter()
+                    .map(|dict| dict.map(|dict| dict.try_borrow(py)).transpose())
+                    .collect::<Result<Vec<_>, _>>()?,
+            )
+        }
+        None => None,
+    };
+    let frame_ddicts: Option<Vec<Option<&DDict>>> = frame_dicts.as_ref().map(|dicts| {
+        dicts
+            .iter()
+            .map(|dict| dict.as_ref().and_then(|dict| dict.ddict()))
+            .collect()
+    });
+
     let pool = rayon::ThreadPoolBuilder::new()
         .num_threads(thread_count)
         .build()
-        .map_err(|err| ZstdError::new_err(format!("error initializing thread pool: {}", err)))?;
+        .map_err(|err| {
+            ZstdDecompressionError::new_err(format!("error initializing thread pool: {}", err))
+        })?;
 
     pool.install(|| {
-        sources
-            .par_iter()
-            .enumerate()
-            .for_each(|(index, source): (usize, &DataSource)| {
+        sources.par_iter().zip(dests).enumerate().for_each(
+            |(index, (source, dest)): (usize, (&DataSource, Option<&mut [u8]>))| {
                 let thread_index = pool.current_thread_index().unwrap();
 
                 let dctx = &dctxs[thread_index];
@@ -194,6 +405,52 @@ fn decompress_from_datasources(
                     data: None,
                 };
 
+                if let Some(ddicts) = &frame_ddicts {
+                    let loaded = dctx.reset().and_then(|()| match ddicts[index] {
+                        Some(ddict) => dctx.load_prepared_dict(ddict),
+                        None => dctx.clear_dict(),
+                    });
+
+                    if let Err(msg) = loaded {
+                        result.error = WorkerError::Zstd(msg);
+                        results.lock().unwrap().push(result);
+                        return;
+                    }
+                }
+
+                if let Some(dest) = dest {
+                    let mut out_buffer = zstd_sys::ZSTD_outBuffer {
+                        dst: dest.as_mut_ptr() as *mut _,
+                        size: dest.len(),
+                        pos: 0,
+                    };
+                    let mut in_buffer = zstd_sys::ZSTD_inBuffer {
+                        src: source.data.as_ptr() as *const _,
+                        size: source.data.len(),
+                        pos: 0,
+                    };
+
+                    match dctx.decompress_buffers(&mut out_buffer, &mut in_buffer) {
+                        Ok(0) if out_buffer.pos == dest.len() => {}
+                        Ok(zresult) => {
+                            result.error = WorkerError::SizeMismatch(
+                                dest.len(),
+                                if zresult == 0 || out_buffer.pos < dest.len() {
+                                    Some(out_buffer.pos)
+                                } else {
+                                    None
+                                },
+                            );
+                        }
+                        Err(msg) => {
+                            result.error = WorkerError::Zstd(msg);
+                        }
+                    }
+
+                    results.lock().unwrap().push(result);
+                    return;
+                }
+
                 let decompressed_size = if source.decompressed_size == 0 {
                     let frame_size = match zstd_safe::get_frame_content_size(source.data) {
                         Err(zstd_safe::ContentSizeError) => {
@@ -221,6 +478,22 @@ fn decompress_from_datasources(
                     };
 
                     match dctx.decompress_into_vec(&mut dest_buffer, &mut in_buffer) {
+                        Ok(zresult) if source.decompressed_size != 0 => {
+                            if zresult == 0 && dest_buffer.len() == decompressed_size {
+                                result.data = Some(dest_buffer);
+                            } else {
+                                // A frame still in progress with a full output
+                                // buffer is larger than expected.
+                                result.error = WorkerError::SizeMismatch(
+                                    decompressed_size,
+                                    if zresult == 0 || dest_buffer.len() < decompressed_size {
+                                        Some(dest_buffer.len())
+                                    } else {
+                                        None
+                                    },
+                                );
+                            }
+                        }
                         Ok(_) => {
                             result.data = Some(dest_buffer);
                         }
@@ -231,7 +504,8 @@ fn decompress_from_datasources(
                 }
 
                 results.lock().unwrap().push(result);
-            });
+            },
+        );
     });
 
     // Need to sort results by their input order or else results aren't
@@ -241,26 +515,48 @@ fn decompress_from_datasources(
         .unwrap()
         .sort_by(|a, b| a.source_offset.cmp(&b.source_offset));
 
+    let results = results.lock().unwrap();
+
+    for result in results.iter() {
+        match &result.error {
+            WorkerError::None => Ok(()),
+            WorkerError::Zstd(msg) => Err(ZstdDecompressionError::new_err(format!(
+                "error decompressing item {}: {}",
+                result.source_offset, msg
+            ))
+            .with_zstd_error(msg)),
+            WorkerError::NoSize => Err(PyValueError::new_err(format!(
+                "could not determine decompressed size of item {}",
+                result.source_offset
+            ))),
+            WorkerError::SizeMismatch(expected, Some(actual)) => {
+                Err(ZstdDecompressionError::new_err(format!(
+                    "error decompressing item {}: decompressed {} bytes; expected {}",
+                    result.source_offset, actual, expected
+                )))
+            }
+            WorkerError::SizeMismatch(expected, None) => {
+                Err(ZstdDecompressionError::new_err(format!(
+                    "error decompressing item {}: decompressed more than expected {} bytes",
+                    result.source_offset, expected
+                )))
+            }
+        }?;
+    }
+
+    // Results written to the caller's buffer are exposed as segments of it.
+    if let (Some(output), Some(segments)) = (output, output_segments) {
+        let buffer = Py::new(py, output.with_segments(py, segments)?)?;
+
+        return ZstdBufferWithSegmentsCollection::new(py, &PyTuple::new(py, [buffer])?);
+    }
+
     // TODO this is horribly inefficient due to memory copies.
     let els = PyTuple::new(
         py,
         results
-            .lock()
-            .unwrap()
             .iter()
             .map(|result| {
-                match result.error {
-                    WorkerError::None => Ok(()),
-                    WorkerError::Zstd(msg) => Err(ZstdError::new_err(format!(
-                        "error decompressing item {}: {}",
-                        result.source_offset, msg
-                    ))),
-                    WorkerError::NoSize => Err(PyValueError::new_err(format!(
-                        "could not determine decompressed size of item {}",
-                        result.source_offset
-                    ))),
-                }?;
-
                 let data = result.data.as_ref().unwrap();
                 let chunk = PyBytes::new(py, data);
                 let segments = vec![BufferSegment {
@@ -275,7 +571,7 @@ fn decompress_from_datasources(
                         segments.len() * std::mem::size_of::<BufferSegment>(),
                     )
                 };
-                let segments_buffer = PyBuffer::get(&segments)?;
+                let segments_buffer = DataBuffer::get(&segments)?;
 
                 Py::new(
                     py,
@@ -4,12 +4,68 @@
 // This software may be modified and distributed under the terms
 // of the BSD license. See the LICENSE file for details.
 
-use pyo3::{create_exception, exceptions::PyException, prelude::*};
+use {
+    crate::zstd_safe::ZstdFailure,
+    pyo3::{create_exception, exceptions::PyException, prelude::*, types::PyString},
+    std::convert::Infallible,
+};
 
 create_exception!(module, ZstdError, PyException);
+create_exception!(module, ZstdCompressionError, ZstdError);
+create_exception!(module, ZstdDecompressionError, ZstdError);
+create_exception!(module, ZstdTruncatedError, ZstdDecompressionError);
+
+pub(crate) trait WithZstdError {
+    /// Record the zstd error behind an exception in its `code` and
+    /// `error_name` attributes.
+    ///
+    /// Errors not reported by zstd, such as allocation failures, leave the
+    /// attributes as `None`.
+    fn with_zstd_error(self, error: &ZstdFailure) -> Self;
+}
+
+impl WithZstdError for PyErr {
+    fn with_zstd_error(self, error: &ZstdFailure) -> Self {
+        if let (Some(code), Some(error_name)) = (error.code(), error.error_name()) {
+            Python::with_gil(|py| {
+                // Setting attributes on exception instances can't fail.
+                let value = self.value(py);
+                let _ = value.setattr("code", code);
+                let _ = value.setattr("error_name", error_name);
+            });
+        }
+
+        self
+    }
+}
+
+// Exceptions raised directly from a failure take its message as argument.
+impl<'py> IntoPyObject<'py> for ZstdFailure {
+    type Target = PyString;
+    type Output = Bound<'py, PyString>;
+    type Error = Infallible;
+
+    fn into_pyobject(self, py: Python<'py>) -> Result<Self::Output, Self::Error> {
+        Ok(PyString::new(py, &self.to_string()))
+    }
+}
 
 pub(crate) fn init_module(py: Python, module: &Bound<'_, PyModule>) -> PyResult<()> {
-    module.add("ZstdError", py.get_type::<ZstdError>())?;
+    // Errors not originating from zstd have no code.
+    let zstd_error = py.get_type::<ZstdError>();
+    zstd_error.setattr("code", py.None())?;
+    zstd_error.setattr("error_name", py.None())?;
+
+    module.add("ZstdError", zstd_error)?;
+    module.add(
+        "ZstdCompressionError",
+        py.get_type::<ZstdCompressionError>(),
+    )?;
+    module.add(
+        "ZstdDecompressionError",
+        py.get_type::<ZstdDecompressionError>(),
+    )?;
+    module.add("ZstdTruncatedError", py.get_type::<ZstdTruncatedError>())?;
 
     Ok(())
 }
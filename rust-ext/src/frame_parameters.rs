@@ -5,8 +5,19 @@
 // of the BSD license. See the LICENSE file for details.
 
 use {
-    crate::ZstdError,
-    pyo3::{buffer::PyBuffer, exceptions::PyValueError, prelude::*, wrap_pyfunction},
+    crate::{
+        buffers::DataBuffer,
+        constants::{FRAMEHEADERSIZE_MIN, WINDOWLOG_ABSOLUTEMIN},
+        exceptions::{WithZstdError, ZstdTruncatedError},
+        zstd_safe::ZstdFailure,
+        ZstdError,
+    },
+    pyo3::{
+        exceptions::PyValueError,
+        prelude::*,
+        types::{PyIterator, PyList},
+        wrap_pyfunction,
+    },
     std::ffi::c_ulonglong,
 };
 
@@ -24,9 +35,18 @@ impl FrameParameters {
         Ok(self.header.frameContentSize)
     }
 
+    /// Window size a decoder must provision to decompress this frame.
+    ///
+    /// Single segment frames don't carry a window descriptor and zstd reports
+    /// the content size instead, which may be smaller than the minimum window
+    /// a decoder allocates. Clamp to that minimum so the value reflects the
+    /// memory budget enforced by `ZSTD_d_windowLogMax`.
     #[getter]
     fn window_size(&self) -> PyResult<libc::c_ulonglong> {
-        Ok(self.header.windowSize)
+        Ok(match self.header.frameType {
+            zstd_sys::ZSTD_FrameType_e::ZSTD_skippableFrame => 0,
+            _ => self.header.windowSize.max(1 << WINDOWLOG_ABSOLUTEMIN),
+        })
     }
 
     #[getter]
@@ -44,7 +64,7 @@ impl FrameParameters {
 }
 
 #[pyfunction]
-fn frame_content_size(data: PyBuffer<u8>) -> PyResult<i64> {
+fn frame_content_size(data: DataBuffer) -> PyResult<i64> {
     let size = unsafe { zstd_sys::ZSTD_getFrameContentSize(data.buf_ptr(), data.len_bytes()) };
 
     if size == zstd_sys::ZSTD_CONTENTSIZE_ERROR as c_ulonglong {
@@ -57,13 +77,22 @@ fn frame_content_size(data: PyBuffer<u8>) -> PyResult<i64> {
 }
 
 #[pyfunction]
-fn frame_header_size(data: PyBuffer<u8>) -> PyResult<usize> {
+fn frame_header_size(data: DataBuffer) -> PyResult<usize> {
+    if data.len_bytes() < FRAMEHEADERSIZE_MIN {
+        return Err(ZstdError::new_err(format!(
+            "could not determine frame header size: Src size is incorrect; need at least {} bytes but got {}",
+            FRAMEHEADERSIZE_MIN,
+            data.len_bytes()
+        )));
+    }
+
     let zresult = unsafe { zstd_sys::ZSTD_frameHeaderSize(data.buf_ptr(), data.len_bytes()) };
     if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
         return Err(ZstdError::new_err(format!(
             "could not determine frame header size: {}",
             zstd_safe::get_error_name(zresult)
-        )));
+        ))
+        .with_zstd_error(&ZstdFailure::from_result(zresult)));
     }
 
     Ok(zresult)
@@ -73,7 +102,7 @@ fn frame_header_size(data: PyBuffer<u8>) -> PyResult<usize> {
 #[pyo3(signature = (buffer, format=zstd_sys::ZSTD_format_e::ZSTD_f_zstd1 as u32))]
 fn get_frame_parameters(
     py: Python,
-    buffer: PyBuffer<u8>,
+    buffer: DataBuffer,
     format: u32,
 ) -> PyResult<Py<FrameParameters>> {
     let raw_data = unsafe {
@@ -111,7 +140,8 @@ fn get_frame_parameters(
         Err(ZstdError::new_err(format!(
             "cannot get frame parameters: {}",
             zstd_safe::get_error_name(zresult)
-        )))
+        ))
+        .with_zstd_error(&ZstdFailure::from_result(zresult)))
     } else if zresult != 0 {
         Err(ZstdError::new_err(format!(
             "not enough data for frame parameters; need {} bytes",
@@ -122,11 +152,100 @@ fn get_frame_parameters(
     }
 }
 
+/// Locate the frames in a buffer of concatenated frames without decompressing.
+///
+/// Locate every frame in `data` as `(offset, length, is_skippable)`.
+pub(crate) fn find_frames(data: &[u8]) -> PyResult<Vec<(usize, usize, bool)>> {
+    let mut frames = vec![];
+
+    let mut offset = 0;
+    while offset < data.len() {
+        let remaining = &data[offset..];
+        let zresult = unsafe {
+            zstd_sys::ZSTD_findFrameCompressedSize(remaining.as_ptr() as *const _, remaining.len())
+        };
+
+        if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
+            let msg = ZstdFailure::from_result(zresult);
+
+            return Err(if unsafe { zstd_sys::ZSTD_getErrorCode(zresult) }
+                == zstd_sys::ZSTD_ErrorCode::ZSTD_error_srcSize_wrong
+            {
+                ZstdTruncatedError::new_err(format!("frame at offset {} is truncated", offset))
+            } else {
+                ZstdError::new_err(format!("frame at offset {} is malformed: {}", offset, msg))
+            }
+            .with_zstd_error(&msg));
+        }
+
+        let skippable = unsafe {
+            zstd_sys::ZSTD_isSkippableFrame(remaining.as_ptr() as *const _, remaining.len())
+        } != 0;
+
+        frames.push((offset, zresult, skippable));
+        offset += zresult;
+    }
+
+    Ok(frames)
+}
+
+/// Count the blocks of a frame located by `find_frames()`.
+///
+/// Each block regenerates at most the frame's `blockSizeMax` bytes, so this
+/// bounds the output of frames whose header declares a bogus content size.
+pub(crate) fn frame_block_count(frame: &[u8], header_size: usize) -> usize {
+    let mut count = 0;
+    let mut offset = header_size;
+
+    while let Some(block) = frame.get(offset..offset + 3) {
+        let block_header = u32::from_le_bytes([block[0], block[1], block[2], 0]);
+        count += 1;
+
+        // RLE blocks store a single byte regardless of their size.
+        offset += 3 + match (block_header >> 1) & 3 {
+            1 => 1,
+            _ => (block_header >> 3) as usize,
+        };
+
+        if block_header & 1 != 0 {
+            break;
+        }
+    }
+
+    count
+}
+
+/// Yields `(offset, length)` for each zstd frame. Skippable frames are
+/// skipped unless `include_skippable` is set, in which case every frame is
+/// yielded as `(offset, length, is_skippable)`.
+#[pyfunction]
+#[pyo3(signature = (data, include_skippable=false))]
+fn iter_frames<'p>(
+    py: Python<'p>,
+    data: DataBuffer,
+    include_skippable: bool,
+) -> PyResult<Bound<'p, PyIterator>> {
+    let raw_data =
+        unsafe { std::slice::from_raw_parts::<u8>(data.buf_ptr() as *const _, data.len_bytes()) };
+    let frames = PyList::empty(py);
+
+    for (offset, length, skippable) in find_frames(raw_data)? {
+        if include_skippable {
+            frames.append((offset, length, skippable))?;
+        } else if !skippable {
+            frames.append((offset, length))?;
+        }
+    }
+
+    frames.try_iter()
+}
+
 pub(crate) fn init_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
     module.add_class::<FrameParameters>()?;
     module.add_function(wrap_pyfunction!(frame_content_size, module)?)?;
     module.add_function(wrap_pyfunction!(frame_header_size, module)?)?;
     module.add_function(wrap_pyfunction!(get_frame_parameters, module)?)?;
+    module.add_function(wrap_pyfunction!(iter_frames, module)?)?;
 
     Ok(())
 }
@@ -0,0 +1,69 @@
+// Copyright (c) 2020-present, Gregory Szorc
+// All rights reserved.
+//
+// This software may be modified and distributed under the terms
+// of the BSD license. See the LICENSE file for details.
+
+use pyo3::prelude::*;
+
+/// Snapshot of `ZSTD_frameProgression` for an in-progress compression session.
+#[pyclass(module = "zstandard.backend_rust")]
+pub struct FrameProgression {
+    /// Input bytes read and buffered by the compressor.
+    #[pyo3(get)]
+    ingested: u64,
+
+    /// Input bytes actually compressed.
+    #[pyo3(get)]
+    consumed: u64,
+
+    /// Compressed bytes generated, whether flushed or not.
+    #[pyo3(get)]
+    produced: u64,
+
+    /// Compressed bytes flushed to the caller.
+    #[pyo3(get)]
+    flushed: u64,
+
+    /// Most recent job started by the multithreaded compressor.
+    #[pyo3(get)]
+    current_job_id: u32,
+
+    /// Number of workers actively compressing.
+    #[pyo3(get)]
+    nb_active_workers: u32,
+}
+
+impl From<zstd_sys::ZSTD_frameProgression> for FrameProgression {
+    fn from(progression: zstd_sys::ZSTD_frameProgression) -> Self {
+        Self {
+            ingested: progression.ingested as _,
+            consumed: progression.consumed as _,
+            produced: progression.produced as _,
+            flushed: progression.flushed as _,
+            current_job_id: progression.currentJobID as _,
+            nb_active_workers: progression.nbActiveWorkers as _,
+        }
+    }
+}
+
+#[pymethods]
+impl FrameProgression {
+    fn __repr__(&self) -> String {
+        format!(
+            "FrameProgression(ingested={}, consumed={}, produced={}, flushed={}, current_job_id={}, nb_active_workers={})",
+            self.ingested,
+            self.consumed,
+            self.produced,
+            self.flushed,
+            self.current_job_id,
+            self.nb_active_workers
+        )
+    }
+}
+
+pub(crate) fn init_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<FrameProgression>()?;
+
+    Ok(())
+}
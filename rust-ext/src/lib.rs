@@ -32,6 +32,8 @@ mod decompressor_iterator;
 mod decompressor_multi;
 mod exceptions;
 mod frame_parameters;
+mod frame_progression;
+mod plaintext_hash;
 mod stream;
 mod zstd_safe;
 
@@ -39,16 +41,105 @@ mod zstd_safe;
 // and debian/changelog as well.
 const VERSION: &str = "0.25.0";
 
+/// Features not implemented by the C backend.
+const RUST_FEATURES: &[&str] = &[
+    "add_dictionary",
+    "allow_store",
+    "at_eof",
+    "block_api",
+    "buffer_dict_id",
+    "buffer_dictionary_samples",
+    "buffer_from_buffers",
+    "buffer_slicing",
+    "chunker_reset",
+    "closefd_on_failed_close",
+    "compress_bound",
+    "compress_into",
+    "compress_iter",
+    "compress_sequences",
+    "compression_level_constants",
+    "compression_reader_readinto_validation",
+    "compressor_format",
+    "compressor_reset",
+    "content_dict_chain_prefix",
+    "contiguous_buffer_inputs",
+    "copy_stream_progress",
+    "decoder_window_size",
+    "decompress_initial_size",
+    "decompress_into",
+    "decompress_stream",
+    "decompress_to_buffer",
+    "decompression_reader_readline",
+    "decompressobj_reset",
+    "decompressor_frame_content_size",
+    "dedicated_dict_search",
+    "dictionary_buffer_protocol",
+    "dictionary_equality",
+    "dictionary_merge",
+    "dictionary_training_algorithms",
+    "dictionary_training_stats",
+    "effective_compression_parameters",
+    "error_subclasses",
+    "flush_end_frame",
+    "frame_bound",
+    "frame_boundaries",
+    "frame_header_size_validation",
+    "frame_progression",
+    "from_level_validation",
+    "full_dictionary_validation",
+    "generate_sequences",
+    "growable_decompress_output",
+    "has_multithread_support",
+    "iter_frames",
+    "ldm_window_log",
+    "length_prefixed_framing",
+    "low_latency_stream_reader",
+    "max_frame_content_size",
+    "max_frames",
+    "max_window_log",
+    "multi_compress_dicts",
+    "multi_decompress_output",
+    "multi_decompress_size_validation",
+    "needs_input",
+    "next_input_hint",
+    "pledged_size_errors",
+    "precomputed_dictionary_level",
+    "prefix",
+    "read_across_frames",
+    "read_to_iter_exhaustion",
+    "require_checksum",
+    "seek_unsupported_operation",
+    "seekable_stream_reader",
+    "seekable_stream_writer",
+    "set_compression_params",
+    "set_parameter",
+    "skippable_frames",
+    "stable_buffers",
+    "strategy_validation",
+    "stream_reader_max_output_size",
+    "stream_reader_stops_at_frame_end",
+    "stream_writer_buffering",
+    "stream_writer_close_size",
+    "stream_writer_compression_params",
+    "stream_writer_hash",
+    "stream_writer_job_params",
+    "target_cblock_size",
+    "validate",
+    "verify_checksum",
+];
+
 /// Module initialization function called by Python.
 /// This sets up the module with all types and constants.
 fn init_module(py: Python<'_>, module: &Bound<'_, PyModule>) -> PyResult<()> {
     let features = PySet::new(
         py,
-        &[
+        [
             "buffer_types",
             "multi_compress_to_buffer",
             "multi_decompress_to_buffer",
-        ],
+        ]
+        .iter()
+        .chain(RUST_FEATURES),
     )?;
     module.add("backend_features", features)?;
 
@@ -60,6 +151,8 @@ fn init_module(py: Python<'_>, module: &Bound<'_, PyModule>) -> PyResult<()> {
     crate::decompressor::init_module(module)?;
     crate::exceptions::init_module(py, module)?;
     crate::frame_parameters::init_module(module)?;
+    crate::frame_progression::init_module(module)?;
+    crate::stream::init_module(module)?;
 
     Ok(())
 }
@@ -0,0 +1,211 @@
+// Copyright (c) 2021-present, Gregory Szorc
+// All rights reserved.
+//
+// This software may be modified and distributed under the terms
+// of the BSD license. See the LICENSE file for details.
+
+use {
+    pyo3::{prelude::*, types::PyBytes},
+    std::cmp::min,
+};
+
+const PRIME64_1: u64 = 0x9E37_79B1_85EB_CA87;
+const PRIME64_2: u64 = 0xC2B2_AE3D_27D4_EB4F;
+const PRIME64_3: u64 = 0x1656_67B1_9E37_79F9;
+const PRIME64_4: u64 = 0x85EB_CA77_C2B2_AE63;
+const PRIME64_5: u64 = 0x27D4_EB2F_1656_67C5;
+
+/// Size of the stripes XXH64 consumes its input in.
+const STRIPE_SIZE: usize = 32;
+
+fn read_u64(data: &[u8]) -> u64 {
+    u64::from_le_bytes(data[..8].try_into().unwrap())
+}
+
+fn read_u32(data: &[u8]) -> u32 {
+    u32::from_le_bytes(data[..4].try_into().unwrap())
+}
+
+fn round(acc: u64, input: u64) -> u64 {
+    acc.wrapping_add(input.wrapping_mul(PRIME64_2))
+        .rotate_left(31)
+        .wrapping_mul(PRIME64_1)
+}
+
+fn merge_round(acc: u64, value: u64) -> u64 {
+    (acc ^ round(0, value))
+        .wrapping_mul(PRIME64_1)
+        .wrapping_add(PRIME64_4)
+}
+
+/// Incremental XXH64 with a seed of 0, the hash zstd checksums frames with.
+///
+/// zstd compiles xxHash into its own functions without exporting them, so
+/// the algorithm is implemented here.
+pub struct Xxh64 {
+    total_len: u64,
+    acc: [u64; 4],
+    buffer: [u8; STRIPE_SIZE],
+    buffered: usize,
+}
+
+impl Xxh64 {
+    pub fn new() -> Self {
+        Self {
+            total_len: 0,
+            acc: [
+                PRIME64_1.wrapping_add(PRIME64_2),
+                PRIME64_2,
+                0,
+                0u64.wrapping_sub(PRIME64_1),
+            ],
+            buffer: [0; STRIPE_SIZE],
+            buffered: 0,
+        }
+    }
+
+    fn consume_stripe(&mut self, stripe: &[u8]) {
+        for (i, acc) in self.acc.iter_mut().enumerate() {
+            *acc = round(*acc, read_u64(&stripe[i * 8..]));
+        }
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.total_len += data.len() as u64;
+
+        if self.buffered > 0 {
+            let count = min(STRIPE_SIZE - self.buffered, data.len());
+            self.buffer[self.buffered..self.buffered + count].copy_from_slice(&data[..count]);
+            self.buffered += count;
+            data = &data[count..];
+
+            if self.buffered < STRIPE_SIZE {
+                return;
+            }
+
+            let stripe = self.buffer;
+            self.consume_stripe(&stripe);
+            self.buffered = 0;
+        }
+
+        while data.len() >= STRIPE_SIZE {
+            self.consume_stripe(&data[..STRIPE_SIZE]);
+            data = &data[STRIPE_SIZE..];
+        }
+
+        self.buffer[..data.len()].copy_from_slice(data);
+        self.buffered = data.len();
+    }
+
+    pub fn digest(&self) -> u64 {
+        let mut hash = if self.total_len >= STRIPE_SIZE as u64 {
+            let [a, b, c, d] = self.acc;
+            let hash = a
+                .rotate_left(1)
+                .wrapping_add(b.rotate_left(7))
+                .wrapping_add(c.rotate_left(12))
+                .wrapping_add(d.rotate_left(18));
+
+            self.acc
+                .iter()
+                .fold(hash, |hash, &acc| merge_round(hash, acc))
+        } else {
+            // The third accumulator still holds the seed.
+            self.acc[2].wrapping_add(PRIME64_5)
+        };
+
+        hash = hash.wrapping_add(self.total_len);
+
+        let mut remaining = &self.buffer[..self.buffered];
+
+        while remaining.len() >= 8 {
+            hash ^= round(0, read_u64(remaining));
+            hash = hash
+                .rotate_left(27)
+                .wrapping_mul(PRIME64_1)
+                .wrapping_add(PRIME64_4);
+            remaining = &remaining[8..];
+        }
+
+        if remaining.len() >= 4 {
+            hash ^= (read_u32(remaining) as u64).wrapping_mul(PRIME64_1);
+            hash = hash
+                .rotate_left(23)
+                .wrapping_mul(PRIME64_2)
+                .wrapping_add(PRIME64_3);
+            remaining = &remaining[4..];
+        }
+
+        for &byte in remaining {
+            hash ^= (byte as u64).wrapping_mul(PRIME64_5);
+            hash = hash.rotate_left(11).wrapping_mul(PRIME64_1);
+        }
+
+        hash ^= hash >> 33;
+        hash = hash.wrapping_mul(PRIME64_2);
+        hash ^= hash >> 29;
+        hash = hash.wrapping_mul(PRIME64_3);
+        hash ^ (hash >> 32)
+    }
+}
+
+/// Hashes data as it is fed to a compressor.
+pub enum PlaintextHasher {
+    Xxh64(Xxh64),
+    /// A `hashlib` hash object.
+    Hashlib(PyObject),
+}
+
+impl PlaintextHasher {
+    /// Create a hasher for the algorithm called `name`.
+    ///
+    /// `"xxh64"` is computed natively. Any other name is handed to
+    /// `hashlib.new()`, which raises `ValueError` for unknown algorithms.
+    pub fn new(py: Python, name: &str) -> PyResult<Self> {
+        if name == "xxh64" {
+            Ok(Self::Xxh64(Xxh64::new()))
+        } else {
+            let hasher = py.import("hashlib")?.call_method1("new", (name,))?;
+
+            Ok(Self::Hashlib(hasher.unbind()))
+        }
+    }
+
+    pub fn update(&mut self, py: Python, data: &[u8]) -> PyResult<()> {
+        match self {
+            Self::Xxh64(hasher) => {
+                hasher.update(data);
+                Ok(())
+            }
+            Self::Hashlib(hasher) => {
+                // hashlib only reads the memory for the duration of the call,
+                // so it is exposed without a copy.
+                let view = unsafe {
+                    Bound::from_owned_ptr_or_err(
+                        py,
+                        pyo3::ffi::PyMemoryView_FromMemory(
+                            data.as_ptr() as *mut _,
+                            data.len() as _,
+                            pyo3::ffi::PyBUF_READ,
+                        ),
+                    )?
+                };
+
+                let res = hasher.call_method1(py, "update", (&view,));
+                view.call_method0("release")?;
+
+                res.map(|_| ())
+            }
+        }
+    }
+
+    /// Obtain the digest of the data hashed so far.
+    ///
+    /// XXH64 digests use the canonical big-endian representation.
+    pub fn digest<'p>(&self, py: Python<'p>) -> PyResult<Bound<'p, PyBytes>> {
+        match self {
+            Self::Xxh64(hasher) => Ok(PyBytes::new(py, &hasher.digest().to_be_bytes())),
+            Self::Hashlib(hasher) => Ok(hasher.bind(py).call_method0("digest")?.downcast_into()?),
+        }
+    }
+}
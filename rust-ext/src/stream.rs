@@ -5,10 +5,29 @@
 // of the BSD license. See the LICENSE file for details.
 
 use {
-    pyo3::{buffer::PyBuffer, exceptions::PyValueError, prelude::*, IntoPyObjectExt},
+    crate::{
+        buffers::DataBuffer,
+        compression_dict::ZstdCompressionDict,
+        compressor::compress,
+        decompressor::decompress,
+        exceptions::{WithZstdError, ZstdDecompressionError, ZstdError, ZstdTruncatedError},
+        zstd_safe::ZstdFailure,
+    },
+    pyo3::{
+        exceptions::PyValueError,
+        prelude::*,
+        types::{PyBytes, PyIterator},
+        wrap_pyfunction, IntoPyObjectExt,
+    },
+    std::cmp::min,
     zstd_sys::ZSTD_inBuffer,
 };
 
+/// Largest read issued while reading a length-prefixed frame.
+///
+/// The prefix is untrusted, so a corrupt one mustn't trigger a huge request.
+const FRAMED_READ_SIZE: usize = 1 << 20;
+
 /// Describes a type that can be resolved to a `zstd_sys::ZSTD_inBuffer`.
 pub trait InBufferSource {
     /// Obtain the PyObject this instance is reading from.
@@ -25,17 +44,105 @@ pub trait InBufferSource {
 
     /// Whether source data has been fully consumed.
     fn finished(&self) -> bool;
+
+    /// Treat `read()` returning `None`, as non-blocking objects do when no data
+    /// is available yet, as a temporary lack of input instead of an error.
+    fn allow_pending(&mut self) {}
+
+    /// Whether the last attempt to obtain input found none available yet,
+    /// without the source being finished.
+    fn pending(&self) -> bool {
+        false
+    }
+
+    /// Avoid consuming input past the end of the current frame.
+    ///
+    /// Called after each decompression with zstd's suggested size of the next
+    /// input, which never extends past the frame, and with `frame_end` set
+    /// once the frame is complete. Only sources reading from an object whose
+    /// remaining data belongs to someone else need to act on this.
+    fn bound_to_frame(
+        &mut self,
+        _py: Python,
+        _next_input: usize,
+        _frame_end: bool,
+    ) -> PyResult<()> {
+        Ok(())
+    }
+
+    /// Obtain up to `size` bytes of upcoming input without consuming them.
+    ///
+    /// By default only input already obtained is returned. Sources yielding
+    /// input in chunks read further ones as needed, so fewer bytes are only
+    /// returned once input runs out.
+    fn peek(&mut self, py: Python, size: usize) -> PyResult<Vec<u8>> {
+        Ok(match self.input_buffer(py)? {
+            Some(in_buffer) => {
+                let available = min(size, in_buffer.size - in_buffer.pos);
+
+                unsafe {
+                    std::slice::from_raw_parts(
+                        (in_buffer.src as *const u8).add(in_buffer.pos),
+                        available,
+                    )
+                }
+                .to_vec()
+            }
+            None => vec![],
+        })
+    }
 }
 
 /// A data source where data is obtaine by calling `read()`.
 struct ReadSource {
     source: PyObject,
-    buffer: Option<PyBuffer<u8>>,
+    buffer: Option<DataBuffer>,
+    /// Method called to obtain data: `read`, or `read1` to avoid waiting for
+    /// a full `read_size` chunk.
+    read_method: &'static str,
     read_size: usize,
+    /// Cap on the size of the next read, when bounded to a frame.
+    read_limit: Option<usize>,
+    /// Whether the source can be repositioned, once determined.
+    seekable: Option<bool>,
+    /// Whether `read()` returning `None` means no data is available yet.
+    allow_pending: bool,
+    pending: bool,
     finished: bool,
     offset: usize,
 }
 
+impl ReadSource {
+    fn new(
+        py: Python,
+        source: &Bound<'_, PyAny>,
+        read_method: &'static str,
+        read_size: usize,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            source: source.into_py_any(py)?,
+            buffer: None,
+            read_method,
+            read_size,
+            read_limit: None,
+            seekable: None,
+            allow_pending: false,
+            pending: false,
+            finished: false,
+            offset: 0,
+        })
+    }
+
+    fn seekable(&mut self, py: Python) -> bool {
+        *self.seekable.get_or_insert_with(|| {
+            self.source
+                .call_method0(py, "seekable")
+                .and_then(|seekable| seekable.extract::<bool>(py))
+                .unwrap_or(false)
+        })
+    }
+}
+
 impl InBufferSource for ReadSource {
     fn source_object(&self) -> &PyObject {
         &self.source
@@ -57,8 +164,19 @@ impl InBufferSource for ReadSource {
             }))
         // Attempt to read new data.
         } else {
-            let data = self.source.call_method1(py, "read", (self.read_size,))?;
-            let buffer = PyBuffer::get(data.bind(py))?;
+            let read_size = self
+                .read_limit
+                .map_or(self.read_size, |limit| min(limit, self.read_size));
+            let data = self
+                .source
+                .call_method1(py, self.read_method, (read_size,))?;
+
+            self.pending = self.allow_pending && data.is_none(py);
+            if self.pending {
+                return Ok(None);
+            }
+
+            let buffer = DataBuffer::get(data.bind(py))?;
 
             if buffer.len_bytes() == 0 {
                 self.finished = true;
@@ -92,13 +210,78 @@ impl InBufferSource for ReadSource {
     fn finished(&self) -> bool {
         self.finished
     }
+
+    fn allow_pending(&mut self) {
+        self.allow_pending = true;
+    }
+
+    fn pending(&self) -> bool {
+        self.pending
+    }
+
+    fn bound_to_frame(&mut self, py: Python, next_input: usize, frame_end: bool) -> PyResult<()> {
+        if self.seekable(py) {
+            // Read ahead as usual and rewind over whatever follows the frame.
+            if frame_end {
+                if let Some(buffer) = self.buffer.take() {
+                    let unused = (buffer.len_bytes() - self.offset) as i64;
+                    self.source.call_method1(py, "seek", (-unused, 1))?;
+                }
+            }
+        } else {
+            // Data read can't be handed back, so only read what zstd needs.
+            self.read_limit = Some(next_input);
+        }
+
+        Ok(())
+    }
+
+    fn peek(&mut self, py: Python, size: usize) -> PyResult<Vec<u8>> {
+        let mut data = match self.buffer.take() {
+            Some(buffer) => buffer.as_slice()[self.offset..].to_vec(),
+            None => vec![],
+        };
+
+        while data.len() < size && !self.finished {
+            // A bounded source must not be read past what was asked for.
+            let read_size = match self.read_limit {
+                Some(_) => size - data.len(),
+                None => self.read_size,
+            };
+            let chunk = self
+                .source
+                .call_method1(py, self.read_method, (read_size,))?;
+
+            self.pending = self.allow_pending && chunk.is_none(py);
+            if self.pending {
+                break;
+            }
+
+            let chunk = DataBuffer::get(chunk.bind(py))?;
+            if chunk.len_bytes() == 0 {
+                self.finished = true;
+            }
+            data.extend_from_slice(chunk.as_slice());
+        }
+
+        // The input is kept as a single buffer so it can still be consumed.
+        if !data.is_empty() {
+            self.buffer = Some(DataBuffer::get(PyBytes::new(py, &data).as_any())?);
+            self.offset = 0;
+            self.finished = false;
+        }
+
+        data.truncate(size);
+
+        Ok(data)
+    }
 }
 
 /// A data source where data is obtained from a `PyObject`
 /// conforming to the buffer protocol.
 struct BufferSource {
     source: PyObject,
-    buffer: PyBuffer<u8>,
+    buffer: DataBuffer,
     offset: usize,
 }
 
@@ -132,24 +315,154 @@ impl InBufferSource for BufferSource {
     }
 }
 
+/// A data source where data is obtained from an iterator of objects
+/// conforming to the buffer protocol.
+struct IterSource {
+    source: PyObject,
+    buffer: Option<DataBuffer>,
+    finished: bool,
+    offset: usize,
+}
+
+impl InBufferSource for IterSource {
+    fn source_object(&self) -> &PyObject {
+        &self.source
+    }
+
+    fn source_size(&self) -> Option<usize> {
+        None
+    }
+
+    fn input_buffer(&mut self, py: Python) -> PyResult<Option<ZSTD_inBuffer>> {
+        if self.finished() {
+            return Ok(None);
+        }
+
+        // Unlike read(), an empty chunk doesn't signal the end of input.
+        while self.buffer.is_none() {
+            let mut iter = self.source.bind(py).downcast::<PyIterator>()?.clone();
+
+            match iter.next() {
+                Some(item) => {
+                    let buffer = DataBuffer::get(&item?)?;
+
+                    if buffer.len_bytes() != 0 {
+                        self.buffer = Some(buffer);
+                        self.offset = 0;
+                    }
+                }
+                None => {
+                    self.finished = true;
+                    return Ok(None);
+                }
+            }
+        }
+
+        let buffer = self.buffer.as_ref().unwrap();
+
+        Ok(Some(ZSTD_inBuffer {
+            src: buffer.buf_ptr(),
+            size: buffer.len_bytes(),
+            pos: self.offset,
+        }))
+    }
+
+    fn record_bytes_read(&mut self, count: usize) {
+        let buffer = self.buffer.as_ref().expect("buffer should be present");
+
+        self.offset += count;
+
+        if self.offset >= buffer.len_bytes() {
+            self.buffer = None;
+        }
+    }
+
+    fn finished(&self) -> bool {
+        self.finished
+    }
+}
+
+/// Parse the header of a frame from the start of `data`.
+///
+/// The returned size is 0 if the header was parsed or the number of bytes
+/// needed to parse it if `data` is too short.
+pub(crate) fn parse_frame_header(
+    data: &[u8],
+    format: zstd_sys::ZSTD_format_e,
+) -> PyResult<(zstd_sys::ZSTD_FrameHeader, usize)> {
+    let mut header: zstd_sys::ZSTD_FrameHeader = unsafe { std::mem::zeroed() };
+    let zresult = unsafe {
+        zstd_sys::ZSTD_getFrameHeader_advanced(
+            &mut header,
+            data.as_ptr() as *const _,
+            data.len(),
+            format,
+        )
+    };
+
+    if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
+        let msg = ZstdFailure::from_result(zresult);
+        return Err(
+            ZstdDecompressionError::new_err(format!("invalid frame header: {}", msg))
+                .with_zstd_error(&msg),
+        );
+    }
+
+    Ok((header, zresult))
+}
+
+/// Parse the header of the frame at the start of upcoming input, without
+/// consuming it.
+///
+/// Returns `None` if the header is malformed or input ends before it does,
+/// leaving those for the decoder to report.
+pub(crate) fn peek_frame_header(
+    py: Python,
+    source: &mut (dyn InBufferSource + Send),
+    format: zstd_sys::ZSTD_format_e,
+) -> PyResult<Option<zstd_sys::ZSTD_FrameHeader>> {
+    let mut needed = 0;
+
+    loop {
+        let data = source.peek(py, needed)?;
+
+        match parse_frame_header(&data, format) {
+            Ok((header, 0)) => return Ok(Some(header)),
+            Ok((_, size)) if data.len() >= needed => needed = size,
+            _ => return Ok(None),
+        }
+    }
+}
+
+/// Obtain a source yielding the chunks of an iterable.
+pub(crate) fn make_iter_source(
+    py: Python,
+    chunks: &Bound<'_, PyAny>,
+) -> PyResult<Box<dyn InBufferSource + Send>> {
+    Ok(Box::new(IterSource {
+        source: chunks.try_iter()?.into_py_any(py)?,
+        buffer: None,
+        finished: false,
+        offset: 0,
+    }))
+}
+
 pub(crate) fn make_in_buffer_source(
     py: Python,
     source: &Bound<'_, PyAny>,
     read_size: usize,
 ) -> PyResult<Box<dyn InBufferSource + Send>> {
     if source.hasattr("read")? {
-        Ok(Box::new(ReadSource {
-            source: source.into_py_any(py)?,
-            buffer: None,
-            read_size,
-            finished: false,
-            offset: 0,
-        }))
+        Ok(Box::new(ReadSource::new(py, source, "read", read_size)?))
     } else {
-        let buffer = PyBuffer::get(&source.as_borrowed()).map_err(|_| {
-            PyValueError::new_err(
-                "must pass an object with a read() method or conforms to buffer protocol",
-            )
+        let buffer = DataBuffer::get(&source.as_borrowed()).map_err(|err| {
+            if err.is_instance_of::<PyValueError>(py) {
+                err
+            } else {
+                PyValueError::new_err(
+                    "must pass an object with a read() method or conforms to buffer protocol",
+                )
+            }
         })?;
 
         Ok(Box::new(BufferSource {
@@ -159,3 +472,174 @@ pub(crate) fn make_in_buffer_source(
         }))
     }
 }
+
+/// Obtain a source that doesn't wait for more input than is available.
+///
+/// Objects having a `read1()` method, such as buffered readers over pipes and
+/// sockets, are read with it so a read returns as soon as any data arrives.
+pub(crate) fn make_low_latency_in_buffer_source(
+    py: Python,
+    source: &Bound<'_, PyAny>,
+    read_size: usize,
+) -> PyResult<Box<dyn InBufferSource + Send>> {
+    if source.hasattr("read1")? {
+        Ok(Box::new(ReadSource::new(py, source, "read1", read_size)?))
+    } else {
+        make_in_buffer_source(py, source, read_size)
+    }
+}
+
+fn check_length_bytes(length_bytes: usize) -> PyResult<()> {
+    if length_bytes == 4 || length_bytes == 8 {
+        Ok(())
+    } else {
+        Err(PyValueError::new_err(format!(
+            "length_bytes must be 4 or 8, not {}",
+            length_bytes
+        )))
+    }
+}
+
+/// Read `size` bytes from `source`, returning fewer only at EOF.
+fn read_exact(source: &Bound<'_, PyAny>, size: usize) -> PyResult<Vec<u8>> {
+    let mut data = Vec::new();
+
+    while data.len() < size {
+        let chunk = source.call_method1("read", (min(size - data.len(), FRAMED_READ_SIZE),))?;
+        let chunk = chunk.extract::<DataBuffer>()?;
+
+        if chunk.len_bytes() == 0 {
+            break;
+        }
+
+        data.extend_from_slice(chunk.as_slice());
+    }
+
+    Ok(data)
+}
+
+/// Compress `data` as a single frame and write it to `dest` preceded by its
+/// compressed length.
+///
+/// The length is a little-endian integer of `length_bytes` bytes, 4 or 8.
+/// `level` and `dict` are as for `compress()`. Returns the number of bytes
+/// written, including the prefix.
+#[pyfunction]
+#[pyo3(signature = (dest, data, length_bytes=4, level=3, dict=None))]
+fn write_framed(
+    py: Python,
+    dest: &Bound<'_, PyAny>,
+    data: DataBuffer,
+    length_bytes: usize,
+    level: i32,
+    dict: Option<Py<ZstdCompressionDict>>,
+) -> PyResult<usize> {
+    check_length_bytes(length_bytes)?;
+
+    if !dest.hasattr("write")? {
+        return Err(PyValueError::new_err(
+            "must pass object with a write() method",
+        ));
+    }
+
+    let frame = compress(py, data, level, dict)?;
+    let size = frame.as_bytes().len();
+
+    if length_bytes == 4 && u32::try_from(size).is_err() {
+        return Err(ZstdError::new_err(format!(
+            "frame of {} bytes is too large for a 4 byte length prefix",
+            size
+        )));
+    }
+
+    let prefix = (size as u64).to_le_bytes();
+    dest.call_method1("write", (PyBytes::new(py, &prefix[..length_bytes]),))?;
+    dest.call_method1("write", (&frame,))?;
+
+    Ok(length_bytes + size)
+}
+
+/// Read a frame written by `write_framed()` from `source` and decompress it.
+///
+/// Exactly the prefix and the frame are read, leaving `source` positioned at
+/// whatever follows. Returns `None` if `source` is at EOF. `max_output_size`
+/// and `dict` are as for `decompress()`.
+#[pyfunction]
+#[pyo3(signature = (source, length_bytes=4, max_output_size=0, dict=None))]
+fn read_framed<'p>(
+    py: Python<'p>,
+    source: &Bound<'_, PyAny>,
+    length_bytes: usize,
+    max_output_size: usize,
+    dict: Option<Py<ZstdCompressionDict>>,
+) -> PyResult<Option<Bound<'p, PyBytes>>> {
+    check_length_bytes(length_bytes)?;
+
+    if !source.hasattr("read")? {
+        return Err(PyValueError::new_err(
+            "must pass object with a read() method",
+        ));
+    }
+
+    let prefix = read_exact(source, length_bytes)?;
+
+    if prefix.is_empty() {
+        return Ok(None);
+    } else if prefix.len() < length_bytes {
+        return Err(ZstdTruncatedError::new_err(format!(
+            "length prefix is truncated: expected {} bytes but read {}",
+            length_bytes,
+            prefix.len()
+        )));
+    }
+
+    let mut size = [0u8; 8];
+    size[..length_bytes].copy_from_slice(&prefix);
+    let size = u64::from_le_bytes(size);
+
+    let frame = read_exact(source, usize::try_from(size).unwrap_or(usize::MAX))?;
+
+    if (frame.len() as u64) < size {
+        return Err(ZstdTruncatedError::new_err(format!(
+            "frame is truncated: length prefix declares {} bytes but read {}",
+            size,
+            frame.len()
+        )));
+    }
+
+    // The prefix must delimit exactly one frame, or the stream has lost sync.
+    let zresult =
+        unsafe { zstd_sys::ZSTD_findFrameCompressedSize(frame.as_ptr() as *const _, frame.len()) };
+
+    if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
+        let msg = ZstdFailure::from_result(zresult);
+
+        return Err(if unsafe { zstd_sys::ZSTD_getErrorCode(zresult) }
+            == zstd_sys::ZSTD_ErrorCode::ZSTD_error_srcSize_wrong
+        {
+            ZstdTruncatedError::new_err(format!(
+                "frame is truncated: length prefix declares {} bytes but the frame is longer",
+                size
+            ))
+        } else {
+            ZstdError::new_err(format!("frame is malformed: {}", msg))
+        }
+        .with_zstd_error(&msg));
+    } else if zresult != frame.len() {
+        return Err(ZstdError::new_err(format!(
+            "length prefix declares {} bytes but the frame is {} bytes",
+            size, zresult
+        )));
+    }
+
+    let frame = PyBytes::new(py, &frame);
+
+    decompress(py, frame.extract()?, max_output_size, dict).map(Some)
+}
+
+pub(crate) fn init_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(write_framed, module)?)?;
+    module.add_function(wrap_pyfunction!(read_framed, module)?)?;
+
+    Ok(())
+}
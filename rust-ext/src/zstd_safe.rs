@@ -4,7 +4,85 @@
 // This software may be modified and distributed under the terms
 // of the BSD license. See the LICENSE file for details.
 
-use {crate::compression_parameters::CCtxParams, std::marker::PhantomData};
+use {
+    crate::compression_parameters::CCtxParams,
+    libc::c_int,
+    std::{borrow::Cow, fmt, marker::PhantomData},
+};
+
+// zstd-sys binds parameters as Rust enums, which can't represent values added
+// by zstd releases newer than the bindings. These take them as integers.
+#[allow(clashing_extern_declarations)]
+extern "C" {
+    #[link_name = "ZSTD_CCtx_setParameter"]
+    fn cctx_set_parameter_raw(cctx: *mut zstd_sys::ZSTD_CCtx, param: c_int, value: c_int) -> usize;
+    #[link_name = "ZSTD_CCtxParams_setParameter"]
+    pub(crate) fn cctx_params_set_parameter_raw(
+        params: *mut zstd_sys::ZSTD_CCtx_params,
+        param: c_int,
+        value: c_int,
+    ) -> usize;
+    #[link_name = "ZSTD_DCtx_setParameter"]
+    fn dctx_set_parameter_raw(dctx: *mut zstd_sys::ZSTD_DCtx, param: c_int, value: c_int) -> usize;
+}
+
+/// Error returned by the wrappers in this module.
+///
+/// Errors reported by zstd keep the raw `size_t` result carrying the zstd
+/// error code. Errors detected by the wrappers themselves, such as
+/// allocation failures, have no code.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ZstdFailure {
+    code: Option<usize>,
+    message: Cow<'static, str>,
+}
+
+impl ZstdFailure {
+    /// An error reported by zstd through the result of a call.
+    pub fn from_result(zresult: usize) -> Self {
+        Self {
+            code: Some(zresult),
+            message: Cow::Borrowed(zstd_safe::get_error_name(zresult)),
+        }
+    }
+
+    /// A zstd error whose message adds `details` to the error name.
+    fn with_details(zresult: usize, details: String) -> Self {
+        Self {
+            code: Some(zresult),
+            message: Cow::Owned(format!(
+                "{}: {}",
+                zstd_safe::get_error_name(zresult),
+                details
+            )),
+        }
+    }
+
+    /// The raw zstd result, if zstd reported the error.
+    pub fn code(&self) -> Option<usize> {
+        self.code
+    }
+
+    /// The name zstd gives the error, if zstd reported it.
+    pub fn error_name(&self) -> Option<&'static str> {
+        self.code.map(zstd_safe::get_error_name)
+    }
+}
+
+impl From<&'static str> for ZstdFailure {
+    fn from(message: &'static str) -> Self {
+        Self {
+            code: None,
+            message: Cow::Borrowed(message),
+        }
+    }
+}
+
+impl fmt::Display for ZstdFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
 
 /// Safe wrapper for ZSTD_CDict instances.
 pub struct CDict<'a> {
@@ -18,7 +96,7 @@ impl<'a> CDict<'a> {
         data: &[u8],
         content_type: zstd_sys::ZSTD_dictContentType_e,
         params: zstd_sys::ZSTD_compressionParameters,
-    ) -> Result<Self, &'static str> {
+    ) -> Result<Self, ZstdFailure> {
         let ptr = unsafe {
             zstd_sys::ZSTD_createCDict_advanced(
                 data.as_ptr() as *const _,
@@ -34,7 +112,42 @@ impl<'a> CDict<'a> {
             )
         };
         if ptr.is_null() {
-            Err("unable to precompute dictionary")
+            Err("unable to precompute dictionary".into())
+        } else {
+            Ok(Self {
+                ptr,
+                _phantom: PhantomData,
+            })
+        }
+    }
+}
+
+impl<'a> CDict<'a> {
+    /// Precompute a dictionary using the parameters of a compression context.
+    ///
+    /// Unlike `from_data()`, this honors parameters that only exist on
+    /// contexts, such as `ZSTD_c_enableDedicatedDictSearch`.
+    pub fn from_cctx_params(
+        data: &[u8],
+        content_type: zstd_sys::ZSTD_dictContentType_e,
+        params: *const zstd_sys::ZSTD_CCtx_params,
+    ) -> Result<Self, ZstdFailure> {
+        let ptr = unsafe {
+            zstd_sys::ZSTD_createCDict_advanced2(
+                data.as_ptr() as *const _,
+                data.len(),
+                zstd_sys::ZSTD_dictLoadMethod_e::ZSTD_dlm_byRef,
+                content_type,
+                params,
+                zstd_sys::ZSTD_customMem {
+                    customAlloc: None,
+                    customFree: None,
+                    opaque: std::ptr::null_mut(),
+                },
+            )
+        };
+        if ptr.is_null() {
+            Err("unable to precompute dictionary".into())
         } else {
             Ok(Self {
                 ptr,
@@ -78,7 +191,7 @@ impl<'a> DDict<'a> {
     pub fn from_data(
         data: &[u8],
         content_type: zstd_sys::ZSTD_dictContentType_e,
-    ) -> Result<Self, &'static str> {
+    ) -> Result<Self, ZstdFailure> {
         let ptr = unsafe {
             zstd_sys::ZSTD_createDDict_advanced(
                 data.as_ptr() as *const _,
@@ -93,7 +206,7 @@ impl<'a> DDict<'a> {
             )
         };
         if ptr.is_null() {
-            Err("could not create compression dict")
+            Err("could not create decompression dict".into())
         } else {
             Ok(Self {
                 ptr,
@@ -117,10 +230,10 @@ unsafe impl<'a> Send for CCtx<'a> {}
 unsafe impl<'a> Sync for CCtx<'a> {}
 
 impl<'a> CCtx<'a> {
-    pub fn new() -> Result<Self, &'static str> {
+    pub fn new() -> Result<Self, ZstdFailure> {
         let cctx = unsafe { zstd_sys::ZSTD_createCCtx() };
         if cctx.is_null() {
-            return Err("could not allocate ZSTD_CCtx instance");
+            return Err("could not allocate ZSTD_CCtx instance".into());
         }
 
         Ok(Self(cctx, PhantomData))
@@ -130,17 +243,21 @@ impl<'a> CCtx<'a> {
         self.0
     }
 
-    pub fn set_parameters(&self, params: &CCtxParams) -> Result<(), String> {
+    pub fn set_parameters(&self, params: &CCtxParams) -> Result<(), ZstdFailure> {
         let zresult = unsafe {
             zstd_sys::ZSTD_CCtx_setParametersUsingCCtxParams(self.0, params.get_raw_ptr())
         };
         if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
-            return Err(zstd_safe::get_error_name(zresult).to_string());
+            return Err(ZstdFailure::from_result(zresult));
         }
 
         Ok(())
     }
 
+    /// Bytes currently allocated by the context.
+    ///
+    /// Buffers are allocated lazily on first use and grow with the parameters
+    /// in effect, so this value changes over the context's lifetime.
     pub fn memory_size(&self) -> usize {
         unsafe { zstd_sys::ZSTD_sizeof_CCtx(self.0 as *const _) }
     }
@@ -154,19 +271,60 @@ impl<'a> CCtx<'a> {
         }
     }
 
-    pub fn set_pledged_source_size(&self, size: u64) -> Result<(), &'static str> {
+    /// Reset parameters and dictionary to their defaults.
+    ///
+    /// With `session`, any in-progress frame is abandoned first. Otherwise
+    /// this fails if a frame is in progress.
+    pub fn reset_parameters(&self, session: bool) -> Result<(), ZstdFailure> {
+        let directive = if session {
+            zstd_sys::ZSTD_ResetDirective::ZSTD_reset_session_and_parameters
+        } else {
+            zstd_sys::ZSTD_ResetDirective::ZSTD_reset_parameters
+        };
+        let zresult = unsafe { zstd_sys::ZSTD_CCtx_reset(self.0, directive) };
+        if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
+            Err(ZstdFailure::from_result(zresult))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn set_parameter(
+        &self,
+        param: zstd_sys::ZSTD_cParameter,
+        value: i32,
+    ) -> Result<(), ZstdFailure> {
+        let zresult = unsafe { zstd_sys::ZSTD_CCtx_setParameter(self.0, param, value) };
+        if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
+            Err(ZstdFailure::from_result(zresult))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Set a parameter by its `ZSTD_cParameter` value, unknown to us or not.
+    pub fn set_raw_parameter(&self, param: c_int, value: c_int) -> Result<usize, ZstdFailure> {
+        let zresult = unsafe { cctx_set_parameter_raw(self.0, param, value) };
+        if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
+            Err(ZstdFailure::from_result(zresult))
+        } else {
+            Ok(zresult)
+        }
+    }
+
+    pub fn set_pledged_source_size(&self, size: u64) -> Result<(), ZstdFailure> {
         let zresult = unsafe { zstd_sys::ZSTD_CCtx_setPledgedSrcSize(self.0, size) };
         if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
-            Err(zstd_safe::get_error_name(zresult))
+            Err(ZstdFailure::from_result(zresult))
         } else {
             Ok(())
         }
     }
 
-    pub fn load_computed_dict<'b: 'a>(&'a self, cdict: &'b CDict) -> Result<(), &'static str> {
+    pub fn load_computed_dict<'b: 'a>(&'a self, cdict: &'b CDict) -> Result<(), ZstdFailure> {
         let zresult = unsafe { zstd_sys::ZSTD_CCtx_refCDict(self.0, cdict.ptr) };
         if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
-            Err(zstd_safe::get_error_name(zresult))
+            Err(ZstdFailure::from_result(zresult))
         } else {
             Ok(())
         }
@@ -176,7 +334,7 @@ impl<'a> CCtx<'a> {
         &'a self,
         data: &'b [u8],
         content_type: zstd_sys::ZSTD_dictContentType_e,
-    ) -> Result<(), &'static str> {
+    ) -> Result<(), ZstdFailure> {
         let zresult = unsafe {
             zstd_sys::ZSTD_CCtx_loadDictionary_advanced(
                 self.0,
@@ -187,7 +345,17 @@ impl<'a> CCtx<'a> {
             )
         };
         if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
-            Err(zstd_safe::get_error_name(zresult))
+            Err(ZstdFailure::from_result(zresult))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Stop using any previously loaded dictionary.
+    pub fn clear_dict(&self) -> Result<(), ZstdFailure> {
+        let zresult = unsafe { zstd_sys::ZSTD_CCtx_loadDictionary(self.0, std::ptr::null(), 0) };
+        if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
+            Err(ZstdFailure::from_result(zresult))
         } else {
             Ok(())
         }
@@ -197,9 +365,144 @@ impl<'a> CCtx<'a> {
         unsafe { zstd_sys::ZSTD_getFrameProgression(self.0) }
     }
 
-    pub fn compress(&self, source: &[u8]) -> Result<Vec<u8>, &'static str> {
+    /// Reference raw content to use as the dictionary for the next frame only.
+    pub fn ref_prefix<'b: 'a>(&'a self, prefix: &'b [u8]) -> Result<(), ZstdFailure> {
+        let zresult = unsafe {
+            zstd_sys::ZSTD_CCtx_refPrefix(self.0, prefix.as_ptr() as *const _, prefix.len())
+        };
+        if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
+            Err(ZstdFailure::from_result(zresult))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn compress(&self, source: &[u8]) -> Result<Vec<u8>, ZstdFailure> {
         self.reset();
 
+        self.compress_frame(source)
+    }
+
+    /// Compress `source` as a single frame from explicit match sequences.
+    ///
+    /// Block delimiters in `sequences` are merged into the following sequence
+    /// first, so sequences with and without delimiters are both accepted.
+    /// zstd validates the sequences, reporting an invalid set as an error.
+    pub fn compress_sequences(
+        &self,
+        sequences: &mut [zstd_sys::ZSTD_Sequence],
+        source: &[u8],
+    ) -> Result<Vec<u8>, ZstdFailure> {
+        self.reset();
+
+        let count =
+            unsafe { zstd_sys::ZSTD_mergeBlockDelimiters(sequences.as_mut_ptr(), sequences.len()) };
+
+        // ZSTD_c_blockDelimiters.
+        self.set_parameter(
+            zstd_sys::ZSTD_cParameter::ZSTD_c_experimentalParam11,
+            zstd_sys::ZSTD_SequenceFormat_e::ZSTD_sf_noBlockDelimiters as i32,
+        )?;
+        // ZSTD_c_validateSequences.
+        self.set_parameter(zstd_sys::ZSTD_cParameter::ZSTD_c_experimentalParam12, 1)?;
+
+        let mut dest: Vec<u8> = Vec::with_capacity(
+            compress_bound(source.len()).ok_or("source is too large to compress")?,
+        );
+
+        let zresult = unsafe {
+            zstd_sys::ZSTD_compressSequences(
+                self.0,
+                dest.as_mut_ptr() as *mut _,
+                dest.capacity(),
+                sequences.as_ptr(),
+                count,
+                source.as_ptr() as *const _,
+                source.len(),
+            )
+        };
+
+        if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
+            // Don't leave a partial frame behind for the next operation.
+            self.reset();
+
+            Err(ZstdFailure::from_result(zresult))
+        } else {
+            unsafe {
+                dest.set_len(zresult);
+            }
+
+            Ok(dest)
+        }
+    }
+
+    /// Match sequences zstd finds when compressing `source`.
+    ///
+    /// Each block ends with a delimiter: a sequence with zero offset and match
+    /// length whose literal length covers the block's trailing literals.
+    ///
+    /// zstd leaves the context collecting sequences into the now released
+    /// output buffer, so the context is consumed.
+    pub fn generate_sequences(
+        self,
+        source: &[u8],
+    ) -> Result<Vec<zstd_sys::ZSTD_Sequence>, ZstdFailure> {
+        let capacity = unsafe { zstd_sys::ZSTD_sequenceBound(source.len()) };
+
+        // zstd doesn't fill in `rep` for block delimiters.
+        let mut sequences = vec![
+            zstd_sys::ZSTD_Sequence {
+                offset: 0,
+                litLength: 0,
+                matchLength: 0,
+                rep: 0,
+            };
+            capacity
+        ];
+
+        let zresult = unsafe {
+            zstd_sys::ZSTD_generateSequences(
+                self.0,
+                sequences.as_mut_ptr(),
+                capacity,
+                source.as_ptr() as *const _,
+                source.len(),
+            )
+        };
+
+        if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
+            Err(ZstdFailure::from_result(zresult))
+        } else {
+            sequences.truncate(zresult);
+
+            Ok(sequences)
+        }
+    }
+
+    /// Compress input data as a single frame referencing `prefix` as raw content.
+    ///
+    /// Referencing a prefix replaces any loaded dictionary, so all dictionary
+    /// state is cleared afterwards and callers must reload their dictionary.
+    pub fn compress_with_prefix(
+        &self,
+        source: &[u8],
+        prefix: &[u8],
+    ) -> Result<Vec<u8>, ZstdFailure> {
+        self.reset();
+
+        let result = self
+            .ref_prefix(prefix)
+            .and_then(|_| self.compress_frame(source));
+
+        // Don't leave a reference to the prefix behind if the frame wasn't
+        // completed.
+        self.reset();
+        self.clear_dict()?;
+
+        result
+    }
+
+    fn compress_frame(&self, source: &[u8]) -> Result<Vec<u8>, ZstdFailure> {
         let dest_len = unsafe { zstd_sys::ZSTD_compressBound(source.len()) };
 
         let mut dest: Vec<u8> = Vec::with_capacity(dest_len);
@@ -230,9 +533,9 @@ impl<'a> CCtx<'a> {
         };
 
         if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
-            Err(zstd_safe::get_error_name(zresult))
+            Err(ZstdFailure::from_result(zresult))
         } else if zresult > 0 {
-            Err("unexpected partial frame flush")
+            Err("unexpected partial frame flush".into())
         } else {
             unsafe { dest.set_len(out_buffer.pos) }
 
@@ -240,6 +543,56 @@ impl<'a> CCtx<'a> {
         }
     }
 
+    /// Compress input data as a single frame into a caller-provided buffer.
+    ///
+    /// Returns the number of bytes written or `None` if `dest` is too small to
+    /// hold the entire frame.
+    pub fn compress_into(
+        &self,
+        source: &[u8],
+        dest: &mut [u8],
+    ) -> Result<Option<usize>, ZstdFailure> {
+        self.reset();
+
+        let mut in_buffer = zstd_sys::ZSTD_inBuffer {
+            src: source.as_ptr() as *const _,
+            size: source.len(),
+            pos: 0,
+        };
+
+        let mut out_buffer = zstd_sys::ZSTD_outBuffer {
+            dst: dest.as_mut_ptr() as *mut _,
+            size: dest.len(),
+            pos: 0,
+        };
+
+        let zresult = unsafe {
+            zstd_sys::ZSTD_compressStream2(
+                self.0,
+                &mut out_buffer as *mut _,
+                &mut in_buffer as *mut _,
+                zstd_sys::ZSTD_EndDirective::ZSTD_e_end,
+            )
+        };
+
+        if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
+            let code = unsafe { zstd_sys::ZSTD_getErrorCode(zresult) };
+            if code == zstd_sys::ZSTD_ErrorCode::ZSTD_error_dstSize_tooSmall {
+                Ok(None)
+            } else {
+                Err(ZstdFailure::from_result(zresult))
+            }
+        } else if zresult > 0 {
+            // The frame didn't fit. Discard the partial frame so the context is
+            // usable again.
+            self.reset();
+
+            Ok(None)
+        } else {
+            Ok(Some(out_buffer.pos))
+        }
+    }
+
     /// Compress input data as part of a stream.
     ///
     /// Returns a tuple of the emitted compressed data, a slice of unconsumed input,
@@ -249,7 +602,7 @@ impl<'a> CCtx<'a> {
         source: &'a [u8],
         end_mode: zstd_sys::ZSTD_EndDirective,
         output_size: usize,
-    ) -> Result<(Vec<u8>, &'a [u8], bool), &'static str> {
+    ) -> Result<(Vec<u8>, &'a [u8], bool), ZstdFailure> {
         let mut in_buffer = zstd_sys::ZSTD_inBuffer {
             src: source.as_ptr() as *const _,
             size: source.len() as _,
@@ -274,7 +627,7 @@ impl<'a> CCtx<'a> {
         };
 
         if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
-            return Err(zstd_safe::get_error_name(zresult));
+            return Err(ZstdFailure::from_result(zresult));
         }
 
         unsafe {
@@ -291,7 +644,7 @@ impl<'a> CCtx<'a> {
         out_buffer: &mut zstd_sys::ZSTD_outBuffer,
         in_buffer: &mut zstd_sys::ZSTD_inBuffer,
         end_mode: zstd_sys::ZSTD_EndDirective,
-    ) -> Result<usize, &'static str> {
+    ) -> Result<usize, ZstdFailure> {
         let zresult = unsafe {
             zstd_sys::ZSTD_compressStream2(
                 self.0,
@@ -302,7 +655,7 @@ impl<'a> CCtx<'a> {
         };
 
         if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
-            Err(zstd_safe::get_error_name(zresult))
+            Err(ZstdFailure::from_result(zresult))
         } else {
             Ok(zresult)
         }
@@ -317,7 +670,7 @@ impl<'a> CCtx<'a> {
         dest_buffer: &mut Vec<u8>,
         in_buffer: &mut zstd_sys::ZSTD_inBuffer,
         end_mode: zstd_sys::ZSTD_EndDirective,
-    ) -> Result<usize, &'static str> {
+    ) -> Result<usize, ZstdFailure> {
         let mut out_buffer = zstd_sys::ZSTD_outBuffer {
             dst: dest_buffer.as_mut_ptr() as *mut _,
             size: dest_buffer.capacity(),
@@ -332,6 +685,80 @@ impl<'a> CCtx<'a> {
 
         Ok(zresult)
     }
+
+    /// Start a session of raw blocks, optionally primed with a dictionary.
+    ///
+    /// Blocks compressed afterwards may reference earlier blocks, so the
+    /// caller must keep the previous block's memory intact between calls.
+    pub fn begin_blocks<'b: 'a>(
+        &'a self,
+        dict: &'b [u8],
+        params: zstd_sys::ZSTD_compressionParameters,
+    ) -> Result<(), ZstdFailure> {
+        let params = zstd_sys::ZSTD_parameters {
+            cParams: params,
+            fParams: zstd_sys::ZSTD_frameParameters {
+                contentSizeFlag: 0,
+                checksumFlag: 0,
+                noDictIDFlag: 1,
+            },
+        };
+
+        let zresult = unsafe {
+            zstd_sys::ZSTD_compressBegin_advanced(
+                self.0,
+                dict.as_ptr() as *const _,
+                dict.len(),
+                params,
+                zstd_sys::ZSTD_CONTENTSIZE_UNKNOWN as _,
+            )
+        };
+        if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
+            Err(ZstdFailure::from_result(zresult))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Start a session of raw blocks using a precomputed dictionary.
+    pub fn begin_blocks_with_cdict<'b: 'a>(&'a self, cdict: &'b CDict) -> Result<(), ZstdFailure> {
+        let zresult = unsafe { zstd_sys::ZSTD_compressBegin_usingCDict(self.0, cdict.ptr) };
+        if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
+            Err(ZstdFailure::from_result(zresult))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Largest block the current block session accepts.
+    pub fn block_size(&self) -> usize {
+        unsafe { zstd_sys::ZSTD_getBlockSize(self.0) }
+    }
+
+    /// Compress `source` as a raw block without any frame metadata.
+    ///
+    /// Returns `false` and leaves `dest` empty if the block isn't
+    /// compressible, in which case it must be sent as is.
+    pub fn compress_block(&self, dest: &mut Vec<u8>, source: &[u8]) -> Result<bool, ZstdFailure> {
+        let zresult = unsafe {
+            zstd_sys::ZSTD_compressBlock(
+                self.0,
+                dest.as_mut_ptr() as *mut _,
+                dest.capacity(),
+                source.as_ptr() as *const _,
+                source.len(),
+            )
+        };
+        if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
+            return Err(ZstdFailure::from_result(zresult));
+        }
+
+        unsafe {
+            dest.set_len(zresult);
+        }
+
+        Ok(zresult != 0)
+    }
 }
 
 pub struct DCtx<'a>(*mut zstd_sys::ZSTD_DCtx, PhantomData<&'a ()>);
@@ -348,10 +775,10 @@ unsafe impl<'a> Send for DCtx<'a> {}
 unsafe impl<'a> Sync for DCtx<'a> {}
 
 impl<'a> DCtx<'a> {
-    pub fn new() -> Result<Self, &'static str> {
+    pub fn new() -> Result<Self, ZstdFailure> {
         let dctx = unsafe { zstd_sys::ZSTD_createDCtx() };
         if dctx.is_null() {
-            return Err("could not allocate ZSTD_DCtx instance");
+            return Err("could not allocate ZSTD_DCtx instance".into());
         }
 
         Ok(Self(dctx, PhantomData))
@@ -361,11 +788,12 @@ impl<'a> DCtx<'a> {
         self.0
     }
 
+    /// Bytes currently allocated by the context, including stream buffers.
     pub fn memory_size(&self) -> usize {
         unsafe { zstd_sys::ZSTD_sizeof_DCtx(self.0) }
     }
 
-    pub fn reset(&self) -> Result<(), &'static str> {
+    pub fn reset(&self) -> Result<(), ZstdFailure> {
         let zresult = unsafe {
             zstd_sys::ZSTD_DCtx_reset(
                 self.0,
@@ -373,50 +801,178 @@ impl<'a> DCtx<'a> {
             )
         };
         if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
-            Err(zstd_safe::get_error_name(zresult))
+            Err(ZstdFailure::from_result(zresult))
         } else {
             Ok(())
         }
     }
 
-    pub fn set_max_window_size(&self, size: usize) -> Result<(), &'static str> {
+    pub fn set_max_window_size(&self, size: usize) -> Result<(), ZstdFailure> {
         let zresult = unsafe { zstd_sys::ZSTD_DCtx_setMaxWindowSize(self.0, size) };
         if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
-            Err(zstd_safe::get_error_name(zresult))
+            Err(ZstdFailure::from_result(zresult))
         } else {
             Ok(())
         }
     }
 
-    pub fn set_format(&self, format: zstd_sys::ZSTD_format_e) -> Result<(), &'static str> {
+    pub fn set_window_log_max(&self, log: i32) -> Result<(), ZstdFailure> {
+        let zresult = unsafe {
+            zstd_sys::ZSTD_DCtx_setParameter(
+                self.0,
+                zstd_sys::ZSTD_dParameter::ZSTD_d_windowLogMax,
+                log,
+            )
+        };
+        if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
+            Err(ZstdFailure::from_result(zresult))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Set a parameter by its `ZSTD_dParameter` value, unknown to us or not.
+    pub fn set_raw_parameter(&self, param: c_int, value: c_int) -> Result<usize, ZstdFailure> {
+        let zresult = unsafe { dctx_set_parameter_raw(self.0, param, value) };
+        if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
+            Err(ZstdFailure::from_result(zresult))
+        } else {
+            Ok(zresult)
+        }
+    }
+
+    fn get_parameter(&self, param: zstd_sys::ZSTD_dParameter) -> i32 {
+        let mut value = 0;
+        unsafe {
+            zstd_sys::ZSTD_DCtx_getParameter(self.0, param, &mut value);
+        }
+
+        value
+    }
+
+    /// Largest window size frames may use with this context.
+    pub fn max_window_size(&self) -> u64 {
+        1 << self.get_parameter(zstd_sys::ZSTD_dParameter::ZSTD_d_windowLogMax)
+    }
+
+    pub fn set_format(&self, format: zstd_sys::ZSTD_format_e) -> Result<(), ZstdFailure> {
         let zresult = unsafe { zstd_sys::ZSTD_DCtx_setFormat(self.0, format) };
         if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
-            Err(zstd_safe::get_error_name(zresult))
+            Err(ZstdFailure::from_result(zresult))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Control whether frame checksums are verified (`ZSTD_d_forceIgnoreChecksum`).
+    pub fn set_verify_checksum(&self, verify: bool) -> Result<(), ZstdFailure> {
+        let zresult = unsafe {
+            zstd_sys::ZSTD_DCtx_setParameter(
+                self.0,
+                zstd_sys::ZSTD_dParameter::ZSTD_d_experimentalParam3,
+                !verify as _,
+            )
+        };
+        if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
+            Err(ZstdFailure::from_result(zresult))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Stop using any previously loaded dictionary.
+    pub fn clear_dict(&self) -> Result<(), ZstdFailure> {
+        let zresult = unsafe { zstd_sys::ZSTD_DCtx_loadDictionary(self.0, std::ptr::null(), 0) };
+        if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
+            Err(ZstdFailure::from_result(zresult))
         } else {
             Ok(())
         }
     }
 
-    pub fn load_prepared_dict<'b: 'a>(&'a self, dict: &'b DDict) -> Result<(), &'static str> {
+    /// Reference raw content to use as the dictionary for the next frame only.
+    pub fn ref_prefix<'b: 'a>(&'a self, prefix: &'b [u8]) -> Result<(), ZstdFailure> {
+        let zresult = unsafe {
+            zstd_sys::ZSTD_DCtx_refPrefix(self.0, prefix.as_ptr() as *const _, prefix.len())
+        };
+        if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
+            Err(ZstdFailure::from_result(zresult))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn load_prepared_dict<'b: 'a>(&'a self, dict: &'b DDict) -> Result<(), ZstdFailure> {
         let zresult = unsafe { zstd_sys::ZSTD_DCtx_refDDict(self.0, dict.ptr) };
         if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
-            Err(zstd_safe::get_error_name(zresult))
+            Err(ZstdFailure::from_result(zresult))
         } else {
             Ok(())
         }
     }
 
+    /// Describe a frame rejected for exceeding the window size limit.
+    ///
+    /// zstd doesn't advance the input position when it rejects a frame
+    /// header, so the offending header is usually at the current input
+    /// position and the window it requires can be reported.
+    fn window_too_large_error(
+        &self,
+        zresult: usize,
+        in_buffer: &zstd_sys::ZSTD_inBuffer,
+    ) -> ZstdFailure {
+        let format = if self.get_parameter(zstd_sys::ZSTD_dParameter::ZSTD_d_experimentalParam1)
+            == zstd_sys::ZSTD_format_e::ZSTD_f_zstd1_magicless as i32
+        {
+            zstd_sys::ZSTD_format_e::ZSTD_f_zstd1_magicless
+        } else {
+            zstd_sys::ZSTD_format_e::ZSTD_f_zstd1
+        };
+
+        let mut header: zstd_sys::ZSTD_FrameHeader = unsafe { std::mem::zeroed() };
+        let header_result = unsafe {
+            zstd_sys::ZSTD_getFrameHeader_advanced(
+                &mut header,
+                (in_buffer.src as *const u8).add(in_buffer.pos) as *const _,
+                in_buffer.size - in_buffer.pos,
+                format,
+            )
+        };
+
+        let limit = self.max_window_size();
+
+        if header_result == 0 && header.windowSize > limit {
+            window_too_large_error(header.windowSize, limit)
+        } else {
+            ZstdFailure::with_details(
+                zresult,
+                format!(
+                    "frame window exceeds limit of {} bytes (max_window_log {}); \
+                     raise max_window_log to decompress it",
+                    limit,
+                    limit.trailing_zeros()
+                ),
+            )
+        }
+    }
+
     pub fn decompress_buffers(
         &self,
         out_buffer: &mut zstd_sys::ZSTD_outBuffer,
         in_buffer: &mut zstd_sys::ZSTD_inBuffer,
-    ) -> Result<usize, &'static str> {
+    ) -> Result<usize, ZstdFailure> {
         let zresult = unsafe {
             zstd_sys::ZSTD_decompressStream(self.0, out_buffer as *mut _, in_buffer as *mut _)
         };
 
         if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
-            Err(zstd_safe::get_error_name(zresult))
+            if unsafe { zstd_sys::ZSTD_getErrorCode(zresult) }
+                == zstd_sys::ZSTD_ErrorCode::ZSTD_error_frameParameter_windowTooLarge
+            {
+                Err(self.window_too_large_error(zresult, in_buffer))
+            } else {
+                Err(ZstdFailure::from_result(zresult))
+            }
         } else {
             Ok(zresult)
         }
@@ -426,7 +982,7 @@ impl<'a> DCtx<'a> {
         &self,
         dest_buffer: &mut Vec<u8>,
         in_buffer: &mut zstd_sys::ZSTD_inBuffer,
-    ) -> Result<usize, &'static str> {
+    ) -> Result<usize, ZstdFailure> {
         let mut out_buffer = zstd_sys::ZSTD_outBuffer {
             dst: dest_buffer.as_mut_ptr() as *mut _,
             size: dest_buffer.capacity(),
@@ -441,31 +997,298 @@ impl<'a> DCtx<'a> {
 
         Ok(zresult)
     }
+
+    /// Start a session of raw blocks, optionally primed with a dictionary.
+    ///
+    /// As with compression, the previous block's memory must stay intact
+    /// until the next block is decompressed.
+    pub fn begin_blocks<'b: 'a>(&'a self, dict: Option<&'b DDict>) -> Result<(), ZstdFailure> {
+        let zresult = unsafe {
+            zstd_sys::ZSTD_decompressBegin_usingDDict(
+                self.0,
+                dict.map_or(std::ptr::null(), |dict| dict.ptr),
+            )
+        };
+        if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
+            Err(ZstdFailure::from_result(zresult))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Decompress a raw block into the spare capacity of `dest`.
+    pub fn decompress_block(&self, dest: &mut Vec<u8>, source: &[u8]) -> Result<(), ZstdFailure> {
+        let zresult = unsafe {
+            zstd_sys::ZSTD_decompressBlock(
+                self.0,
+                dest.as_mut_ptr() as *mut _,
+                dest.capacity(),
+                source.as_ptr() as *const _,
+                source.len(),
+            )
+        };
+        if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
+            return Err(ZstdFailure::from_result(zresult));
+        }
+
+        unsafe {
+            dest.set_len(zresult);
+        }
+
+        Ok(())
+    }
+
+    /// Record a block sent uncompressed so later blocks can reference it.
+    pub fn insert_block(&self, block: &[u8]) {
+        unsafe {
+            zstd_sys::ZSTD_insertBlock(self.0, block.as_ptr() as *const _, block.len());
+        }
+    }
 }
 
-pub fn train_dictionary_fastcover(
+/// Maximum compressed size of `size` bytes of input.
+///
+/// Returns `None` if `size` exceeds the largest input zstd can compress.
+pub fn compress_bound(size: usize) -> Option<usize> {
+    let bound = unsafe { zstd_sys::ZSTD_compressBound(size) };
+
+    if unsafe { zstd_sys::ZSTD_isError(bound) } != 0 {
+        None
+    } else {
+        Some(bound)
+    }
+}
+
+/// Whether the linked zstd was built with multithreading support.
+///
+/// Probed by requesting a worker on a scratch context, which zstd rejects
+/// with `parameter_unsupported` when built without it.
+pub fn has_multithread_support() -> Result<bool, ZstdFailure> {
+    let cctx = CCtx::new()?;
+
+    let zresult = unsafe {
+        zstd_sys::ZSTD_CCtx_setParameter(cctx.0, zstd_sys::ZSTD_cParameter::ZSTD_c_nbWorkers, 1)
+    };
+
+    Ok(unsafe { zstd_sys::ZSTD_isError(zresult) } == 0
+        || unsafe { zstd_sys::ZSTD_getErrorCode(zresult) }
+            != zstd_sys::ZSTD_ErrorCode::ZSTD_error_parameter_unsupported)
+}
+
+/// Error for a frame whose window of `window_size` bytes exceeds `limit`.
+///
+/// `limit` is a power of 2, as zstd only enforces a maximum window log.
+pub fn window_too_large_error(window_size: u64, limit: u64) -> ZstdFailure {
+    // The smallest window log accommodating the window.
+    let window_log = u64::BITS - (window_size - 1).leading_zeros();
+
+    // zstd reports error codes as negated `size_t` results.
+    let zresult = 0usize
+        .wrapping_sub(zstd_sys::ZSTD_ErrorCode::ZSTD_error_frameParameter_windowTooLarge as usize);
+
+    ZstdFailure::with_details(
+        zresult,
+        format!(
+            "frame requires a window of {} bytes, exceeding limit of {} bytes \
+             (window log {} > max_window_log {}); raise max_window_log to decompress it",
+            window_size,
+            limit,
+            window_log,
+            limit.trailing_zeros()
+        ),
+    )
+}
+
+fn finish_trained_dictionary(dict_buffer: &mut Vec<u8>, zresult: usize) -> Result<(), ZstdFailure> {
+    if unsafe { zstd_sys::ZDICT_isError(zresult) } != 0 {
+        Err(ZstdFailure::from_result(zresult))
+    } else {
+        unsafe {
+            dict_buffer.set_len(zresult);
+        }
+
+        Ok(())
+    }
+}
+
+/// Build a skippable frame holding `data` with the given magic number variant.
+pub fn write_skippable_frame(data: &[u8], magic_variant: u32) -> Result<Vec<u8>, ZstdFailure> {
+    let mut frame: Vec<u8> = Vec::with_capacity(data.len() + 8);
+
+    let zresult = unsafe {
+        zstd_sys::ZSTD_writeSkippableFrame(
+            frame.as_mut_ptr() as *mut _,
+            frame.capacity(),
+            data.as_ptr() as *const _,
+            data.len(),
+            magic_variant,
+        )
+    };
+
+    if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
+        Err(ZstdFailure::from_result(zresult))
+    } else {
+        unsafe {
+            frame.set_len(zresult);
+        }
+
+        Ok(frame)
+    }
+}
+
+/// Obtain the magic number variant and payload of a complete skippable frame.
+pub fn read_skippable_frame(frame: &[u8]) -> Result<(u32, Vec<u8>), ZstdFailure> {
+    let mut payload: Vec<u8> = Vec::with_capacity(frame.len());
+    let mut magic_variant = 0;
+
+    let zresult = unsafe {
+        zstd_sys::ZSTD_readSkippableFrame(
+            payload.as_mut_ptr() as *mut _,
+            payload.capacity(),
+            &mut magic_variant,
+            frame.as_ptr() as *const _,
+            frame.len(),
+        )
+    };
+
+    if unsafe { zstd_sys::ZSTD_isError(zresult) } != 0 {
+        Err(ZstdFailure::from_result(zresult))
+    } else {
+        unsafe {
+            payload.set_len(zresult);
+        }
+
+        Ok((magic_variant, payload))
+    }
+}
+
+/// Size of the header preceding the content of a full dictionary.
+pub fn dict_header_size(dict: &[u8]) -> Result<usize, ZstdFailure> {
+    let zresult =
+        unsafe { zstd_sys::ZDICT_getDictHeaderSize(dict.as_ptr() as *const _, dict.len()) };
+
+    if unsafe { zstd_sys::ZDICT_isError(zresult) } != 0 {
+        Err(ZstdFailure::from_result(zresult))
+    } else {
+        Ok(zresult)
+    }
+}
+
+/// Build a full dictionary around `content`, computing entropy tables from
+/// the samples.
+///
+/// If `content` and the header don't fit in the capacity of `dict_buffer`,
+/// the start of `content` is dropped.
+pub fn finalize_dictionary(
     dict_buffer: &mut Vec<u8>,
+    content: &[u8],
     samples_buffer: &[u8],
     samples_sizes: &[usize],
-    params: &zstd_sys::ZDICT_fastCover_params_t,
-) -> Result<(), &'static str> {
+    params: zstd_sys::ZDICT_params_t,
+) -> Result<(), ZstdFailure> {
     let zresult = unsafe {
-        zstd_sys::ZDICT_optimizeTrainFromBuffer_fastCover(
+        zstd_sys::ZDICT_finalizeDictionary(
             dict_buffer.as_mut_ptr() as *mut _,
             dict_buffer.capacity(),
+            content.as_ptr() as *const _,
+            content.len(),
             samples_buffer.as_ptr() as *const _,
             samples_sizes.as_ptr(),
             samples_sizes.len() as _,
-            params as *const _ as *mut _,
+            params,
         )
     };
-    if unsafe { zstd_sys::ZDICT_isError(zresult) } != 0 {
-        Err(zstd_safe::get_error_name(zresult))
-    } else {
-        unsafe {
-            dict_buffer.set_len(zresult);
+
+    finish_trained_dictionary(dict_buffer, zresult)
+}
+
+/// Train a dictionary using the fastCover algorithm.
+///
+/// When `optimize` is set, `params` is updated with the parameters selected.
+pub fn train_dictionary_fastcover(
+    dict_buffer: &mut Vec<u8>,
+    samples_buffer: &[u8],
+    samples_sizes: &[usize],
+    params: &mut zstd_sys::ZDICT_fastCover_params_t,
+    optimize: bool,
+) -> Result<(), ZstdFailure> {
+    let zresult = unsafe {
+        if optimize {
+            zstd_sys::ZDICT_optimizeTrainFromBuffer_fastCover(
+                dict_buffer.as_mut_ptr() as *mut _,
+                dict_buffer.capacity(),
+                samples_buffer.as_ptr() as *const _,
+                samples_sizes.as_ptr(),
+                samples_sizes.len() as _,
+                params as *mut _,
+            )
+        } else {
+            zstd_sys::ZDICT_trainFromBuffer_fastCover(
+                dict_buffer.as_mut_ptr() as *mut _,
+                dict_buffer.capacity(),
+                samples_buffer.as_ptr() as *const _,
+                samples_sizes.as_ptr(),
+                samples_sizes.len() as _,
+                *params,
+            )
         }
+    };
 
-        Ok(())
-    }
+    finish_trained_dictionary(dict_buffer, zresult)
+}
+
+/// Train a dictionary using the COVER algorithm.
+///
+/// When `optimize` is set, `params` is updated with the parameters selected.
+pub fn train_dictionary_cover(
+    dict_buffer: &mut Vec<u8>,
+    samples_buffer: &[u8],
+    samples_sizes: &[usize],
+    params: &mut zstd_sys::ZDICT_cover_params_t,
+    optimize: bool,
+) -> Result<(), ZstdFailure> {
+    let zresult = unsafe {
+        if optimize {
+            zstd_sys::ZDICT_optimizeTrainFromBuffer_cover(
+                dict_buffer.as_mut_ptr() as *mut _,
+                dict_buffer.capacity(),
+                samples_buffer.as_ptr() as *const _,
+                samples_sizes.as_ptr(),
+                samples_sizes.len() as _,
+                params as *mut _,
+            )
+        } else {
+            zstd_sys::ZDICT_trainFromBuffer_cover(
+                dict_buffer.as_mut_ptr() as *mut _,
+                dict_buffer.capacity(),
+                samples_buffer.as_ptr() as *const _,
+                samples_sizes.as_ptr(),
+                samples_sizes.len() as _,
+                *params,
+            )
+        }
+    };
+
+    finish_trained_dictionary(dict_buffer, zresult)
+}
+
+/// Train a dictionary using the legacy algorithm.
+pub fn train_dictionary_legacy(
+    dict_buffer: &mut Vec<u8>,
+    samples_buffer: &[u8],
+    samples_sizes: &[usize],
+    params: zstd_sys::ZDICT_legacy_params_t,
+) -> Result<(), ZstdFailure> {
+    let zresult = unsafe {
+        zstd_sys::ZDICT_trainFromBuffer_legacy(
+            dict_buffer.as_mut_ptr() as *mut _,
+            dict_buffer.capacity(),
+            samples_buffer.as_ptr() as *const _,
+            samples_sizes.as_ptr(),
+            samples_sizes.len() as _,
+            params,
+        )
+    };
+
+    finish_trained_dictionary(dict_buffer, zresult)
 }
@@ -2,6 +2,8 @@ import io
 import os
 from typing import List  # noqa: F401
 
+import zstandard as zstd
+
 
 class NonClosingBytesIO(io.BytesIO):
     """BytesIO that saves the underlying buffer on close().
@@ -26,6 +28,7 @@ class NonClosingBytesIO(io.BytesIO):
 
 class CustomBytesIO(io.BytesIO):
     def __init__(self, *args, **kwargs):
+        self._close_count = 0
         self._flush_count = 0
         self._read_count = 0
         self._write_count = 0
@@ -34,6 +37,11 @@ class CustomBytesIO(io.BytesIO):
         self.write_exception = None
         super(CustomBytesIO, self).__init__(*args, **kwargs)
 
+    def close(self):
+        self._close_count += 1
+
+        return super(CustomBytesIO, self).close()
+
     def flush(self):
         self._flush_count += 1
 
@@ -59,6 +67,33 @@ class CustomBytesIO(io.BytesIO):
         return super(CustomBytesIO, self).write(data)
 
 
+class NonSeekableReader:
+    """Source with only read() and tell() that records each read() size."""
+
+    def __init__(self, data):
+        self._source = io.BytesIO(data)
+        self.reads = []
+
+    def read(self, size):
+        self.reads.append(size)
+        return self._source.read(size)
+
+    def tell(self):
+        return self._source.tell()
+
+    def remaining(self):
+        return self._source.read()
+
+
+def sizeless_frame(data):
+    """Compress data into a frame whose header omits the content size."""
+    dest = io.BytesIO()
+    with zstd.ZstdCompressor().stream_writer(dest, closefd=False) as writer:
+        writer.write(data)
+
+    return dest.getvalue()
+
+
 _source_files = []  # type: List[bytes]
 
 
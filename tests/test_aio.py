@@ -0,0 +1,326 @@
+import asyncio
+import concurrent.futures
+import io
+import unittest
+
+import zstandard as zstd
+import zstandard.aio as zstd_aio
+
+
+class AsyncSink:
+    """Destination with a coroutine write(), like an async HTTP response."""
+
+    def __init__(self):
+        self.chunks = []
+        self.closed = False
+
+    async def write(self, data):
+        self.chunks.append(bytes(data))
+
+    async def close(self):
+        self.closed = True
+
+    def getvalue(self):
+        return b"".join(self.chunks)
+
+
+class CountingExecutor(concurrent.futures.ThreadPoolExecutor):
+    def __init__(self):
+        super().__init__(max_workers=1)
+        self.calls = 0
+
+    def submit(self, fn, *args, **kwargs):
+        self.calls += 1
+        return super().submit(fn, *args, **kwargs)
+
+
+def stream_reader(data):
+    reader = asyncio.StreamReader()
+    reader.feed_data(data)
+    reader.feed_eof()
+
+    return reader
+
+
+class TestAsyncCompressionWriter(unittest.TestCase):
+    def test_round_trip(self):
+        source = b"foobar" * 8192
+
+        async def run():
+            sink = AsyncSink()
+            async with zstd_aio.AsyncCompressionWriter(sink) as writer:
+                for i in range(0, len(source), 1000):
+                    self.assertEqual(
+                        await writer.write(source[i : i + 1000]),
+                        len(source[i : i + 1000]),
+                    )
+
+            self.assertTrue(writer.closed)
+            self.assertTrue(sink.closed)
+            self.assertEqual(writer.tell(), len(sink.getvalue()))
+
+            return sink.getvalue()
+
+        frame = asyncio.run(run())
+        dctx = zstd.ZstdDecompressor()
+        self.assertEqual(dctx.stream_reader(frame).read(), source)
+
+    def test_sync_writer(self):
+        async def run():
+            dest = io.BytesIO()
+            writer = zstd_aio.AsyncCompressionWriter(dest, closefd=False)
+            await writer.write(b"foo" * 100)
+            await writer.close()
+
+            self.assertFalse(dest.closed)
+
+            return dest.getvalue()
+
+        frame = asyncio.run(run())
+        self.assertEqual(
+            zstd.get_frame_parameters(frame).content_size,
+            zstd.CONTENTSIZE_UNKNOWN,
+        )
+        self.assertEqual(
+            zstd.ZstdDecompressor().decompressobj().decompress(frame),
+            b"foo" * 100,
+        )
+
+    def test_size(self):
+        async def run():
+            sink = AsyncSink()
+            async with zstd_aio.AsyncCompressionWriter(sink, size=6) as writer:
+                await writer.write(b"foobar")
+
+            return sink.getvalue()
+
+        frame = asyncio.run(run())
+        self.assertEqual(zstd.get_frame_parameters(frame).content_size, 6)
+
+    def test_flush(self):
+        async def run():
+            sink = AsyncSink()
+            writer = zstd_aio.AsyncCompressionWriter(sink)
+            await writer.write(b"foobar" * 100)
+            self.assertEqual(sink.getvalue(), b"")
+
+            await writer.flush()
+            flushed = sink.getvalue()
+            self.assertNotEqual(flushed, b"")
+
+            dobj = zstd.ZstdDecompressor().decompressobj()
+            self.assertEqual(dobj.decompress(flushed), b"foobar" * 100)
+
+            await writer.flush(zstd.FLUSH_FRAME)
+            await writer.close()
+
+            return sink.getvalue()
+
+        frame = asyncio.run(run())
+        self.assertEqual(
+            zstd.ZstdDecompressor().stream_reader(frame).read(),
+            b"foobar" * 100,
+        )
+
+    def test_closed(self):
+        async def run():
+            writer = zstd_aio.AsyncCompressionWriter(AsyncSink())
+            await writer.close()
+            await writer.close()
+
+            with self.assertRaisesRegex(ValueError, "stream is closed"):
+                await writer.write(b"foo")
+
+            with self.assertRaisesRegex(ValueError, "stream is closed"):
+                await writer.flush()
+
+            with self.assertRaisesRegex(ValueError, "stream is closed"):
+                async with writer:
+                    pass
+
+        asyncio.run(run())
+
+    def test_stream_writer(self):
+        source = b"foobar" * 4096
+
+        async def run():
+            received = []
+
+            async def handle(reader, writer):
+                async with zstd_aio.AsyncDecompressionReader(
+                    reader
+                ) as decompressor:
+                    received.append(await decompressor.read())
+
+                writer.close()
+                await writer.wait_closed()
+
+            server = await asyncio.start_server(handle, "127.0.0.1", 0)
+            port = server.sockets[0].getsockname()[1]
+
+            async with server:
+                _, stream = await asyncio.open_connection("127.0.0.1", port)
+                async with zstd_aio.AsyncCompressionWriter(stream) as writer:
+                    await writer.write(source)
+
+                while not received:
+                    await asyncio.sleep(0.01)
+
+            return received[0]
+
+        self.assertEqual(asyncio.run(run()), source)
+
+    def test_concurrent_writes(self):
+        chunks = [b"chunk %d " % i * 100 for i in range(16)]
+
+        async def run():
+            sink = AsyncSink()
+            async with zstd_aio.AsyncCompressionWriter(sink) as writer:
+                await asyncio.gather(*(writer.write(c) for c in chunks))
+
+            return sink.getvalue()
+
+        data = zstd.ZstdDecompressor().stream_reader(asyncio.run(run())).read()
+        self.assertEqual(len(data), sum(len(c) for c in chunks))
+
+    def test_executor(self):
+        executor = CountingExecutor()
+
+        async def run():
+            sink = AsyncSink()
+            writer = zstd_aio.AsyncCompressionWriter(sink, executor=executor)
+            async with writer:
+                await writer.write(b"foo")
+                await writer.write(b"bar")
+
+            return sink.getvalue()
+
+        with executor:
+            frame = asyncio.run(run())
+
+        # 2 writes + close.
+        self.assertEqual(executor.calls, 3)
+        self.assertEqual(
+            zstd.ZstdDecompressor().stream_reader(frame).read(), b"foobar"
+        )
+
+    def test_compressor(self):
+        cctx = zstd.ZstdCompressor(level=1, write_checksum=True)
+
+        async def run():
+            sink = AsyncSink()
+            async with zstd_aio.AsyncCompressionWriter(sink, cctx) as writer:
+                await writer.write(b"foobar")
+
+            return sink.getvalue()
+
+        frame = asyncio.run(run())
+        self.assertTrue(zstd.get_frame_parameters(frame).has_checksum)
+
+
+class TestAsyncDecompressionReader(unittest.TestCase):
+    def test_read(self):
+        source = b"foobar" * 8192
+        frame = zstd.ZstdCompressor().compress(source)
+
+        async def run():
+            reader = zstd_aio.AsyncDecompressionReader(stream_reader(frame))
+            chunks = []
+            while True:
+                chunk = await reader.read(1000)
+                if not chunk:
+                    break
+
+                self.assertLessEqual(len(chunk), 1000)
+                chunks.append(chunk)
+
+            self.assertEqual(reader.tell(), len(source))
+            await reader.close()
+            self.assertTrue(reader.closed)
+
+            return b"".join(chunks)
+
+        self.assertEqual(asyncio.run(run()), source)
+
+    def test_read_all(self):
+        frame = zstd.ZstdCompressor().compress(b"foobar" * 100)
+
+        async def run():
+            source = stream_reader(frame)
+            async with zstd_aio.AsyncDecompressionReader(
+                source, read_size=16
+            ) as reader:
+                return await reader.read()
+
+        self.assertEqual(asyncio.run(run()), b"foobar" * 100)
+
+    def test_sync_reader(self):
+        frame = zstd.ZstdCompressor().compress(b"foobar" * 100)
+
+        async def run():
+            source = io.BytesIO(frame)
+            async with zstd_aio.AsyncDecompressionReader(
+                source, closefd=False
+            ) as reader:
+                data = await reader.read()
+
+            self.assertFalse(source.closed)
+
+            return data
+
+        self.assertEqual(asyncio.run(run()), b"foobar" * 100)
+
+    def test_read_across_frames(self):
+        cctx = zstd.ZstdCompressor()
+        frames = cctx.compress(b"foo") + cctx.compress(b"bar")
+
+        async def run(**kwargs):
+            async with zstd_aio.AsyncDecompressionReader(
+                io.BytesIO(frames), **kwargs
+            ) as reader:
+                return await reader.read(100)
+
+        self.assertEqual(asyncio.run(run()), b"foo")
+        self.assertEqual(asyncio.run(run(read_across_frames=True)), b"foobar")
+
+    def test_closed(self):
+        async def run():
+            reader = zstd_aio.AsyncDecompressionReader(io.BytesIO())
+            await reader.close()
+            await reader.close()
+
+            with self.assertRaisesRegex(ValueError, "stream is closed"):
+                await reader.read()
+
+            with self.assertRaisesRegex(ValueError, "stream is closed"):
+                async with reader:
+                    pass
+
+        asyncio.run(run())
+
+    def test_invalid_input(self):
+        async def run():
+            reader = zstd_aio.AsyncDecompressionReader(
+                stream_reader(b"foobarbaz" * 4)
+            )
+
+            with self.assertRaises(zstd.ZstdError):
+                await reader.read()
+
+        asyncio.run(run())
+
+    def test_executor(self):
+        executor = CountingExecutor()
+        frame = zstd.ZstdCompressor().compress(b"foobar" * 100)
+
+        async def run():
+            reader = zstd_aio.AsyncDecompressionReader(
+                stream_reader(frame), executor=executor
+            )
+            async with reader:
+                return await reader.read(), await reader.read()
+
+        with executor:
+            self.assertEqual(asyncio.run(run()), (b"foobar" * 100, b""))
+
+        self.assertEqual(executor.calls, 2)
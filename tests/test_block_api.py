@@ -0,0 +1,154 @@
+import os
+import unittest
+
+import zstandard as zstd
+
+BLOCKSIZE_MAX = 128 * 1024
+
+
+def messages():
+    return [b"message %d: " % i + b"foobar" * (i % 7) for i in range(200)]
+
+
+@unittest.skipUnless(
+    "block_api" in zstd.backend_features, "block api not available"
+)
+class TestBlockAPI(unittest.TestCase):
+    def test_round_trip(self):
+        cctx = zstd.ZstdCompressor()
+        dctx = zstd.ZstdDecompressor()
+
+        for message in messages():
+            block = cctx.compress_block(message)
+            self.assertLessEqual(len(block), len(message))
+            self.assertEqual(
+                dctx.decompress_block(block, len(message)), message
+            )
+
+    def test_history(self):
+        cctx = zstd.ZstdCompressor()
+        message = os.urandom(1024)
+
+        first = cctx.compress_block(message)
+        second = cctx.compress_block(message)
+
+        # Random data doesn't compress until it can reference the previous
+        # block.
+        self.assertEqual(first, message)
+        self.assertLess(len(second), 100)
+
+        dctx = zstd.ZstdDecompressor()
+        self.assertEqual(dctx.decompress_block(first, 1024), message)
+        self.assertEqual(dctx.decompress_block(second, 1024), message)
+
+        # A fresh decompressor lacks the history the block references.
+        with self.assertRaisesRegex(
+            zstd.ZstdDecompressionError, "cannot decompress block"
+        ):
+            zstd.ZstdDecompressor().decompress_block(second, 1024)
+
+    def test_independent_of_frame_operations(self):
+        cctx = zstd.ZstdCompressor()
+        dctx = zstd.ZstdDecompressor()
+        blocks = []
+
+        for message in messages():
+            blocks.append(cctx.compress_block(message))
+            cctx.compress(b"unrelated frame")
+
+        for message, block in zip(messages(), blocks):
+            self.assertEqual(
+                dctx.decompress_block(block, len(message)), message
+            )
+            dctx.decompress(zstd.compress(b"unrelated frame"))
+
+    def test_empty(self):
+        cctx = zstd.ZstdCompressor()
+        dctx = zstd.ZstdDecompressor()
+
+        first = cctx.compress_block(b"foobar" * 100)
+        self.assertEqual(cctx.compress_block(b""), b"")
+        second = cctx.compress_block(b"foobar" * 100)
+
+        self.assertEqual(dctx.decompress_block(first, 600), b"foobar" * 100)
+        self.assertEqual(dctx.decompress_block(b"", 0), b"")
+        self.assertEqual(dctx.decompress_block(second, 600), b"foobar" * 100)
+
+    def test_max_block_size(self):
+        cctx = zstd.ZstdCompressor()
+        source = b"x" * BLOCKSIZE_MAX
+        block = cctx.compress_block(source)
+
+        self.assertEqual(
+            zstd.ZstdDecompressor().decompress_block(block, len(source)),
+            source,
+        )
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "block of 131073 bytes exceeds the maximum block size of 131072 "
+            "bytes",
+        ):
+            cctx.compress_block(source + b"x")
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "original_size of 131073 bytes exceeds the maximum block size",
+        ):
+            zstd.ZstdDecompressor().decompress_block(block, BLOCKSIZE_MAX + 1)
+
+    def test_small_window(self):
+        params = zstd.ZstdCompressionParameters(window_log=10)
+        cctx = zstd.ZstdCompressor(compression_params=params)
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "exceeds the maximum block size of 1024 bytes"
+        ):
+            cctx.compress_block(b"x" * 1025)
+
+        block = cctx.compress_block(b"x" * 1024)
+        dctx = zstd.ZstdDecompressor()
+        self.assertEqual(dctx.decompress_block(block, 1024), b"x" * 1024)
+
+    def test_wrong_size(self):
+        block = zstd.ZstdCompressor().compress_block(b"foobar" * 100)
+
+        with self.assertRaisesRegex(
+            zstd.ZstdDecompressionError,
+            "block of %d bytes is larger than original_size of 10 bytes"
+            % len(block),
+        ):
+            zstd.ZstdDecompressor().decompress_block(block, 10)
+
+        with self.assertRaises(zstd.ZstdDecompressionError):
+            zstd.ZstdDecompressor().decompress_block(block, 601)
+
+    def test_invalid_block(self):
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(
+            zstd.ZstdDecompressionError, "cannot decompress block"
+        ):
+            dctx.decompress_block(b"\xff" * 16, 100)
+
+        # The failed block doesn't poison later ones.
+        cctx = zstd.ZstdCompressor()
+        block = cctx.compress_block(b"foobar" * 100)
+        self.assertEqual(dctx.decompress_block(block, 600), b"foobar" * 100)
+
+    def test_dictionary(self):
+        samples = [b"message %d: " % i + b"foobar" * 8 for i in range(128)]
+        d = zstd.train_dictionary(8192, samples)
+        message = b"message 1000: " + b"foobar" * 8
+
+        plain = zstd.ZstdCompressor().compress_block(message)
+        block = zstd.ZstdCompressor(dict_data=d).compress_block(message)
+        self.assertLess(len(block), len(plain))
+
+        dctx = zstd.ZstdDecompressor(dict_data=d)
+        self.assertEqual(dctx.decompress_block(block, len(message)), message)
+
+        d.precompute_compress(level=3)
+        block = zstd.ZstdCompressor(dict_data=d).compress_block(message)
+        dctx = zstd.ZstdDecompressor(dict_data=d)
+        self.assertEqual(dctx.decompress_block(block, len(message)), message)
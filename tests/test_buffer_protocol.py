@@ -0,0 +1,83 @@
+import array
+import io
+import unittest
+
+import zstandard as zstd
+
+
+@unittest.skipUnless(
+    "contiguous_buffer_inputs" in zstd.backend_features,
+    "contiguous buffer inputs not available",
+)
+class TestBufferProtocolInputs(unittest.TestCase):
+    def test_memoryview_slice(self):
+        source = b"foobar" * 1024
+        padded = b"x" * 100 + source + b"y" * 100
+
+        cctx = zstd.ZstdCompressor()
+        frame = cctx.compress(memoryview(padded)[100:-100])
+        self.assertEqual(frame, cctx.compress(source))
+
+        padded_frame = b"x" * 10 + frame + b"y" * 10
+        dctx = zstd.ZstdDecompressor()
+        self.assertEqual(
+            dctx.decompress(memoryview(padded_frame)[10:-10]), source
+        )
+
+    def test_array(self):
+        source = array.array("i", range(4096))
+        raw = source.tobytes()
+
+        cctx = zstd.ZstdCompressor()
+        frame = cctx.compress(source)
+        self.assertEqual(frame, cctx.compress(raw))
+        self.assertEqual(
+            zstd.get_frame_parameters(frame).content_size, len(raw)
+        )
+
+        dctx = zstd.ZstdDecompressor()
+        self.assertEqual(dctx.decompress(array.array("B", frame)), raw)
+
+        cobj = cctx.compressobj()
+        chunks = cobj.compress(source) + cobj.flush()
+        self.assertEqual(dctx.stream_reader(chunks).read(), raw)
+
+        dest = io.BytesIO()
+        with cctx.stream_writer(dest, closefd=False) as writer:
+            self.assertEqual(writer.write(source), len(raw))
+        self.assertEqual(dctx.stream_reader(dest.getvalue()).read(), raw)
+
+    def test_multidimensional(self):
+        source = memoryview(bytes(range(256)) * 4).cast("B", (32, 32))
+
+        cctx = zstd.ZstdCompressor()
+        frame = cctx.compress(source)
+        self.assertEqual(
+            zstd.ZstdDecompressor().decompress(frame), source.tobytes()
+        )
+
+    def test_non_contiguous(self):
+        source = memoryview(b"foobar" * 1024)[::2]
+        cctx = zstd.ZstdCompressor()
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(ValueError, "buffer must be contiguous"):
+            cctx.compress(source)
+
+        with self.assertRaisesRegex(ValueError, "buffer must be contiguous"):
+            cctx.compressobj().compress(source)
+
+        with self.assertRaisesRegex(ValueError, "buffer must be contiguous"):
+            cctx.stream_writer(io.BytesIO()).write(source)
+
+        with self.assertRaisesRegex(ValueError, "buffer must be contiguous"):
+            cctx.stream_reader(source)
+
+        with self.assertRaisesRegex(ValueError, "buffer must be contiguous"):
+            dctx.decompress(source)
+
+    def test_not_a_buffer(self):
+        cctx = zstd.ZstdCompressor()
+
+        with self.assertRaises(TypeError):
+            cctx.compress("foobar")
@@ -1,3 +1,5 @@
+import array
+import gc
 import struct
 import unittest
 
@@ -6,6 +8,27 @@ import zstandard as zstd
 ss = struct.Struct("=QQ")
 
 
+def frames_with_dict_ids():
+    samples = [b"foo%d bar %d" % (i, i * 7) * 32 for i in range(256)]
+    d = zstd.train_dictionary(8192, samples, dict_id=42)
+
+    frames = [
+        zstd.ZstdCompressor(dict_data=d).compress(b"foo1 bar 7" * 32),
+        zstd.ZstdCompressor().compress(b"foobar"),
+        zstd.ZstdCompressor(dict_data=d, write_dict_id=False).compress(
+            b"foo2 bar 14" * 32
+        ),
+    ]
+
+    offsets = []
+    offset = 0
+    for frame in frames:
+        offsets.append(ss.pack(offset, len(frame)))
+        offset += len(frame)
+
+    return zstd.BufferWithSegments(b"".join(frames), b"".join(offsets))
+
+
 @unittest.skipUnless(
     "buffer_types" in zstd.backend_features, "buffer types not available"
 )
@@ -32,8 +55,16 @@ class TestBufferWithSegments(unittest.TestCase):
     def test_invalid_getitem(self):
         b = zstd.BufferWithSegments(b"foo", ss.pack(0, 3))
 
-        with self.assertRaisesRegex(IndexError, "offset must be non-negative"):
-            b[-10]
+        if "buffer_slicing" in zstd.backend_features:
+            with self.assertRaisesRegex(
+                IndexError, "offset must be at least -1"
+            ):
+                b[-10]
+        else:
+            with self.assertRaisesRegex(
+                IndexError, "offset must be non-negative"
+            ):
+                b[-10]
 
         with self.assertRaisesRegex(IndexError, "offset must be less than 1"):
             b[1]
@@ -64,6 +95,176 @@ class TestBufferWithSegments(unittest.TestCase):
         self.assertEqual(b[1].tobytes(), b"foox")
         self.assertEqual(b[2].tobytes(), b"fooxy")
 
+    @unittest.skipUnless(
+        "buffer_slicing" in zstd.backend_features,
+        "buffer slicing not available",
+    )
+    def test_slice(self):
+        b = zstd.BufferWithSegments(
+            b"aabbbccccdddddeeeeee",
+            b"".join(
+                ss.pack(offset, length)
+                for offset, length in [(0, 2), (2, 3), (5, 4), (9, 5), (14, 6)]
+            ),
+        )
+
+        s = b[1:4]
+        self.assertIsInstance(s, zstd.BufferWithSegments)
+        self.assertEqual(len(s), 3)
+        self.assertEqual(
+            [s[i].tobytes() for i in range(len(s))], [b"bbb", b"cccc", b"ddddd"]
+        )
+        self.assertEqual([s[i].offset for i in range(len(s))], [2, 5, 9])
+
+        # Slices share the backing buffer.
+        self.assertEqual(s.size, 20)
+        self.assertEqual(s.tobytes(), b"aabbbccccdddddeeeeee")
+
+        self.assertEqual(
+            [bytes(v) for v in b[::-2]], [b"eeeeee", b"cccc", b"aa"]
+        )
+        self.assertEqual([bytes(v) for v in b[-2:]], [b"ddddd", b"eeeeee"])
+        self.assertEqual(len(b[10:]), 0)
+        self.assertEqual(len(b[:]), 5)
+
+        # Negative indices count from the end.
+        self.assertEqual(b[-1].tobytes(), b"eeeeee")
+        self.assertEqual(b[-5].tobytes(), b"aa")
+        self.assertEqual(s[-1].tobytes(), b"ddddd")
+
+        with self.assertRaises(ValueError):
+            b[::0]
+
+        with self.assertRaises(TypeError):
+            b["foo"]
+
+        c = zstd.BufferWithSegmentsCollection(s)
+        self.assertEqual(len(c), 3)
+        self.assertEqual(c.size(), 12)
+
+    @unittest.skipUnless(
+        "buffer_slicing" in zstd.backend_features,
+        "buffer slicing not available",
+    )
+    def test_slice_keeps_parent_alive(self):
+        data = bytearray(b"foobarbaz")
+        b = zstd.BufferWithSegments(
+            data, b"".join([ss.pack(0, 3), ss.pack(3, 3), ss.pack(6, 3)])
+        )
+
+        s = b[1:]
+        views = list(b)
+        del b
+        gc.collect()
+
+        self.assertEqual(s[0].tobytes(), b"bar")
+        self.assertEqual([bytes(v) for v in views], [b"foo", b"bar", b"baz"])
+
+        # The backing buffer stays exported while slices reference it.
+        with self.assertRaises(BufferError):
+            data.extend(b"x")
+
+    @unittest.skipUnless(
+        "buffer_slicing" in zstd.backend_features,
+        "buffer slicing not available",
+    )
+    def test_iter(self):
+        b = zstd.BufferWithSegments(
+            b"foofooxfooxy",
+            b"".join([ss.pack(0, 3), ss.pack(3, 4), ss.pack(7, 5)]),
+        )
+
+        views = list(b)
+        self.assertEqual(len(views), 3)
+        for view in views:
+            self.assertIsInstance(view, memoryview)
+            self.assertTrue(view.readonly)
+
+        self.assertEqual(
+            [view.tobytes() for view in views], [b"foo", b"foox", b"fooxy"]
+        )
+        self.assertEqual(list(zstd.BufferWithSegments(b"", b"")), [])
+
+        self.assertEqual(bytes(memoryview(b)), b"foofooxfooxy")
+        self.assertEqual(bytes(memoryview(b[1])), b"foox")
+        self.assertEqual(len(memoryview(b.segments())), 48)
+
+    def test_memoryview_keeps_parent_alive(self):
+        data = bytearray(b"foobarbaz")
+        b = zstd.BufferWithSegments(
+            data, b"".join([ss.pack(0, 3), ss.pack(3, 3), ss.pack(6, 3)])
+        )
+
+        whole = memoryview(b)
+        segment = memoryview(b[1])
+        segments = memoryview(b.segments())
+        del b
+        gc.collect()
+
+        self.assertEqual(bytes(whole), b"foobarbaz")
+        self.assertEqual(bytes(segment), b"bar")
+        self.assertEqual(
+            [ss.unpack_from(segments, i * 16) for i in range(3)],
+            [(0, 3), (3, 3), (6, 3)],
+        )
+
+        with self.assertRaises(BufferError):
+            data.extend(b"x")
+
+        whole.release()
+        segments.release()
+        gc.collect()
+        self.assertEqual(bytes(segment), b"bar")
+
+        with self.assertRaises(BufferError):
+            data.extend(b"x")
+
+        # Releasing the last view frees the instances and their export of
+        # the source.
+        segment.release()
+        gc.collect()
+        data.extend(b"x")
+        self.assertEqual(data, b"foobarbazx")
+
+    @unittest.skipUnless(
+        "buffer_dict_id" in zstd.backend_features,
+        "buffer dict id not available",
+    )
+    def test_dict_id(self):
+        b = frames_with_dict_ids()
+
+        self.assertEqual([b.dict_id(i) for i in range(len(b))], [42, 0, 0])
+        self.assertEqual([b.dict_id(i) for i in range(-3, 0)], [42, 0, 0])
+
+        with self.assertRaisesRegex(IndexError, "offset must be at least -3"):
+            b.dict_id(-4)
+
+        with self.assertRaisesRegex(IndexError, "offset must be less than 3"):
+            b.dict_id(3)
+
+    @unittest.skipUnless(
+        "buffer_dict_id" in zstd.backend_features,
+        "buffer dict id not available",
+    )
+    def test_dict_id_invalid_frame(self):
+        b = zstd.BufferWithSegments(
+            b"foobarbaz", b"".join([ss.pack(0, 6), ss.pack(6, 3)])
+        )
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "segment 0 is not a valid zstd frame: Unknown frame"
+        ):
+            b.dict_id(0)
+
+        frame = zstd.ZstdCompressor().compress(b"foobar")
+        b = zstd.BufferWithSegments(frame, ss.pack(0, 3))
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "segment 0 is too small for a frame header; need 5 bytes",
+        ):
+            b.dict_id(0)
+
 
 @unittest.skipUnless(
     "buffer_types" in zstd.backend_features, "buffer types not available"
@@ -128,3 +329,93 @@ class TestBufferWithSegmentsCollection(unittest.TestCase):
         self.assertEqual(c[0].tobytes(), b"foo")
         self.assertEqual(c[1].tobytes(), b"bar")
         self.assertEqual(c[2].tobytes(), b"baz")
+
+        if "buffer_slicing" in zstd.backend_features:
+            self.assertEqual(c[-1].tobytes(), b"baz")
+            self.assertEqual(c[-3].tobytes(), b"foo")
+
+            with self.assertRaisesRegex(
+                IndexError, "offset must be at least -3"
+            ):
+                c[-4]
+
+    @unittest.skipUnless(
+        "buffer_dict_id" in zstd.backend_features,
+        "buffer dict id not available",
+    )
+    def test_dict_id(self):
+        b1 = frames_with_dict_ids()
+        b2 = zstd.BufferWithSegments(b"foo", ss.pack(0, 3))
+        c = zstd.BufferWithSegmentsCollection(b1, b2, b1)
+
+        self.assertEqual([c.dict_id(i) for i in range(3)], [42, 0, 0])
+        self.assertEqual([c.dict_id(i) for i in range(4, 7)], [42, 0, 0])
+
+        # Errors identify the index within the collection.
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "segment 3 is not a valid zstd frame"
+        ):
+            c.dict_id(3)
+
+        with self.assertRaisesRegex(IndexError, "offset must be less than 7"):
+            c.dict_id(7)
+
+        self.assertEqual(c.dict_id(-3), 42)
+
+        with self.assertRaisesRegex(IndexError, "offset must be at least -7"):
+            c.dict_id(-8)
+
+    @unittest.skipUnless(
+        "buffer_from_buffers" in zstd.backend_features,
+        "buffer from buffers not available",
+    )
+    def test_from_buffers(self):
+        c = zstd.BufferWithSegmentsCollection.from_buffers(
+            [b"foo", b"", bytearray(b"bar"), memoryview(b"bazz")]
+        )
+
+        self.assertEqual(len(c), 4)
+        self.assertEqual(c.size(), 10)
+        self.assertEqual(
+            [c[i].tobytes() for i in range(len(c))],
+            [b"foo", b"", b"bar", b"bazz"],
+        )
+        self.assertEqual([c[i].offset for i in range(len(c))], [0, 3, 3, 6])
+
+        # Items of any format are copied as their raw bytes.
+        ints = array.array("i", [1, 2])
+        c = zstd.BufferWithSegmentsCollection.from_buffers([b"ab", ints])
+        self.assertEqual(c[1].tobytes(), ints.tobytes())
+        self.assertEqual(c.size(), 2 + len(ints.tobytes()))
+
+        c = zstd.BufferWithSegmentsCollection.from_buffers([b""])
+        self.assertEqual(len(c), 1)
+        self.assertEqual(c.size(), 0)
+
+        with self.assertRaisesRegex(ValueError, "must pass at least 1 item"):
+            zstd.BufferWithSegmentsCollection.from_buffers([])
+
+        with self.assertRaisesRegex(
+            TypeError, "item 1 does not support the buffer protocol"
+        ):
+            zstd.BufferWithSegmentsCollection.from_buffers([b"foo", 42])
+
+        with self.assertRaisesRegex(ValueError, "item 1 is not contiguous"):
+            zstd.BufferWithSegmentsCollection.from_buffers(
+                [b"foo", memoryview(b"abcdef")[::2]]
+            )
+
+    @unittest.skipUnless(
+        "buffer_from_buffers" in zstd.backend_features,
+        "buffer from buffers not available",
+    )
+    def test_from_buffers_decompress(self):
+        cctx = zstd.ZstdCompressor()
+        sources = [b"foo%d" % i * 64 for i in range(8)]
+
+        c = zstd.BufferWithSegmentsCollection.from_buffers(
+            [cctx.compress(source) for source in sources]
+        )
+
+        result = zstd.ZstdDecompressor().multi_decompress_to_buffer(c)
+        self.assertEqual([result[i].tobytes() for i in range(8)], sources)
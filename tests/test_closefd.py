@@ -0,0 +1,120 @@
+import io
+import unittest
+
+import zstandard as zstd
+
+from .common import CustomBytesIO
+
+
+class TestCloseFd(unittest.TestCase):
+    def _writers(self):
+        cctx = zstd.ZstdCompressor()
+        dctx = zstd.ZstdDecompressor()
+
+        yield (
+            cctx.stream_writer,
+            b"foo" * 1024,
+            lambda data: dctx.decompressobj().decompress(data),
+        )
+        yield (
+            dctx.stream_writer,
+            cctx.compress(b"foo" * 1024),
+            lambda data: data,
+        )
+
+        if "seekable_stream_writer" in zstd.backend_features:
+            yield (
+                cctx.seekable_stream_writer,
+                b"foo" * 1024,
+                lambda data: dctx.seekable_stream_reader(io.BytesIO(data)).read(),
+            )
+
+    def test_writer_closefd_true(self):
+        for make_writer, data, _ in self._writers():
+            dest = CustomBytesIO()
+            writer = make_writer(dest)
+            writer.write(data)
+            writer.close()
+
+            self.assertTrue(writer.closed)
+            self.assertTrue(dest.closed)
+            self.assertEqual(dest._close_count, 1)
+
+            # Closing again doesn't close the wrapped object again.
+            writer.close()
+            self.assertEqual(dest._close_count, 1)
+
+    def test_writer_closefd_false(self):
+        for make_writer, data, decode in self._writers():
+            dest = CustomBytesIO()
+            writer = make_writer(dest, closefd=False)
+            writer.write(data)
+            writer.close()
+
+            self.assertTrue(writer.closed)
+            self.assertFalse(dest.closed)
+            self.assertEqual(dest._close_count, 0)
+            self.assertEqual(decode(dest.getvalue()), b"foo" * 1024)
+
+    def test_writer_context_manager(self):
+        for closefd in (True, False):
+            for make_writer, data, _ in self._writers():
+                dest = CustomBytesIO()
+                with make_writer(dest, closefd=closefd) as writer:
+                    writer.write(data)
+
+                self.assertEqual(dest._close_count, 1 if closefd else 0)
+
+    def test_reader(self):
+        cctx = zstd.ZstdCompressor()
+        dctx = zstd.ZstdDecompressor()
+        frame = cctx.compress(b"foo" * 1024)
+
+        readers = [
+            (cctx.stream_reader, b"foo" * 1024),
+            (dctx.stream_reader, frame),
+        ]
+        if "seekable_stream_reader" in zstd.backend_features:
+            seekable = io.BytesIO()
+            with cctx.seekable_stream_writer(seekable, closefd=False) as writer:
+                writer.write(b"foo" * 1024)
+
+            readers.append((dctx.seekable_stream_reader, seekable.getvalue()))
+
+        for make_reader, data in readers:
+            for closefd in (True, False):
+                source = CustomBytesIO(data)
+                reader = make_reader(source, closefd=closefd)
+                reader.read(1)
+                reader.close()
+
+                self.assertTrue(reader.closed)
+                self.assertEqual(source._close_count, 1 if closefd else 0)
+                self.assertEqual(source.closed, closefd)
+
+            source = CustomBytesIO(data)
+            with make_reader(source) as reader:
+                reader.read(1)
+
+            self.assertEqual(source._close_count, 1)
+
+    @unittest.skipUnless(
+        "closefd_on_failed_close" in zstd.backend_features,
+        "closefd on failed close not available",
+    )
+    def test_writer_closes_on_error(self):
+        cctx = zstd.ZstdCompressor()
+
+        # Both writers buffer output until the frame is finished.
+        for make_writer in (cctx.stream_writer, cctx.seekable_stream_writer):
+            data = b"foo" * 1024
+            dest = CustomBytesIO()
+            writer = make_writer(dest)
+            writer.write(data)
+            dest.write_exception = IOError("write")
+
+            with self.assertRaisesRegex(IOError, "write"):
+                writer.close()
+
+            self.assertTrue(writer.closed)
+            self.assertEqual(dest._close_count, 1)
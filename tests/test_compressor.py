@@ -1,3 +1,4 @@
+import io
 import unittest
 
 import zstandard as zstd
@@ -11,3 +12,292 @@ class TestCompressor(unittest.TestCase):
     def test_memory_size(self):
         cctx = zstd.ZstdCompressor(level=1)
         self.assertGreater(cctx.memory_size(), 100)
+
+    def test_memory_size_grows(self):
+        cctx = zstd.ZstdCompressor(level=19)
+        initial = cctx.memory_size()
+
+        cctx.compress(b"foobar" * 65536)
+        used = cctx.memory_size()
+        self.assertGreater(used, initial)
+
+        # Reusing the context for similar input doesn't allocate further.
+        cctx.compress(b"foobar" * 65536)
+        self.assertEqual(cctx.memory_size(), used)
+
+
+@unittest.skipUnless(
+    "compressor_reset" in zstd.backend_features,
+    "compressor reset not available",
+)
+class TestCompressor_reset(unittest.TestCase):
+    def test_reset_abandons_frame(self):
+        cctx = zstd.ZstdCompressor(level=5, write_checksum=True)
+        expected = cctx.compress(b"foobar" * 256)
+
+        cobj = cctx.compressobj()
+        cobj.compress(b"partial input " * 100)
+        cctx.reset()
+
+        self.assertEqual(cctx.compress(b"foobar" * 256), expected)
+
+        params = zstd.get_frame_parameters(expected)
+        self.assertTrue(params.has_checksum)
+
+    def test_reset_stream_writer(self):
+        cctx = zstd.ZstdCompressor()
+        expected = cctx.compress(b"foobar")
+
+        buffer = io.BytesIO()
+        writer = cctx.stream_writer(buffer, size=6)
+        writer.write(b"foo")
+        cctx.reset()
+
+        buffer = io.BytesIO()
+        with cctx.stream_writer(buffer, size=6, closefd=False) as writer:
+            writer.write(b"foobar")
+
+        self.assertEqual(buffer.getvalue(), expected)
+
+    def test_reset_keeps_dictionary(self):
+        samples = []
+        for i in range(128):
+            samples.append(b"foo" * 64)
+            samples.append(b"bar" * 64)
+            samples.append(b"foobar" * 64)
+
+        d = zstd.train_dictionary(8192, samples)
+        cctx = zstd.ZstdCompressor(dict_data=d)
+        expected = cctx.compress(b"foobar" * 64)
+
+        cctx.reset()
+        frame = cctx.compress(b"foobar" * 64)
+
+        self.assertEqual(frame, expected)
+        self.assertEqual(zstd.get_frame_parameters(frame).dict_id, d.dict_id())
+        self.assertEqual(
+            zstd.ZstdDecompressor(dict_data=d).decompress(frame),
+            b"foobar" * 64,
+        )
+
+
+@unittest.skipUnless(
+    "set_compression_params" in zstd.backend_features,
+    "set compression params not available",
+)
+class TestCompressor_set_compression_params(unittest.TestCase):
+    def test_changes_parameters(self):
+        source = b"foobar" * 1024
+        params = zstd.ZstdCompressionParameters(
+            compression_level=19, write_checksum=True
+        )
+        expected = zstd.ZstdCompressor(compression_params=params).compress(
+            source
+        )
+
+        cctx = zstd.ZstdCompressor(level=1)
+        original = cctx.compress(source)
+        cctx.set_compression_params(params)
+
+        self.assertEqual(cctx.compress(source), expected)
+        self.assertEqual(b"".join(cctx.read_to_iter(source)), expected)
+        self.assertTrue(zstd.get_frame_parameters(expected).has_checksum)
+
+        # A session reset keeps the new parameters. A full reset restores the
+        # ones the compressor was constructed with.
+        cctx.reset()
+        self.assertEqual(cctx.compress(source), expected)
+        cctx.reset(session_and_parameters=True)
+        self.assertEqual(cctx.compress(source), original)
+
+    def test_rejected_mid_frame(self):
+        params = zstd.ZstdCompressionParameters(compression_level=19)
+        cctx = zstd.ZstdCompressor(level=1)
+        expected = cctx.compress(b"foobar" * 256)
+
+        cobj = cctx.compressobj()
+        cobj.compress(b"partial input " * 100)
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "cannot change parameters mid-frame; call reset\\(\\) first",
+        ):
+            cctx.set_compression_params(params)
+
+        # The compressor's parameters are unchanged.
+        cctx.reset()
+        self.assertEqual(cctx.compress(b"foobar" * 256), expected)
+
+        writer = cctx.stream_writer(io.BytesIO())
+        writer.write(b"foobar")
+        with self.assertRaises(zstd.ZstdError):
+            cctx.set_compression_params(params)
+
+    def test_allowed_after_reset(self):
+        params = zstd.ZstdCompressionParameters(compression_level=19)
+        expected = zstd.ZstdCompressor(level=19).compress(b"foobar" * 256)
+        cctx = zstd.ZstdCompressor(level=1)
+
+        for session_and_parameters in (False, True):
+            cobj = cctx.compressobj()
+            cobj.compress(b"partial input " * 100)
+            cctx.reset(session_and_parameters=session_and_parameters)
+
+            cctx.set_compression_params(params)
+            self.assertEqual(cctx.compress(b"foobar" * 256), expected)
+
+        # Finished frames don't block parameter changes.
+        cobj = cctx.compressobj()
+        cobj.compress(b"foobar")
+        cobj.flush()
+        cctx.set_compression_params(params)
+
+    def test_keeps_dictionary(self):
+        samples = [b"foo%dbar" % i * 64 for i in range(128)]
+        d = zstd.train_dictionary(8192, samples)
+        cctx = zstd.ZstdCompressor(dict_data=d)
+
+        cctx.set_compression_params(
+            zstd.ZstdCompressionParameters(
+                compression_level=19, write_dict_id=True
+            )
+        )
+        frame = cctx.compress(b"foo42bar" * 64)
+
+        self.assertEqual(zstd.get_frame_parameters(frame).dict_id, d.dict_id())
+        self.assertEqual(
+            zstd.ZstdDecompressor(dict_data=d).decompress(frame),
+            b"foo42bar" * 64,
+        )
+
+        cctx.reset(session_and_parameters=True)
+        frame = cctx.compress(b"foo42bar" * 64)
+        self.assertEqual(zstd.get_frame_parameters(frame).dict_id, d.dict_id())
+
+
+# Raw zstd parameter values from zstd.h.
+ZSTD_C_COMPRESSION_LEVEL = 100
+ZSTD_C_WINDOW_LOG = 101
+ZSTD_C_CHECKSUM_FLAG = 201
+
+
+@unittest.skipUnless(
+    "set_parameter" in zstd.backend_features, "set parameter not available"
+)
+class TestCompressor_set_parameter(unittest.TestCase):
+    def test_set_parameter(self):
+        source = b"foobar" * 1024
+        cctx = zstd.ZstdCompressor(write_checksum=False)
+        self.assertFalse(
+            zstd.get_frame_parameters(cctx.compress(source)).has_checksum
+        )
+
+        self.assertEqual(cctx.set_parameter(ZSTD_C_CHECKSUM_FLAG, 1), 1)
+
+        # Both the compressor's context and ones created for an operation
+        # pick up the parameter.
+        frames = [
+            cctx.compress(source),
+            b"".join(cctx.read_to_iter(source)),
+            cctx.multi_compress_to_buffer([source] * 2, threads=2)[0].tobytes(),
+        ]
+
+        for frame in frames:
+            self.assertTrue(zstd.get_frame_parameters(frame).has_checksum)
+            self.assertEqual(zstd.ZstdDecompressor().decompress(frame), source)
+
+    def test_result(self):
+        cctx = zstd.ZstdCompressor()
+
+        # zstd returns the value in effect, with 0 selecting the default level.
+        self.assertEqual(cctx.set_parameter(ZSTD_C_COMPRESSION_LEVEL, 7), 7)
+        self.assertEqual(cctx.set_parameter(ZSTD_C_COMPRESSION_LEVEL, 0), 3)
+
+    def test_errors(self):
+        cctx = zstd.ZstdCompressor()
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "unable to set parameter 999999: Unsupported"
+        ) as cm:
+            cctx.set_parameter(999999, 1)
+
+        self.assertEqual(cm.exception.error_name, "Unsupported parameter")
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "unable to set parameter 101: Parameter is out of bound",
+        ):
+            cctx.set_parameter(ZSTD_C_WINDOW_LOG, 5)
+
+        # zstd refuses most parameter changes mid-frame.
+        cobj = cctx.compressobj()
+        cobj.compress(b"partial input " * 100)
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "unable to set parameter 101: Operation not authorized",
+        ):
+            cctx.set_parameter(ZSTD_C_WINDOW_LOG, 20)
+
+        # Failed calls leave the parameters alone.
+        cctx.reset()
+        self.assertEqual(
+            cctx.compress(b"foobar"), zstd.ZstdCompressor().compress(b"foobar")
+        )
+
+    def test_replaced_by_set_compression_params(self):
+        cctx = zstd.ZstdCompressor()
+        cctx.set_parameter(ZSTD_C_CHECKSUM_FLAG, 1)
+        cctx.set_compression_params(zstd.ZstdCompressionParameters())
+
+        frame = cctx.compress(b"foobar")
+        self.assertFalse(zstd.get_frame_parameters(frame).has_checksum)
+
+
+@unittest.skipUnless(
+    "skippable_frames" in zstd.backend_features,
+    "skippable frames not available",
+)
+class TestCompressor_write_skippable_frame(unittest.TestCase):
+    def test_frame(self):
+        cctx = zstd.ZstdCompressor()
+
+        self.assertEqual(
+            cctx.write_skippable_frame(b"foobar"),
+            b"\x50\x2a\x4d\x18\x06\x00\x00\x00foobar",
+        )
+        self.assertEqual(
+            cctx.write_skippable_frame(bytearray(b"foo"), magic_variant=15),
+            b"\x5f\x2a\x4d\x18\x03\x00\x00\x00foo",
+        )
+        self.assertEqual(
+            cctx.write_skippable_frame(b""), b"\x50\x2a\x4d\x18" + b"\x00" * 4
+        )
+
+        # Decoders skip over the frame.
+        frame = cctx.compress(b"foobar")
+        data = frame + cctx.write_skippable_frame(b"metadata", 7) + frame
+        self.assertEqual(
+            zstd.ZstdDecompressor().decompressobj().decompress(data),
+            b"foobar",
+        )
+        self.assertEqual(
+            b"".join(
+                zstd.ZstdDecompressor().read_to_iter(
+                    data, read_across_frames=True
+                )
+            ),
+            b"foobar" * 2,
+        )
+
+    def test_invalid_magic_variant(self):
+        cctx = zstd.ZstdCompressor()
+
+        for value in (16, 1000):
+            with self.assertRaisesRegex(
+                ValueError, "magic_variant must be between 0 and 15"
+            ):
+                cctx.write_skippable_frame(b"foo", magic_variant=value)
+
+        with self.assertRaises(OverflowError):
+            cctx.write_skippable_frame(b"foo", magic_variant=-1)
@@ -97,6 +97,38 @@ class TestCompressor_chunker(unittest.TestCase):
             b"foo" * 1024,
         )
 
+    def test_invalid_chunk_size(self):
+        cctx = zstd.ZstdCompressor()
+
+        with self.assertRaisesRegex(
+            ValueError, "chunk_size must be greater than 0"
+        ):
+            cctx.chunker(chunk_size=0)
+
+    def test_large_input_multiple_chunks(self):
+        source = b"".join(b"line %d\n" % i for i in range(100000))
+
+        cctx = zstd.ZstdCompressor(level=1)
+        chunker = cctx.chunker(chunk_size=4096)
+
+        # A single input larger than chunk_size is split across chunks.
+        chunks = list(chunker.compress(source))
+        self.assertGreater(len(chunks), 1)
+        self.assertTrue(all(len(chunk) == 4096 for chunk in chunks))
+
+        final = list(chunker.finish())
+        self.assertGreater(len(final), 0)
+        self.assertTrue(all(len(chunk) <= 4096 for chunk in final))
+        self.assertTrue(all(len(chunk) == 4096 for chunk in final[:-1]))
+
+        dctx = zstd.ZstdDecompressor()
+        self.assertEqual(
+            dctx.decompress(
+                b"".join(chunks + final), max_output_size=len(source)
+            ),
+            source,
+        )
+
     def test_input_types(self):
         cctx = zstd.ZstdCompressor()
 
@@ -191,3 +223,98 @@ class TestCompressor_chunker(unittest.TestCase):
             zstd.ZstdError, r"cannot call finish\(\) after compression finished"
         ):
             list(chunker.finish())
+
+
+
+def chunker_frame(chunker, data):
+    return b"".join(list(chunker.compress(data)) + list(chunker.finish()))
+
+
+@unittest.skipUnless(
+    "chunker_reset" in zstd.backend_features, "chunker reset not available"
+)
+class TestCompressor_chunker_reset(unittest.TestCase):
+    def test_reuse(self):
+        cctx = zstd.ZstdCompressor()
+        chunker = cctx.chunker(chunk_size=32)
+        dctx = zstd.ZstdDecompressor()
+
+        for i in range(3):
+            message = b"message %d " % i * 100
+            chunks = list(chunker.compress(message))
+            chunks.extend(chunker.finish())
+
+            self.assertTrue(all(len(c) == 32 for c in chunks[:-1]))
+            frame = b"".join(chunks)
+            self.assertEqual(dctx.decompressobj().decompress(frame), message)
+
+            chunker.reset()
+
+    def test_compress_after_finish_requires_reset(self):
+        cctx = zstd.ZstdCompressor()
+        dctx = zstd.ZstdDecompressor()
+        chunker = cctx.chunker()
+
+        list(chunker.compress(b"foo"))
+        it = chunker.finish()
+
+        # The frame is finished even before its output is consumed.
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            r"cannot call compress\(\) after compression finished; call "
+            r"reset\(\) to start a new frame",
+        ):
+            chunker.compress(b"bar")
+
+        frame = b"".join(it)
+        self.assertEqual(dctx.decompressobj().decompress(frame), b"foo")
+
+        chunker.reset()
+        frame = chunker_frame(chunker, b"bar")
+        self.assertEqual(dctx.decompressobj().decompress(frame), b"bar")
+
+    def test_reset_size(self):
+        cctx = zstd.ZstdCompressor()
+        chunker = cctx.chunker(size=3)
+
+        frame = chunker_frame(chunker, b"foo")
+        self.assertEqual(zstd.get_frame_parameters(frame).content_size, 3)
+
+        chunker.reset(size=6)
+        frame = chunker_frame(chunker, b"foobar")
+        self.assertEqual(zstd.get_frame_parameters(frame).content_size, 6)
+
+        chunker.reset()
+        frame = chunker_frame(chunker, b"foo")
+        self.assertEqual(
+            zstd.get_frame_parameters(frame).content_size,
+            zstd.CONTENTSIZE_UNKNOWN,
+        )
+
+    def test_reset_mid_frame(self):
+        cctx = zstd.ZstdCompressor()
+        chunker = cctx.chunker(chunk_size=16)
+
+        list(chunker.compress(b"abandoned" * 100))
+        list(chunker.flush())
+        it = chunker.compress(b"more" * 100)
+
+        chunker.reset()
+
+        # Iterators from before the reset produce nothing more.
+        self.assertEqual(list(it), [])
+
+        frame = chunker_frame(chunker, b"fresh")
+        self.assertEqual(
+            zstd.ZstdDecompressor().decompressobj().decompress(frame), b"fresh"
+        )
+
+    def test_reset_before_use(self):
+        cctx = zstd.ZstdCompressor()
+        chunker = cctx.chunker()
+        chunker.reset()
+
+        frame = chunker_frame(chunker, b"foo")
+        self.assertEqual(
+            zstd.ZstdDecompressor().decompressobj().decompress(frame), b"foo"
+        )
@@ -1,3 +1,4 @@
+import os
 import struct
 import unittest
 
@@ -136,6 +137,34 @@ class TestCompressor_compress(unittest.TestCase):
         self.assertEqual(no_params.dict_id, 0)
         self.assertEqual(with_params.dict_id, 1123828263)
 
+    def test_no_dict_id_decompress(self):
+        samples = []
+        for i in range(128):
+            samples.append(b"foo%d" % i * 64)
+            samples.append(b"bar%d" % i * 64)
+            samples.append(b"foobar%d" % i * 64)
+
+        d = zstd.train_dictionary(1024, samples)
+        source = b"foo42" * 64 + b"foobar7" * 64
+
+        cctx = zstd.ZstdCompressor(dict_data=d, write_dict_id=False)
+        frame = cctx.compress(source)
+        self.assertEqual(zstd.get_frame_parameters(frame).dict_id, 0)
+
+        # The frame doesn't identify its dictionary, so it must be supplied.
+        dctx = zstd.ZstdDecompressor(dict_data=d)
+        self.assertEqual(dctx.decompress(frame), source)
+        self.assertEqual(dctx.decompressobj().decompress(frame), source)
+
+        with self.assertRaises(zstd.ZstdError):
+            zstd.ZstdDecompressor().decompress(frame)
+
+        other = zstd.train_dictionary(
+            1024, [b"unrelated %d data" % i * 16 for i in range(384)]
+        )
+        with self.assertRaises(zstd.ZstdError):
+            zstd.ZstdDecompressor(dict_data=other).decompress(frame)
+
     def test_compress_dict_multiple(self):
         samples = []
         for i in range(128):
@@ -165,6 +194,41 @@ class TestCompressor_compress(unittest.TestCase):
         for i in range(32):
             cctx.compress(b"foo bar foobar foo bar foobar")
 
+    @unittest.skipUnless(
+        "precomputed_dictionary_level" in zstd.backend_features,
+        "precomputed dictionary level not available",
+    )
+    def test_dict_precompute_level_mismatch(self):
+        samples = [b"foo%d bar %d" % (i, i * 7) * 32 for i in range(256)]
+        d = zstd.train_dictionary(8192, samples)
+        source = b"".join(samples[:40])
+
+        expected = zstd.ZstdCompressor(level=1, dict_data=d).compress(source)
+
+        d.precompute_compress(level=1)
+        cctx = zstd.ZstdCompressor(level=1, dict_data=d)
+        self.assertEqual(cctx.compress(source), expected)
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "dictionary was precomputed for compression level 1; cannot "
+            "compress at level 19",
+        ):
+            zstd.ZstdCompressor(level=19, dict_data=d)
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "precomputed for compression level 1"
+        ):
+            zstd.compress(source, level=19, dict=d)
+
+        self.assertEqual(zstd.compress(source, level=1, dict=d), expected)
+
+        # Precomputing from parameters defers to those parameters.
+        d.precompute_compress(
+            compression_params=zstd.ZstdCompressionParameters.from_level(1)
+        )
+        zstd.ZstdCompressor(level=19, dict_data=d)
+
     def test_multithreaded(self):
         chunk_size = multithreaded_chunk_size(1)
         source = b"".join([b"x" * chunk_size, b"y" * chunk_size])
@@ -283,3 +347,101 @@ class TestCompressor_compress(unittest.TestCase):
                 write_dict_id=True,
                 threads=2,
             )
+
+
+@unittest.skipUnless(
+    "allow_store" in zstd.backend_features, "allow store not available"
+)
+class TestCompressor_compress_allow_store(unittest.TestCase):
+    def dict(self):
+        samples = [b"foo%dbar" % i * 64 for i in range(128)]
+        return zstd.train_dictionary(8192, samples)
+
+    def test_random(self):
+        source = os.urandom(1 << 16)
+        frame = zstd.ZstdCompressor().compress(source, allow_store=True)
+
+        # Frame header plus a single block header.
+        self.assertLessEqual(len(frame), len(source) + 18 + 3)
+        self.assertEqual(zstd.ZstdDecompressor().decompress(frame), source)
+
+    def test_dictionary(self):
+        d = self.dict()
+        source = os.urandom(1 << 16)
+        cctx = zstd.ZstdCompressor(dict_data=d)
+
+        frame = cctx.compress(source)
+        stored = cctx.compress(source, allow_store=True)
+        self.assertLess(len(stored), len(frame))
+
+        # The stored frame doesn't reference the dictionary.
+        params = zstd.get_frame_parameters(stored)
+        self.assertEqual(params.dict_id, 0)
+        self.assertEqual(params.content_size, len(source))
+        self.assertEqual(zstd.ZstdDecompressor().decompress(stored), source)
+        self.assertEqual(
+            zstd.ZstdDecompressor(dict_data=d).decompress(stored), source
+        )
+
+    def test_compressible(self):
+        source = b"foobar" * 8192
+
+        for cctx in (
+            zstd.ZstdCompressor(),
+            zstd.ZstdCompressor(dict_data=self.dict()),
+        ):
+            self.assertEqual(
+                cctx.compress(source, allow_store=True), cctx.compress(source)
+            )
+
+    def test_frame_parameters(self):
+        d = self.dict()
+
+        for size in (0, 1, 255, 256, 65791, 65792, 300000):
+            source = os.urandom(size)
+
+            for kwargs in (
+                {"write_checksum": True},
+                {"write_content_size": False},
+                {"write_checksum": True, "write_content_size": False},
+            ):
+                cctx = zstd.ZstdCompressor(dict_data=d, **kwargs)
+                frame = cctx.compress(source, allow_store=True)
+                params = zstd.get_frame_parameters(frame)
+
+                self.assertEqual(params.dict_id, 0)
+                self.assertEqual(
+                    params.has_checksum, kwargs.get("write_checksum", False)
+                )
+                if kwargs.get("write_content_size", True):
+                    self.assertEqual(params.content_size, size)
+                else:
+                    self.assertEqual(
+                        params.content_size, zstd.CONTENTSIZE_UNKNOWN
+                    )
+
+                dobj = zstd.ZstdDecompressor().decompressobj()
+                self.assertEqual(dobj.decompress(frame), source)
+                self.assertTrue(dobj.eof)
+
+    def test_checksum_verified(self):
+        source = os.urandom(1024)
+        cctx = zstd.ZstdCompressor(dict_data=self.dict(), write_checksum=True)
+        frame = cctx.compress(source, allow_store=True)
+
+        corrupt = frame[:-1] + bytes([frame[-1] ^ 0xFF])
+        with self.assertRaisesRegex(zstd.ZstdError, "checksum"):
+            zstd.ZstdDecompressor().decompress(corrupt)
+
+    def test_magicless(self):
+        source = os.urandom(4096)
+        params = zstd.ZstdCompressionParameters(
+            format=zstd.FORMAT_ZSTD1_MAGICLESS
+        )
+        cctx = zstd.ZstdCompressor(
+            dict_data=self.dict(), compression_params=params
+        )
+        frame = cctx.compress(source, allow_store=True)
+
+        dctx = zstd.ZstdDecompressor(format=zstd.FORMAT_ZSTD1_MAGICLESS)
+        self.assertEqual(dctx.decompressobj().decompress(frame), source)
@@ -0,0 +1,78 @@
+import unittest
+
+import zstandard as zstd
+
+
+@unittest.skipUnless(
+    "compress_into" in zstd.backend_features, "compress into not available"
+)
+class TestCompressor_compress_into(unittest.TestCase):
+    def test_compress_into(self):
+        source = b"foobar" * 1024
+        cctx = zstd.ZstdCompressor()
+
+        output = bytearray(cctx.compress_bound(len(source)))
+        written = cctx.compress_into(source, output)
+
+        self.assertEqual(bytes(output[:written]), cctx.compress(source))
+        self.assertEqual(
+            zstd.ZstdDecompressor().decompress(output[:written]), source
+        )
+
+    def test_empty(self):
+        cctx = zstd.ZstdCompressor()
+
+        output = bytearray(cctx.compress_bound(0))
+        written = cctx.compress_into(b"", output)
+
+        self.assertEqual(bytes(output[:written]), cctx.compress(b""))
+        params = zstd.get_frame_parameters(output[:written])
+        self.assertEqual(params.content_size, 0)
+        self.assertEqual(
+            zstd.ZstdDecompressor().decompress(output[:written]), b""
+        )
+
+    def test_output_too_small(self):
+        source = b"foobar" * 1024
+        cctx = zstd.ZstdCompressor()
+        frame = cctx.compress(source)
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "output buffer of 10 bytes is too small"
+        ):
+            cctx.compress_into(source, bytearray(10))
+
+        # The compressor is still usable afterwards.
+        output = bytearray(len(frame))
+        self.assertEqual(cctx.compress_into(source, output), len(frame))
+        self.assertEqual(bytes(output), frame)
+
+    def test_memoryview_output(self):
+        source = b"foobar" * 1024
+        cctx = zstd.ZstdCompressor()
+        frame = cctx.compress(source)
+
+        output = bytearray(len(frame) + 16)
+        written = cctx.compress_into(source, memoryview(output)[16:])
+        self.assertEqual(written, len(frame))
+        self.assertEqual(bytes(output[16:]), frame)
+
+    def test_readonly_output(self):
+        cctx = zstd.ZstdCompressor()
+
+        with self.assertRaisesRegex(
+            ValueError, "output buffer is not writable"
+        ):
+            cctx.compress_into(b"foobar", b"\x00" * 64)
+
+    def test_compress_bound(self):
+        cctx = zstd.ZstdCompressor()
+
+        self.assertGreater(cctx.compress_bound(0), 0)
+        self.assertGreaterEqual(cctx.compress_bound(1024), 1024)
+
+        with self.assertRaises(OverflowError):
+            cctx.compress_bound(-1)
+
+        with self.assertRaises(OverflowError):
+            cctx.compress_bound(2**64 - 1)
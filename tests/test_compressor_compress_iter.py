@@ -0,0 +1,115 @@
+import unittest
+
+import zstandard as zstd
+
+
+def failing_chunks(chunks):
+    yield from chunks
+    raise RuntimeError("source failed")
+
+
+@unittest.skipUnless(
+    "compress_iter" in zstd.backend_features, "compress iter not available"
+)
+class TestCompressor_compress_iter(unittest.TestCase):
+    def test_round_trip(self):
+        chunks = [b"foo" * 1024, bytearray(b"bar" * 1024), memoryview(b"baz")]
+        source = b"".join(chunks)
+
+        cctx = zstd.ZstdCompressor()
+        frame = b"".join(cctx.compress_iter(chunks))
+
+        params = zstd.get_frame_parameters(frame)
+        self.assertEqual(params.content_size, zstd.CONTENTSIZE_UNKNOWN)
+        self.assertEqual(zstd.frame_header_size(frame), 6)
+        self.assertEqual(
+            zstd.ZstdDecompressor().decompress(
+                frame, max_output_size=len(source)
+            ),
+            source,
+        )
+
+    def test_single_frame(self):
+        chunks = [b"chunk %d" % i for i in range(1000)]
+        frame = b"".join(zstd.ZstdCompressor().compress_iter(iter(chunks)))
+
+        dctx = zstd.ZstdDecompressor()
+        with dctx.stream_reader(frame) as reader:
+            self.assertEqual(reader.read(), b"".join(chunks))
+            self.assertEqual(reader.tell(), len(b"".join(chunks)))
+
+        self.assertEqual(b"".join(dctx.read_to_iter(frame)), b"".join(chunks))
+
+    def test_empty(self):
+        cctx = zstd.ZstdCompressor(write_content_size=False)
+        expected = b"".join(cctx.read_to_iter(b""))
+
+        for chunks in ([], [b""], [b"", b"", bytearray()]):
+            frame = b"".join(cctx.compress_iter(chunks))
+            self.assertEqual(frame, expected)
+            self.assertEqual(
+                zstd.ZstdDecompressor().decompress(frame, max_output_size=1),
+                b"",
+            )
+
+    def test_empty_chunks_skipped(self):
+        cctx = zstd.ZstdCompressor()
+        chunks = [b"", b"foo", b"", b"", b"bar", b""]
+
+        self.assertEqual(
+            b"".join(cctx.compress_iter(chunks)),
+            b"".join(cctx.compress_iter([b"foobar"])),
+        )
+
+    def test_size(self):
+        cctx = zstd.ZstdCompressor()
+        frame = b"".join(cctx.compress_iter([b"foo", b"bar"], size=6))
+
+        self.assertEqual(zstd.get_frame_parameters(frame).content_size, 6)
+        self.assertEqual(zstd.ZstdDecompressor().decompress(frame), b"foobar")
+
+        with self.assertRaisesRegex(zstd.ZstdError, "Src size is incorrect"):
+            b"".join(cctx.compress_iter([b"foo", b"bar"], size=5))
+
+    def test_write_size(self):
+        source = bytes(range(256)) * 1024
+        cctx = zstd.ZstdCompressor(level=1)
+        chunks = list(cctx.compress_iter([source], write_size=1024))
+
+        self.assertGreater(len(chunks), 1)
+        for chunk in chunks:
+            self.assertLessEqual(len(chunk), 1024)
+
+        self.assertEqual(
+            zstd.ZstdDecompressor().decompress(
+                b"".join(chunks), max_output_size=len(source)
+            ),
+            source,
+        )
+
+    def test_bad_chunk(self):
+        cctx = zstd.ZstdCompressor()
+
+        with self.assertRaises(TypeError):
+            iter(cctx.compress_iter(None))
+
+        it = cctx.compress_iter([b"foo", "bar"])
+        with self.assertRaises(TypeError):
+            list(it)
+
+    def test_source_error(self):
+        cctx = zstd.ZstdCompressor()
+        it = cctx.compress_iter(failing_chunks([b"foo" * 65536] * 4))
+
+        with self.assertRaisesRegex(RuntimeError, "source failed"):
+            list(it)
+
+        # Iteration ends after the error instead of emitting a partial frame.
+        self.assertEqual(list(it), [])
+
+        # The compressor is left usable, without data from the partial frame.
+        self.assertEqual(
+            b"".join(cctx.compress_iter([b"foobar"])),
+            b"".join(zstd.ZstdCompressor().compress_iter([b"foobar"])),
+        )
+        self.assertEqual(cctx.frame_progression(), (6, 6, 15))
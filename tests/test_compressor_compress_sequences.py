@@ -0,0 +1,103 @@
+import unittest
+
+import zstandard as zstd
+
+
+@unittest.skipUnless(
+    "compress_sequences" in zstd.backend_features,
+    "compress sequences not available",
+)
+class TestCompressor_compress_sequences(unittest.TestCase):
+    def test_round_trip(self):
+        source = b"abcd" * 100
+
+        cctx = zstd.ZstdCompressor()
+        frame = cctx.compress_sequences([(4, 396, 4, 0)], source)
+
+        self.assertEqual(zstd.get_frame_parameters(frame).content_size, 400)
+        self.assertEqual(zstd.ZstdDecompressor().decompress(frame), source)
+
+    def test_block_delimiters(self):
+        source = b"abcd" * 100 + b"trailer"
+
+        cctx = zstd.ZstdCompressor()
+        frame = cctx.compress_sequences(
+            [(0, 0, 4, 0), (4, 396, 0, 0), (0, 0, 7, 0)], source
+        )
+
+        self.assertEqual(zstd.ZstdDecompressor().decompress(frame), source)
+
+    def test_empty(self):
+        cctx = zstd.ZstdCompressor()
+        frame = cctx.compress_sequences([], b"")
+
+        self.assertEqual(zstd.ZstdDecompressor().decompress(frame), b"")
+
+    def test_length_mismatch(self):
+        cctx = zstd.ZstdCompressor()
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "sequences cover 399 bytes but src is 400 bytes"
+        ):
+            cctx.compress_sequences([(4, 396, 3, 0)], b"abcd" * 100)
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "sequences cover 0 bytes but src is 3 bytes"
+        ):
+            cctx.compress_sequences([], b"foo")
+
+    def test_invalid_sequences(self):
+        cctx = zstd.ZstdCompressor()
+        source = b"abcd" * 100
+
+        # Offset before the start of the input.
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "cannot compress sequences"
+        ) as cm:
+            cctx.compress_sequences([(1000, 396, 4, 0)], source)
+
+        self.assertIsNotNone(cm.exception.error_name)
+
+        # Match shorter than the minimum match length.
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "cannot compress sequences"
+        ):
+            cctx.compress_sequences([(4, 2, 4, 0), (0, 0, 394, 0)], source)
+
+        # The compressor remains usable.
+        frame = cctx.compress(source)
+        self.assertEqual(zstd.ZstdDecompressor().decompress(frame), source)
+
+    def test_invalid_tuples(self):
+        cctx = zstd.ZstdCompressor()
+
+        with self.assertRaises(ValueError):
+            cctx.compress_sequences([(4, 396, 4)], b"abcd" * 100)
+
+        with self.assertRaises(TypeError):
+            cctx.compress_sequences([b"foo"], b"abcd" * 100)
+
+        with self.assertRaises(OverflowError):
+            cctx.compress_sequences([(-1, 396, 4, 0)], b"abcd" * 100)
+
+    def test_dictionary(self):
+        samples = [b"foo%dbar" % i * 64 for i in range(128)]
+        d = zstd.train_dictionary(8192, samples)
+
+        cctx = zstd.ZstdCompressor(dict_data=d)
+        frame = cctx.compress_sequences([(0, 0, 6, 0)], b"foobar")
+
+        params = zstd.get_frame_parameters(frame)
+        self.assertEqual(params.dict_id, d.dict_id())
+
+        dctx = zstd.ZstdDecompressor(dict_data=d)
+        self.assertEqual(dctx.decompress(frame), b"foobar")
+
+    def test_buffer_types(self):
+        source = b"abcd" * 100
+        cctx = zstd.ZstdCompressor()
+        dctx = zstd.ZstdDecompressor()
+
+        for src in (source, bytearray(source), memoryview(source)):
+            frame = cctx.compress_sequences(iter([(4, 396, 4, 0)]), src)
+            self.assertEqual(dctx.decompress(frame), source)
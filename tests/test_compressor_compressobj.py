@@ -171,6 +171,30 @@ class TestCompressor_compressobj(unittest.TestCase):
         cobj.flush()
         self.assertEqual(cctx.frame_progression(), (6, 6, 15))
 
+    @unittest.skipUnless(
+        "frame_progression" in zstd.backend_features,
+        "frame progression not available",
+    )
+    def test_session_frame_progression(self):
+        cctx = zstd.ZstdCompressor()
+        cobj = cctx.compressobj()
+
+        progression = cobj.frame_progression()
+        self.assertEqual(progression.ingested, 0)
+        self.assertEqual(progression.consumed, 0)
+        self.assertEqual(progression.produced, 0)
+        self.assertEqual(progression.current_job_id, 0)
+
+        cobj.compress(b"foobar")
+        progression = cobj.frame_progression()
+        self.assertEqual(progression.ingested, 6)
+        self.assertEqual(progression.consumed, 0)
+
+        cobj.flush()
+
+        with self.assertRaisesRegex(zstd.ZstdError, "already finished"):
+            cobj.frame_progression()
+
     def test_bad_size(self):
         cctx = zstd.ZstdCompressor()
 
@@ -185,3 +209,72 @@ class TestCompressor_compressobj(unittest.TestCase):
         # Try another operation on the compressor.
         cctx.compressobj(size=4)
         cctx.compress(b"foobar")
+
+    def test_pledged_size_content_size(self):
+        cctx = zstd.ZstdCompressor()
+        cobj = cctx.compressobj(size=6)
+        frame = cobj.compress(b"foo") + cobj.compress(b"bar") + cobj.flush()
+
+        params = zstd.get_frame_parameters(frame)
+        self.assertEqual(params.content_size, 6)
+        self.assertEqual(zstd.ZstdDecompressor().decompress(frame), b"foobar")
+
+    @unittest.skipUnless(
+        "pledged_size_errors" in zstd.backend_features,
+        "pledged size errors not available",
+    )
+    def test_pledged_size_too_few_bytes(self):
+        cctx = zstd.ZstdCompressor()
+        cobj = cctx.compressobj(size=10)
+        chunks = [cobj.compress(b"foo"), cobj.compress(b"bar")]
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "source size mismatch; expected 10 bytes but received 6",
+        ):
+            cobj.flush()
+
+        # Block flushes don't end the frame and aren't checked.
+        chunks.append(cobj.flush(zstd.COMPRESSOBJ_FLUSH_BLOCK))
+
+        # Supplying the remaining input allows the frame to end.
+        chunks.append(cobj.compress(b"bazz"))
+        chunks.append(cobj.flush())
+        frame = b"".join(chunks)
+        self.assertEqual(
+            zstd.ZstdDecompressor().decompress(frame), b"foobarbazz"
+        )
+
+    @unittest.skipUnless(
+        "pledged_size_errors" in zstd.backend_features,
+        "pledged size errors not available",
+    )
+    def test_pledged_size_too_many_bytes(self):
+        cctx = zstd.ZstdCompressor()
+        cobj = cctx.compressobj(size=4)
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "Src size is incorrect; expected 4 bytes but received 6",
+        ):
+            cobj.compress(b"foobar")
+
+    def test_flush_block_incremental_decode(self):
+        cctx = zstd.ZstdCompressor(level=1)
+        cobj = cctx.compressobj()
+
+        dctx = zstd.ZstdDecompressor()
+        dobj = dctx.decompressobj()
+
+        # Each block flush makes everything compressed so far decodable while
+        # leaving the frame open for more input.
+        for i in range(5):
+            data = b"message %d" % i
+            chunk = cobj.compress(data) + cobj.flush(
+                zstd.COMPRESSOBJ_FLUSH_BLOCK
+            )
+            self.assertEqual(dobj.decompress(chunk), data)
+            self.assertFalse(dobj.eof)
+
+        self.assertEqual(dobj.decompress(cobj.flush()), b"")
+        self.assertTrue(dobj.eof)
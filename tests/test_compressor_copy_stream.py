@@ -125,6 +125,25 @@ class TestCompressor_copy_stream(unittest.TestCase):
         self.assertEqual(source._read_count, len(source.getvalue()) + 1)
         self.assertEqual(dest._write_count, len(dest.getvalue()))
 
+    def test_counts_match_io(self):
+        source = CustomBytesIO(b"foobar" * 65536 + b"x" * 100)
+        dest = CustomBytesIO()
+        cctx = zstd.ZstdCompressor(write_checksum=True)
+
+        r, w = cctx.copy_stream(source, dest, read_size=8192, write_size=8)
+
+        # The written count covers the output of the final flush, which ends
+        # the frame with its checksum.
+        self.assertEqual(r, len(source.getvalue()))
+        self.assertEqual(w, len(dest.getvalue()))
+        self.assertGreater(dest._write_count, 1)
+        self.assertEqual(
+            zstd.ZstdDecompressor().decompress(
+                dest.getvalue(), max_output_size=r
+            ),
+            source.getvalue(),
+        )
+
     def test_multithreaded(self):
         source = io.BytesIO()
         source.write(b"a" * 1048576)
@@ -193,3 +212,59 @@ class TestCompressor_copy_stream(unittest.TestCase):
 
         with self.assertRaisesRegex(IOError, "write"):
             cctx.copy_stream(source, dest)
+
+    @unittest.skipUnless(
+        "copy_stream_progress" in zstd.backend_features,
+        "copy stream progress not available",
+    )
+    def test_progress(self):
+        source = io.BytesIO(b"foo" * 4096)
+        dest = io.BytesIO()
+        calls = []
+
+        cctx = zstd.ZstdCompressor()
+        r, w = cctx.copy_stream(
+            source,
+            dest,
+            read_size=4096,
+            progress=lambda read, written: calls.append((read, written)),
+        )
+
+        # One call per chunk read plus a final call at EOF.
+        self.assertEqual(len(calls), 4)
+        self.assertEqual([c[0] for c in calls], [4096, 8192, 12288, 12288])
+        self.assertEqual(calls[-1], (r, w))
+        self.assertEqual(w, len(dest.getvalue()))
+
+    @unittest.skipUnless(
+        "copy_stream_progress" in zstd.backend_features,
+        "copy stream progress not available",
+    )
+    def test_progress_exception(self):
+        source = io.BytesIO(b"foo" * 4096)
+        dest = io.BytesIO()
+
+        def progress(read, written):
+            raise ValueError("progress")
+
+        cctx = zstd.ZstdCompressor()
+        with self.assertRaisesRegex(ValueError, "progress"):
+            cctx.copy_stream(source, dest, read_size=4096, progress=progress)
+
+        # The in-flight frame was ended and is decodable.
+        dctx = zstd.ZstdDecompressor()
+        self.assertEqual(
+            dctx.decompressobj().decompress(dest.getvalue()),
+            b"foo" * 1365 + b"f",
+        )
+
+        source = io.BytesIO(b"foo" * 4096)
+        dest = io.BytesIO()
+        with self.assertRaisesRegex(ValueError, "progress"):
+            cctx.copy_stream(
+                source,
+                dest,
+                size=len(source.getvalue()),
+                read_size=4096,
+                progress=progress,
+            )
@@ -0,0 +1,93 @@
+import unittest
+
+import zstandard as zstd
+
+
+@unittest.skipUnless(
+    "generate_sequences" in zstd.backend_features,
+    "generate sequences not available",
+)
+class TestCompressor_generate_sequences(unittest.TestCase):
+    def test_sequences(self):
+        cctx = zstd.ZstdCompressor()
+        sequences = cctx.generate_sequences(b"foobar" * 10)
+
+        self.assertIsInstance(sequences, list)
+        self.assertEqual(
+            sequences,
+            [
+                (6, 54, 6, 0),
+                (0, 0, 0, 0),
+            ],
+        )
+
+        first = sequences[0]
+        self.assertIsInstance(first, zstd.Sequence)
+        self.assertEqual(first.offset, 6)
+        self.assertEqual(first.match_length, 54)
+        self.assertEqual(first.lit_length, 6)
+        self.assertEqual(first.rep, 0)
+
+    def test_empty(self):
+        cctx = zstd.ZstdCompressor()
+        self.assertEqual(cctx.generate_sequences(b""), [])
+
+    def test_covers_input(self):
+        source = (b"hello world, " * 50 + bytes(range(256))) * 1000
+        cctx = zstd.ZstdCompressor()
+        sequences = cctx.generate_sequences(source)
+
+        self.assertEqual(
+            sum(s.lit_length + s.match_length for s in sequences), len(source)
+        )
+
+        # Each block of at most 128 KiB ends with a delimiter.
+        delimiters = [s for s in sequences if s.offset == 0]
+        self.assertGreaterEqual(len(delimiters), len(source) // 131072)
+        self.assertTrue(all(s.match_length == 0 for s in delimiters))
+
+    def test_compress_sequences(self):
+        source = (b"hello world, " * 50 + bytes(range(256))) * 1000
+        cctx = zstd.ZstdCompressor()
+
+        frame = cctx.compress_sequences(
+            cctx.generate_sequences(source), source
+        )
+        self.assertEqual(zstd.ZstdDecompressor().decompress(frame), source)
+
+    def test_compressor_reusable(self):
+        cctx = zstd.ZstdCompressor()
+        expected = cctx.compress(b"foobar" * 1024)
+
+        cctx.generate_sequences(b"foobar" * 1024)
+        self.assertEqual(cctx.compress(b"foobar" * 1024), expected)
+
+    def test_threads(self):
+        cctx = zstd.ZstdCompressor(threads=2)
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "cannot generate sequences"
+        ) as cm:
+            cctx.generate_sequences(b"foobar" * 1024)
+
+        self.assertIsNotNone(cm.exception.error_name)
+
+    def test_dictionary(self):
+        samples = [b"foo%dbar" % i * 64 for i in range(128)]
+        d = zstd.train_dictionary(8192, samples)
+        source = b"foo42bar" * 4
+
+        without_dict = zstd.ZstdCompressor().generate_sequences(source)
+        with_dict = zstd.ZstdCompressor(dict_data=d).generate_sequences(source)
+
+        # The dictionary supplies the first literals.
+        self.assertEqual(without_dict[0].lit_length, 8)
+        self.assertEqual(with_dict[0].lit_length, 0)
+
+    def test_buffer_types(self):
+        source = b"foobar" * 10
+        cctx = zstd.ZstdCompressor()
+        expected = cctx.generate_sequences(source)
+
+        for data in (bytearray(source), memoryview(source)):
+            self.assertEqual(cctx.generate_sequences(data), expected)
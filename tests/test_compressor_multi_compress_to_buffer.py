@@ -1,3 +1,4 @@
+import random
 import struct
 import unittest
 
@@ -129,3 +130,126 @@ class TestCompressor_multi_compress_to_buffer(unittest.TestCase):
                 self.assertEqual(result[i].tobytes(), reference[0])
             else:
                 self.assertEqual(result[i].tobytes(), reference[1])
+
+    @unittest.skipUnless(
+        "multi_compress_dicts" in zstd.backend_features,
+        "multi compress dicts not available",
+    )
+    def test_per_item_dicts(self):
+        samples = []
+        for i in range(128):
+            samples.append(b"foo" * 64)
+            samples.append(b"bar" * 64)
+            samples.append(b"foobar" * 64)
+
+        d1 = zstd.train_dictionary(8192, samples)
+        d2 = zstd.ZstdCompressionDict(
+            b"baz" * 256, dict_type=zstd.DICT_TYPE_RAWCONTENT
+        )
+
+        original = [b"foobar" * 32, b"baz" * 64, b"qux" * 16] * 16
+        dicts = [d1, d2, None] * 16
+
+        cctx = zstd.ZstdCompressor(level=1)
+        result = cctx.multi_compress_to_buffer(original, threads=4, dicts=dicts)
+
+        self.assertEqual(len(result), len(original))
+
+        for i, (data, d) in enumerate(zip(original, dicts)):
+            frame = result[i].tobytes()
+            expected_id = d.dict_id() if d is not None else 0
+            self.assertEqual(
+                zstd.get_frame_parameters(frame).dict_id, expected_id
+            )
+
+            dctx = zstd.ZstdDecompressor(dict_data=d)
+            self.assertEqual(dctx.decompress(frame), data)
+
+    @unittest.skipUnless(
+        "multi_compress_dicts" in zstd.backend_features,
+        "multi compress dicts not available",
+    )
+    def test_per_item_dicts_default(self):
+        samples = [b"foo" * 64, b"bar" * 64, b"foobar" * 64] * 128
+        d = zstd.train_dictionary(8192, samples)
+
+        # None entries use the compressor's dictionary.
+        cctx = zstd.ZstdCompressor(level=1, dict_data=d)
+        result = cctx.multi_compress_to_buffer(
+            [b"foo" * 32, b"bar" * 32], dicts=[None, None]
+        )
+        for i in range(2):
+            self.assertEqual(
+                zstd.get_frame_parameters(result[i].tobytes()).dict_id,
+                d.dict_id(),
+            )
+
+    @unittest.skipUnless(
+        "multi_compress_dicts" in zstd.backend_features,
+        "multi compress dicts not available",
+    )
+    def test_per_item_dicts_invalid(self):
+        cctx = zstd.ZstdCompressor()
+
+        with self.assertRaisesRegex(TypeError, "dicts must be a list"):
+            cctx.multi_compress_to_buffer([b"foo"], dicts=(None,))
+
+        with self.assertRaisesRegex(
+            ValueError, r"dicts must have the same length as data \(1 != 2\)"
+        ):
+            cctx.multi_compress_to_buffer([b"foo", b"bar"], dicts=[None])
+
+        with self.assertRaisesRegex(
+            TypeError, "dicts item 1 is not a ZstdCompressionDict or None"
+        ):
+            cctx.multi_compress_to_buffer([b"foo", b"bar"], dicts=[None, b"x"])
+
+    @unittest.skipUnless(
+        "multi_compress_dicts" in zstd.backend_features,
+        "multi compress dicts not available",
+    )
+    def test_worker_error(self):
+        bad = zstd.ZstdCompressionDict(
+            b"\x37\xa4\x30\xec" + b"junk" * 32,
+            dict_type=zstd.DICT_TYPE_FULLDICT,
+        )
+
+        cctx = zstd.ZstdCompressor()
+        original = [b"foo" * 16] * 8
+        dicts = [None] * 8
+        dicts[5] = bad
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "error compressing item 5: "
+        ):
+            cctx.multi_compress_to_buffer(original, threads=4, dicts=dicts)
+
+    def test_deterministic_thread_count(self):
+        rng = random.Random(42)
+        original = []
+        for i in range(64):
+            chunk = rng.randbytes(rng.randint(1, 64))
+            original.append(chunk * rng.randint(1, 4096))
+        original.append(b"")
+
+        samples = [b"foo%dbar" % i * 64 for i in range(128)]
+        d = zstd.train_dictionary(8192, samples)
+
+        for kwargs in (
+            {"level": 1},
+            {"level": 19, "write_checksum": True},
+            {"level": 3, "dict_data": d},
+            {"level": 3, "threads": 2},
+        ):
+            cctx = zstd.ZstdCompressor(**kwargs)
+            expected = [cctx.compress(chunk) for chunk in original]
+
+            for threads in (1, 2, 8, -1):
+                result = cctx.multi_compress_to_buffer(
+                    original, threads=threads
+                )
+
+                self.assertEqual(
+                    [result[i].tobytes() for i in range(len(result))],
+                    expected,
+                )
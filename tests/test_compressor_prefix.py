@@ -0,0 +1,83 @@
+import unittest
+
+import zstandard as zstd
+
+
+@unittest.skipUnless("prefix" in zstd.backend_features, "prefix not available")
+class TestCompressor_prefix(unittest.TestCase):
+    def test_round_trip(self):
+        prefix = b"foobar" * 1024
+        source = b"foobar" * 1024 + b"baz"
+
+        cctx = zstd.ZstdCompressor()
+        frame = cctx.compress(source, prefix=prefix)
+
+        dctx = zstd.ZstdDecompressor()
+        self.assertEqual(dctx.decompress(frame, prefix=prefix), source)
+
+    def test_delta(self):
+        v1 = b"".join(b"line %d of version 1\n" % i for i in range(2048))
+        v2 = v1.replace(b"line 1000 ", b"line 1000 changed ")
+
+        cctx = zstd.ZstdCompressor(level=19)
+        standalone = cctx.compress(v2)
+        delta = cctx.compress(v2, prefix=v1)
+
+        self.assertLess(len(delta), len(standalone) // 10)
+
+        dctx = zstd.ZstdDecompressor()
+        self.assertEqual(dctx.decompress(delta, prefix=v1), v2)
+
+    def test_prefix_single_frame(self):
+        prefix = b"foobar" * 1024
+        source = b"foobar" * 1024
+
+        cctx = zstd.ZstdCompressor(write_checksum=True)
+        with_prefix = cctx.compress(source, prefix=prefix)
+        without_prefix = cctx.compress(source)
+
+        self.assertLess(len(with_prefix), len(without_prefix))
+        self.assertEqual(
+            without_prefix,
+            zstd.ZstdCompressor(write_checksum=True).compress(source),
+        )
+
+        dctx = zstd.ZstdDecompressor()
+        self.assertEqual(dctx.decompress(without_prefix), source)
+
+        with self.assertRaises(zstd.ZstdError):
+            dctx.decompress(with_prefix)
+
+        self.assertEqual(dctx.decompress(with_prefix, prefix=prefix), source)
+        self.assertEqual(dctx.decompress(without_prefix), source)
+
+    def test_preserves_dictionary(self):
+        samples = [b"foo%dbar" % i * 64 for i in range(128)]
+        d = zstd.train_dictionary(8192, samples)
+        source = b"foo42bar" * 64
+
+        cctx = zstd.ZstdCompressor(dict_data=d)
+        expected = cctx.compress(source)
+        cctx.compress(source, prefix=b"prefix data")
+
+        self.assertEqual(cctx.compress(source), expected)
+
+    def test_buffer_types(self):
+        prefix = b"foobar" * 256
+        source = b"foobar" * 256
+
+        cctx = zstd.ZstdCompressor()
+        dctx = zstd.ZstdDecompressor()
+
+        for p in (prefix, bytearray(prefix), memoryview(prefix)):
+            frame = cctx.compress(source, prefix=p)
+            self.assertEqual(dctx.decompress(frame, prefix=p), source)
+
+    def test_empty_prefix(self):
+        cctx = zstd.ZstdCompressor()
+        frame = cctx.compress(b"foobar", prefix=b"")
+
+        self.assertEqual(frame, cctx.compress(b"foobar"))
+        self.assertEqual(
+            zstd.ZstdDecompressor().decompress(frame, prefix=b""), b"foobar"
+        )
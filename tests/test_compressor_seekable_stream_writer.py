@@ -0,0 +1,140 @@
+import io
+import struct
+import unittest
+
+import zstandard as zstd
+
+from .common import NonClosingBytesIO
+
+
+def parse_seek_table(data):
+    footer = data[-9:]
+    frame_count, descriptor, magic = struct.unpack("<IBI", footer)
+    assert magic == 0x8F92EAB1
+
+    has_checksum = bool(descriptor & 0x80)
+    entry_size = 12 if has_checksum else 8
+    table_size = 8 + frame_count * entry_size + 9
+
+    skippable_magic, frame_size = struct.unpack_from("<II", data, len(data) - table_size)
+    assert skippable_magic == 0x184D2A5E
+    assert frame_size == table_size - 8
+
+    entries = []
+    offset = len(data) - table_size + 8
+    for _ in range(frame_count):
+        entries.append(
+            struct.unpack_from("<III" if has_checksum else "<II", data, offset)
+        )
+        offset += entry_size
+
+    return has_checksum, entries, len(data) - table_size
+
+
+@unittest.skipUnless(
+    "seekable_stream_writer" in zstd.backend_features,
+    "seekable stream writer not available",
+)
+class TestCompressor_seekable_stream_writer(unittest.TestCase):
+    def test_empty(self):
+        dest = NonClosingBytesIO()
+        cctx = zstd.ZstdCompressor()
+        with cctx.seekable_stream_writer(dest) as writer:
+            self.assertEqual(writer.frame_count(), 0)
+
+        self.assertEqual(
+            dest.getvalue(),
+            b"\x5e\x2a\x4d\x18\x09\x00\x00\x00"
+            b"\x00\x00\x00\x00\x00\xb1\xea\x92\x8f",
+        )
+
+    def test_max_frame_size(self):
+        dest = NonClosingBytesIO()
+        cctx = zstd.ZstdCompressor()
+        source = b"".join(b"foo%d" % i for i in range(4096))
+
+        with cctx.seekable_stream_writer(dest, max_frame_size=1000) as writer:
+            for i in range(0, len(source), 700):
+                self.assertEqual(writer.write(source[i : i + 700]), len(source[i : i + 700]))
+
+        frame_count = (len(source) + 999) // 1000
+        self.assertEqual(writer.frame_count(), frame_count)
+
+        data = dest.getvalue()
+        has_checksum, entries, table_offset = parse_seek_table(data)
+        self.assertFalse(has_checksum)
+        self.assertEqual(len(entries), frame_count)
+        self.assertEqual(sum(e[1] for e in entries), len(source))
+        self.assertTrue(all(e[1] == 1000 for e in entries[:-1]))
+        self.assertEqual(sum(e[0] for e in entries), table_offset)
+
+        dctx = zstd.ZstdDecompressor()
+        offset = 0
+        chunks = []
+        for compressed_size, decompressed_size in entries:
+            frame = data[offset : offset + compressed_size]
+            chunk = dctx.decompressobj().decompress(frame)
+            self.assertEqual(len(chunk), decompressed_size)
+            chunks.append(chunk)
+            offset += compressed_size
+
+        self.assertEqual(b"".join(chunks), source)
+
+    def test_checksum(self):
+        dest = NonClosingBytesIO()
+        cctx = zstd.ZstdCompressor(write_checksum=True)
+
+        with cctx.seekable_stream_writer(dest, max_frame_size=4) as writer:
+            writer.write(b"foobarbaz")
+
+        data = dest.getvalue()
+        has_checksum, entries, _ = parse_seek_table(data)
+        self.assertTrue(has_checksum)
+        self.assertEqual(len(entries), 3)
+
+        offset = 0
+        for compressed_size, _, checksum in entries:
+            offset += compressed_size
+            self.assertEqual(struct.pack("<I", checksum), data[offset - 4 : offset])
+
+    def test_flush_frame(self):
+        dest = NonClosingBytesIO()
+        cctx = zstd.ZstdCompressor()
+
+        with cctx.seekable_stream_writer(dest) as writer:
+            writer.write(b"foo")
+            writer.flush(zstd.FLUSH_FRAME)
+            self.assertEqual(writer.frame_count(), 1)
+            writer.flush(zstd.FLUSH_FRAME)
+            self.assertEqual(writer.frame_count(), 1)
+            writer.write(b"bar")
+            writer.flush(zstd.FLUSH_BLOCK)
+            self.assertEqual(writer.frame_count(), 1)
+
+        self.assertEqual(writer.frame_count(), 2)
+        _, entries, _ = parse_seek_table(dest.getvalue())
+        self.assertEqual([e[1] for e in entries], [3, 3])
+
+    def test_bad_max_frame_size(self):
+        cctx = zstd.ZstdCompressor()
+
+        with self.assertRaisesRegex(
+            ValueError, "max_frame_size must be between"
+        ):
+            cctx.seekable_stream_writer(io.BytesIO(), max_frame_size=0)
+
+        with self.assertRaisesRegex(
+            ValueError, "max_frame_size must be between"
+        ):
+            cctx.seekable_stream_writer(
+                io.BytesIO(), max_frame_size=zstd.SEEKABLE_MAX_FRAME_SIZE + 1
+            )
+
+    def test_write_after_close(self):
+        cctx = zstd.ZstdCompressor()
+        writer = cctx.seekable_stream_writer(io.BytesIO())
+        writer.close()
+        self.assertTrue(writer.closed)
+
+        with self.assertRaisesRegex(ValueError, "stream is closed"):
+            writer.write(b"foo")
@@ -223,6 +223,67 @@ class TestCompressor_stream_reader(unittest.TestCase):
         self.assertEqual(reader.readinto(b), 2)
         self.assertEqual(b[:], foo[4:6])
 
+    def test_read_bounded_sizes(self):
+        source = bytes(range(256)) * 64 + b"foo" * 65536
+        cctx = zstd.ZstdCompressor(write_checksum=True)
+
+        for size in (1, 7, 256, 8192):
+            reader = cctx.stream_reader(io.BytesIO(source))
+            chunks = []
+
+            while True:
+                chunk = reader.read(size)
+                self.assertLessEqual(len(chunk), size)
+                if not chunk:
+                    break
+                chunks.append(chunk)
+                self.assertEqual(reader.tell(), sum(map(len, chunks)))
+
+            frame = b"".join(chunks)
+            dobj = zstd.ZstdDecompressor().decompressobj()
+            self.assertEqual(dobj.decompress(frame), source)
+            self.assertTrue(dobj.eof)
+
+    def test_readinto_small_buffer_epilogue(self):
+        source = b"foo" * 4096
+        cctx = zstd.ZstdCompressor(write_checksum=True)
+        frame = b"".join(cctx.read_to_iter(io.BytesIO(source)))
+
+        reader = cctx.stream_reader(io.BytesIO(source))
+        b = bytearray(3)
+        chunks = []
+
+        while True:
+            count = reader.readinto(b)
+            if not count:
+                break
+            chunks.append(bytes(b[:count]))
+
+        self.assertEqual(b"".join(chunks), frame)
+        self.assertEqual(reader.tell(), len(frame))
+
+    @unittest.skipUnless(
+        "compression_reader_readinto_validation" in zstd.backend_features,
+        "compression reader readinto validation not available",
+    )
+    def test_readinto_buffer_types(self):
+        source = bytes(range(256)) * 1024
+        cctx = zstd.ZstdCompressor()
+
+        for method in ("readinto", "readinto1"):
+            reader = cctx.stream_reader(io.BytesIO(source))
+            readinto = getattr(reader, method)
+
+            self.assertEqual(readinto(bytearray()), 0)
+            self.assertEqual(reader.tell(), 0)
+
+            with self.assertRaisesRegex(ValueError, "must be contiguous"):
+                readinto(memoryview(bytearray(16))[::2])
+
+            frame = reader.read()
+            dobj = zstd.ZstdDecompressor().decompressobj()
+            self.assertEqual(dobj.decompress(frame), source)
+
     def test_readinto1(self):
         cctx = zstd.ZstdCompressor()
         foo = b"".join(cctx.read_to_iter(io.BytesIO(b"foo")))
@@ -373,3 +434,78 @@ class TestCompressor_stream_reader(unittest.TestCase):
 
         with self.assertRaisesRegex(IOError, "write"):
             writer.flush()
+
+
+class NonBlockingSource:
+    """Source whose ``read()`` returns ``None`` until data is "available"."""
+
+    def __init__(self, chunks):
+        self._chunks = list(chunks)
+
+    def read(self, size):
+        if not self._chunks:
+            return b""
+
+        chunk = self._chunks.pop(0)
+        if chunk is None:
+            return None
+
+        assert len(chunk) <= size
+        return chunk
+
+
+def decompress(frame):
+    return zstd.ZstdDecompressor().decompressobj().decompress(frame)
+
+
+@unittest.skipUnless("at_eof" in zstd.backend_features, "at eof not available")
+class TestCompressor_stream_reader_at_eof(unittest.TestCase):
+    def test_read(self):
+        cctx = zstd.ZstdCompressor()
+
+        for source in (b"foobar" * 1024, io.BytesIO(b"foobar" * 1024)):
+            reader = cctx.stream_reader(source)
+            self.assertFalse(reader.at_eof)
+
+            chunks = []
+            while not reader.at_eof:
+                chunks.append(reader.read(1))
+
+            self.assertGreater(len(chunks), 1)
+            self.assertNotIn(b"", chunks)
+            self.assertEqual(decompress(b"".join(chunks)), b"foobar" * 1024)
+            self.assertEqual(reader.read(1), b"")
+            self.assertTrue(reader.at_eof)
+
+    def test_empty_read_ends_input(self):
+        # As with files, an empty read() from the source marks its end.
+        reader = zstd.ZstdCompressor().stream_reader(
+            NonBlockingSource([b"", b"foobar"])
+        )
+
+        frame = reader.read(65536)
+        self.assertTrue(reader.at_eof)
+        self.assertEqual(decompress(frame), b"")
+
+    def test_no_data_available(self):
+        cctx = zstd.ZstdCompressor()
+        chunks = [None, None, b"foo" * 100, None, b"bar" * 100, None]
+
+        for method in ("read", "read1", "readinto", "readinto1"):
+            reader = cctx.stream_reader(NonBlockingSource(chunks))
+            output = []
+
+            while not reader.at_eof:
+                if method.startswith("readinto"):
+                    b = bytearray(65536)
+                    count = getattr(reader, method)(b)
+                    output.append(bytes(b[:count]))
+                else:
+                    output.append(getattr(reader, method)(65536))
+
+            # Reads return nothing while the source has no data.
+            self.assertIn(b"", output)
+            self.assertEqual(
+                decompress(b"".join(output)),
+                b"foo" * 100 + b"bar" * 100,
+            )
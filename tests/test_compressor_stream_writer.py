@@ -1,9 +1,11 @@
 import hashlib
 import io
 import os
+import sys
 import tarfile
 import tempfile
 import unittest
+import unittest.mock
 
 import zstandard as zstd
 
@@ -65,8 +67,9 @@ class TestCompressor_stream_writer(unittest.TestCase):
 
         self.assertTrue(writer.writable())
 
-        with self.assertRaises(NotImplementedError):
-            writer.writelines([])
+        if "stream_writer_buffering" not in zstd.backend_features:
+            with self.assertRaises(NotImplementedError):
+                writer.writelines([])
 
         with self.assertRaises(io.UnsupportedOperation):
             writer.read()
@@ -570,6 +573,59 @@ class TestCompressor_stream_writer(unittest.TestCase):
                 compressor.write(b"foo" * (i + 1))
                 self.assertEqual(compressor.tell(), dest.tell())
 
+    @unittest.skipUnless(
+        "frame_progression" in zstd.backend_features,
+        "frame progression not available",
+    )
+    def test_frame_progression(self):
+        dest = io.BytesIO()
+        cctx = zstd.ZstdCompressor()
+        compressor = cctx.stream_writer(dest)
+
+        progression = compressor.frame_progression()
+        self.assertEqual(progression.ingested, 0)
+        self.assertEqual(progression.produced, 0)
+
+        compressor.write(b"foobar")
+        progression = compressor.frame_progression()
+        self.assertEqual(progression.ingested, 6)
+
+        compressor.flush(zstd.FLUSH_BLOCK)
+        progression = compressor.frame_progression()
+        self.assertEqual(progression.ingested, 6)
+        self.assertEqual(progression.consumed, 6)
+        self.assertGreater(progression.produced, 0)
+        self.assertEqual(progression.flushed, progression.produced)
+
+        compressor.close()
+
+        with self.assertRaisesRegex(zstd.ZstdError, "stream is closed"):
+            compressor.frame_progression()
+
+    def test_flush_block_incremental_decode(self):
+        dest = NonClosingBytesIO()
+        cctx = zstd.ZstdCompressor(level=1)
+        compressor = cctx.stream_writer(dest)
+
+        dctx = zstd.ZstdDecompressor()
+        dobj = dctx.decompressobj()
+        offset = 0
+
+        for i in range(5):
+            data = b"message %d" % i
+            compressor.write(data)
+            compressor.flush(zstd.FLUSH_BLOCK)
+
+            chunk = dest.getvalue()[offset:]
+            offset += len(chunk)
+            self.assertEqual(dobj.decompress(chunk), data)
+            self.assertFalse(dobj.eof)
+
+        # Unlike FLUSH_BLOCK, FLUSH_FRAME ends the frame.
+        compressor.flush(zstd.FLUSH_FRAME)
+        self.assertEqual(dobj.decompress(dest.getvalue()[offset:]), b"")
+        self.assertTrue(dobj.eof)
+
     def test_bad_size(self):
         cctx = zstd.ZstdCompressor()
 
@@ -579,9 +635,92 @@ class TestCompressor_stream_writer(unittest.TestCase):
             with cctx.stream_writer(dest, size=2) as compressor:
                 compressor.write(b"foo")
 
-        # Test another operation.
-        with cctx.stream_writer(dest, size=42):
-            pass
+        # Test another operation. The failed writer closed dest.
+        dest = io.BytesIO()
+        with cctx.stream_writer(dest, size=42) as compressor:
+            compressor.write(b"x" * 42)
+
+    @unittest.skipUnless(
+        "pledged_size_errors" in zstd.backend_features,
+        "pledged size errors not available",
+    )
+    def test_pledged_size_too_few_bytes(self):
+        cctx = zstd.ZstdCompressor()
+        dest = CustomBytesIO()
+        writer = cctx.stream_writer(dest, size=1000)
+        writer.write(b"x" * 999)
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "pledged 1000 bytes but wrote 999"
+        ):
+            writer.close()
+
+        self.assertTrue(writer.closed)
+        self.assertEqual(dest._close_count, 1)
+
+        # The compressor remains usable.
+        dest = io.BytesIO()
+        with cctx.stream_writer(dest, size=3, closefd=False) as writer:
+            writer.write(b"foo")
+
+        self.assertEqual(
+            zstd.get_frame_parameters(dest.getvalue()).content_size, 3
+        )
+
+    @unittest.skipUnless(
+        "pledged_size_errors" in zstd.backend_features,
+        "pledged size errors not available",
+    )
+    def test_pledged_size_too_many_bytes(self):
+        cctx = zstd.ZstdCompressor()
+        writer = cctx.stream_writer(io.BytesIO(), size=4)
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "Src size is incorrect; pledged 4 bytes but wrote 6",
+        ):
+            writer.write(b"foobar")
+
+    def test_pledged_size_exact(self):
+        cctx = zstd.ZstdCompressor()
+        dest = io.BytesIO()
+
+        with cctx.stream_writer(dest, size=6, closefd=False) as writer:
+            writer.write(b"foo")
+            writer.write(b"bar")
+
+        frame = dest.getvalue()
+        self.assertEqual(zstd.get_frame_parameters(frame).content_size, 6)
+        self.assertEqual(zstd.ZstdDecompressor().decompress(frame), b"foobar")
+
+    @unittest.skipUnless(
+        "pledged_size_errors" in zstd.backend_features,
+        "pledged size errors not available",
+    )
+    def test_pledged_size_first_frame_only(self):
+        cctx = zstd.ZstdCompressor()
+        dest = io.BytesIO()
+
+        with cctx.stream_writer(dest, size=3, closefd=False) as writer:
+            writer.write(b"foo")
+            writer.flush(zstd.FLUSH_FRAME)
+            # Later frames have no pledged size to check against.
+            writer.write(b"foobar")
+
+        dobj = zstd.ZstdDecompressor().decompressobj(read_across_frames=True)
+        self.assertEqual(dobj.decompress(dest.getvalue()), b"foofoobar")
+
+    def test_no_size(self):
+        cctx = zstd.ZstdCompressor()
+        dest = io.BytesIO()
+
+        with cctx.stream_writer(dest, closefd=False) as writer:
+            writer.write(b"foo" * 100)
+
+        self.assertEqual(
+            zstd.get_frame_parameters(dest.getvalue()).content_size,
+            zstd.CONTENTSIZE_UNKNOWN,
+        )
 
     def test_tarfile_compat(self):
         dest = io.BytesIO()
@@ -597,3 +736,746 @@ class TestCompressor_stream_writer(unittest.TestCase):
             with tarfile.open(mode="r|", fileobj=reader) as tf:
                 for member in tf:
                     self.assertEqual(member.name, "test_compressor.py")
+
+
+@unittest.skipUnless(
+    "stream_writer_buffering" in zstd.backend_features,
+    "stream writer buffering not available",
+)
+class TestCompressor_stream_writer_buffering(unittest.TestCase):
+    def test_writelines(self):
+        lines = [b"line %d\n" % i for i in range(1000)]
+        cctx = zstd.ZstdCompressor()
+
+        buffer = io.BytesIO()
+        with cctx.stream_writer(buffer, closefd=False) as writer:
+            self.assertIsNone(writer.writelines(lines))
+            writer.writelines([])
+            writer.writelines(iter([b"end", bytearray(b"\n")]))
+
+        self.assertEqual(
+            zstd.ZstdDecompressor().decompressobj().decompress(
+                buffer.getvalue()
+            ),
+            b"".join(lines) + b"end\n",
+        )
+
+        with cctx.stream_writer(io.BytesIO()) as writer:
+            with self.assertRaises(TypeError):
+                writer.writelines(42)
+
+            with self.assertRaises(TypeError):
+                writer.writelines(["not bytes"])
+
+    def test_writelines_generator(self):
+        cctx = zstd.ZstdCompressor(level=1)
+        buffer = io.BytesIO()
+        offsets = []
+
+        def chunks():
+            for i in range(64):
+                offsets.append(buffer.tell())
+                yield bytes([i]) * 65536
+
+        with cctx.stream_writer(buffer, closefd=False) as writer:
+            writer.writelines(chunks())
+
+        # Output reaches the destination while the generator is still
+        # producing items.
+        self.assertEqual(len(offsets), 64)
+        self.assertEqual(offsets[0], 0)
+        self.assertGreater(offsets[32], 0)
+
+        dobj = zstd.ZstdDecompressor().decompressobj()
+        self.assertEqual(
+            dobj.decompress(buffer.getvalue()),
+            b"".join(bytes([i]) * 65536 for i in range(64)),
+        )
+
+    def test_min_flush_size(self):
+        source = os.urandom(1024 * 1024)
+        cctx = zstd.ZstdCompressor(level=1)
+
+        dest = CustomBytesIO()
+        with cctx.stream_writer(
+            dest, closefd=False, write_size=8192, min_flush_size=262144
+        ) as writer:
+            writer.write(source[0:200000])
+            self.assertEqual(dest._write_count, 0)
+            self.assertEqual(writer.tell(), 0)
+
+            writer.write(source[200000:])
+            self.assertGreater(dest._write_count, 0)
+            self.assertLess(dest._write_count, 8)
+            self.assertEqual(writer.tell(), len(dest.getvalue()))
+
+            # flush() writes out everything regardless of the threshold.
+            writer.write(b"foo")
+            writer.flush()
+            self.assertEqual(writer.tell(), len(dest.getvalue()))
+
+        self.assertEqual(
+            zstd.ZstdDecompressor().decompress(
+                dest.getvalue(), max_output_size=len(source) + 3
+            ),
+            source + b"foo",
+        )
+
+    def test_min_flush_size_close(self):
+        cctx = zstd.ZstdCompressor()
+
+        dest = CustomBytesIO()
+        with cctx.stream_writer(
+            dest, closefd=False, min_flush_size=1 << 20
+        ) as writer:
+            writer.write(b"foobar" * 1024)
+            self.assertEqual(dest._write_count, 0)
+
+        self.assertEqual(dest._write_count, 1)
+        self.assertEqual(
+            zstd.ZstdDecompressor().decompressobj().decompress(
+                dest.getvalue()
+            ),
+            b"foobar" * 1024,
+        )
+
+    def test_flush_on_close(self):
+        cctx = zstd.ZstdCompressor()
+
+        dest = CustomBytesIO()
+        with cctx.stream_writer(dest, closefd=False) as writer:
+            writer.write(b"foo")
+        self.assertEqual(dest._flush_count, 0)
+
+        dest = CustomBytesIO()
+        with cctx.stream_writer(
+            dest, closefd=False, flush_on_close=True
+        ) as writer:
+            writer.write(b"foo")
+        self.assertEqual(dest._flush_count, 1)
+        self.assertEqual(
+            zstd.ZstdDecompressor().decompressobj().decompress(
+                dest.getvalue()
+            ),
+            b"foo",
+        )
+
+        dest = CustomBytesIO()
+        writer = cctx.stream_writer(dest, flush_on_close=True)
+        writer.write(b"foo")
+        writer.close()
+        self.assertEqual(dest._flush_count, 1)
+        self.assertTrue(dest.closed)
+
+
+@unittest.skipUnless(
+    "stream_writer_close_size" in zstd.backend_features,
+    "stream writer close size not available",
+)
+class TestCompressor_stream_writer_close(unittest.TestCase):
+    def test_close_returns_written(self):
+        cctx = zstd.ZstdCompressor()
+        dest = io.BytesIO()
+
+        writer = cctx.stream_writer(dest, closefd=False)
+        writer.write(b"foobar" * 1024)
+        self.assertEqual(writer.tell(), 0)
+
+        written = writer.close()
+        self.assertGreater(written, 0)
+        self.assertEqual(written, len(dest.getvalue()))
+        self.assertEqual(writer.tell(), len(dest.getvalue()))
+
+        # A second close doesn't flush again.
+        self.assertEqual(writer.close(), 0)
+        self.assertEqual(writer.tell(), len(dest.getvalue()))
+
+    def test_close_after_flush(self):
+        cctx = zstd.ZstdCompressor()
+        dest = io.BytesIO()
+
+        writer = cctx.stream_writer(dest, closefd=False)
+        writer.write(b"foobar" * 1024)
+        flushed = writer.flush()
+        self.assertEqual(writer.tell(), flushed)
+
+        # Only the frame epilogue remains to be written.
+        written = writer.close()
+        self.assertEqual(flushed + written, len(dest.getvalue()))
+        self.assertEqual(written, 3)
+
+    def test_context_manager_tell(self):
+        cctx = zstd.ZstdCompressor()
+        dest = io.BytesIO()
+
+        with cctx.stream_writer(dest, closefd=False) as writer:
+            writer.write(b"foobar" * 1024)
+
+        self.assertTrue(writer.closed)
+        self.assertEqual(writer.tell(), len(dest.getvalue()))
+        self.assertEqual(writer.close(), 0)
+
+
+
+def job_source():
+    return b"".join(
+        b"line %d %d\n" % (i, i * 7919 % 104729) for i in range(400000)
+    )
+
+
+@unittest.skipUnless(
+    "stream_writer_job_params" in zstd.backend_features,
+    "stream writer job params not available",
+)
+class TestCompressor_stream_writer_job_params(unittest.TestCase):
+    def compress(self, cctx, source, **kwargs):
+        dest = io.BytesIO()
+        writer = cctx.stream_writer(
+            dest, size=len(source), closefd=False, **kwargs
+        )
+        writer.write(source)
+        writer.flush(zstd.FLUSH_FRAME)
+        progression = writer.frame_progression()
+        writer.close()
+
+        return dest.getvalue(), progression
+
+    def test_job_size(self):
+        source = job_source()
+        cctx = zstd.ZstdCompressor(threads=2)
+
+        frame, default = self.compress(cctx, source)
+        small_frame, small = self.compress(cctx, source, job_size=1024)
+
+        self.assertGreater(small.current_job_id, default.current_job_id)
+        self.assertGreaterEqual(small.current_job_id, 8)
+
+        dctx = zstd.ZstdDecompressor()
+        self.assertEqual(dctx.decompress(frame), source)
+        self.assertEqual(dctx.decompress(small_frame), source)
+
+    def test_overlap_log(self):
+        source = job_source()
+        cctx = zstd.ZstdCompressor(threads=2)
+
+        frame, _ = self.compress(cctx, source, job_size=1048576, overlap_log=9)
+        self.assertEqual(zstd.ZstdDecompressor().decompress(frame), source)
+
+    def test_single_threaded(self):
+        source = job_source()[:1048576]
+        cctx = zstd.ZstdCompressor()
+
+        expected, _ = self.compress(cctx, source)
+        frame, progression = self.compress(
+            cctx, source, job_size=1024, overlap_log=9
+        )
+
+        # Without workers there are no jobs to size.
+        self.assertEqual(frame, expected)
+        self.assertEqual(progression.current_job_id, 0)
+
+    def test_compressor_unchanged(self):
+        source = job_source()
+        cctx = zstd.ZstdCompressor(threads=2)
+
+        expected, default = self.compress(cctx, source)
+        self.compress(cctx, source, job_size=1024, overlap_log=0)
+        frame, progression = self.compress(cctx, source)
+
+        self.assertEqual(frame, expected)
+        self.assertEqual(progression.current_job_id, default.current_job_id)
+
+    def test_params_match_compression_params(self):
+        source = job_source()
+        params = zstd.ZstdCompressionParameters.from_level(
+            3, threads=2, job_size=1048576, overlap_log=5
+        )
+
+        from_params, _ = self.compress(
+            zstd.ZstdCompressor(compression_params=params), source
+        )
+        overridden, _ = self.compress(
+            zstd.ZstdCompressor(threads=2),
+            source,
+            job_size=1048576,
+            overlap_log=5,
+        )
+
+        self.assertEqual(params.job_size, 1048576)
+        self.assertEqual(params.overlap_log, 5)
+        self.assertEqual(overridden, from_params)
+
+
+@unittest.skipUnless(
+    "stream_writer_compression_params" in zstd.backend_features,
+    "stream writer compression params not available",
+)
+class TestCompressor_stream_writer_compression_params(unittest.TestCase):
+    def setUp(self):
+        self.source = b"".join(b"line %d\n" % i for i in range(4096))
+        self.params = zstd.ZstdCompressionParameters(
+            window_log=10, write_checksum=True
+        )
+
+    def compress(self, cctx, **kwargs):
+        dest = io.BytesIO()
+        with cctx.stream_writer(dest, closefd=False, **kwargs) as writer:
+            writer.write(self.source)
+
+        return dest.getvalue()
+
+    def test_override(self):
+        frame = self.compress(
+            zstd.ZstdCompressor(), compression_params=self.params
+        )
+
+        frame_params = zstd.get_frame_parameters(frame)
+        self.assertEqual(frame_params.window_size, 1024)
+        self.assertTrue(frame_params.has_checksum)
+        self.assertEqual(
+            zstd.ZstdDecompressor().decompress(
+                frame, max_output_size=len(self.source)
+            ),
+            self.source,
+        )
+
+        # The output matches a compressor constructed with the parameters.
+        expected = self.compress(
+            zstd.ZstdCompressor(compression_params=self.params)
+        )
+        self.assertEqual(frame, expected)
+
+    def test_compressor_unchanged(self):
+        cctx = zstd.ZstdCompressor(level=1)
+
+        expected = self.compress(cctx)
+        self.compress(cctx, compression_params=self.params)
+        frame = self.compress(cctx)
+
+        self.assertEqual(frame, expected)
+        self.assertFalse(zstd.get_frame_parameters(frame).has_checksum)
+        self.assertEqual(
+            cctx.compress(self.source),
+            zstd.ZstdCompressor(level=1).compress(self.source),
+        )
+
+    def test_concurrent_streams(self):
+        cctx = zstd.ZstdCompressor(level=1)
+        other_params = zstd.ZstdCompressionParameters(window_log=12)
+
+        first_dest = io.BytesIO()
+        second_dest = io.BytesIO()
+        first = cctx.stream_writer(
+            first_dest, closefd=False, compression_params=self.params
+        )
+        second = cctx.stream_writer(
+            second_dest, closefd=False, compression_params=other_params
+        )
+
+        # Each stream has a context of its own, so interleaving them, or
+        # using the compressor meanwhile, doesn't mix their state.
+        for i in range(0, len(self.source), 1000):
+            first.write(self.source[i : i + 1000])
+            second.write(self.source[i : i + 1000])
+            cctx.compress(b"unrelated")
+
+        first.close()
+        second.close()
+
+        first_params = zstd.get_frame_parameters(first_dest.getvalue())
+        second_params = zstd.get_frame_parameters(second_dest.getvalue())
+        self.assertEqual(first_params.window_size, 1024)
+        self.assertTrue(first_params.has_checksum)
+        self.assertEqual(second_params.window_size, 4096)
+        self.assertFalse(second_params.has_checksum)
+
+        dctx = zstd.ZstdDecompressor()
+        for dest in (first_dest, second_dest):
+            self.assertEqual(
+                b"".join(dctx.read_to_iter(dest.getvalue())), self.source
+            )
+
+    def test_dictionary(self):
+        samples = [b"line %d\n" % i * 16 for i in range(128)]
+        d = zstd.train_dictionary(8192, samples)
+        cctx = zstd.ZstdCompressor(dict_data=d)
+
+        frame = self.compress(cctx, compression_params=self.params)
+
+        # The compressor's dictionary is still used.
+        dctx = zstd.ZstdDecompressor(dict_data=d)
+        self.assertEqual(b"".join(dctx.read_to_iter(frame)), self.source)
+
+        with self.assertRaises(zstd.ZstdDecompressionError):
+            b"".join(zstd.ZstdDecompressor().read_to_iter(frame))
+
+    def test_job_size(self):
+        params = zstd.ZstdCompressionParameters(threads=2)
+        frame = self.compress(
+            zstd.ZstdCompressor(), compression_params=params, job_size=1024
+        )
+
+        dctx = zstd.ZstdDecompressor()
+        self.assertEqual(b"".join(dctx.read_to_iter(frame)), self.source)
+
+
+@unittest.skipUnless(
+    "stream_writer_hash" in zstd.backend_features,
+    "stream writer hash not available",
+)
+class TestCompressor_stream_writer_hash(unittest.TestCase):
+    def setUp(self):
+        self.source = b"".join(b"line %d\n" % i for i in range(4096))
+
+    def compress(self, hash, chunks, cctx=None):
+        cctx = cctx or zstd.ZstdCompressor()
+        dest = io.BytesIO()
+        writer = cctx.stream_writer(dest, closefd=False, hash=hash)
+        for chunk in chunks:
+            writer.write(chunk)
+        writer.close()
+
+        return dest.getvalue(), writer.plaintext_digest()
+
+    def chunks(self, sizes):
+        offset = 0
+        for size in sizes:
+            yield self.source[offset : offset + size]
+            offset += size
+
+        yield self.source[offset:]
+
+    def test_sha256(self):
+        frame, digest = self.compress("sha256", [self.source])
+
+        self.assertEqual(digest, hashlib.sha256(self.source).digest())
+        self.assertEqual(
+            zstd.ZstdDecompressor().decompress(
+                frame, max_output_size=len(self.source)
+            ),
+            self.source,
+        )
+
+    def test_hashlib_algorithm(self):
+        _, digest = self.compress("md5", [self.source])
+        self.assertEqual(digest, hashlib.md5(self.source).digest())
+
+    def test_xxh64(self):
+        _, digest = self.compress("xxh64", [])
+        self.assertEqual(digest, bytes.fromhex("ef46db3751d8e999"))
+
+        # zstd checksums frames with the low 32 bits of the XXH64 of their
+        # content.
+        cctx = zstd.ZstdCompressor(write_checksum=True)
+        for length in (0, 1, 3, 4, 7, 8, 31, 32, 33, 100, len(self.source)):
+            source = self.source[:length]
+            frame, digest = self.compress("xxh64", [source], cctx=cctx)
+            self.assertEqual(len(digest), 8)
+            self.assertEqual(digest[4:], frame[-4:][::-1])
+
+    def test_chunked_writes(self):
+        sizes = [1, 7, 33, 0, 64, 5, 1000, 31]
+        expected = hashlib.sha256(self.source).digest()
+
+        for algorithm in ("xxh64", "sha256"):
+            _, whole = self.compress(algorithm, [self.source])
+            _, chunked = self.compress(algorithm, self.chunks(sizes))
+            self.assertEqual(chunked, whole)
+
+        self.assertEqual(whole, expected)
+
+    def test_buffer_types(self):
+        chunks = [
+            bytearray(self.source[:100]),
+            memoryview(self.source[100:200]),
+            self.source[200:],
+        ]
+        _, digest = self.compress("sha256", chunks)
+        self.assertEqual(digest, hashlib.sha256(self.source).digest())
+
+    def test_multiple_frames(self):
+        dest = io.BytesIO()
+        cctx = zstd.ZstdCompressor()
+        writer = cctx.stream_writer(dest, closefd=False, hash="sha256")
+        writer.write(b"foo")
+        writer.flush(zstd.FLUSH_FRAME)
+        writer.write(b"bar")
+        writer.close()
+
+        self.assertEqual(
+            writer.plaintext_digest(), hashlib.sha256(b"foobar").digest()
+        )
+
+    def test_before_close(self):
+        writer = zstd.ZstdCompressor().stream_writer(
+            io.BytesIO(), hash="sha256"
+        )
+        writer.write(self.source)
+
+        with self.assertRaisesRegex(
+            ValueError,
+            "plaintext digest is not available until the writer is closed",
+        ):
+            writer.plaintext_digest()
+
+        with writer:
+            pass
+
+        self.assertEqual(
+            writer.plaintext_digest(), hashlib.sha256(self.source).digest()
+        )
+
+    def test_no_hash(self):
+        writer = zstd.ZstdCompressor().stream_writer(io.BytesIO())
+        writer.close()
+
+        with self.assertRaisesRegex(
+            ValueError, "no hash algorithm was passed to stream_writer"
+        ):
+            writer.plaintext_digest()
+
+    def test_unknown_algorithm(self):
+        with self.assertRaisesRegex(ValueError, "unsupported hash type"):
+            zstd.ZstdCompressor().stream_writer(io.BytesIO(), hash="bogus")
+
+
+@unittest.skipUnless(
+    "flush_end_frame" in zstd.backend_features, "flush end frame not available"
+)
+class TestCompressor_stream_writer_end_frame(unittest.TestCase):
+    def test_frames(self):
+        dest = io.BytesIO()
+        writer = zstd.ZstdCompressor().stream_writer(dest, closefd=False)
+
+        writer.write(b"first payload")
+        writer.flush(end_frame=True)
+        first = dest.tell()
+        writer.write(b"second payload")
+        writer.flush(end_frame=True)
+
+        data = dest.getvalue()
+
+        # Each frame decodes on its own.
+        dobj = zstd.ZstdDecompressor().decompressobj()
+        self.assertEqual(dobj.decompress(data[:first]), b"first payload")
+        dobj = zstd.ZstdDecompressor().decompressobj()
+        self.assertEqual(dobj.decompress(data[first:]), b"second payload")
+
+        reader = zstd.ZstdDecompressor().stream_reader(
+            data, read_across_frames=False
+        )
+        self.assertEqual(reader.read(1024), b"first payload")
+        self.assertEqual(reader.read(1024), b"second payload")
+        self.assertEqual(reader.read(1024), b"")
+
+    def test_flush_frame_equivalent(self):
+        results = []
+        for kwargs in ({"end_frame": True}, {"flush_mode": zstd.FLUSH_FRAME}):
+            dest = io.BytesIO()
+            writer = zstd.ZstdCompressor().stream_writer(dest, closefd=False)
+            writer.write(b"foo")
+            writer.flush(**kwargs)
+            writer.write(b"bar")
+            writer.flush(**kwargs)
+            results.append(dest.getvalue())
+
+        self.assertEqual(results[0], results[1])
+
+    def test_pledged_size(self):
+        dest = io.BytesIO()
+        cctx = zstd.ZstdCompressor()
+        writer = cctx.stream_writer(dest, size=6, closefd=False)
+
+        writer.write(b"foobar")
+        writer.flush(end_frame=True)
+        first = dest.tell()
+
+        # The pledged size only applied to the first frame.
+        writer.write(b"more than six bytes")
+        writer.flush(end_frame=True)
+
+        data = dest.getvalue()
+        self.assertEqual(zstd.get_frame_parameters(data).content_size, 6)
+        self.assertEqual(
+            zstd.get_frame_parameters(data[first:]).content_size,
+            zstd.CONTENTSIZE_UNKNOWN,
+        )
+
+    def test_pledged_size_short(self):
+        writer = zstd.ZstdCompressor().stream_writer(io.BytesIO(), size=6)
+        writer.write(b"foo")
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "pledged 6 bytes but wrote 3"
+        ):
+            writer.flush(end_frame=True)
+
+    def test_conflicting_flush_mode(self):
+        writer = zstd.ZstdCompressor().stream_writer(io.BytesIO())
+
+        with self.assertRaisesRegex(
+            ValueError, "end_frame=True conflicts with flush_mode 0"
+        ):
+            writer.flush(zstd.FLUSH_BLOCK, end_frame=True)
+
+        self.assertGreater(
+            writer.flush(zstd.FLUSH_FRAME, end_frame=True), 0
+        )
+
+
+@unittest.skipUnless("at_eof" in zstd.backend_features, "at eof not available")
+class TestCompressor_stream_writer_at_eof(unittest.TestCase):
+    def test_at_eof(self):
+        dest = io.BytesIO()
+        writer = zstd.ZstdCompressor().stream_writer(dest, closefd=False)
+        self.assertFalse(writer.at_eof)
+
+        writer.write(b"foo")
+        writer.flush()
+        self.assertFalse(writer.at_eof)
+
+        writer.flush(zstd.FLUSH_FRAME)
+        self.assertTrue(writer.at_eof)
+
+        # Empty writes don't start a frame.
+        writer.write(b"")
+        self.assertTrue(writer.at_eof)
+
+        writer.write(b"bar")
+        self.assertFalse(writer.at_eof)
+
+        writer.close()
+        self.assertTrue(writer.at_eof)
+
+        dctx = zstd.ZstdDecompressor()
+        reader = dctx.stream_reader(dest.getvalue(), read_across_frames=True)
+        self.assertEqual(reader.read(), b"foobar")
+
+
+@unittest.skipUnless(
+    "stable_buffers" in zstd.backend_features, "stable buffers not available"
+)
+class TestCompressor_stream_writer_stable_buffers(unittest.TestCase):
+    def test_frame_per_write(self):
+        chunks = [b"foo" * 1000, bytearray(b"bar" * 2000), memoryview(b"baz")]
+        dest = io.BytesIO()
+
+        with zstd.ZstdCompressor().stream_writer(
+            dest, closefd=False, stable_buffers=True
+        ) as writer:
+            for chunk in chunks:
+                self.assertEqual(writer.write(chunk), len(chunk))
+                self.assertTrue(writer.at_eof)
+
+            # Empty writes don't produce a frame.
+            self.assertEqual(writer.write(b""), 0)
+            writer.flush()
+
+        frames = list(zstd.iter_frames(dest.getvalue()))
+        self.assertEqual(len(frames), len(chunks))
+
+        dctx = zstd.ZstdDecompressor()
+        for (offset, length), chunk in zip(frames, chunks):
+            frame = dest.getvalue()[offset : offset + length]
+            self.assertEqual(dctx.decompress(frame), chunk)
+
+    def test_without_gil(self):
+        # Writable buffers are copied rather than shared with zstd.
+        source = bytearray(b"foobar" * 1000)
+        dest = io.BytesIO()
+
+        with unittest.mock.patch.object(
+            sys, "_is_gil_enabled", lambda: False, create=True
+        ):
+            with zstd.ZstdCompressor().stream_writer(
+                dest, closefd=False, stable_buffers=True
+            ) as writer:
+                self.assertEqual(writer.write(source), len(source))
+
+        self.assertEqual(
+            zstd.ZstdDecompressor().decompress(dest.getvalue()), source
+        )
+
+    def test_no_writes(self):
+        dest = io.BytesIO()
+        zstd.ZstdCompressor().stream_writer(
+            dest, closefd=False, stable_buffers=True
+        ).close()
+
+        self.assertEqual(
+            zstd.ZstdDecompressor().decompress(dest.getvalue()), b""
+        )
+
+    def test_size(self):
+        dest = io.BytesIO()
+        cctx = zstd.ZstdCompressor()
+
+        with cctx.stream_writer(
+            dest, size=6, closefd=False, stable_buffers=True
+        ) as writer:
+            writer.write(b"foobar")
+            writer.write(b"more")
+
+        frames = list(zstd.iter_frames(dest.getvalue()))
+        first = dest.getvalue()[: frames[0][1]]
+        self.assertEqual(zstd.get_frame_parameters(first).content_size, 6)
+
+        writer = cctx.stream_writer(io.BytesIO(), size=6, stable_buffers=True)
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "stable_buffers requires each frame in a single write\\(\\); "
+            "pledged 6 bytes but wrote 3",
+        ):
+            writer.write(b"foo")
+
+    def test_memory_size(self):
+        # zstd allocates no buffers of its own to copy data through.
+        source = b"foobar" * 100000
+        sizes = []
+
+        for stable_buffers in (False, True):
+            writer = zstd.ZstdCompressor().stream_writer(
+                io.BytesIO(), stable_buffers=stable_buffers
+            )
+            writer.write(source)
+            sizes.append(writer.memory_size())
+
+        self.assertLess(
+            sizes[1], sizes[0] - zstd.COMPRESSION_RECOMMENDED_INPUT_SIZE
+        )
+
+    def test_options(self):
+        source = b"foobar" * 10000
+        dest = io.BytesIO()
+        cctx = zstd.ZstdCompressor(threads=2, write_checksum=True)
+
+        with cctx.stream_writer(
+            dest,
+            closefd=False,
+            min_flush_size=1 << 20,
+            hash="sha256",
+            write_return_read=False,
+            stable_buffers=True,
+        ) as writer:
+            self.assertEqual(writer.write(source), 0)
+            self.assertEqual(dest.getvalue(), b"")
+
+        self.assertEqual(
+            writer.plaintext_digest(), hashlib.sha256(source).digest()
+        )
+        self.assertEqual(
+            zstd.ZstdDecompressor().decompress(dest.getvalue()), source
+        )
+
+    def test_shared_context_untouched(self):
+        cctx = zstd.ZstdCompressor()
+        cctx.stream_writer(io.BytesIO(), stable_buffers=True).write(b"foo")
+
+        # The compressor's context still accepts input across writes.
+        dest = io.BytesIO()
+        with cctx.stream_writer(dest, closefd=False) as writer:
+            writer.write(b"foo")
+            writer.write(b"bar")
+
+        dobj = zstd.ZstdDecompressor().decompressobj()
+        self.assertEqual(dobj.decompress(dest.getvalue()), b"foobar")
@@ -1,8 +1,45 @@
+import io
+import random
 import unittest
 
 import zstandard as zstd
 
 
+def words(size):
+    rng = random.Random(42)
+    vocabulary = [
+        bytes(rng.choices(b"abcdefghijklmnopqrstuvwxyz", k=rng.randint(2, 9)))
+        for i in range(4096)
+    ]
+
+    data = bytearray()
+    while len(data) < size:
+        data += rng.choice(vocabulary) + b" "
+
+    return bytes(data[:size])
+
+
+def compressed_block_sizes(frame):
+    """Sizes of the compressed blocks in a single frame."""
+    offset = zstd.frame_header_size(frame)
+    sizes = []
+
+    while True:
+        header = int.from_bytes(frame[offset : offset + 3], "little")
+        block_type = (header >> 1) & 3
+        size = header >> 3
+        offset += 3
+
+        # Raw and RLE blocks don't count.
+        if block_type == 2:
+            sizes.append(size)
+
+        offset += 1 if block_type == 1 else size
+
+        if header & 1:
+            return sizes
+
+
 class TestCompressionParameters(unittest.TestCase):
     def test_bounds(self):
         zstd.ZstdCompressionParameters(
@@ -34,6 +71,60 @@ class TestCompressionParameters(unittest.TestCase):
         p = zstd.ZstdCompressionParameters.from_level(-4)
         self.assertEqual(p.window_log, 19)
 
+    @unittest.skipUnless(
+        "from_level_validation" in zstd.backend_features,
+        "from level validation not available",
+    )
+    def test_from_level_bounds(self):
+        max_level = zstd.MAX_COMPRESSION_LEVEL
+        p = zstd.ZstdCompressionParameters.from_level(max_level)
+        self.assertEqual(p.compression_level, max_level)
+
+        for level in (zstd.MAX_COMPRESSION_LEVEL + 1, -(2**17) - 1, 2**31 - 1):
+            with self.assertRaisesRegex(
+                ValueError,
+                "level must be between %d and %d"
+                % (zstd.MIN_COMPRESSION_LEVEL, zstd.MAX_COMPRESSION_LEVEL),
+            ):
+                zstd.ZstdCompressionParameters.from_level(level)
+
+        p = zstd.ZstdCompressionParameters.from_level(
+            zstd.MIN_COMPRESSION_LEVEL
+        )
+        self.assertEqual(p.target_length, -zstd.MIN_COMPRESSION_LEVEL)
+
+    @unittest.skipUnless(
+        "from_level_validation" in zstd.backend_features,
+        "from level validation not available",
+    )
+    def test_from_level_template(self):
+        p = zstd.ZstdCompressionParameters.from_level(19)
+        self.assertEqual(p.compression_level, 19)
+
+        # A smaller window is chosen for small inputs.
+        small = zstd.ZstdCompressionParameters.from_level(19, source_size=1024)
+        self.assertLess(small.window_log, p.window_log)
+        self.assertLess(small.hash_log, p.hash_log)
+
+        with_dict = zstd.ZstdCompressionParameters.from_level(
+            19, source_size=1024, dict_size=65536
+        )
+        self.assertGreater(with_dict.window_log, small.window_log)
+
+        # Explicit parameters override the level's defaults.
+        p = zstd.ZstdCompressionParameters.from_level(
+            19, window_log=20, compression_level=5, write_checksum=True
+        )
+        self.assertEqual(p.window_log, 20)
+        self.assertEqual(p.chain_log, 24)
+        self.assertEqual(p.compression_level, 5)
+        self.assertEqual(p.write_checksum, 1)
+
+        source = b"foobar" * 8192
+        frame = zstd.ZstdCompressor(compression_params=small).compress(source)
+        self.assertEqual(zstd.get_frame_parameters(frame).window_size, 1024)
+        self.assertEqual(zstd.ZstdDecompressor().decompress(frame), source)
+
     def test_members(self):
         p = zstd.ZstdCompressionParameters(
             window_log=10,
@@ -65,6 +156,9 @@ class TestCompressionParameters(unittest.TestCase):
         self.assertEqual(p.job_size, 1048576)
         self.assertEqual(p.overlap_log, 6)
 
+        p = zstd.ZstdCompressionParameters(threads=-1)
+        self.assertGreater(p.threads, 0)
+
         p = zstd.ZstdCompressionParameters(compression_level=-1)
         self.assertEqual(p.compression_level, -1)
 
@@ -112,14 +206,358 @@ class TestCompressionParameters(unittest.TestCase):
         p = zstd.ZstdCompressionParameters(strategy=3)
         self.assertEqual(p.strategy, 3)
 
+    @unittest.skipUnless(
+        "strategy_validation" in zstd.backend_features,
+        "strategy validation not available",
+    )
+    def test_strategy_invalid(self):
+        for strategy in (-2, zstd.STRATEGY_BTULTRA2 + 1, 42):
+            with self.assertRaisesRegex(
+                zstd.ZstdError,
+                "invalid compression strategy %d; must be between 1 and 9"
+                % strategy,
+            ):
+                zstd.ZstdCompressionParameters(strategy=strategy)
+
+    def test_strategy_constants(self):
+        strategies = [
+            zstd.STRATEGY_FAST,
+            zstd.STRATEGY_DFAST,
+            zstd.STRATEGY_GREEDY,
+            zstd.STRATEGY_LAZY,
+            zstd.STRATEGY_LAZY2,
+            zstd.STRATEGY_BTLAZY2,
+            zstd.STRATEGY_BTOPT,
+            zstd.STRATEGY_BTULTRA,
+            zstd.STRATEGY_BTULTRA2,
+        ]
+        self.assertEqual(strategies, list(range(1, 10)))
+
+        for strategy in strategies:
+            p = zstd.ZstdCompressionParameters(strategy=strategy)
+            self.assertEqual(p.strategy, strategy)
+
+    def test_strategy_compression_ratio(self):
+        source = b"".join(
+            b"line %d: %s\n" % (i, b"x" * (i % 37)) for i in range(20000)
+        )
+
+        def compressed_size(strategy):
+            p = zstd.ZstdCompressionParameters(
+                window_log=20, target_length=64, strategy=strategy
+            )
+            cctx = zstd.ZstdCompressor(compression_params=p)
+            return len(cctx.compress(source))
+
+        self.assertLess(
+            compressed_size(zstd.STRATEGY_BTULTRA2),
+            compressed_size(zstd.STRATEGY_FAST),
+        )
+
+    def test_target_length(self):
+        p = zstd.ZstdCompressionParameters(target_length=zstd.TARGETLENGTH_MAX)
+        self.assertEqual(p.target_length, zstd.TARGETLENGTH_MAX)
+
+        with self.assertRaises(zstd.ZstdError):
+            zstd.ZstdCompressionParameters(
+                target_length=zstd.TARGETLENGTH_MAX + 1
+            )
+
     def test_ldm_hash_rate_log(self):
         p = zstd.ZstdCompressionParameters(ldm_hash_rate_log=8)
         self.assertEqual(p.ldm_hash_rate_log, 8)
 
+    @unittest.skipUnless(
+        "effective_compression_parameters" in zstd.backend_features,
+        "effective compression parameters not available",
+    )
+    def test_effective_parameters(self):
+        # zstd's default parameters for level 19 on large inputs.
+        p = zstd.ZstdCompressionParameters.from_level(19)
+        self.assertEqual(p.window_log, 23)
+        self.assertEqual(p.chain_log, 24)
+        self.assertEqual(p.hash_log, 22)
+        self.assertEqual(p.search_log, 7)
+        self.assertEqual(p.min_match, 3)
+        self.assertEqual(p.target_length, 256)
+        self.assertEqual(p.strategy, zstd.STRATEGY_BTULTRA2)
+
+        # Parameters not set explicitly reflect the compression level.
+        p = zstd.ZstdCompressionParameters(compression_level=19, hash_log=20)
+        self.assertEqual(p.window_log, 23)
+        self.assertEqual(p.hash_log, 20)
+        self.assertEqual(p.strategy, zstd.STRATEGY_BTULTRA2)
+
+        self.assertEqual(
+            zstd.ZstdCompressionParameters().window_log,
+            zstd.ZstdCompressionParameters.from_level(3).window_log,
+        )
+
+        p = zstd.ZstdCompressionParameters(compression_level=-5)
+        self.assertEqual(p.strategy, zstd.STRATEGY_FAST)
+        self.assertEqual(p.target_length, 5)
+
+    @unittest.skipUnless(
+        "target_cblock_size" in zstd.backend_features,
+        "target cblock size not available",
+    )
+    def test_target_cblock_size(self):
+        p = zstd.ZstdCompressionParameters()
+        self.assertEqual(p.target_cblock_size, 0)
+
+        p = zstd.ZstdCompressionParameters(target_cblock_size=1340)
+        self.assertEqual(p.target_cblock_size, 1340)
+
+        for value in (-1, 1, 1339, 131073):
+            with self.assertRaisesRegex(
+                zstd.ZstdError,
+                "invalid target_cblock_size %d; must be 0 or between 1340 "
+                "and 131072" % value,
+            ):
+                zstd.ZstdCompressionParameters(target_cblock_size=value)
+
+    @unittest.skipUnless(
+        "target_cblock_size" in zstd.backend_features,
+        "target cblock size not available",
+    )
+    def test_target_cblock_size_blocks(self):
+        source = words(1048576)
+
+        frame = zstd.ZstdCompressor().compress(source)
+        default = compressed_block_sizes(frame)
+
+        params = zstd.ZstdCompressionParameters(target_cblock_size=1340)
+        frame = zstd.ZstdCompressor(compression_params=params).compress(source)
+        targeted = compressed_block_sizes(frame)
+
+        self.assertEqual(zstd.ZstdDecompressor().decompress(frame), source)
+        self.assertGreater(len(targeted), len(default) * 10)
+        self.assertLess(max(targeted), max(default))
+        self.assertLess(sum(targeted) / len(targeted), 2 * 1340)
+
+    @unittest.skipUnless(
+        "target_cblock_size" in zstd.backend_features,
+        "target cblock size not available",
+    )
+    def test_target_cblock_size_flush_block(self):
+        source = words(65536)
+        params = zstd.ZstdCompressionParameters(target_cblock_size=1340)
+        cobj = zstd.ZstdCompressor(compression_params=params).compressobj()
+
+        chunks = []
+        for i in range(0, len(source), 16384):
+            chunks.append(cobj.compress(source[i : i + 16384]))
+            chunks.append(cobj.flush(zstd.COMPRESSOBJ_FLUSH_BLOCK))
+        chunks.append(cobj.flush())
+        frame = b"".join(chunks)
+
+        # Flushed blocks are still split near the target.
+        sizes = compressed_block_sizes(frame)
+        self.assertGreater(len(sizes), 4)
+        self.assertLess(sum(sizes) / len(sizes), 2 * 1340)
+
+        dobj = zstd.ZstdDecompressor().decompressobj()
+        self.assertEqual(dobj.decompress(frame), source)
+
+    @unittest.skipUnless(
+        "dedicated_dict_search" in zstd.backend_features,
+        "dedicated dict search not available",
+    )
+    def test_enable_dedicated_dict_search(self):
+        p = zstd.ZstdCompressionParameters()
+        self.assertEqual(p.enable_dedicated_dict_search, 0)
+
+        p = zstd.ZstdCompressionParameters.from_level(
+            5, enable_dedicated_dict_search=1
+        )
+        self.assertEqual(p.enable_dedicated_dict_search, 1)
+
+    @unittest.skipUnless(
+        "dedicated_dict_search" in zstd.backend_features,
+        "dedicated dict search not available",
+    )
+    def test_enable_dedicated_dict_search_round_trip(self):
+        samples = [
+            b"message %d: " % i + b"foobar" * (i % 16) for i in range(1024)
+        ]
+        d = zstd.train_dictionary(16384, samples)
+        inputs = [
+            b"message %d: " % i + b"foobar" * (i % 8)
+            for i in range(2000, 2100)
+        ]
+
+        def compress_all(params, precompute):
+            data = zstd.ZstdCompressionDict(d.as_bytes())
+            if precompute:
+                data.precompute_compress(compression_params=params)
+            cctx = zstd.ZstdCompressor(
+                dict_data=data, compression_params=params
+            )
+            return [cctx.compress(i) for i in inputs]
+
+        default = compress_all(
+            zstd.ZstdCompressionParameters.from_level(5, write_dict_id=True),
+            False,
+        )
+        dctx = zstd.ZstdDecompressor(dict_data=d)
+
+        for precompute in (False, True):
+            params = zstd.ZstdCompressionParameters.from_level(
+                5, write_dict_id=True, enable_dedicated_dict_search=1
+            )
+            frames = compress_all(params, precompute)
+
+            for frame, source in zip(frames, inputs):
+                self.assertEqual(
+                    zstd.get_frame_parameters(frame).dict_id, d.dict_id()
+                )
+                self.assertEqual(dctx.decompress(frame), source)
+
+            # The search trades a little ratio at most for speed.
+            self.assertLess(
+                sum(map(len, frames)), sum(map(len, default)) * 1.1
+            )
+
+    @unittest.skipUnless(
+        "effective_compression_parameters" in zstd.backend_features,
+        "effective compression parameters not available",
+    )
+    def test_repr(self):
+        p = zstd.ZstdCompressionParameters(
+            compression_level=19, write_checksum=True, threads=2
+        )
+
+        self.assertEqual(
+            repr(p),
+            "ZstdCompressionParameters(format=0, compression_level=19, "
+            "window_log=23, hash_log=22, chain_log=24, search_log=7, "
+            "min_match=3, target_length=256, strategy=9, "
+            "write_content_size=1, write_checksum=1, write_dict_id=0, "
+            "job_size=0, overlap_log=0, force_max_window=0, enable_ldm=0, "
+            "ldm_hash_log=0, ldm_min_match=0, ldm_bucket_size_log=0, "
+            "ldm_hash_rate_log=0, threads=2, target_cblock_size=0, "
+            "enable_dedicated_dict_search=0)",
+        )
+
     def test_overlap_log(self):
         p = zstd.ZstdCompressionParameters(overlap_log=2)
         self.assertEqual(p.overlap_log, 2)
 
+    @unittest.skipUnless(
+        "ldm_window_log" in zstd.backend_features,
+        "ldm window log not available",
+    )
+    def test_ldm_window_log(self):
+        p = zstd.ZstdCompressionParameters(enable_ldm=True)
+        self.assertEqual(p.enable_ldm, 1)
+        self.assertEqual(p.window_log, 27)
+
+        p = zstd.ZstdCompressionParameters(enable_ldm=True, window_log=20)
+        self.assertEqual(p.window_log, 20)
+
+        p = zstd.ZstdCompressionParameters(enable_ldm=False)
+        default = zstd.ZstdCompressionParameters.from_level(3)
+        self.assertEqual(p.window_log, default.window_log)
+
+        p = zstd.ZstdCompressionParameters.from_level(3, enable_ldm=True)
+        self.assertEqual(p.window_log, 27)
+
+        p = zstd.ZstdCompressionParameters.from_level(
+            22, enable_ldm=True, source_size=1 << 30
+        )
+        self.assertEqual(p.window_log, 27)
+
+        p = zstd.ZstdCompressionParameters.from_level(
+            3, enable_ldm=True, window_log=20
+        )
+        self.assertEqual(p.window_log, 20)
+
+    @unittest.skipUnless(
+        "ldm_window_log" in zstd.backend_features,
+        "ldm window log not available",
+    )
+    def test_ldm_round_trip(self):
+        p = zstd.ZstdCompressionParameters(
+            enable_ldm=True,
+            ldm_hash_log=20,
+            ldm_min_match=64,
+            ldm_bucket_size_log=4,
+            ldm_hash_rate_log=7,
+        )
+        self.assertEqual(p.ldm_hash_log, 20)
+        self.assertEqual(p.ldm_min_match, 64)
+        self.assertEqual(p.ldm_bucket_size_log, 4)
+        self.assertEqual(p.ldm_hash_rate_log, 7)
+
+        source = b"".join(b"line %d\n" % i for i in range(100000))
+        cctx = zstd.ZstdCompressor(compression_params=p)
+        frame = b"".join(cctx.read_to_iter(io.BytesIO(source)))
+
+        self.assertEqual(zstd.get_frame_parameters(frame).window_size, 2**27)
+
+        dctx = zstd.ZstdDecompressor(max_window_size=2**27)
+        self.assertEqual(dctx.stream_reader(frame).read(), source)
+
+
+    def test_frame_flags_defaults(self):
+        p = zstd.ZstdCompressionParameters()
+        self.assertEqual(p.write_content_size, 1)
+        self.assertEqual(p.write_checksum, 0)
+
+        p = zstd.ZstdCompressionParameters.from_level(3)
+        self.assertEqual(p.write_content_size, 1)
+        self.assertEqual(p.write_checksum, 0)
+
+    def test_write_content_size(self):
+        source = b"foobar" * 1024
+        with_size = zstd.ZstdCompressionParameters.from_level(3)
+        without_size = zstd.ZstdCompressionParameters.from_level(
+            3, write_content_size=False
+        )
+        self.assertEqual(without_size.write_content_size, 0)
+
+        frame = zstd.ZstdCompressor(compression_params=with_size).compress(
+            source
+        )
+        smaller = zstd.ZstdCompressor(
+            compression_params=without_size
+        ).compress(source)
+
+        self.assertLess(len(smaller), len(frame))
+        self.assertLessEqual(len(frame) - len(smaller), 8)
+        self.assertEqual(
+            zstd.get_frame_parameters(smaller).content_size,
+            zstd.CONTENTSIZE_UNKNOWN,
+        )
+
+        dctx = zstd.ZstdDecompressor()
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "could not determine content size"
+        ):
+            dctx.decompress(smaller)
+
+        self.assertEqual(
+            dctx.decompress(smaller, max_output_size=len(source)), source
+        )
+
+    def test_write_checksum(self):
+        source = b"foobar" * 1024
+        p = zstd.ZstdCompressionParameters.from_level(3, write_checksum=True)
+        self.assertEqual(p.write_checksum, 1)
+
+        frame = zstd.ZstdCompressor(compression_params=p).compress(source)
+        plain = zstd.ZstdCompressor(level=3).compress(source)
+
+        self.assertTrue(zstd.get_frame_parameters(frame).has_checksum)
+        self.assertEqual(len(frame), len(plain) + 4)
+
+        dctx = zstd.ZstdDecompressor()
+        self.assertEqual(dctx.decompress(frame), source)
+
+        corrupt = frame[:-1] + bytes([frame[-1] ^ 0xFF])
+        with self.assertRaisesRegex(zstd.ZstdError, "checksum"):
+            dctx.decompress(corrupt)
+
 
 class TestFrameParameters(unittest.TestCase):
     def test_invalid_type(self):
@@ -218,3 +656,47 @@ class TestFrameParameters(unittest.TestCase):
             self.assertEqual(params.window_size, 1024)
             self.assertEqual(params.dict_id, 0)
             self.assertFalse(params.has_checksum)
+
+    @unittest.skipUnless(
+        "decoder_window_size" in zstd.backend_features,
+        "decoder window size not available",
+    )
+    def test_window_size_single_segment(self):
+        # Single segment flag with a 1 byte content size and no window
+        # descriptor.
+        params = zstd.get_frame_parameters(zstd.FRAME_HEADER + b"\x20\x03")
+        self.assertEqual(params.content_size, 3)
+        self.assertEqual(params.window_size, 1024)
+
+        cctx = zstd.ZstdCompressor()
+        params = zstd.get_frame_parameters(cctx.compress(b"foo"))
+        self.assertEqual(params.content_size, 3)
+        self.assertEqual(params.window_size, 1024)
+
+        data = b"x" * 100000
+        params = zstd.get_frame_parameters(cctx.compress(data))
+        self.assertEqual(params.content_size, 100000)
+        self.assertEqual(params.window_size, 100000)
+
+    def test_window_size_without_content_size(self):
+        cctx = zstd.ZstdCompressor(write_content_size=False)
+        frame = cctx.compress(b"x" * 100000)
+        params = zstd.get_frame_parameters(frame)
+        self.assertEqual(params.content_size, zstd.CONTENTSIZE_UNKNOWN)
+        self.assertGreaterEqual(params.window_size, 100000)
+
+        # A streamed frame has neither content size nor single segment flag,
+        # so the window descriptor is authoritative.
+        cctx = zstd.ZstdCompressor(
+            compression_params=zstd.ZstdCompressionParameters(window_log=20)
+        )
+        frame = b"".join(cctx.read_to_iter(io.BytesIO(b"x" * 100000)))
+        params = zstd.get_frame_parameters(frame)
+        self.assertEqual(params.content_size, zstd.CONTENTSIZE_UNKNOWN)
+        self.assertEqual(params.window_size, 1 << 20)
+
+    def test_window_size_skippable_frame(self):
+        params = zstd.get_frame_parameters(
+            b"\x50\x2a\x4d\x18\x00\x00\x00\x00"
+        )
+        self.assertEqual(params.window_size, 0)
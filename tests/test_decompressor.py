@@ -1,7 +1,12 @@
+import io
+import random
 import unittest
 
 import zstandard as zstd
 
+# Raw zstd parameter value from zstd.h.
+ZSTD_D_WINDOW_LOG_MAX = 100
+
 
 class TestFrameHeaderSize(unittest.TestCase):
     def test_empty(self):
@@ -22,6 +27,27 @@ class TestFrameHeaderSize(unittest.TestCase):
         # It doesn't matter that it isn't a valid frame.
         self.assertEqual(zstd.frame_header_size(b"long enough but no magic"), 6)
 
+    @unittest.skipUnless(
+        "frame_header_size_validation" in zstd.backend_features,
+        "frame header size validation not available",
+    )
+    def test_minimum_size(self):
+        frame = zstd.ZstdCompressor().compress(b"foobar" * 64)
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "need at least %d bytes but got 5" % zstd.FRAMEHEADERSIZE_MIN,
+        ):
+            zstd.frame_header_size(frame[0:5])
+
+        size = zstd.frame_header_size(
+            memoryview(frame)[0 : zstd.FRAMEHEADERSIZE_MIN]
+        )
+        self.assertGreaterEqual(size, zstd.FRAMEHEADERSIZE_MIN)
+        self.assertLessEqual(size, zstd.FRAMEHEADERSIZE_MAX)
+        self.assertEqual(size, zstd.frame_header_size(frame))
+        self.assertEqual(frame[0:4], zstd.MAGIC_NUMBER.to_bytes(4, "little"))
+
 
 class TestFrameContentSize(unittest.TestCase):
     def test_empty_input(self):
@@ -66,3 +92,475 @@ class TestDecompressor(unittest.TestCase):
         dctx = zstd.ZstdDecompressor()
 
         self.assertGreater(dctx.memory_size(), 100)
+
+    def test_memory_size_streaming(self):
+        source = b"foobar" * 65536
+        cctx = zstd.ZstdCompressor()
+        frame = b"".join(cctx.read_to_iter(io.BytesIO(source)))
+
+        dctx = zstd.ZstdDecompressor()
+        initial = dctx.memory_size()
+        self.assertGreaterEqual(
+            initial, zstd.estimate_decompression_context_size()
+        )
+
+        # Streaming decompression allocates window and output buffers.
+        self.assertEqual(dctx.decompressobj().decompress(frame), source)
+        self.assertGreater(dctx.memory_size(), initial)
+
+
+@unittest.skipUnless(
+    "max_window_log" in zstd.backend_features, "max window log not available"
+)
+class TestDecompressor_max_window_log(unittest.TestCase):
+    def setUp(self):
+        self.source = b"".join(b"line %d\n" % i for i in range(300000))
+
+        # Streaming without a content size preserves the full window.
+        params = zstd.ZstdCompressionParameters(window_log=24, enable_ldm=True)
+        cctx = zstd.ZstdCompressor(compression_params=params)
+        self.frame = b"".join(cctx.read_to_iter(io.BytesIO(self.source)))
+
+        self.assertEqual(
+            zstd.get_frame_parameters(self.frame).window_size, 2**24
+        )
+
+    def test_invalid_values(self):
+        with self.assertRaisesRegex(
+            ValueError, "max_window_log must be between"
+        ):
+            zstd.ZstdDecompressor(max_window_log=1)
+
+        with self.assertRaisesRegex(
+            ValueError, "max_window_log must be between"
+        ):
+            zstd.ZstdDecompressor(max_window_log=64)
+
+        with self.assertRaisesRegex(
+            ValueError, "cannot specify both max_window_size and max_window_log"
+        ):
+            zstd.ZstdDecompressor(max_window_size=2**20, max_window_log=20)
+
+    def test_decompress(self):
+        dctx = zstd.ZstdDecompressor(max_window_log=20)
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "frame requires a window of 16777216 bytes, exceeding limit of "
+            "1048576 bytes",
+        ):
+            dctx.decompress(self.frame, max_output_size=len(self.source))
+
+        dctx = zstd.ZstdDecompressor(max_window_log=24)
+        self.assertEqual(
+            dctx.decompress(self.frame, max_output_size=len(self.source)),
+            self.source,
+        )
+
+    def test_decompress_content_size(self):
+        # Frames with a content size are decompressed in a single pass.
+        params = zstd.ZstdCompressionParameters(window_log=22)
+        frame = zstd.ZstdCompressor(compression_params=params).compress(
+            self.source
+        )
+        window_size = zstd.get_frame_parameters(frame).window_size
+        self.assertGreater(window_size, 2**20)
+
+        dctx = zstd.ZstdDecompressor(max_window_log=20)
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "frame requires a window of %d bytes" % window_size,
+        ):
+            dctx.decompress(frame)
+
+        dctx = zstd.ZstdDecompressor(max_window_log=22)
+        self.assertEqual(dctx.decompress(frame), self.source)
+
+    def test_stream_reader(self):
+        dctx = zstd.ZstdDecompressor(max_window_log=20)
+        with dctx.stream_reader(self.frame) as reader:
+            with self.assertRaisesRegex(
+                zstd.ZstdError,
+                "frame requires a window of 16777216 bytes, exceeding limit "
+                "of 1048576 bytes",
+            ):
+                reader.read()
+
+        dctx = zstd.ZstdDecompressor(max_window_log=24)
+        with dctx.stream_reader(self.frame) as reader:
+            self.assertEqual(reader.read(), self.source)
+
+    def test_stream_reader_window_logs(self):
+        dctx = zstd.ZstdDecompressor(max_window_log=20)
+        with dctx.stream_reader(io.BytesIO(self.frame)) as reader:
+            with self.assertRaisesRegex(
+                zstd.ZstdDecompressionError,
+                r"\(window log 24 > max_window_log 20\); raise max_window_log "
+                "to decompress it",
+            ) as cm:
+                reader.read(1024)
+
+        self.assertEqual(
+            cm.exception.error_name,
+            "Frame requires too much memory for decoding",
+        )
+
+        # max_window_size is enforced as the window log it rounds down to.
+        dctx = zstd.ZstdDecompressor(max_window_size=3 * 2**20)
+        with dctx.stream_reader(io.BytesIO(self.frame)) as reader:
+            with self.assertRaisesRegex(
+                zstd.ZstdError,
+                r"exceeding limit of 2097152 bytes \(window log 24 > "
+                r"max_window_log 21\)",
+            ):
+                reader.readline()
+
+    def test_decompress_max_window_size(self):
+        params = zstd.ZstdCompressionParameters(window_log=22)
+        frame = zstd.ZstdCompressor(compression_params=params).compress(
+            self.source
+        )
+
+        dctx = zstd.ZstdDecompressor(max_window_size=2**20)
+        with self.assertRaisesRegex(
+            zstd.ZstdError, r"\(window log 22 > max_window_log 20\)"
+        ):
+            dctx.decompress(frame)
+
+        dctx = zstd.ZstdDecompressor(max_window_size=2**22)
+        self.assertEqual(dctx.decompress(frame), self.source)
+
+    def test_decompressobj(self):
+        dctx = zstd.ZstdDecompressor(max_window_log=20)
+        dobj = dctx.decompressobj()
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "frame requires a window of 16777216 bytes, exceeding limit of "
+            "1048576 bytes",
+        ):
+            dobj.decompress(self.frame)
+
+        dctx = zstd.ZstdDecompressor(max_window_log=24)
+        dobj = dctx.decompressobj()
+        self.assertEqual(dobj.decompress(self.frame), self.source)
+
+    def test_read_to_iter(self):
+        dctx = zstd.ZstdDecompressor(max_window_log=20)
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "Frame requires too much memory for decoding"
+        ):
+            list(dctx.read_to_iter(io.BytesIO(self.frame)))
+
+        dctx = zstd.ZstdDecompressor(max_window_log=24)
+        self.assertEqual(
+            b"".join(dctx.read_to_iter(io.BytesIO(self.frame))), self.source
+        )
+
+
+@unittest.skipUnless(
+    "set_parameter" in zstd.backend_features, "set parameter not available"
+)
+class TestDecompressor_set_parameter(unittest.TestCase):
+    def test_set_parameter(self):
+        params = zstd.ZstdCompressionParameters(window_log=20)
+        frame = zstd.ZstdCompressor(compression_params=params).compress(
+            b"a" * 2000000
+        )
+        dctx = zstd.ZstdDecompressor()
+
+        self.assertEqual(dctx.set_parameter(ZSTD_D_WINDOW_LOG_MAX, 10), 0)
+
+        with self.assertRaisesRegex(
+            zstd.ZstdDecompressionError, "Frame requires too much memory"
+        ):
+            dctx.stream_reader(frame).read()
+
+    def test_errors(self):
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "unable to set parameter 999999: Unsupported"
+        ) as cm:
+            dctx.set_parameter(999999, 1)
+
+        self.assertEqual(cm.exception.error_name, "Unsupported parameter")
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "unable to set parameter 100: Parameter is out of bound",
+        ):
+            dctx.set_parameter(ZSTD_D_WINDOW_LOG_MAX, 1)
+
+
+@unittest.skipUnless(
+    "verify_checksum" in zstd.backend_features, "verify checksum not available"
+)
+class TestDecompressor_verify_checksum(unittest.TestCase):
+    def setUp(self):
+        self.source = b"foobar" * 1024
+        cctx = zstd.ZstdCompressor(write_checksum=True)
+        frame = cctx.compress(self.source)
+        self.assertTrue(zstd.get_frame_parameters(frame).has_checksum)
+
+        # Corrupt the trailing checksum only.
+        self.corrupt = frame[:-1] + bytes([frame[-1] ^ 0xFF])
+
+    def test_decompress(self):
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(zstd.ZstdError, "doesn't match checksum"):
+            dctx.decompress(self.corrupt)
+
+        dctx = zstd.ZstdDecompressor(verify_checksum=False)
+        self.assertEqual(dctx.decompress(self.corrupt), self.source)
+
+    def test_stream_reader(self):
+        dctx = zstd.ZstdDecompressor()
+
+        with dctx.stream_reader(self.corrupt) as reader:
+            with self.assertRaisesRegex(
+                zstd.ZstdError, "doesn't match checksum"
+            ):
+                reader.read()
+
+        dctx = zstd.ZstdDecompressor(verify_checksum=False)
+        with dctx.stream_reader(self.corrupt) as reader:
+            self.assertEqual(reader.read(), self.source)
+
+    def test_decompressobj(self):
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(zstd.ZstdError, "doesn't match checksum"):
+            dctx.decompressobj().decompress(self.corrupt)
+
+        dctx = zstd.ZstdDecompressor(verify_checksum=False)
+        self.assertEqual(
+            dctx.decompressobj().decompress(self.corrupt), self.source
+        )
+
+    def test_multi_decompress_to_buffer(self):
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(zstd.ZstdError, "doesn't match checksum"):
+            dctx.multi_decompress_to_buffer([self.corrupt])
+
+        dctx = zstd.ZstdDecompressor(verify_checksum=False)
+        result = dctx.multi_decompress_to_buffer([self.corrupt])
+        self.assertEqual(result[0].tobytes(), self.source)
+
+    def test_corrupt_content(self):
+        # Incompressible input is stored in a raw block, so flipping a bit
+        # alters the content without breaking the frame structure.
+        cctx = zstd.ZstdCompressor(level=1, write_checksum=True)
+        source = random.Random(0).randbytes(1024)
+        frame = bytearray(cctx.compress(source))
+        self.assertGreater(len(frame), len(source))
+        frame[-10] ^= 0x01
+
+        dctx = zstd.ZstdDecompressor()
+        with self.assertRaisesRegex(zstd.ZstdError, "doesn't match checksum"):
+            dctx.decompress(frame)
+
+    def test_truncated_checksum(self):
+        frame = self.corrupt[:-2]
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "did not decompress full frame"
+        ):
+            dctx.decompress(frame)
+
+        with dctx.stream_reader(frame, read_across_frames=True) as reader:
+            with self.assertRaisesRegex(
+                zstd.ZstdError,
+                "input ended with an incomplete frame or non-frame data",
+            ):
+                reader.read()
+
+
+@unittest.skipUnless(
+    "require_checksum" in zstd.backend_features,
+    "require checksum not available",
+)
+class TestDecompressor_require_checksum(unittest.TestCase):
+    def setUp(self):
+        self.source = b"foobar" * 1024
+        self.checksummed = zstd.ZstdCompressor(write_checksum=True).compress(
+            self.source
+        )
+        self.plain = zstd.ZstdCompressor(write_checksum=False).compress(
+            self.source
+        )
+        self.assertFalse(zstd.get_frame_parameters(self.plain).has_checksum)
+
+    def test_default(self):
+        dctx = zstd.ZstdDecompressor()
+        self.assertEqual(dctx.decompress(self.plain), self.source)
+
+    def test_decompress(self):
+        dctx = zstd.ZstdDecompressor(require_checksum=True)
+        self.assertEqual(dctx.decompress(self.checksummed), self.source)
+
+        with self.assertRaisesRegex(
+            zstd.ZstdDecompressionError,
+            "frame has no content checksum, which require_checksum disallows",
+        ):
+            dctx.decompress(self.plain)
+
+        output = bytearray(len(self.source))
+        self.assertEqual(
+            dctx.decompress_into(self.checksummed, output), len(self.source)
+        )
+
+        with self.assertRaisesRegex(zstd.ZstdError, "no content checksum"):
+            dctx.decompress_into(self.plain, output)
+
+    def test_stream_reader(self):
+        dctx = zstd.ZstdDecompressor(require_checksum=True)
+
+        with dctx.stream_reader(self.checksummed) as reader:
+            self.assertEqual(reader.read(), self.source)
+
+        with dctx.stream_reader(self.plain) as reader:
+            with self.assertRaisesRegex(zstd.ZstdError, "no content checksum"):
+                reader.read(1)
+
+        # Every frame is checked, not just the first.
+        data = self.checksummed + self.plain
+        with dctx.stream_reader(data, read_across_frames=True) as reader:
+            self.assertEqual(reader.read(len(self.source)), self.source)
+
+            with self.assertRaisesRegex(zstd.ZstdError, "no content checksum"):
+                reader.read(1)
+
+    def test_read_to_iter(self):
+        dctx = zstd.ZstdDecompressor(require_checksum=True)
+
+        self.assertEqual(
+            b"".join(dctx.read_to_iter(io.BytesIO(self.checksummed))),
+            self.source,
+        )
+
+        with self.assertRaisesRegex(zstd.ZstdError, "no content checksum"):
+            list(dctx.read_to_iter(self.plain))
+
+        data = self.checksummed + self.plain
+        with self.assertRaisesRegex(zstd.ZstdError, "no content checksum"):
+            list(dctx.read_to_iter(data, read_size=7, read_across_frames=True))
+
+    def test_decompress_stream(self):
+        dctx = zstd.ZstdDecompressor(require_checksum=True)
+
+        dest = io.BytesIO()
+        dctx.decompress_stream(io.BytesIO(self.checksummed), dest)
+        self.assertEqual(dest.getvalue(), self.source)
+
+        # The frame is rejected before any of its content is decoded.
+        dest = io.BytesIO()
+        source = io.BytesIO(self.checksummed + self.plain)
+        with self.assertRaisesRegex(zstd.ZstdError, "no content checksum"):
+            dctx.decompress_stream(source, dest, read_across_frames=True)
+
+        self.assertEqual(dest.getvalue(), self.source)
+
+    def test_validate(self):
+        dctx = zstd.ZstdDecompressor(require_checksum=True)
+        self.assertTrue(dctx.validate(self.checksummed))
+
+        with self.assertRaisesRegex(zstd.ZstdError, "no content checksum"):
+            dctx.validate(self.checksummed + self.plain)
+
+    def test_decompress_to_buffer(self):
+        dctx = zstd.ZstdDecompressor(require_checksum=True)
+
+        result = dctx.decompress_to_buffer(self.checksummed * 2)
+        self.assertEqual(result.tobytes(), self.source * 2)
+
+        with self.assertRaisesRegex(zstd.ZstdError, "no content checksum"):
+            dctx.decompress_to_buffer(self.checksummed + self.plain)
+
+    def test_decompress_content_dict_chain(self):
+        dctx = zstd.ZstdDecompressor(require_checksum=True)
+
+        with self.assertRaisesRegex(zstd.ZstdError, "no content checksum"):
+            dctx.decompress_content_dict_chain([self.plain])
+
+        self.assertEqual(
+            dctx.decompress_content_dict_chain([self.checksummed]),
+            self.source,
+        )
+
+    def test_skippable_frame(self):
+        skippable = b"\x50\x2a\x4d\x18\x04\x00\x00\x00abcd"
+        dctx = zstd.ZstdDecompressor(require_checksum=True)
+
+        self.assertTrue(dctx.validate(skippable + self.checksummed))
+
+        data = skippable + self.checksummed
+        with dctx.stream_reader(data, read_across_frames=True) as reader:
+            self.assertEqual(reader.read(), self.source)
+
+    def test_decompressobj(self):
+        dctx = zstd.ZstdDecompressor(require_checksum=True)
+
+        dobj = dctx.decompressobj()
+        self.assertEqual(dobj.decompress(self.checksummed), self.source)
+
+        # A header split across calls is checked once it is complete.
+        dobj = dctx.decompressobj()
+        self.assertEqual(dobj.decompress(self.plain[:3]), b"")
+        self.assertTrue(dobj.needs_input)
+
+        with self.assertRaisesRegex(zstd.ZstdError, "no content checksum"):
+            dobj.decompress(self.plain[3:])
+
+        dobj = dctx.decompressobj(read_across_frames=True)
+        self.assertEqual(dobj.decompress(self.checksummed), self.source)
+
+        with self.assertRaisesRegex(zstd.ZstdError, "no content checksum"):
+            dobj.decompress(self.plain)
+
+    def test_stream_writer(self):
+        dctx = zstd.ZstdDecompressor(require_checksum=True)
+
+        dest = io.BytesIO()
+        writer = dctx.stream_writer(dest)
+        for i in range(len(self.checksummed)):
+            writer.write(self.checksummed[i : i + 1])
+
+        self.assertEqual(dest.getvalue(), self.source)
+
+        dest = io.BytesIO()
+        writer = dctx.stream_writer(dest)
+        self.assertEqual(writer.write(self.checksummed), len(self.checksummed))
+        self.assertEqual(writer.write(self.plain[:3]), 3)
+
+        with self.assertRaisesRegex(zstd.ZstdError, "no content checksum"):
+            writer.write(self.plain[3:])
+
+        self.assertEqual(dest.getvalue(), self.source)
+
+    def test_copy_stream(self):
+        dctx = zstd.ZstdDecompressor(require_checksum=True)
+
+        dest = io.BytesIO()
+        dctx.copy_stream(io.BytesIO(self.checksummed), dest)
+        self.assertEqual(dest.getvalue(), self.source)
+
+        dest = io.BytesIO()
+        source = io.BytesIO(self.checksummed + self.plain)
+        with self.assertRaisesRegex(zstd.ZstdError, "no content checksum"):
+            dctx.copy_stream(source, dest, read_size=5)
+
+        self.assertEqual(dest.getvalue(), self.source)
+
+    def test_multi_decompress_to_buffer(self):
+        dctx = zstd.ZstdDecompressor(require_checksum=True)
+
+        result = dctx.multi_decompress_to_buffer([self.checksummed] * 2)
+        self.assertEqual(result[1].tobytes(), self.source)
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "error decompressing item 1: frame has no content checksum",
+        ):
+            dctx.multi_decompress_to_buffer([self.checksummed, self.plain])
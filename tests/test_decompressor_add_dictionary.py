@@ -0,0 +1,219 @@
+import io
+import unittest
+
+import zstandard as zstd
+
+from .common import NonClosingBytesIO
+
+
+def train(prefix, dict_id):
+    samples = [b"%s %d: " % (prefix, i) + prefix * 16 for i in range(128)]
+    return zstd.train_dictionary(8192, samples, dict_id=dict_id)
+
+
+@unittest.skipUnless(
+    "add_dictionary" in zstd.backend_features, "add dictionary not available"
+)
+class TestDecompressor_add_dictionary(unittest.TestCase):
+    def setUp(self):
+        self.foo = train(b"foo", 1000)
+        self.bar = train(b"bar", 2000)
+        self.foo_source = b"foo 1000: " + b"foo" * 16
+        self.bar_source = b"bar 1000: " + b"bar" * 16
+
+        self.foo_frame = zstd.ZstdCompressor(dict_data=self.foo).compress(
+            self.foo_source
+        )
+        self.bar_frame = zstd.ZstdCompressor(dict_data=self.bar).compress(
+            self.bar_source
+        )
+        self.plain_frame = zstd.ZstdCompressor().compress(b"plain" * 16)
+
+        self.frames = self.foo_frame + self.bar_frame + self.plain_frame
+        self.sources = self.foo_source + self.bar_source + b"plain" * 16
+
+    def dctx(self, **kwargs):
+        dctx = zstd.ZstdDecompressor(**kwargs)
+        dctx.add_dictionary(self.foo)
+        dctx.add_dictionary(self.bar)
+
+        return dctx
+
+    def test_decompress(self):
+        dctx = self.dctx()
+
+        for _ in range(2):
+            self.assertEqual(dctx.decompress(self.foo_frame), self.foo_source)
+            self.assertEqual(dctx.decompress(self.bar_frame), self.bar_source)
+
+        # Frames without a dictionary don't pick up the last one used.
+        self.assertEqual(dctx.decompress(self.plain_frame), b"plain" * 16)
+
+        output = bytearray(len(self.bar_source))
+        self.assertEqual(
+            dctx.decompress_into(self.bar_frame, output), len(output)
+        )
+        self.assertEqual(output, self.bar_source)
+
+    def test_unknown_dict_id(self):
+        dctx = zstd.ZstdDecompressor()
+        dctx.add_dictionary(self.foo)
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "frame requires dictionary 2000, which has not been added",
+        ):
+            dctx.decompress(self.bar_frame)
+
+        with self.assertRaisesRegex(zstd.ZstdError, "dictionary 2000"):
+            dctx.decompress_to_buffer(self.foo_frame + self.bar_frame)
+
+    def test_dict_data(self):
+        # dict_data is used for frames without a dictionary ID and for those
+        # naming it.
+        cctx = zstd.ZstdCompressor(dict_data=self.foo, write_dict_id=False)
+        no_id_frame = cctx.compress(self.foo_source)
+
+        dctx = zstd.ZstdDecompressor(dict_data=self.foo)
+        dctx.add_dictionary(self.bar)
+
+        self.assertEqual(dctx.decompress(no_id_frame), self.foo_source)
+        self.assertEqual(dctx.decompress(self.foo_frame), self.foo_source)
+        self.assertEqual(dctx.decompress(self.bar_frame), self.bar_source)
+
+    def test_decompress_to_buffer(self):
+        dctx = self.dctx()
+        data = self.foo_frame + self.bar_frame + self.plain_frame
+
+        result = dctx.decompress_to_buffer(data)
+        self.assertEqual(
+            [segment.tobytes() for segment in result],
+            [self.foo_source, self.bar_source, b"plain" * 16],
+        )
+
+    def test_validate(self):
+        dctx = self.dctx()
+        self.assertTrue(
+            dctx.validate(self.foo_frame + self.bar_frame + self.plain_frame)
+        )
+
+        with self.assertRaisesRegex(zstd.ZstdError, "dictionary 3000"):
+            other = zstd.ZstdCompressor(dict_data=train(b"baz", 3000))
+            dctx.validate(self.foo_frame + other.compress(b"baz" * 16))
+
+    def test_replace(self):
+        dctx = zstd.ZstdDecompressor()
+        dctx.add_dictionary(train(b"baz", 1000))
+        dctx.add_dictionary(self.foo)
+
+        self.assertEqual(dctx.decompress(self.foo_frame), self.foo_source)
+
+    def test_no_dict_id(self):
+        dctx = zstd.ZstdDecompressor()
+        raw = zstd.ZstdCompressionDict(
+            b"foobar" * 64, dict_type=zstd.DICT_TYPE_RAWCONTENT
+        )
+
+        with self.assertRaisesRegex(
+            ValueError, "dictionary has no dictionary ID"
+        ):
+            dctx.add_dictionary(raw)
+
+    def test_stream_reader(self):
+        dctx = self.dctx()
+
+        with dctx.stream_reader(self.frames, read_across_frames=True) as reader:
+            self.assertEqual(reader.read(), self.sources)
+
+        with dctx.stream_reader(self.bar_frame, read_size=3) as reader:
+            self.assertEqual(reader.read(), self.bar_source)
+
+        dctx = zstd.ZstdDecompressor()
+        dctx.add_dictionary(self.foo)
+
+        data = self.foo_frame + self.bar_frame
+        with dctx.stream_reader(data, read_across_frames=True) as reader:
+            self.assertEqual(reader.read(len(self.foo_source)), self.foo_source)
+
+            with self.assertRaisesRegex(zstd.ZstdError, "dictionary 2000"):
+                reader.read()
+
+    def test_seekable_stream_reader(self):
+        if "seekable_stream_reader" not in zstd.backend_features:
+            self.skipTest("seekable stream reader not available")
+
+        dest = NonClosingBytesIO()
+        cctx = zstd.ZstdCompressor(dict_data=self.bar)
+        with cctx.seekable_stream_writer(dest, max_frame_size=16) as writer:
+            writer.write(self.bar_source)
+
+        reader = self.dctx().seekable_stream_reader(dest.getvalue())
+        self.assertGreater(reader.frame_count(), 1)
+        self.assertEqual(reader.read(), self.bar_source)
+
+    def test_read_to_iter(self):
+        dctx = self.dctx()
+
+        chunks = dctx.read_to_iter(
+            self.frames, read_size=5, read_across_frames=True
+        )
+        self.assertEqual(b"".join(chunks), self.sources)
+
+    def test_decompressobj(self):
+        dctx = self.dctx()
+
+        dobj = dctx.decompressobj(read_across_frames=True)
+        output = b"".join(
+            dobj.decompress(self.frames[i : i + 1])
+            for i in range(len(self.frames))
+        )
+        self.assertEqual(output, self.sources)
+
+        dobj = dctx.decompressobj()
+        self.assertEqual(dobj.decompress(self.bar_frame), self.bar_source)
+
+    def test_stream_writer(self):
+        dctx = self.dctx()
+
+        dest = io.BytesIO()
+        writer = dctx.stream_writer(dest)
+        for i in range(len(self.frames)):
+            writer.write(self.frames[i : i + 1])
+
+        self.assertEqual(dest.getvalue(), self.sources)
+
+    def test_copy_stream(self):
+        dctx = self.dctx()
+
+        dest = io.BytesIO()
+        dctx.copy_stream(io.BytesIO(self.frames), dest, read_size=5)
+        self.assertEqual(dest.getvalue(), self.sources)
+
+    def test_decompress_stream(self):
+        dctx = self.dctx()
+
+        dest = io.BytesIO()
+        dctx.decompress_stream(
+            io.BytesIO(self.frames), dest, read_across_frames=True
+        )
+        self.assertEqual(dest.getvalue(), self.sources)
+
+    def test_multi_decompress_to_buffer(self):
+        dctx = self.dctx()
+        frames = [self.foo_frame, self.bar_frame, self.plain_frame]
+
+        for threads in (0, 2):
+            result = dctx.multi_decompress_to_buffer(frames, threads=threads)
+            self.assertEqual(
+                [segment.tobytes() for segment in result],
+                [self.foo_source, self.bar_source, b"plain" * 16],
+            )
+
+        dctx = zstd.ZstdDecompressor()
+        dctx.add_dictionary(self.foo)
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "error decompressing item 1: frame requires dictionary 2000",
+        ):
+            dctx.multi_decompress_to_buffer(frames)
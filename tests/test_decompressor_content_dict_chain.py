@@ -1,7 +1,13 @@
+import os
 import unittest
 
 import zstandard as zstd
 
+from .common import (
+    generate_samples,
+    get_optimal_dict_size_heuristically,
+)
+
 
 class TestDecompressor_content_dict_chain(unittest.TestCase):
     def test_bad_inputs_simple(self):
@@ -113,3 +119,50 @@ class TestDecompressor_content_dict_chain(unittest.TestCase):
             dctx = zstd.ZstdDecompressor()
             decompressed = dctx.decompress_content_dict_chain(chain)
             self.assertEqual(decompressed, expected)
+
+    def test_prefix_required(self):
+        # Random content can only be compressed well by referencing the
+        # previous chunk, so each frame requires its content dictionary.
+        base = os.urandom(8192)
+        original = [base]
+        for i in range(4):
+            original.append(original[-1][1024:] + os.urandom(1024))
+
+        chunks = [zstd.ZstdCompressor().compress(original[0])]
+        for i, chunk in enumerate(original[1:]):
+            d = zstd.ZstdCompressionDict(
+                original[i], dict_type=zstd.DICT_TYPE_RAWCONTENT
+            )
+            cctx = zstd.ZstdCompressor(dict_data=d)
+            chunks.append(cctx.compress(chunk))
+            self.assertLess(len(chunks[-1]), 4096)
+
+        dctx = zstd.ZstdDecompressor()
+        self.assertEqual(
+            dctx.decompress_content_dict_chain(chunks), original[-1]
+        )
+
+    @unittest.skipUnless(
+        "content_dict_chain_prefix" in zstd.backend_features,
+        "content dict chain prefix not available",
+    )
+    def test_structured_dictionary(self):
+        samples = generate_samples()
+        d = zstd.train_dictionary(
+            get_optimal_dict_size_heuristically(samples), samples, k=64, d=8
+        )
+
+        initial = zstd.ZstdCompressor().compress(b"foo" * 64)
+        frame = zstd.ZstdCompressor(dict_data=d).compress(b"foo" * 64)
+
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "chunk 0 requires dictionary"
+        ):
+            dctx.decompress_content_dict_chain([frame])
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "chunk 1 requires dictionary"
+        ):
+            dctx.decompress_content_dict_chain([initial, frame])
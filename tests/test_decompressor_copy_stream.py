@@ -68,6 +68,19 @@ class TestDecompressor_copy_stream(unittest.TestCase):
         self.assertEqual(source._read_count, len(source.getvalue()) + 1)
         self.assertEqual(dest._write_count, len(dest.getvalue()))
 
+    def test_counts_match_io(self):
+        data = b"foobar" * 65536 + b"x" * 100
+        cctx = zstd.ZstdCompressor(write_checksum=True)
+        source = CustomBytesIO(cctx.compress(data) + cctx.compress(data))
+        dest = CustomBytesIO()
+
+        dctx = zstd.ZstdDecompressor()
+        r, w = dctx.copy_stream(source, dest, read_size=7, write_size=8192)
+
+        self.assertEqual(r, len(source.getvalue()))
+        self.assertEqual(w, len(dest.getvalue()))
+        self.assertEqual(dest.getvalue(), data * 2)
+
     def test_read_exception(self):
         source = CustomBytesIO(zstd.ZstdCompressor().compress(b"foo" * 1024))
         dest = CustomBytesIO()
@@ -45,7 +45,53 @@ class TestDecompressor_decompress(unittest.TestCase):
         compressed = cctx.compress(b"foo")
 
         dctx = zstd.ZstdDecompressor(format=zstd.FORMAT_ZSTD1_MAGICLESS)
-        self.assertEqual(dctx.decompress(compressed), b"foo")
+        self.assertEqual(dctx.decompress(compressed, max_output_size=3), b"foo")
+
+    @unittest.skipUnless(
+        "compressor_format" in zstd.backend_features,
+        "compressor format not available",
+    )
+    def test_magicless_round_trip(self):
+        source = b"foobar" * 64
+        magic = zstd.ZstdCompressor().compress(source)
+        no_magic = zstd.ZstdCompressor(format="magic-less").compress(source)
+
+        self.assertEqual(len(no_magic), len(magic) - 4)
+        self.assertEqual(magic[4:], no_magic)
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "error determining content size"
+        ):
+            zstd.ZstdDecompressor().decompress(no_magic)
+
+        dctx = zstd.ZstdDecompressor(format="magic-less")
+        self.assertEqual(
+            dctx.decompress(no_magic, max_output_size=len(source)), source
+        )
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "max_output_size must be specified"
+        ):
+            dctx.decompress(no_magic)
+
+    @unittest.skipUnless(
+        "compressor_format" in zstd.backend_features,
+        "compressor format not available",
+    )
+    def test_bad_format(self):
+        with self.assertRaisesRegex(ValueError, "invalid format value: foo"):
+            zstd.ZstdCompressor(format="foo")
+
+        with self.assertRaisesRegex(ValueError, "invalid format value: foo"):
+            zstd.ZstdDecompressor(format="foo")
+
+        with self.assertRaisesRegex(
+            ValueError, "cannot define compression_params and format"
+        ):
+            zstd.ZstdCompressor(
+                compression_params=zstd.ZstdCompressionParameters(),
+                format="magic-less",
+            )
 
     def test_no_content_size_in_frame(self):
         cctx = zstd.ZstdCompressor(write_content_size=False)
@@ -107,10 +153,96 @@ class TestDecompressor_decompress(unittest.TestCase):
         compressed = cctx.compress(b"foobar" * 256)
         dctx = zstd.ZstdDecompressor()
 
-        # Will get OverflowError on some Python distributions that can't
-        # handle really large integers.
-        with self.assertRaises((MemoryError, OverflowError)):
-            dctx.decompress(compressed, max_output_size=2**62)
+        if "growable_decompress_output" in zstd.backend_features:
+            # The output buffer grows as needed instead of being preallocated.
+            self.assertEqual(
+                dctx.decompress(compressed, max_output_size=2**62),
+                b"foobar" * 256,
+            )
+        else:
+            # Will get OverflowError on some Python distributions that can't
+            # handle really large integers.
+            with self.assertRaises((MemoryError, OverflowError)):
+                dctx.decompress(compressed, max_output_size=2**62)
+
+    @unittest.skipUnless(
+        "growable_decompress_output" in zstd.backend_features,
+        "growable decompress output not available",
+    )
+    def test_max_output_size_streaming(self):
+        cctx = zstd.ZstdCompressor(write_content_size=False)
+        source = b"".join(b"line %d\n" % i for i in range(262144))
+        compressed = cctx.compress(source)
+        dctx = zstd.ZstdDecompressor()
+
+        # Output spans many growths of the output buffer.
+        self.assertGreater(len(source), 2**21)
+        self.assertEqual(
+            dctx.decompress(compressed, max_output_size=2**40), source
+        )
+        self.assertEqual(
+            dctx.decompress(compressed, max_output_size=len(source)), source
+        )
+
+        with self.assertRaisesRegex(
+            zstd.ZstdDecompressionError,
+            "output exceeds max_output_size of %d bytes" % (len(source) - 1),
+        ) as cm:
+            dctx.decompress(compressed, max_output_size=len(source) - 1)
+
+        self.assertNotIsInstance(cm.exception, zstd.ZstdTruncatedError)
+
+        with self.assertRaises(zstd.ZstdTruncatedError):
+            dctx.decompress(compressed[:-1], max_output_size=2**40)
+
+    @unittest.skipUnless(
+        "decompress_initial_size" in zstd.backend_features,
+        "decompress initial size not available",
+    )
+    def test_initial_size(self):
+        cctx = zstd.ZstdCompressor(write_content_size=False)
+        source = b"".join(b"line %d\n" % i for i in range(131072))
+        compressed = cctx.compress(source)
+        dctx = zstd.ZstdDecompressor()
+
+        # Hints below, at, and above the output size all work.
+        for initial_size in (0, 1, 65536, len(source), len(source) + 1):
+            self.assertEqual(
+                dctx.decompress(
+                    compressed,
+                    max_output_size=2**40,
+                    initial_size=initial_size,
+                ),
+                source,
+            )
+
+        # The hint is allocated up front...
+        with self.assertRaises(MemoryError):
+            dctx.decompress(
+                compressed, max_output_size=2**62, initial_size=2**62
+            )
+
+        # ...but clamped to max_output_size.
+        self.assertEqual(
+            dctx.decompress(
+                compressed, max_output_size=len(source), initial_size=2**62
+            ),
+            source,
+        )
+
+        with self.assertRaisesRegex(
+            zstd.ZstdDecompressionError,
+            "output exceeds max_output_size of %d bytes" % (len(source) - 1),
+        ):
+            dctx.decompress(
+                compressed,
+                max_output_size=len(source) - 1,
+                initial_size=len(source),
+            )
+
+        # Frames with a content size ignore the hint.
+        frame = zstd.ZstdCompressor().compress(source)
+        self.assertEqual(dctx.decompress(frame, initial_size=2**62), source)
 
     def test_dictionary(self):
         samples = []
@@ -0,0 +1,125 @@
+import io
+import unittest
+
+import zstandard as zstd
+
+
+@unittest.skipUnless(
+    "decompress_into" in zstd.backend_features, "decompress into not available"
+)
+class TestDecompressor_decompress_into(unittest.TestCase):
+    def test_content_size(self):
+        source = b"foobar" * 1024
+        frame = zstd.ZstdCompressor().compress(source)
+        dctx = zstd.ZstdDecompressor()
+
+        output = bytearray(len(source))
+        self.assertEqual(dctx.decompress_into(frame, output), len(source))
+        self.assertEqual(output, source)
+
+    def test_larger_output(self):
+        source = b"foobar" * 1024
+        frame = zstd.ZstdCompressor().compress(source)
+        dctx = zstd.ZstdDecompressor()
+
+        output = bytearray(len(source) + 10)
+        self.assertEqual(dctx.decompress_into(frame, output), len(source))
+        self.assertEqual(output[: len(source)], source)
+        self.assertEqual(output[len(source) :], b"\x00" * 10)
+
+    def test_output_too_small(self):
+        source = b"foobar" * 1024
+        frame = zstd.ZstdCompressor().compress(source)
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "decompressed size of 6144 bytes exceeds output buffer size of 6143 bytes",
+        ):
+            dctx.decompress_into(frame, bytearray(len(source) - 1))
+
+    def test_empty(self):
+        frame = zstd.ZstdCompressor().compress(b"")
+        dctx = zstd.ZstdDecompressor()
+
+        self.assertEqual(dctx.decompress_into(frame, bytearray()), 0)
+
+    def test_no_content_size(self):
+        source = b"foobar" * 65536
+        cctx = zstd.ZstdCompressor(write_content_size=False)
+        frame = cctx.compress(source)
+        dctx = zstd.ZstdDecompressor()
+
+        output = bytearray(len(source))
+        self.assertEqual(dctx.decompress_into(frame, output), len(source))
+        self.assertEqual(output, source)
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "decompressed data exceeds output limit of 1024 bytes"
+        ):
+            dctx.decompress_into(frame, bytearray(1024))
+
+    def test_max_output_size(self):
+        source = b"foobar" * 65536
+        cctx = zstd.ZstdCompressor(write_content_size=False)
+        frame = cctx.compress(source)
+        dctx = zstd.ZstdDecompressor()
+
+        output = bytearray(len(source) * 2)
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "decompressed data exceeds output limit of 4096 bytes"
+        ):
+            dctx.decompress_into(frame, output, max_output_size=4096)
+
+        self.assertEqual(
+            dctx.decompress_into(frame, output, max_output_size=len(source)),
+            len(source),
+        )
+        self.assertEqual(output[: len(source)], source)
+
+    def test_memoryview_output(self):
+        source = b"foobar" * 1024
+        frame = zstd.ZstdCompressor().compress(source)
+        dctx = zstd.ZstdDecompressor()
+
+        output = bytearray(len(source) + 100)
+        view = memoryview(output)[100:]
+        self.assertEqual(dctx.decompress_into(frame, view), len(source))
+        self.assertEqual(output[100:], source)
+
+    def test_readonly_output(self):
+        frame = zstd.ZstdCompressor().compress(b"foobar")
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(
+            ValueError, "output buffer is not writable"
+        ):
+            dctx.decompress_into(frame, b"\x00" * 6)
+
+    def test_truncated_input(self):
+        source = b"foobar" * 1024
+        cctx = zstd.ZstdCompressor(level=1)
+        frame = b"".join(cctx.read_to_iter(io.BytesIO(source)))
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "did not decompress full frame"
+        ):
+            dctx.decompress_into(frame[:-1], bytearray(len(source)))
+
+    def test_dictionary(self):
+        samples = []
+        for i in range(128):
+            samples.append(b"foo" * 64)
+            samples.append(b"bar" * 64)
+            samples.append(b"foobar" * 64)
+
+        d = zstd.train_dictionary(8192, samples)
+        source = b"foobar" * 64
+        frame = zstd.ZstdCompressor(dict_data=d).compress(source)
+        dctx = zstd.ZstdDecompressor(dict_data=d)
+
+        output = bytearray(len(source))
+        self.assertEqual(dctx.decompress_into(frame, output), len(source))
+        self.assertEqual(output, source)
@@ -0,0 +1,143 @@
+import io
+import unittest
+
+import zstandard as zstd
+
+
+class ShortReadIO(io.BytesIO):
+    """Returns at most 3 bytes from each read() call."""
+
+    def read(self, size=-1):
+        return super().read(min(size, 3) if size >= 0 else 3)
+
+
+@unittest.skipUnless(
+    "decompress_stream" in zstd.backend_features,
+    "decompress stream not available",
+)
+class TestDecompressor_decompress_stream(unittest.TestCase):
+    def test_round_trip(self):
+        source = b"".join(b"line %d\n" % i for i in range(65536))
+        cctx = zstd.ZstdCompressor(level=1)
+        compressed = io.BytesIO()
+        cctx.copy_stream(io.BytesIO(source), compressed)
+
+        dctx = zstd.ZstdDecompressor()
+        dest = io.BytesIO()
+        r, w = dctx.decompress_stream(io.BytesIO(compressed.getvalue()), dest)
+
+        self.assertEqual(r, len(compressed.getvalue()))
+        self.assertEqual(w, len(source))
+        self.assertEqual(dest.getvalue(), source)
+
+    def test_buffer_source(self):
+        frame = zstd.ZstdCompressor().compress(b"foobar" * 1024)
+        dest = io.BytesIO()
+
+        dctx = zstd.ZstdDecompressor()
+        self.assertEqual(
+            dctx.decompress_stream(frame, dest), (len(frame), 6144)
+        )
+        self.assertEqual(dest.getvalue(), b"foobar" * 1024)
+
+    def test_validation(self):
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(
+            ValueError, "must pass an object with a read"
+        ):
+            dctx.decompress_stream(object(), io.BytesIO())
+
+        with self.assertRaisesRegex(
+            ValueError, "second argument must have a write"
+        ):
+            dctx.decompress_stream(io.BytesIO(), object())
+
+    def test_empty(self):
+        dctx = zstd.ZstdDecompressor()
+        dest = io.BytesIO()
+
+        self.assertEqual(dctx.decompress_stream(io.BytesIO(), dest), (0, 0))
+        self.assertEqual(dest.getvalue(), b"")
+
+    def test_short_reads(self):
+        source = b"foobar" * 8192
+        frame = zstd.ZstdCompressor(write_checksum=True).compress(source)
+
+        dctx = zstd.ZstdDecompressor()
+        dest = io.BytesIO()
+        r, w = dctx.decompress_stream(ShortReadIO(frame), dest, read_size=1024)
+
+        self.assertEqual((r, w), (len(frame), len(source)))
+        self.assertEqual(dest.getvalue(), source)
+
+    def test_small_write_size(self):
+        source = bytes(range(256)) * 512
+        frame = zstd.ZstdCompressor().compress(source)
+
+        dctx = zstd.ZstdDecompressor()
+        dest = io.BytesIO()
+        dctx.decompress_stream(io.BytesIO(frame), dest, write_size=1)
+
+        self.assertEqual(dest.getvalue(), source)
+
+    def test_read_across_frames(self):
+        cctx = zstd.ZstdCompressor()
+        first = cctx.compress(b"foo" * 1024)
+        second = cctx.compress(b"bar" * 1024)
+        data = first + second
+
+        dctx = zstd.ZstdDecompressor()
+        dest = io.BytesIO()
+        r, w = dctx.decompress_stream(io.BytesIO(data), dest)
+        self.assertEqual((r, w), (len(first), 3072))
+        self.assertEqual(dest.getvalue(), b"foo" * 1024)
+
+        dest = io.BytesIO()
+        r, w = dctx.decompress_stream(
+            ShortReadIO(data), dest, read_across_frames=True
+        )
+        self.assertEqual((r, w), (len(data), 6144))
+        self.assertEqual(dest.getvalue(), b"foo" * 1024 + b"bar" * 1024)
+
+    def test_truncated(self):
+        frame = zstd.ZstdCompressor().compress(b"foobar" * 1024)
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(
+            zstd.ZstdTruncatedError, "input ended with an incomplete frame"
+        ):
+            dctx.decompress_stream(io.BytesIO(frame[:-1]), io.BytesIO())
+
+        with self.assertRaises(zstd.ZstdTruncatedError):
+            dctx.decompress_stream(
+                io.BytesIO(frame + frame[:10]),
+                io.BytesIO(),
+                read_across_frames=True,
+            )
+
+    def test_corrupt(self):
+        frame = bytearray(
+            zstd.ZstdCompressor(write_checksum=True).compress(b"foo" * 1024)
+        )
+        frame[-1] ^= 0xFF
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(
+            zstd.ZstdDecompressionError, "Restored data doesn't match checksum"
+        ):
+            dctx.decompress_stream(io.BytesIO(frame), io.BytesIO())
+
+        with self.assertRaisesRegex(zstd.ZstdError, "Unknown frame descriptor"):
+            dctx.decompress_stream(io.BytesIO(b"\x00" * 16), io.BytesIO())
+
+    def test_write_exception(self):
+        class BadWriter:
+            def write(self, data):
+                raise OSError("write failed")
+
+        frame = zstd.ZstdCompressor().compress(b"foo")
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(OSError, "write failed"):
+            dctx.decompress_stream(io.BytesIO(frame), BadWriter())
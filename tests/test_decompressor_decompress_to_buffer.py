@@ -0,0 +1,202 @@
+import struct
+import unittest
+
+import zstandard as zstd
+
+from .common import (
+    sizeless_frame,
+)
+
+
+SKIPPABLE = b"\x50\x2a\x4d\x18\x04\x00\x00\x00abcd"
+
+
+@unittest.skipUnless(
+    "decompress_to_buffer" in zstd.backend_features,
+    "decompress to buffer not available",
+)
+class TestDecompressor_decompress_to_buffer(unittest.TestCase):
+    def test_frames(self):
+        cctx = zstd.ZstdCompressor()
+        sources = [b"foo" * 100, b"bar" * 2000, b"x"]
+        data = b"".join(cctx.compress(source) for source in sources)
+
+        result = zstd.ZstdDecompressor().decompress_to_buffer(data)
+
+        self.assertIsInstance(result, zstd.BufferWithSegments)
+        self.assertEqual(len(result), 3)
+        self.assertEqual(result.size, sum(len(s) for s in sources))
+        self.assertEqual([s.tobytes() for s in result], sources)
+        self.assertEqual(result.tobytes(), b"".join(sources))
+
+        # Segments are laid out back to back in one allocation.
+        self.assertEqual([result[i].offset for i in range(3)], [0, 300, 6300])
+
+    def test_buffer_types(self):
+        data = zstd.ZstdCompressor().compress(b"foobar" * 1024)
+        dctx = zstd.ZstdDecompressor()
+
+        for source in (bytearray(data), memoryview(data)):
+            result = dctx.decompress_to_buffer(source)
+            self.assertEqual(result[0].tobytes(), b"foobar" * 1024)
+
+    def test_empty(self):
+        dctx = zstd.ZstdDecompressor()
+
+        result = dctx.decompress_to_buffer(b"")
+        self.assertEqual(len(result), 0)
+        self.assertEqual(result.size, 0)
+
+        frame = zstd.ZstdCompressor().compress(b"")
+        result = dctx.decompress_to_buffer(frame + frame)
+        self.assertEqual([s.tobytes() for s in result], [b"", b""])
+
+    def test_skippable_frames(self):
+        cctx = zstd.ZstdCompressor()
+        data = (
+            SKIPPABLE
+            + cctx.compress(b"foo")
+            + SKIPPABLE
+            + cctx.compress(b"bar")
+        )
+
+        result = zstd.ZstdDecompressor().decompress_to_buffer(data)
+        self.assertEqual([s.tobytes() for s in result], [b"foo", b"bar"])
+
+    def test_decompressed_sizes(self):
+        cctx = zstd.ZstdCompressor()
+        data = (
+            cctx.compress(b"foo" * 100)
+            + sizeless_frame(b"bar" * 200)
+            + cctx.compress(b"baz")
+        )
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "could not determine decompressed size of frame 1; pass "
+            "decompressed_sizes",
+        ):
+            dctx.decompress_to_buffer(data)
+
+        result = dctx.decompress_to_buffer(data, decompressed_sizes=[0, 600, 0])
+        self.assertEqual(
+            [s.tobytes() for s in result], [b"foo" * 100, b"bar" * 200, b"baz"]
+        )
+
+        with self.assertRaisesRegex(
+            ValueError, "decompressed_sizes size mismatch; expected 3, got 2"
+        ):
+            dctx.decompress_to_buffer(data, decompressed_sizes=[0, 600])
+
+    def test_wrong_size(self):
+        data = zstd.ZstdCompressor().compress(b"a") + sizeless_frame(b"foo")
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(
+            zstd.ZstdDecompressionError,
+            "error decompressing frame 1: decompressed 3 bytes; expected 10",
+        ):
+            dctx.decompress_to_buffer(data, decompressed_sizes=[0, 10])
+
+        with self.assertRaisesRegex(
+            zstd.ZstdDecompressionError,
+            "error decompressing frame 1: decompressed more than expected 2 "
+            "bytes",
+        ):
+            dctx.decompress_to_buffer(data, decompressed_sizes=[0, 2])
+
+        # The decompressor remains usable.
+        result = dctx.decompress_to_buffer(data, decompressed_sizes=[0, 3])
+        self.assertEqual([s.tobytes() for s in result], [b"a", b"foo"])
+
+    def test_bogus_content_size(self):
+        # A single segment frame declaring 1 GiB over a 3 byte raw block.
+        lie = (
+            zstd.FRAME_HEADER
+            + b"\xa0"
+            + struct.pack("<I", 2**30)
+            + b"\x19\x00\x00foo"
+        )
+        data = zstd.ZstdCompressor().compress(b"a") + lie
+        dctx = zstd.ZstdDecompressor()
+
+        # Rejected before the declared size is allocated.
+        with self.assertRaisesRegex(
+            zstd.ZstdDecompressionError,
+            "frame 1 can't decompress to 1073741824 bytes; its blocks produce "
+            "at most 131072 bytes",
+        ):
+            dctx.decompress_to_buffer(data, max_output_size=0)
+
+        # Sizes given for frames are bounded the same way.
+        with self.assertRaisesRegex(
+            zstd.ZstdDecompressionError,
+            "frame 1 can't decompress to 131073 bytes",
+        ):
+            dctx.decompress_to_buffer(
+                zstd.ZstdCompressor().compress(b"a") + sizeless_frame(b"foo"),
+                decompressed_sizes=[0, 131073],
+            )
+
+    def test_max_output_size(self):
+        data = zstd.ZstdCompressor().compress(b"foo" * 100) * 2
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(
+            zstd.ZstdDecompressionError,
+            "frame 1 brings the decompressed size to 600 bytes, exceeding "
+            "max_output_size of 599 bytes",
+        ):
+            dctx.decompress_to_buffer(data, max_output_size=599)
+
+        result = dctx.decompress_to_buffer(data, max_output_size=600)
+        self.assertEqual(result.size, 600)
+
+        # A valid frame of 8193 128 KiB RLE blocks exceeds the 1 GiB default.
+        blocks = 8193
+        rle = b"\x02\x00\x10a"
+        frame = (
+            zstd.FRAME_HEADER
+            + b"\xe0"
+            + struct.pack("<Q", blocks * 131072)
+            + rle * (blocks - 1)
+            + b"\x03\x00\x10a"
+        )
+
+        with self.assertRaisesRegex(
+            zstd.ZstdDecompressionError,
+            "exceeding max_output_size of 1073741824 bytes",
+        ):
+            dctx.decompress_to_buffer(frame)
+
+    def test_corrupt_frame(self):
+        cctx = zstd.ZstdCompressor(write_checksum=True)
+        second = cctx.compress(b"bar" * 100)
+        second = second[:-1] + bytes([second[-1] ^ 0xFF])
+        data = cctx.compress(b"foo") + second
+
+        with self.assertRaisesRegex(
+            zstd.ZstdDecompressionError, "error decompressing frame 1: "
+        ) as cm:
+            zstd.ZstdDecompressor().decompress_to_buffer(data)
+
+        self.assertEqual(
+            cm.exception.error_name, "Restored data doesn't match checksum"
+        )
+
+        with self.assertRaisesRegex(
+            zstd.ZstdTruncatedError, "frame at offset 0 is truncated"
+        ):
+            zstd.ZstdDecompressor().decompress_to_buffer(second[:-2])
+
+    def test_dictionary(self):
+        samples = [b"foo%dbar" % i * 64 for i in range(128)]
+        d = zstd.train_dictionary(8192, samples)
+        cctx = zstd.ZstdCompressor(dict_data=d)
+        data = cctx.compress(b"foo1bar" * 64) + cctx.compress(b"foo2bar" * 64)
+
+        result = zstd.ZstdDecompressor(dict_data=d).decompress_to_buffer(data)
+        self.assertEqual(
+            [s.tobytes() for s in result], [b"foo1bar" * 64, b"foo2bar" * 64]
+        )
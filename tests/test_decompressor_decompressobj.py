@@ -60,6 +60,32 @@ class TestDecompressor_decompressobj(unittest.TestCase):
         self.assertTrue(dobj.eof)
         self.assertEqual(dobj.unused_data, b"extra")
 
+    def test_unused_data_incremental(self):
+        source = b"".join(b"line %d\n" % i for i in range(8192))
+        frame = zstd.ZstdCompressor(level=1).compress(source)
+        data = frame + b"HTTP/1.1 200 OK\r\n"
+
+        dctx = zstd.ZstdDecompressor()
+
+        for chunk_size in (1, 7, 1024, len(frame)):
+            dobj = dctx.decompressobj()
+            chunks = []
+            offset = 0
+
+            while not dobj.eof:
+                self.assertEqual(dobj.unused_data, b"")
+                chunk = data[offset : offset + chunk_size]
+                offset += len(chunk)
+                chunks.append(dobj.decompress(chunk))
+
+            self.assertEqual(b"".join(chunks), source)
+            self.assertEqual(
+                dobj.unused_data + data[offset:], b"HTTP/1.1 200 OK\r\n"
+            )
+
+            # Only bytes from the final decompress() call are unused.
+            self.assertEqual(dobj.unused_data, data[len(frame) : offset])
+
     def test_reuse(self):
         data = zstd.ZstdCompressor(level=1).compress(b"foobar")
 
@@ -145,3 +171,204 @@ class TestDecompressor_decompressobj(unittest.TestCase):
         self.assertEqual(dobj.decompress(foo + bar), b"foobar")
         self.assertEqual(dobj.unused_data, b"")
         self.assertEqual(dobj.unconsumed_tail, b"")
+
+    def test_read_across_frames_trailing_data(self):
+        cctx = zstd.ZstdCompressor()
+        foo = cctx.compress(b"foo")
+        bar = cctx.compress(b"bar")
+
+        dctx = zstd.ZstdDecompressor()
+        dobj = dctx.decompressobj(read_across_frames=True)
+
+        self.assertEqual(dobj.decompress(foo + bar), b"foobar")
+        with self.assertRaisesRegex(zstd.ZstdError, "Unknown frame descriptor"):
+            dobj.decompress(b"garbage")
+
+
+@unittest.skipUnless(
+    "decompressobj_reset" in zstd.backend_features,
+    "decompressobj reset not available",
+)
+class TestDecompressor_decompressobj_reset(unittest.TestCase):
+    def test_reuse(self):
+        cctx = zstd.ZstdCompressor(level=1)
+        frames = [cctx.compress(b"frame %d" % i) for i in range(10)]
+
+        dctx = zstd.ZstdDecompressor()
+        dobj = dctx.decompressobj()
+
+        for i, frame in enumerate(frames):
+            self.assertEqual(dobj.decompress(frame + b"extra"), b"frame %d" % i)
+            self.assertTrue(dobj.eof)
+            self.assertEqual(dobj.unused_data, b"extra")
+
+            dobj.reset()
+            self.assertFalse(dobj.eof)
+            self.assertEqual(dobj.unused_data, b"")
+
+    def test_reset_mid_frame(self):
+        cctx = zstd.ZstdCompressor(level=1, write_content_size=False)
+        first = cctx.compress(b"foobar" * 4096)
+        second = cctx.compress(b"bazqux" * 4096)
+
+        dctx = zstd.ZstdDecompressor()
+        dobj = dctx.decompressobj()
+        partial = dobj.decompress(first[0 : len(first) // 2])
+        self.assertFalse(dobj.eof)
+        self.assertLess(len(partial), len(b"foobar" * 4096))
+
+        dobj.reset()
+        self.assertEqual(dobj.decompress(second), b"bazqux" * 4096)
+        self.assertTrue(dobj.eof)
+
+    def test_preserves_dictionary(self):
+        samples = []
+        for i in range(128):
+            samples.append(b"foo" * 64)
+            samples.append(b"bar" * 64)
+            samples.append(b"foobar" * 64)
+
+        d = zstd.train_dictionary(8192, samples)
+        cctx = zstd.ZstdCompressor(level=1, dict_data=d)
+        frames = [cctx.compress(b"foobar" * 64), cctx.compress(b"bar" * 64)]
+
+        dctx = zstd.ZstdDecompressor(dict_data=d)
+        dobj = dctx.decompressobj()
+        self.assertEqual(dobj.decompress(frames[0]), b"foobar" * 64)
+        dobj.reset()
+        self.assertEqual(dobj.decompress(frames[1]), b"bar" * 64)
+
+    def test_preserves_max_window_log(self):
+        params = zstd.ZstdCompressionParameters(window_log=22)
+        cctx = zstd.ZstdCompressor(compression_params=params)
+        frame = cctx.compress(b"foo" * 2000000)
+
+        dctx = zstd.ZstdDecompressor(max_window_log=20)
+        dobj = dctx.decompressobj()
+        self.assertEqual(dobj.decompress(cctx.compress(b"foo")), b"foo")
+        dobj.reset()
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "Frame requires too much memory for decoding"
+        ):
+            dobj.decompress(frame)
+
+
+@unittest.skipUnless(
+    "next_input_hint" in zstd.backend_features, "next input hint not available"
+)
+class TestDecompressor_decompressobj_next_input_hint(unittest.TestCase):
+    def test_follow_hint(self):
+        source = bytes(range(256)) * 4096
+        cctx = zstd.ZstdCompressor(level=1, write_checksum=True)
+        frame = b"".join(cctx.read_to_iter(source, write_size=1024))
+
+        dobj = zstd.ZstdDecompressor().decompressobj()
+        hint = dobj.next_input_hint()
+        self.assertGreater(hint, 0)
+
+        offset = 0
+        chunks = []
+        calls = 0
+        while not dobj.eof:
+            hint = dobj.next_input_hint()
+            self.assertGreater(hint, 0)
+            chunks.append(dobj.decompress(frame[offset : offset + hint]))
+            offset += hint
+            calls += 1
+
+        self.assertEqual(b"".join(chunks), source)
+        self.assertEqual(offset, len(frame))
+        self.assertEqual(dobj.unused_data, b"")
+        self.assertEqual(dobj.next_input_hint(), 0)
+        self.assertGreater(calls, 2)
+
+    def test_advisory(self):
+        source = b"foobar" * 65536
+        frame = zstd.ZstdCompressor(level=1).compress(source)
+
+        for size in (1, 7, 4096, len(frame)):
+            dobj = zstd.ZstdDecompressor().decompressobj()
+            chunks = []
+            for offset in range(0, len(frame), size):
+                chunks.append(dobj.decompress(frame[offset : offset + size]))
+
+            self.assertEqual(b"".join(chunks), source)
+            self.assertEqual(dobj.next_input_hint(), 0)
+
+    def test_updates(self):
+        frame = zstd.ZstdCompressor(level=1).compress(b"foobar" * 65536)
+        dobj = zstd.ZstdDecompressor().decompressobj()
+        initial = dobj.next_input_hint()
+
+        dobj.decompress(frame[0:3])
+        self.assertEqual(dobj.next_input_hint(), initial - 3)
+
+        # Empty input leaves the hint alone.
+        dobj.decompress(b"")
+        self.assertEqual(dobj.next_input_hint(), initial - 3)
+
+        dobj.reset()
+        self.assertEqual(dobj.next_input_hint(), initial)
+
+
+@unittest.skipUnless(
+    "needs_input" in zstd.backend_features, "needs input not available"
+)
+class TestDecompressor_decompressobj_needs_input(unittest.TestCase):
+    def setUp(self):
+        self.source = bytes(range(256)) * 2048
+        cctx = zstd.ZstdCompressor(level=1, write_checksum=True)
+        self.frame = b"".join(cctx.read_to_iter(self.source, write_size=1024))
+
+    def test_byte_at_a_time(self):
+        dobj = zstd.ZstdDecompressor().decompressobj()
+        self.assertTrue(dobj.needs_input)
+
+        chunks = []
+        for i in range(len(self.frame)):
+            self.assertTrue(dobj.needs_input)
+            self.assertFalse(dobj.eof)
+            chunks.append(dobj.decompress(self.frame[i : i + 1]))
+
+        self.assertEqual(b"".join(chunks), self.source)
+        self.assertFalse(dobj.needs_input)
+        self.assertTrue(dobj.eof)
+        self.assertEqual(dobj.unused_data, b"")
+
+        # Output is produced as blocks complete, not only at the end.
+        self.assertGreater(len([chunk for chunk in chunks if chunk]), 1)
+
+    def test_split_anywhere(self):
+        for split in (1, 4, 5, 6, 100, len(self.frame) - 1):
+            dobj = zstd.ZstdDecompressor().decompressobj()
+            first = dobj.decompress(self.frame[:split])
+            self.assertTrue(dobj.needs_input)
+
+            second = dobj.decompress(self.frame[split:])
+            self.assertFalse(dobj.needs_input)
+            self.assertEqual(first + second, self.source)
+
+    def test_read_across_frames(self):
+        other = zstd.ZstdCompressor().compress(b"foobar")
+        data = self.frame + other
+        dobj = zstd.ZstdDecompressor().decompressobj(read_across_frames=True)
+
+        boundaries = []
+        chunks = []
+        for i in range(len(data)):
+            chunks.append(dobj.decompress(data[i : i + 1]))
+            if not dobj.needs_input:
+                boundaries.append(i + 1)
+
+        self.assertEqual(boundaries, [len(self.frame), len(data)])
+        self.assertEqual(b"".join(chunks), self.source + b"foobar")
+        self.assertFalse(dobj.eof)
+
+    def test_reset(self):
+        dobj = zstd.ZstdDecompressor().decompressobj()
+        dobj.decompress(self.frame)
+        self.assertFalse(dobj.needs_input)
+
+        dobj.reset()
+        self.assertTrue(dobj.needs_input)
@@ -0,0 +1,77 @@
+import io
+import unittest
+
+import zstandard as zstd
+
+
+@unittest.skipUnless(
+    "frame_bound" in zstd.backend_features, "frame bound not available"
+)
+class TestDecompressor_frame_bound(unittest.TestCase):
+    def test_empty_input(self):
+        dctx = zstd.ZstdDecompressor()
+        self.assertEqual(dctx.frame_bound(b""), 0)
+
+    def test_invalid_input(self):
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "error determining decompressed size bound"
+        ):
+            dctx.frame_bound(b"foobarbaz")
+
+    def test_truncated_frame(self):
+        cctx = zstd.ZstdCompressor()
+        frame = cctx.compress(b"foobar" * 1024)
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "error determining decompressed size bound"
+        ):
+            dctx.frame_bound(frame[:-1])
+
+    def test_content_size(self):
+        cctx = zstd.ZstdCompressor()
+        frame = cctx.compress(b"foobar" * 1024)
+        dctx = zstd.ZstdDecompressor()
+
+        self.assertEqual(dctx.frame_bound(frame), 6144)
+
+    def test_no_content_size(self):
+        source = b"foobar" * 65536
+        cctx = zstd.ZstdCompressor()
+        frame = b"".join(cctx.read_to_iter(io.BytesIO(source)))
+        dctx = zstd.ZstdDecompressor()
+
+        self.assertEqual(
+            zstd.get_frame_parameters(frame).content_size,
+            zstd.CONTENTSIZE_UNKNOWN,
+        )
+        self.assertGreaterEqual(dctx.frame_bound(frame), len(source))
+
+    def test_multiple_frames(self):
+        cctx = zstd.ZstdCompressor()
+        frames = [
+            cctx.compress(b"foo" * 100),
+            cctx.compress(b"bar" * 200),
+            cctx.compress(b""),
+        ]
+        dctx = zstd.ZstdDecompressor()
+
+        self.assertEqual(dctx.frame_bound(b"".join(frames)), 900)
+
+    def test_skippable_frame(self):
+        cctx = zstd.ZstdCompressor()
+        skippable = b"\x50\x2a\x4d\x18\x04\x00\x00\x00abcd"
+        data = skippable + cctx.compress(b"foo" * 100)
+        dctx = zstd.ZstdDecompressor()
+
+        self.assertEqual(dctx.frame_bound(data), 300)
+
+    def test_buffer_types(self):
+        cctx = zstd.ZstdCompressor()
+        frame = cctx.compress(b"foo" * 100)
+        dctx = zstd.ZstdDecompressor()
+
+        for source in (frame, bytearray(frame), memoryview(frame)):
+            self.assertEqual(dctx.frame_bound(source), 300)
@@ -0,0 +1,129 @@
+import io
+import tempfile
+import unittest
+
+import zstandard as zstd
+
+from .common import (
+    NonSeekableReader,
+    sizeless_frame,
+)
+
+
+@unittest.skipUnless(
+    "decompressor_frame_content_size" in zstd.backend_features,
+    "decompressor frame content size not available",
+)
+class TestDecompressor_frame_content_size(unittest.TestCase):
+    def test_buffer(self):
+        frame = zstd.ZstdCompressor().compress(b"foobar" * 1024)
+        dctx = zstd.ZstdDecompressor()
+
+        for source in (frame, bytearray(frame), memoryview(frame)):
+            self.assertEqual(dctx.frame_content_size(source), 6144)
+
+        self.assertEqual(
+            dctx.frame_content_size(zstd.ZstdCompressor().compress(b"")), 0
+        )
+
+    def test_seekable(self):
+        frame = zstd.ZstdCompressor().compress(b"foobar" * 1024)
+        dctx = zstd.ZstdDecompressor()
+
+        source = io.BytesIO(b"prefix" + frame)
+        source.seek(6)
+        self.assertEqual(dctx.frame_content_size(source), 6144)
+        self.assertEqual(source.tell(), 6)
+
+        # The position is restored on error too.
+        source.seek(1)
+        with self.assertRaises(zstd.ZstdError):
+            dctx.frame_content_size(source)
+        self.assertEqual(source.tell(), 1)
+
+    def test_file(self):
+        frame = zstd.ZstdCompressor().compress(b"foobar" * 1024)
+
+        with tempfile.TemporaryFile() as fh:
+            fh.write(frame)
+            fh.seek(0)
+
+            dctx = zstd.ZstdDecompressor()
+            self.assertEqual(dctx.frame_content_size(fh), 6144)
+            self.assertEqual(fh.tell(), 0)
+            self.assertEqual(fh.read(), frame)
+
+    def test_non_seekable(self):
+        frame = zstd.ZstdCompressor().compress(b"foobar" * 1024)
+        source = NonSeekableReader(frame)
+
+        dctx = zstd.ZstdDecompressor()
+        self.assertEqual(dctx.frame_content_size(source), 6144)
+
+        # Only the frame header is consumed.
+        self.assertEqual(source.tell(), zstd.frame_header_size(frame))
+        self.assertEqual(sum(source.reads), zstd.frame_header_size(frame))
+
+    def test_no_content_size(self):
+        frame = sizeless_frame(b"foobar" * 1024)
+        dctx = zstd.ZstdDecompressor()
+
+        for source in (frame, io.BytesIO(frame)):
+            with self.assertRaisesRegex(
+                zstd.ZstdError, "frame header does not declare the content size"
+            ):
+                dctx.frame_content_size(source)
+
+    def test_skippable_frame(self):
+        skippable = b"\x50\x2a\x4d\x18\x04\x00\x00\x00abcd"
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "source begins with a skippable frame"
+        ):
+            dctx.frame_content_size(io.BytesIO(skippable))
+
+    def test_truncated(self):
+        frame = zstd.ZstdCompressor().compress(b"foobar" * 1024)
+        dctx = zstd.ZstdDecompressor()
+
+        for size in (0, 3, 5):
+            with self.assertRaisesRegex(
+                zstd.ZstdTruncatedError,
+                "source ended after %d bytes, before the end of the frame "
+                "header" % size,
+            ):
+                dctx.frame_content_size(io.BytesIO(frame[:size]))
+
+        with self.assertRaisesRegex(
+            zstd.ZstdTruncatedError,
+            "need at least 7 bytes to parse the frame header but got 5",
+        ):
+            dctx.frame_content_size(frame[:5])
+
+    def test_invalid_input(self):
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(
+            zstd.ZstdDecompressionError, "invalid frame header"
+        ) as cm:
+            dctx.frame_content_size(io.BytesIO(b"foobarbaz"))
+
+        self.assertEqual(cm.exception.error_name, "Unknown frame descriptor")
+
+        with self.assertRaisesRegex(
+            ValueError, "must pass an object with a read\\(\\) method"
+        ):
+            dctx.frame_content_size(42)
+
+    def test_magicless(self):
+        cctx = zstd.ZstdCompressor(
+            compression_params=zstd.ZstdCompressionParameters(
+                format=zstd.FORMAT_ZSTD1_MAGICLESS
+            )
+        )
+        frame = cctx.compress(b"foobar" * 1024)
+        dctx = zstd.ZstdDecompressor(format=zstd.FORMAT_ZSTD1_MAGICLESS)
+
+        self.assertEqual(dctx.frame_content_size(frame), 6144)
+        self.assertEqual(dctx.frame_content_size(io.BytesIO(frame)), 6144)
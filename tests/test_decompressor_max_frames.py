@@ -0,0 +1,107 @@
+import io
+import unittest
+
+import zstandard as zstd
+
+
+@unittest.skipUnless(
+    "max_frames" in zstd.backend_features, "max frames not available"
+)
+class TestDecompressor_max_frames(unittest.TestCase):
+    def setUp(self):
+        cctx = zstd.ZstdCompressor()
+        self.frames = [cctx.compress(b"frame %d" % i) for i in range(10)]
+        self.data = b"".join(self.frames)
+        self.expected = b"".join(b"frame %d" % i for i in range(10))
+
+    def test_stream_reader(self):
+        dctx = zstd.ZstdDecompressor()
+
+        with dctx.stream_reader(
+            self.data, read_across_frames=True, max_frames=10
+        ) as reader:
+            self.assertEqual(reader.read(), self.expected)
+
+        with dctx.stream_reader(
+            self.data, read_across_frames=True, max_frames=9
+        ) as reader:
+            with self.assertRaisesRegex(
+                zstd.ZstdDecompressionError,
+                "input contains more than 9 frames, exceeding max_frames",
+            ):
+                reader.read()
+
+    def test_stream_reader_small_reads(self):
+        dctx = zstd.ZstdDecompressor()
+
+        # The count spans read() calls and never resets.
+        with dctx.stream_reader(
+            io.BytesIO(self.data),
+            read_size=1,
+            read_across_frames=True,
+            max_frames=3,
+        ) as reader:
+            chunks = []
+            with self.assertRaisesRegex(zstd.ZstdError, "more than 3 frames"):
+                while True:
+                    chunks.append(reader.read(1))
+
+        self.assertEqual(b"".join(chunks), b"frame 0frame 1frame 2")
+
+    def test_read_to_iter(self):
+        dctx = zstd.ZstdDecompressor()
+
+        chunks = list(
+            dctx.read_to_iter(
+                io.BytesIO(self.data), read_across_frames=True, max_frames=10
+            )
+        )
+        self.assertEqual(b"".join(chunks), self.expected)
+
+        it = dctx.read_to_iter(
+            io.BytesIO(self.data),
+            read_size=1,
+            read_across_frames=True,
+            max_frames=2,
+        )
+        chunks = []
+        with self.assertRaisesRegex(
+            zstd.ZstdDecompressionError,
+            "input contains more than 2 frames, exceeding max_frames",
+        ):
+            for chunk in it:
+                chunks.append(chunk)
+
+        self.assertEqual(b"".join(chunks), b"frame 0frame 1")
+
+    def test_skippable_frames(self):
+        skippable = b"\x50\x2a\x4d\x18\x04\x00\x00\x00abcd"
+        dctx = zstd.ZstdDecompressor()
+
+        with dctx.stream_reader(
+            skippable * 5 + self.frames[0],
+            read_across_frames=True,
+            max_frames=5,
+        ) as reader:
+            with self.assertRaisesRegex(zstd.ZstdError, "more than 5 frames"):
+                reader.read()
+
+    def test_unlimited(self):
+        data = zstd.ZstdCompressor().compress(b"") * 10000
+        dctx = zstd.ZstdDecompressor()
+
+        with dctx.stream_reader(data, read_across_frames=True) as reader:
+            self.assertEqual(reader.read(), b"")
+
+        self.assertEqual(
+            list(dctx.read_to_iter(data, read_across_frames=True)), []
+        )
+
+    def test_new_reader_resets_count(self):
+        dctx = zstd.ZstdDecompressor()
+
+        for _ in range(3):
+            with dctx.stream_reader(
+                self.data, read_across_frames=True, max_frames=10
+            ) as reader:
+                self.assertEqual(reader.read(), self.expected)
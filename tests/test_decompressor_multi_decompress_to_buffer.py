@@ -225,3 +225,237 @@ class TestDecompressor_multi_decompress_to_buffer(unittest.TestCase):
             "error decompressing item 1: (Data corruption detected|Destination buffer is too small)",
         ):
             dctx.multi_decompress_to_buffer(frames, threads=2)
+
+    @unittest.skipUnless(
+        "multi_decompress_size_validation" in zstd.backend_features,
+        "multi decompress size validation not available",
+    )
+    def test_decompressed_sizes_list(self):
+        cctx = zstd.ZstdCompressor(write_content_size=False)
+
+        original = [b"foo" * 4, b"bar" * 6, b"baz" * 8 * 1024]
+        frames = [cctx.compress(d) for d in original]
+
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(
+            ValueError, "could not determine decompressed size of item 0"
+        ):
+            dctx.multi_decompress_to_buffer(frames)
+
+        for threads in (0, 2):
+            result = dctx.multi_decompress_to_buffer(
+                frames,
+                decompressed_sizes=[len(d) for d in original],
+                threads=threads,
+            )
+
+            self.assertEqual(len(result), len(frames))
+            for i, data in enumerate(original):
+                self.assertEqual(result[i].tobytes(), data)
+
+    @unittest.skipUnless(
+        "multi_decompress_size_validation" in zstd.backend_features,
+        "multi decompress size validation not available",
+    )
+    def test_decompressed_sizes_magic_less(self):
+        cctx = zstd.ZstdCompressor(format="magic-less")
+
+        original = [b"foo" * 4, b"bar" * 6]
+        frames = [cctx.compress(d) for d in original]
+        sizes = struct.pack("<" + "Q" * len(original), *map(len, original))
+
+        dctx = zstd.ZstdDecompressor(format="magic-less")
+        result = dctx.multi_decompress_to_buffer(
+            frames, decompressed_sizes=sizes
+        )
+
+        for i, data in enumerate(original):
+            self.assertEqual(result[i].tobytes(), data)
+
+    @unittest.skipUnless(
+        "multi_decompress_size_validation" in zstd.backend_features,
+        "multi decompress size validation not available",
+    )
+    def test_decompressed_sizes_mismatch(self):
+        cctx = zstd.ZstdCompressor(write_content_size=False)
+
+        original = [b"foo" * 4, b"bar" * 6, b"baz" * 8]
+        frames = [cctx.compress(d) for d in original]
+
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "error decompressing item 1: decompressed 18 bytes; expected 20",
+        ):
+            dctx.multi_decompress_to_buffer(
+                frames, decompressed_sizes=[12, 20, 24]
+            )
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "error decompressing item 2: decompressed more than expected 10 "
+            "bytes",
+        ):
+            dctx.multi_decompress_to_buffer(
+                frames, decompressed_sizes=[12, 18, 10], threads=2
+            )
+
+    @unittest.skipUnless(
+        "multi_decompress_size_validation" in zstd.backend_features,
+        "multi decompress size validation not available",
+    )
+    def test_decompressed_sizes_invalid(self):
+        frames = [zstd.ZstdCompressor().compress(b"foo")]
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(
+            ValueError,
+            "decompressed_sizes buffer length must be a multiple of 8",
+        ):
+            dctx.multi_decompress_to_buffer(frames, decompressed_sizes=b"\x03")
+
+        with self.assertRaisesRegex(
+            TypeError,
+            "decompressed_sizes must only contain non-negative integers",
+        ):
+            dctx.multi_decompress_to_buffer(frames, decompressed_sizes=[-1])
+
+    @unittest.skipUnless(
+        "multi_decompress_output" in zstd.backend_features,
+        "multi decompress output not available",
+    )
+    def test_output(self):
+        cctx = zstd.ZstdCompressor()
+        original = [b"foo%d" % i * (i + 1) for i in range(64)]
+        frames = [cctx.compress(d) for d in original]
+
+        data = bytearray(65536)
+        output = zstd.BufferWithSegments(data, b"")
+        dctx = zstd.ZstdDecompressor()
+
+        for threads in (0, 2, -1):
+            result = dctx.multi_decompress_to_buffer(
+                frames, threads=threads, output=output
+            )
+
+            self.assertEqual(len(result), len(original))
+            self.assertEqual(result.size(), sum(map(len, original)))
+            self.assertEqual(
+                [result[i].tobytes() for i in range(len(result))], original
+            )
+            self.assertEqual(result[1].offset, len(original[0]))
+
+        # Results are written to the caller's memory.
+        self.assertEqual(data[: len(original[0])], original[0])
+
+        # Reusing the output overwrites earlier results.
+        frames = [cctx.compress(b"bar" * 4)]
+        result = dctx.multi_decompress_to_buffer(frames, output=output)
+        self.assertEqual(result[0].tobytes(), b"bar" * 4)
+        self.assertEqual(data[:12], b"bar" * 4)
+
+    @unittest.skipUnless(
+        "multi_decompress_output" in zstd.backend_features,
+        "multi decompress output not available",
+    )
+    def test_output_exported_views(self):
+        frames = [zstd.ZstdCompressor().compress(b"foo" * 4)]
+        output = zstd.BufferWithSegments(bytearray(12), b"")
+        dctx = zstd.ZstdDecompressor()
+
+        result = dctx.multi_decompress_to_buffer(frames, output=output)
+        views = [memoryview(result[0]), memoryview(output)]
+
+        with self.assertRaisesRegex(
+            ValueError,
+            "output buffer has 2 exported views; release them before "
+            "reusing it",
+        ):
+            dctx.multi_decompress_to_buffer(frames, output=output)
+
+        self.assertEqual(bytes(views[0]), b"foo" * 4)
+
+        for view in views:
+            view.release()
+
+        result = dctx.multi_decompress_to_buffer(frames, output=output)
+        self.assertEqual(result[0].tobytes(), b"foo" * 4)
+
+    @unittest.skipUnless(
+        "multi_decompress_output" in zstd.backend_features,
+        "multi decompress output not available",
+    )
+    def test_output_decompressed_sizes(self):
+        cctx = zstd.ZstdCompressor(write_content_size=False)
+        original = [b"foo" * 4, b"bar" * 6]
+        frames = [cctx.compress(d) for d in original]
+
+        output = zstd.BufferWithSegments(bytearray(30), b"")
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(
+            ValueError, "could not determine decompressed size of item 0"
+        ):
+            dctx.multi_decompress_to_buffer(frames, output=output)
+
+        result = dctx.multi_decompress_to_buffer(
+            frames, decompressed_sizes=[12, 18], output=output
+        )
+        self.assertEqual(result[0].tobytes(), original[0])
+        self.assertEqual(result[1].tobytes(), original[1])
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "error decompressing item 1: decompressed more than expected 17 "
+            "bytes",
+        ):
+            dctx.multi_decompress_to_buffer(
+                frames, decompressed_sizes=[12, 17], output=output
+            )
+
+    @unittest.skipUnless(
+        "multi_decompress_output" in zstd.backend_features,
+        "multi decompress output not available",
+    )
+    def test_output_too_small(self):
+        frames = [zstd.ZstdCompressor().compress(b"foo" * 4)] * 3
+        output = zstd.BufferWithSegments(bytearray(35), b"")
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "output buffer too small; need 36 bytes, got 35"
+        ):
+            zstd.ZstdDecompressor().multi_decompress_to_buffer(
+                frames, output=output
+            )
+
+    @unittest.skipUnless(
+        "multi_decompress_output" in zstd.backend_features,
+        "multi decompress output not available",
+    )
+    def test_output_invalid(self):
+        frame = zstd.ZstdCompressor().compress(b"foo" * 4)
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(
+            ValueError, "output buffer must be writable"
+        ):
+            dctx.multi_decompress_to_buffer(
+                [frame], output=zstd.BufferWithSegments(b"x" * 12, b"")
+            )
+
+        with self.assertRaises(TypeError):
+            dctx.multi_decompress_to_buffer([frame], output=bytearray(12))
+
+        data = bytearray(frame + b"\x00" * 12)
+        frames = zstd.BufferWithSegments(
+            data, struct.pack("=QQ", 0, len(frame))
+        )
+
+        with self.assertRaisesRegex(
+            ValueError, "item 0 overlaps the output buffer"
+        ):
+            dctx.multi_decompress_to_buffer(
+                frames, output=zstd.BufferWithSegments(data, b"")
+            )
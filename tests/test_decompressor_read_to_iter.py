@@ -232,3 +232,187 @@ class TestDecompressor_read_to_iter(unittest.TestCase):
         dctx = zstd.ZstdDecompressor(format=zstd.FORMAT_ZSTD1_MAGICLESS)
         res = b"".join(dctx.read_to_iter(frame))
         self.assertEqual(res, b"foobar")
+
+    @unittest.skipUnless(
+        "read_across_frames" in zstd.backend_features,
+        "read across frames not available",
+    )
+    def test_read_across_frames_false(self):
+        cctx = zstd.ZstdCompressor()
+        source = cctx.compress(b"foo") + cctx.compress(b"bar")
+
+        dctx = zstd.ZstdDecompressor()
+        self.assertEqual(b"".join(dctx.read_to_iter(source)), b"foo")
+        self.assertEqual(
+            b"".join(dctx.read_to_iter(io.BytesIO(source), read_size=1)),
+            b"foo",
+        )
+
+    @unittest.skipUnless(
+        "read_across_frames" in zstd.backend_features,
+        "read across frames not available",
+    )
+    def test_read_across_frames_true(self):
+        cctx = zstd.ZstdCompressor()
+        source = cctx.compress(b"foo") + cctx.compress(b"bar")
+
+        dctx = zstd.ZstdDecompressor()
+        self.assertEqual(
+            b"".join(dctx.read_to_iter(source, read_across_frames=True)),
+            b"foobar",
+        )
+        self.assertEqual(
+            b"".join(
+                dctx.read_to_iter(
+                    io.BytesIO(source), read_size=1, read_across_frames=True
+                )
+            ),
+            b"foobar",
+        )
+
+    @unittest.skipUnless(
+        "read_across_frames" in zstd.backend_features,
+        "read across frames not available",
+    )
+    def test_read_across_frames_trailing_data(self):
+        cctx = zstd.ZstdCompressor()
+        source = cctx.compress(b"foo") + cctx.compress(b"bar")
+
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(zstd.ZstdError, "Unknown frame descriptor"):
+            list(
+                dctx.read_to_iter(source + b"garbage", read_across_frames=True)
+            )
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "input ended with an incomplete frame or non-frame data",
+        ):
+            list(dctx.read_to_iter(source + b"\x28", read_across_frames=True))
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "input ended with an incomplete frame or non-frame data",
+        ):
+            list(
+                dctx.read_to_iter(
+                    io.BytesIO(source + cctx.compress(b"baz")[:-1]),
+                    read_across_frames=True,
+                )
+            )
+
+    @unittest.skipUnless(
+        "frame_boundaries" in zstd.backend_features,
+        "frame boundaries not available",
+    )
+    def test_frame_boundaries(self):
+        cctx = zstd.ZstdCompressor()
+        source = (
+            cctx.compress(b"foo") + cctx.compress(b"") + cctx.compress(b"bar")
+        )
+
+        dctx = zstd.ZstdDecompressor()
+        self.assertEqual(
+            list(
+                dctx.read_to_iter(
+                    source, read_across_frames=True, frame_boundaries=True
+                )
+            ),
+            [(b"foo", True), (b"", True), (b"bar", True)],
+        )
+
+        # Without read_across_frames, iteration ends at the first boundary.
+        self.assertEqual(
+            list(dctx.read_to_iter(source, frame_boundaries=True)),
+            [(b"foo", True)],
+        )
+
+    @unittest.skipUnless(
+        "frame_boundaries" in zstd.backend_features,
+        "frame boundaries not available",
+    )
+    def test_frame_boundaries_chunked(self):
+        cctx = zstd.ZstdCompressor()
+        records = [os.urandom(1000) for _ in range(3)]
+        source = b"".join(cctx.compress(record) for record in records)
+
+        dctx = zstd.ZstdDecompressor()
+        it = dctx.read_to_iter(
+            io.BytesIO(source),
+            read_size=7,
+            write_size=64,
+            read_across_frames=True,
+            frame_boundaries=True,
+        )
+
+        frames = []
+        current = []
+        for chunk, is_frame_end in it:
+            self.assertLessEqual(len(chunk), 64)
+            current.append(chunk)
+            if is_frame_end:
+                frames.append(b"".join(current))
+                current = []
+
+        self.assertEqual(frames, records)
+        self.assertEqual(current, [])
+
+    def test_bounded_chunks(self):
+        source = os.urandom(1048576)
+        frame = zstd.ZstdCompressor().compress(source)
+        reader = CustomBytesIO(frame)
+
+        dctx = zstd.ZstdDecompressor()
+        chunks = []
+        for chunk in dctx.read_to_iter(reader, read_size=512, write_size=8192):
+            self.assertLessEqual(len(chunk), 8192)
+            chunks.append(chunk)
+
+            # Input is consumed lazily as chunks are requested.
+            if len(chunks) == 1:
+                self.assertLess(reader.tell(), len(frame))
+
+        self.assertEqual(b"".join(chunks), source)
+
+    @unittest.skipUnless(
+        "read_to_iter_exhaustion" in zstd.backend_features,
+        "read to iter exhaustion not available",
+    )
+    def test_exhausted(self):
+        cctx = zstd.ZstdCompressor()
+        frames = cctx.compress(b"foo" * 1024) + cctx.compress(b"bar" * 1024)
+        dctx = zstd.ZstdDecompressor()
+
+        for read_across_frames in (False, True):
+            source = CustomBytesIO(frames)
+            it = dctx.read_to_iter(
+                source, read_across_frames=read_across_frames
+            )
+            b"".join(it)
+            read_count = source._read_count
+
+            # The source isn't read again once the iterator is exhausted.
+            for _ in range(2):
+                with self.assertRaises(StopIteration):
+                    next(it)
+            self.assertEqual(source._read_count, read_count)
+
+    @unittest.skipUnless(
+        "read_to_iter_exhaustion" in zstd.backend_features,
+        "read to iter exhaustion not available",
+    )
+    def test_read_error(self):
+        source = os.urandom(1048576)
+        reader = CustomBytesIO(zstd.ZstdCompressor().compress(source))
+
+        it = zstd.ZstdDecompressor().read_to_iter(reader, read_size=512)
+        self.assertTrue(next(it))
+
+        reader.read_exception = IOError("read failed")
+        with self.assertRaisesRegex(IOError, "read failed"):
+            for _ in it:
+                pass
+
+        with self.assertRaises(StopIteration):
+            next(it)
@@ -0,0 +1,173 @@
+import io
+import os
+import unittest
+
+import zstandard as zstd
+
+from .common import NonClosingBytesIO
+
+
+def make_seekable(data, max_frame_size, **kwargs):
+    dest = NonClosingBytesIO()
+    cctx = zstd.ZstdCompressor(**kwargs)
+    with cctx.seekable_stream_writer(dest, max_frame_size=max_frame_size) as writer:
+        writer.write(data)
+
+    return dest.getvalue()
+
+
+@unittest.skipUnless(
+    "seekable_stream_reader" in zstd.backend_features,
+    "seekable stream reader not available",
+)
+class TestDecompressor_seekable_stream_reader(unittest.TestCase):
+    def setUp(self):
+        self.source = b"".join(b"line %d\n" % i for i in range(10000))
+        self.compressed = make_seekable(self.source, 1024)
+
+    def test_read_all(self):
+        dctx = zstd.ZstdDecompressor()
+
+        with dctx.seekable_stream_reader(self.compressed) as reader:
+            self.assertTrue(reader.seekable())
+            self.assertEqual(reader.frame_count(), (len(self.source) + 1023) // 1024)
+            self.assertEqual(reader.read(), self.source)
+            self.assertEqual(reader.tell(), len(self.source))
+            self.assertEqual(reader.read(), b"")
+
+    def test_file_source(self):
+        dctx = zstd.ZstdDecompressor()
+        source = io.BytesIO(self.compressed)
+
+        with dctx.seekable_stream_reader(source, closefd=False) as reader:
+            reader.seek(5000)
+            self.assertEqual(reader.read(100), self.source[5000:5100])
+
+        self.assertFalse(source.closed)
+
+    def test_random_seek(self):
+        dctx = zstd.ZstdDecompressor()
+        reader = dctx.seekable_stream_reader(self.compressed)
+
+        for offset, size in ((50000, 10), (10, 2000), (1023, 2), (len(self.source) - 5, 100)):
+            self.assertEqual(reader.seek(offset), offset)
+            self.assertEqual(reader.tell(), offset)
+            self.assertEqual(reader.read(size), self.source[offset : offset + size])
+            self.assertEqual(reader.tell(), min(offset + size, len(self.source)))
+
+    def test_seek_frame_boundary(self):
+        dctx = zstd.ZstdDecompressor()
+        reader = dctx.seekable_stream_reader(self.compressed)
+
+        self.assertEqual(reader.seek(2048), 2048)
+        self.assertEqual(reader.read(1), self.source[2048:2049])
+        self.assertEqual(reader.tell(), 2049)
+
+        reader.seek(1024)
+        self.assertEqual(reader.read(1024), self.source[1024:2048])
+        self.assertEqual(reader.tell(), 2048)
+
+    def test_seek_whence(self):
+        dctx = zstd.ZstdDecompressor()
+        reader = dctx.seekable_stream_reader(self.compressed)
+
+        reader.seek(100)
+        self.assertEqual(reader.seek(50, os.SEEK_CUR), 150)
+        self.assertEqual(reader.seek(-10, os.SEEK_END), len(self.source) - 10)
+        self.assertEqual(reader.read(), self.source[-10:])
+
+        self.assertEqual(reader.seek(10, os.SEEK_END), len(self.source) + 10)
+        self.assertEqual(reader.read(10), b"")
+
+        with self.assertRaisesRegex(
+            OSError, "cannot seek to negative position"
+        ):
+            reader.seek(-1)
+
+    def test_readinto(self):
+        dctx = zstd.ZstdDecompressor()
+        reader = dctx.seekable_stream_reader(self.compressed)
+
+        reader.seek(3000)
+        b = bytearray(2000)
+        self.assertEqual(reader.readinto(b), 2000)
+        self.assertEqual(bytes(b), self.source[3000:5000])
+
+    def test_checksum(self):
+        compressed = make_seekable(self.source, 4096, write_checksum=True)
+        dctx = zstd.ZstdDecompressor()
+        reader = dctx.seekable_stream_reader(compressed)
+
+        reader.seek(10000)
+        self.assertEqual(reader.read(5000), self.source[10000:15000])
+
+    @unittest.skipUnless(
+        "require_checksum" in zstd.backend_features,
+        "require checksum not available",
+    )
+    def test_require_checksum(self):
+        dctx = zstd.ZstdDecompressor(require_checksum=True)
+
+        compressed = make_seekable(self.source, 4096, write_checksum=True)
+        with dctx.seekable_stream_reader(compressed) as reader:
+            self.assertEqual(reader.read(), self.source)
+
+        reader = dctx.seekable_stream_reader(self.compressed)
+        with self.assertRaisesRegex(zstd.ZstdError, "no content checksum"):
+            reader.read(1)
+
+    def test_empty(self):
+        compressed = make_seekable(b"", 1024)
+        dctx = zstd.ZstdDecompressor()
+        reader = dctx.seekable_stream_reader(compressed)
+
+        self.assertEqual(reader.frame_count(), 0)
+        self.assertEqual(reader.read(), b"")
+
+    def test_missing_seek_table(self):
+        dctx = zstd.ZstdDecompressor()
+        frame = zstd.ZstdCompressor().compress(b"foo" * 100)
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "lacks a valid seek table footer"
+        ):
+            dctx.seekable_stream_reader(frame)
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "lacks a valid seek table footer"
+        ):
+            dctx.seekable_stream_reader(b"foo")
+
+    def test_corrupt_seek_table(self):
+        dctx = zstd.ZstdDecompressor()
+        compressed = bytearray(self.compressed)
+        compressed[-9] ^= 0x01
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "invalid seek table header"
+        ):
+            dctx.seekable_stream_reader(bytes(compressed))
+
+    def test_frame_size_mismatch(self):
+        dctx = zstd.ZstdDecompressor()
+        compressed = bytearray(self.compressed)
+        # Most significant byte of the final entry's decompressed size.
+        compressed[-10] ^= 0x01
+
+        reader = dctx.seekable_stream_reader(bytes(compressed))
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "does not match seek table"
+        ):
+            reader.read()
+
+    def test_closed(self):
+        dctx = zstd.ZstdDecompressor()
+        reader = dctx.seekable_stream_reader(self.compressed)
+        reader.close()
+
+        with self.assertRaisesRegex(ValueError, "stream is closed"):
+            reader.read(1)
+
+        with self.assertRaisesRegex(ValueError, "stream is closed"):
+            reader.seek(0)
@@ -1,11 +1,14 @@
 import io
 import os
+import socket
+import threading
 import unittest
 
 import zstandard as zstd
 
 from .common import (
     CustomBytesIO,
+    NonSeekableReader,
 )
 
 
@@ -24,17 +27,18 @@ class TestDecompressor_stream_reader(unittest.TestCase):
         dctx = zstd.ZstdDecompressor()
 
         with dctx.stream_reader(b"foo") as reader:
-            with self.assertRaises(io.UnsupportedOperation):
-                reader.readline()
+            if "decompression_reader_readline" not in zstd.backend_features:
+                with self.assertRaises(io.UnsupportedOperation):
+                    reader.readline()
 
-            with self.assertRaises(io.UnsupportedOperation):
-                reader.readlines()
+                with self.assertRaises(io.UnsupportedOperation):
+                    reader.readlines()
 
-            with self.assertRaises(io.UnsupportedOperation):
-                iter(reader)
+                with self.assertRaises(io.UnsupportedOperation):
+                    iter(reader)
 
-            with self.assertRaises(io.UnsupportedOperation):
-                next(reader)
+                with self.assertRaises(io.UnsupportedOperation):
+                    next(reader)
 
             with self.assertRaises(io.UnsupportedOperation):
                 reader.write(b"foo")
@@ -326,6 +330,60 @@ class TestDecompressor_stream_reader(unittest.TestCase):
             reader.seek(4, os.SEEK_CUR)
             self.assertEqual(reader.read(2), b"ar")
 
+    @unittest.skipUnless(
+        "seek_unsupported_operation" in zstd.backend_features,
+        "seek unsupported operation not available",
+    )
+    def test_backwards_seek_value_error(self):
+        frame = zstd.ZstdCompressor().compress(b"foobar" * 60)
+        dctx = zstd.ZstdDecompressor()
+
+        with dctx.stream_reader(frame) as reader:
+            reader.read(6)
+
+            for pos, whence in ((0, os.SEEK_SET), (-1, os.SEEK_CUR)):
+                with self.assertRaises(ValueError):
+                    reader.seek(pos, whence)
+
+                with self.assertRaises(io.UnsupportedOperation):
+                    reader.seek(pos, whence)
+
+            self.assertEqual(reader.tell(), 6)
+
+    def test_tell_partial_reads(self):
+        source = b"foobar" * 60
+        frame = zstd.ZstdCompressor().compress(source)
+        dctx = zstd.ZstdDecompressor()
+
+        with dctx.stream_reader(frame) as reader:
+            self.assertEqual(reader.tell(), 0)
+            reader.read(5)
+            self.assertEqual(reader.tell(), 5)
+            reader.read1(7)
+            self.assertEqual(reader.tell(), 12)
+            reader.readinto(bytearray(8))
+            self.assertEqual(reader.tell(), 20)
+            self.assertEqual(reader.seek(10, os.SEEK_CUR), 30)
+            self.assertEqual(reader.tell(), 30)
+            self.assertEqual(reader.read(6), source[30:36])
+            self.assertEqual(reader.tell(), 36)
+
+    def test_seek_past_eof(self):
+        source = b"foobar" * 60
+        frame = zstd.ZstdCompressor().compress(source)
+        dctx = zstd.ZstdDecompressor()
+
+        with dctx.stream_reader(frame) as reader:
+            self.assertEqual(reader.seek(len(source) * 2), len(source))
+            self.assertEqual(reader.tell(), len(source))
+            self.assertEqual(reader.read(), b"")
+            self.assertEqual(reader.read(10), b"")
+
+        with dctx.stream_reader(frame) as reader:
+            reader.read(10)
+            self.assertEqual(reader.seek(10000, os.SEEK_CUR), len(source))
+            self.assertEqual(reader.read(1), b"")
+
     def test_no_context_manager(self):
         source = b"foobar" * 60
         cctx = zstd.ZstdCompressor()
@@ -457,6 +515,49 @@ class TestDecompressor_stream_reader(unittest.TestCase):
         reader = dctx.stream_reader(source, read_across_frames=True)
         self.assertEqual(reader.read(128), b"foobar")
 
+    @unittest.skipUnless(
+        "read_across_frames" in zstd.backend_features,
+        "read across frames not available",
+    )
+    def test_read_across_frames_trailing_data(self):
+        cctx = zstd.ZstdCompressor()
+        source = cctx.compress(b"foo") + cctx.compress(b"bar")
+        partial = cctx.compress(b"baz" * 1024)[:-1]
+
+        dctx = zstd.ZstdDecompressor()
+
+        reader = dctx.stream_reader(
+            source + b"garbage", read_across_frames=True
+        )
+        with self.assertRaisesRegex(zstd.ZstdError, "Unknown frame descriptor"):
+            reader.read()
+
+        for trailer in (b"\x28", partial):
+            reader = dctx.stream_reader(
+                source + trailer, read_across_frames=True
+            )
+            with self.assertRaisesRegex(
+                zstd.ZstdError,
+                "input ended with an incomplete frame or non-frame data",
+            ):
+                reader.read()
+
+            reader = dctx.stream_reader(
+                io.BytesIO(source + trailer), read_across_frames=True
+            )
+            self.assertEqual(reader.read(6), b"foobar")
+            with self.assertRaisesRegex(
+                zstd.ZstdError,
+                "input ended with an incomplete frame or non-frame data",
+            ):
+                while reader.read1(128):
+                    pass
+
+        # Without read_across_frames, reads stop at frame boundaries and
+        # the trailer is left for the caller to detect.
+        reader = dctx.stream_reader(source + b"\x28")
+        self.assertEqual(reader.read(128), b"foo")
+
     def test_readinto(self):
         cctx = zstd.ZstdCompressor()
         foo = cctx.compress(b"foo")
@@ -519,6 +620,34 @@ class TestDecompressor_stream_reader(unittest.TestCase):
         self.assertEqual(reader.readinto1(b), 2)
         self.assertEqual(b[:], b"fo")
 
+    def test_readinto_empty_buffer(self):
+        source = b"foobar" * 1024
+        frame = zstd.ZstdCompressor().compress(source)
+        dctx = zstd.ZstdDecompressor()
+
+        for method in ("readinto", "readinto1"):
+            compressed = io.BytesIO(frame)
+            reader = dctx.stream_reader(compressed)
+
+            readinto = getattr(reader, method)
+
+            self.assertEqual(readinto(bytearray()), 0)
+            self.assertEqual(readinto(memoryview(bytearray())), 0)
+            self.assertEqual(reader.tell(), 0)
+            self.assertEqual(compressed.tell(), 0)
+
+            self.assertEqual(reader.read(), source)
+
+    def test_buffered_reader(self):
+        source = b"foobar" * 65536
+        frame = zstd.ZstdCompressor().compress(source)
+        dctx = zstd.ZstdDecompressor()
+
+        reader = io.BufferedReader(dctx.stream_reader(frame), 8192)
+        self.assertEqual(reader.read(10), source[0:10])
+        self.assertEqual(reader.peek(1)[0:1], source[10:11])
+        self.assertEqual(reader.read(), source[10:])
+
     def test_readall(self):
         cctx = zstd.ZstdCompressor()
         foo = cctx.compress(b"foo")
@@ -590,3 +719,534 @@ class TestDecompressor_stream_reader(unittest.TestCase):
 
         self.assertEqual(len(lines), 1024)
         self.assertEqual(b"".join(lines), source)
+
+
+@unittest.skipUnless(
+    "decompression_reader_readline" in zstd.backend_features,
+    "decompression reader readline not available",
+)
+class TestDecompressor_stream_reader_readline(unittest.TestCase):
+    def setUp(self):
+        self.lines = [b"line %d\n" % i for i in range(50000)]
+        self.source = b"".join(self.lines)
+        self.frame = zstd.ZstdCompressor().compress(self.source)
+
+    def test_readline(self):
+        dctx = zstd.ZstdDecompressor()
+
+        with dctx.stream_reader(self.frame) as reader:
+            for line in self.lines:
+                self.assertEqual(reader.readline(), line)
+
+            self.assertEqual(reader.tell(), len(self.source))
+            self.assertEqual(reader.readline(), b"")
+
+    def test_final_line_without_newline(self):
+        frame = zstd.ZstdCompressor().compress(b"foo\nbar\n\nbaz")
+        dctx = zstd.ZstdDecompressor()
+
+        with dctx.stream_reader(frame) as reader:
+            self.assertEqual(reader.readline(), b"foo\n")
+            self.assertEqual(reader.readline(), b"bar\n")
+            self.assertEqual(reader.readline(), b"\n")
+            self.assertEqual(reader.readline(), b"baz")
+            self.assertEqual(reader.readline(), b"")
+
+    def test_size(self):
+        frame = zstd.ZstdCompressor().compress(b"foobar\nbaz\n")
+        dctx = zstd.ZstdDecompressor()
+
+        with dctx.stream_reader(frame) as reader:
+            self.assertEqual(reader.readline(0), b"")
+            self.assertEqual(reader.readline(4), b"foob")
+            self.assertEqual(reader.readline(10), b"ar\n")
+            self.assertEqual(reader.readline(-1), b"baz\n")
+            self.assertEqual(reader.readline(None), b"")
+
+    def test_iteration(self):
+        dctx = zstd.ZstdDecompressor()
+
+        with dctx.stream_reader(self.frame) as reader:
+            self.assertIs(iter(reader), reader)
+            self.assertEqual(list(reader), self.lines)
+
+        with self.assertRaisesRegex(ValueError, "stream is closed"):
+            iter(reader)
+
+    def test_readlines(self):
+        dctx = zstd.ZstdDecompressor()
+
+        with dctx.stream_reader(self.frame) as reader:
+            self.assertEqual(reader.readlines(10), self.lines[0:2])
+            self.assertEqual(reader.readlines(), self.lines[2:])
+
+    def test_mixed_reads(self):
+        dctx = zstd.ZstdDecompressor()
+
+        with dctx.stream_reader(self.frame) as reader:
+            self.assertEqual(reader.readline(), b"line 0\n")
+            self.assertEqual(reader.read(3), b"lin")
+            self.assertEqual(reader.readline(), b"e 1\n")
+            self.assertEqual(reader.read1(2), b"li")
+
+            b = bytearray(5)
+            self.assertEqual(reader.readinto(b), 5)
+            self.assertEqual(b, b"ne 2\n")
+
+            self.assertEqual(reader.readline(), b"line 3\n")
+            self.assertEqual(reader.tell(), 28)
+            self.assertEqual(reader.read(), self.source[28:])
+
+    def test_source_not_over_read(self):
+        dctx = zstd.ZstdDecompressor()
+
+        # Only as much input as needed to produce the first output is read.
+        expected = CustomBytesIO(self.frame)
+        with dctx.stream_reader(expected, read_size=1024) as reader:
+            reader.read1(1)
+
+        source = CustomBytesIO(self.frame)
+        with dctx.stream_reader(source, read_size=1024) as reader:
+            self.assertEqual(reader.readline(), b"line 0\n")
+            self.assertEqual(source._read_count, expected._read_count)
+            self.assertLess(source.tell(), len(self.frame))
+
+    def test_text_wrapper(self):
+        frame = zstd.ZstdCompressor().compress(b"foo\r\nbar\rbaz\n")
+        dctx = zstd.ZstdDecompressor()
+
+        with dctx.stream_reader(frame) as reader:
+            wrapper = io.TextIOWrapper(reader, encoding="utf-8")
+            self.assertEqual(list(wrapper), ["foo\n", "bar\n", "baz\n"])
+
+
+class MessageWriter(threading.Thread):
+    """Write flushed zstd messages to a pipe, one per ``advance()``.
+
+    Each message is only written after the previous one has been read, so a
+    reader waiting on more input than was sent stalls until ``timeout``.
+    """
+
+    def __init__(self, fh, messages, timeout=5.0):
+        super().__init__()
+        self.fh = fh
+        self.messages = messages
+        self.timeout = timeout
+        self.event = threading.Event()
+        self.stalled = False
+
+    def advance(self):
+        self.event.set()
+
+    def run(self):
+        with self.fh:
+            writer = zstd.ZstdCompressor().stream_writer(self.fh, closefd=False)
+
+            for i, message in enumerate(self.messages):
+                if i and not self.event.wait(self.timeout):
+                    self.stalled = True
+                self.event.clear()
+
+                writer.write(message)
+                writer.flush()
+                self.fh.flush()
+
+            writer.flush(zstd.FLUSH_FRAME)
+
+
+@unittest.skipUnless(
+    "low_latency_stream_reader" in zstd.backend_features,
+    "low latency stream reader not available",
+)
+class TestDecompressor_stream_reader_low_latency(unittest.TestCase):
+    def pipe(self, messages):
+        r, w = os.pipe()
+        writer = MessageWriter(os.fdopen(w, "wb"), messages)
+        writer.start()
+        self.addCleanup(writer.join)
+
+        source = os.fdopen(r, "rb")
+        self.addCleanup(source.close)
+
+        return source, writer
+
+    def test_returns_flushed_data(self):
+        messages = [b"foo" * 100, b"bar" * 100, b"baz"]
+        source, writer = self.pipe(messages)
+
+        dctx = zstd.ZstdDecompressor()
+        reader = dctx.stream_reader(source, low_latency=True)
+
+        for message in messages:
+            self.assertEqual(reader.read(1048576), message)
+            writer.advance()
+
+        self.assertEqual(reader.read(1048576), b"")
+        writer.join()
+        self.assertFalse(writer.stalled)
+
+    def test_small_reads(self):
+        messages = [bytes(range(256)) * 4, b"foobar"]
+        source, writer = self.pipe(messages)
+
+        reader = zstd.ZstdDecompressor().stream_reader(source, low_latency=True)
+
+        # Output zstd held back from filling earlier reads is returned
+        # without waiting for more input.
+        chunks = []
+        while len(b"".join(chunks)) < len(messages[0]):
+            chunk = reader.read(100)
+            self.assertLessEqual(len(chunk), 100)
+            chunks.append(chunk)
+
+        self.assertEqual(b"".join(chunks), messages[0])
+        writer.advance()
+
+        self.assertEqual(reader.read(), b"foobar")
+        writer.join()
+        self.assertFalse(writer.stalled)
+
+    def test_readinto(self):
+        messages = [b"foo" * 100, b"bar"]
+        source, writer = self.pipe(messages)
+
+        reader = zstd.ZstdDecompressor().stream_reader(source, low_latency=True)
+
+        buffer = bytearray(1024)
+        self.assertEqual(reader.readinto(buffer), 300)
+        self.assertEqual(buffer[:300], b"foo" * 100)
+        writer.advance()
+
+        self.assertEqual(reader.readinto(buffer), 3)
+        self.assertEqual(buffer[:3], b"bar")
+        writer.join()
+        self.assertFalse(writer.stalled)
+
+    def test_read_across_frames(self):
+        cctx = zstd.ZstdCompressor()
+        frames = cctx.compress(b"foo") + cctx.compress(b"bar")
+
+        reader = zstd.ZstdDecompressor().stream_reader(
+            io.BytesIO(frames), read_across_frames=True, low_latency=True
+        )
+        self.assertEqual(reader.read(6), b"foo")
+        self.assertEqual(reader.read(6), b"bar")
+
+        reader = zstd.ZstdDecompressor().stream_reader(
+            io.BytesIO(frames), read_across_frames=True
+        )
+        self.assertEqual(reader.read(6), b"foobar")
+
+    def test_source_types(self):
+        frame = zstd.ZstdCompressor().compress(b"foobar" * 1024)
+        dctx = zstd.ZstdDecompressor()
+
+        # Sources without read1() and buffers work too.
+        for source in (CustomBytesIO(frame), frame):
+            reader = dctx.stream_reader(source, low_latency=True)
+            self.assertEqual(reader.read(), b"foobar" * 1024)
+
+
+@unittest.skipUnless(
+    "stream_reader_stops_at_frame_end" in zstd.backend_features,
+    "stream reader stops at frame end not available",
+)
+class TestDecompressor_stream_reader_trailing_data(unittest.TestCase):
+    def setUp(self):
+        self.data = b"foobar" * 100000
+        self.frame = zstd.ZstdCompressor(write_checksum=True).compress(
+            self.data
+        )
+        self.trailer = b"trailer bytes"
+
+    def test_seekable(self):
+        dctx = zstd.ZstdDecompressor()
+
+        source = io.BytesIO(self.frame + self.trailer)
+        reader = dctx.stream_reader(source)
+        self.assertEqual(reader.read(len(self.data) + 1), self.data)
+        self.assertEqual(source.tell(), len(self.frame))
+        self.assertEqual(source.read(), self.trailer)
+
+        source = io.BytesIO(self.frame + self.trailer)
+        reader = dctx.stream_reader(source, read_size=1000)
+        chunks = []
+        while sum(map(len, chunks)) < len(self.data):
+            chunks.append(reader.read1())
+        self.assertEqual(b"".join(chunks), self.data)
+        self.assertEqual(source.read(), self.trailer)
+
+    def test_non_seekable(self):
+        dctx = zstd.ZstdDecompressor()
+
+        for read_size in (zstd.DECOMPRESSION_RECOMMENDED_INPUT_SIZE, 7):
+            source = NonSeekableReader(self.frame + self.trailer)
+            reader = dctx.stream_reader(source, read_size=read_size)
+
+            self.assertEqual(reader.read(len(self.data) + 1), self.data)
+            self.assertLessEqual(max(source.reads), read_size)
+            self.assertEqual(source.remaining(), self.trailer)
+
+        source = NonSeekableReader(self.frame + self.trailer)
+        reader = dctx.stream_reader(source)
+        b = bytearray(len(self.data) + 1)
+        self.assertEqual(reader.readinto(b), len(self.data))
+        self.assertEqual(b[:-1], self.data)
+        self.assertEqual(source.remaining(), self.trailer)
+
+    def test_multiple_frames(self):
+        cctx = zstd.ZstdCompressor()
+        source = NonSeekableReader(
+            cctx.compress(b"foo" * 1024)
+            + cctx.compress(b"bar" * 1024)
+            + self.trailer
+        )
+        reader = zstd.ZstdDecompressor().stream_reader(source)
+
+        self.assertEqual(reader.read(8192), b"foo" * 1024)
+        self.assertEqual(reader.read(8192), b"bar" * 1024)
+        self.assertEqual(source.remaining(), self.trailer)
+
+    def test_socket(self):
+        a, b = socket.socketpair()
+        with a, b:
+            a.sendall(self.frame + self.trailer)
+            a.shutdown(socket.SHUT_WR)
+
+            with b.makefile("rb", buffering=0) as fh:
+                reader = zstd.ZstdDecompressor().stream_reader(fh)
+                self.assertEqual(reader.read(len(self.data) + 1), self.data)
+                self.assertEqual(fh.read(), self.trailer)
+
+
+@unittest.skipUnless(
+    "stream_reader_max_output_size" in zstd.backend_features,
+    "stream reader max output size not available",
+)
+class TestDecompressor_stream_reader_max_output_size(unittest.TestCase):
+    def setUp(self):
+        cctx = zstd.ZstdCompressor()
+        self.frames = [
+            cctx.compress(b"foo\n" * 1024),
+            cctx.compress(b"bar\n" * 1024),
+        ]
+        self.data = b"foo\n" * 1024 + b"bar\n" * 1024
+
+    def test_within_limit(self):
+        dctx = zstd.ZstdDecompressor()
+
+        for limit in (len(self.data), len(self.data) + 1):
+            reader = dctx.stream_reader(
+                b"".join(self.frames),
+                read_across_frames=True,
+                max_output_size=limit,
+            )
+            self.assertEqual(reader.read(), self.data)
+
+    def test_across_frames(self):
+        dctx = zstd.ZstdDecompressor()
+        reader = dctx.stream_reader(
+            b"".join(self.frames),
+            read_across_frames=True,
+            max_output_size=6000,
+        )
+
+        # Output up to the limit is returned before the error.
+        self.assertEqual(reader.read(8192), self.data[:6000])
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "decompressed output exceeds max_output_size of 6000 bytes",
+        ):
+            reader.read(8192)
+
+        # The limit spans frames when reading them one at a time too.
+        reader = dctx.stream_reader(
+            b"".join(self.frames), max_output_size=6000
+        )
+        self.assertEqual(reader.read(8192), b"foo\n" * 1024)
+        with self.assertRaises(zstd.ZstdDecompressionError):
+            reader.read()
+
+    def test_stops_early(self):
+        frame = zstd.ZstdCompressor().compress(b"\x00" * (64 * 1048576))
+        source = io.BytesIO(frame)
+        reader = zstd.ZstdDecompressor().stream_reader(
+            source, read_size=256, max_output_size=1048576
+        )
+
+        with self.assertRaises(zstd.ZstdDecompressionError):
+            reader.read()
+
+        # Decompression stopped long before the end of the input.
+        self.assertLess(source.tell(), len(frame) // 2)
+
+    def test_readinto(self):
+        reader = zstd.ZstdDecompressor().stream_reader(
+            self.frames[0], max_output_size=100
+        )
+
+        b = bytearray(1024)
+        self.assertEqual(reader.readinto(b), 100)
+        self.assertEqual(b[:100], self.data[:100])
+
+        with self.assertRaises(zstd.ZstdDecompressionError):
+            reader.readinto(b)
+
+    def test_readline(self):
+        reader = zstd.ZstdDecompressor().stream_reader(
+            self.frames[0], max_output_size=10
+        )
+
+        self.assertEqual(reader.readline(), b"foo\n")
+        self.assertEqual(reader.readline(), b"foo\n")
+
+        with self.assertRaises(zstd.ZstdDecompressionError):
+            reader.readline()
+
+
+@unittest.skipUnless(
+    "max_frame_content_size" in zstd.backend_features,
+    "max frame content size not available",
+)
+class TestDecompressor_stream_reader_max_frame_content_size(unittest.TestCase):
+    def test_within_limit(self):
+        frame = zstd.ZstdCompressor().compress(b"foo" * 1000)
+        dctx = zstd.ZstdDecompressor()
+
+        for limit in (0, 3000, 3001):
+            reader = dctx.stream_reader(frame, max_frame_content_size=limit)
+            self.assertEqual(reader.read(), b"foo" * 1000)
+
+    def test_exceeds_limit(self):
+        frame = zstd.ZstdCompressor().compress(b"foo" * 1000)
+        source = CustomBytesIO(frame)
+        dctx = zstd.ZstdDecompressor()
+        reader = dctx.stream_reader(
+            source, read_size=1, max_frame_content_size=2999
+        )
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "frame declares a content size of 3000 bytes, exceeding "
+            "max_frame_content_size of 2999 bytes",
+        ):
+            reader.read()
+
+        # Only the frame header was read.
+        self.assertEqual(source.tell(), zstd.frame_header_size(frame))
+
+    def test_bogus_content_size(self):
+        # A frame header declaring 2**60 bytes, followed by an empty block.
+        frame = (
+            b"\x28\xb5\x2f\xfd\xc0\x00"
+            + (2**60).to_bytes(8, "little")
+            + b"\x01\x00\x00"
+        )
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "frame declares a content size of %d bytes" % 2**60
+        ):
+            dctx.stream_reader(frame, max_frame_content_size=2**30).read()
+
+    def test_later_frame(self):
+        cctx = zstd.ZstdCompressor()
+        data = cctx.compress(b"foo" * 100) + cctx.compress(b"bar" * 1000)
+        dctx = zstd.ZstdDecompressor()
+        reader = dctx.stream_reader(
+            data, read_across_frames=True, max_frame_content_size=1000
+        )
+
+        self.assertEqual(reader.read(300), b"foo" * 100)
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "content size of 3000 bytes"
+        ):
+            reader.read()
+
+    def test_unchecked_frames(self):
+        dest = io.BytesIO()
+        cctx = zstd.ZstdCompressor()
+        with cctx.stream_writer(dest, closefd=False) as writer:
+            writer.write(b"foo" * 1000)
+
+        # Frames without a declared content size and skippable frames are
+        # let through.
+        skippable = b"\x50\x2a\x4d\x18\x04\x00\x00\x00abcd"
+        dctx = zstd.ZstdDecompressor()
+        reader = dctx.stream_reader(
+            skippable + dest.getvalue(),
+            read_across_frames=True,
+            max_frame_content_size=1,
+        )
+        self.assertEqual(reader.read(), b"foo" * 1000)
+
+
+@unittest.skipUnless(
+    "skippable_frames" in zstd.backend_features,
+    "skippable frames not available",
+)
+class TestDecompressor_stream_reader_skippable_frame(unittest.TestCase):
+    def setUp(self):
+        cctx = zstd.ZstdCompressor()
+        self.first = b"foo" * 1024
+        self.second = b"bar" * 2048
+        self.data = (
+            cctx.compress(self.first)
+            + cctx.write_skippable_frame(b"manifest", magic_variant=3)
+            + cctx.compress(self.second)
+        )
+
+    def test_round_trip(self):
+        dctx = zstd.ZstdDecompressor()
+
+        for source in (
+            self.data,
+            io.BytesIO(self.data),
+            NonSeekableReader(self.data),
+        ):
+            reader = dctx.stream_reader(source)
+
+            self.assertIsNone(reader.read_skippable_frame())
+            self.assertEqual(reader.read(16384), self.first)
+            self.assertEqual(reader.read_skippable_frame(), (3, b"manifest"))
+            self.assertIsNone(reader.read_skippable_frame())
+            self.assertEqual(reader.read(16384), self.second)
+            self.assertIsNone(reader.read_skippable_frame())
+            self.assertEqual(reader.read(), b"")
+
+    def test_skipped_by_reads(self):
+        reader = zstd.ZstdDecompressor().stream_reader(
+            self.data, read_across_frames=True
+        )
+        self.assertEqual(reader.read(), self.first + self.second)
+
+    def test_small_reads(self):
+        source = NonSeekableReader(self.data)
+        reader = zstd.ZstdDecompressor().stream_reader(source, read_size=3)
+
+        self.assertEqual(reader.read(16384), self.first)
+        self.assertEqual(reader.read_skippable_frame(), (3, b"manifest"))
+        self.assertEqual(reader.read(16384), self.second)
+        self.assertLessEqual(max(source.reads), 8)
+
+    def test_mid_frame(self):
+        reader = zstd.ZstdDecompressor().stream_reader(self.data)
+        reader.read(10)
+
+        with self.assertRaisesRegex(
+            ValueError,
+            "cannot read a skippable frame before the current frame is fully "
+            "read",
+        ):
+            reader.read_skippable_frame()
+
+    def test_truncated(self):
+        frame = zstd.ZstdCompressor().write_skippable_frame(b"manifest")
+
+        with self.assertRaisesRegex(
+            zstd.ZstdTruncatedError,
+            "skippable frame of 16 bytes is truncated after 12 bytes",
+        ):
+            reader = zstd.ZstdDecompressor().stream_reader(frame[:-4])
+            reader.read_skippable_frame()
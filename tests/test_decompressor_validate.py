@@ -0,0 +1,132 @@
+import unittest
+
+import zstandard as zstd
+
+
+def corrupt_checksum(frame):
+    return frame[:-1] + bytes([frame[-1] ^ 0xFF])
+
+
+@unittest.skipUnless(
+    "validate" in zstd.backend_features, "validate not available"
+)
+class TestDecompressor_validate(unittest.TestCase):
+    def test_valid(self):
+        cctx = zstd.ZstdCompressor(write_checksum=True)
+        frame = cctx.compress(b"foobar" * 1024)
+
+        dctx = zstd.ZstdDecompressor()
+        self.assertIs(dctx.validate(frame), True)
+        self.assertIs(dctx.validate(bytearray(frame)), True)
+        self.assertIs(dctx.validate(memoryview(frame)), True)
+
+    def test_no_checksum(self):
+        cctx = zstd.ZstdCompressor(write_checksum=False)
+        frame = cctx.compress(b"foobar" * 1024)
+
+        self.assertTrue(zstd.ZstdDecompressor().validate(frame))
+
+    def test_large_output(self):
+        source = b"\x00" * (64 * 1048576)
+        cctx = zstd.ZstdCompressor(write_checksum=True)
+        frame = cctx.compress(source)
+
+        dctx = zstd.ZstdDecompressor()
+        self.assertTrue(dctx.validate(frame))
+        self.assertTrue(dctx.validate(b"".join(cctx.read_to_iter(source))))
+
+    def test_multiple_frames(self):
+        cctx = zstd.ZstdCompressor(write_checksum=True)
+        skippable = b"\x50\x2a\x4d\x18\x04\x00\x00\x00abcd"
+        frames = [cctx.compress(b"foo" * 100), cctx.compress(b"bar" * 200)]
+        data = frames[0] + skippable + frames[1] + cctx.compress(b"")
+
+        self.assertTrue(zstd.ZstdDecompressor().validate(data))
+
+    def test_checksum_mismatch(self):
+        cctx = zstd.ZstdCompressor(write_checksum=True)
+        frame = corrupt_checksum(cctx.compress(b"foobar" * 1024))
+
+        for verify_checksum in (True, False):
+            dctx = zstd.ZstdDecompressor(verify_checksum=verify_checksum)
+
+            with self.assertRaisesRegex(
+                zstd.ZstdDecompressionError,
+                "frame at offset 0 failed checksum verification",
+            ) as cm:
+                dctx.validate(frame)
+
+            self.assertNotIsInstance(cm.exception, zstd.ZstdTruncatedError)
+            self.assertEqual(
+                cm.exception.error_name, "Restored data doesn't match checksum"
+            )
+
+        # Other operations still honor verify_checksum.
+        dctx = zstd.ZstdDecompressor(verify_checksum=False)
+        self.assertEqual(dctx.decompress(frame), b"foobar" * 1024)
+
+    def test_truncated(self):
+        cctx = zstd.ZstdCompressor(write_checksum=True)
+        frame = cctx.compress(b"foobar" * 1024)
+        dctx = zstd.ZstdDecompressor()
+
+        for size in (4, len(frame) // 2, len(frame) - 1):
+            with self.assertRaisesRegex(
+                zstd.ZstdTruncatedError, "frame at offset 0 is truncated"
+            ):
+                dctx.validate(frame[:size])
+
+    def test_truncated_later_frame(self):
+        cctx = zstd.ZstdCompressor(write_checksum=True)
+        first = cctx.compress(b"foo" * 100)
+        second = cctx.compress(b"bar" * 200)
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(
+            zstd.ZstdTruncatedError,
+            "frame at offset %d is truncated" % len(first),
+        ):
+            dctx.validate(first + second[:-2])
+
+        with self.assertRaisesRegex(
+            zstd.ZstdDecompressionError,
+            "frame at offset %d failed checksum verification" % len(first),
+        ):
+            dctx.validate(first + corrupt_checksum(second))
+
+    def test_invalid_input(self):
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaisesRegex(
+            zstd.ZstdDecompressionError, "frame at offset 0 failed validation"
+        ) as cm:
+            dctx.validate(b"foobarbaz" * 4)
+
+        self.assertNotIsInstance(cm.exception, zstd.ZstdTruncatedError)
+        self.assertEqual(cm.exception.error_name, "Unknown frame descriptor")
+
+        with self.assertRaisesRegex(
+            zstd.ZstdDecompressionError, "cannot validate empty input"
+        ):
+            dctx.validate(b"")
+
+    def test_dictionary(self):
+        samples = [b"foo%dbar" % i * 64 for i in range(128)]
+        d = zstd.train_dictionary(8192, samples)
+        frame = zstd.ZstdCompressor(dict_data=d).compress(b"foo42bar" * 64)
+
+        self.assertTrue(zstd.ZstdDecompressor(dict_data=d).validate(frame))
+
+        with self.assertRaises(zstd.ZstdDecompressionError):
+            zstd.ZstdDecompressor().validate(frame)
+
+    def test_max_window_log(self):
+        params = zstd.ZstdCompressionParameters(window_log=24)
+        cctx = zstd.ZstdCompressor(compression_params=params)
+        frame = b"".join(cctx.read_to_iter(b"foobar" * 1048576))
+        dctx = zstd.ZstdDecompressor(max_window_log=20)
+
+        with self.assertRaisesRegex(
+            zstd.ZstdDecompressionError, "raise max_window_log"
+        ):
+            dctx.validate(frame)
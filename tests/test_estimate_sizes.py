@@ -7,3 +7,35 @@ class TestSizes(unittest.TestCase):
     def test_decompression_size(self):
         size = zstd.estimate_decompression_context_size()
         self.assertGreater(size, 90000)
+
+    @unittest.skipUnless(
+        "compress_bound" in zstd.backend_features,
+        "compress bound not available",
+    )
+    def test_compress_bound(self):
+        self.assertGreater(zstd.compress_bound(0), 0)
+        self.assertGreaterEqual(zstd.compress_bound(1024), 1024)
+        self.assertEqual(
+            zstd.compress_bound(1024),
+            zstd.ZstdCompressor().compress_bound(1024),
+        )
+
+        source = b"foobar" * 1024
+        self.assertLessEqual(
+            len(zstd.ZstdCompressor(level=1).compress(source)),
+            zstd.compress_bound(len(source)),
+        )
+
+    @unittest.skipUnless(
+        "compress_bound" in zstd.backend_features,
+        "compress bound not available",
+    )
+    def test_compress_bound_invalid(self):
+        with self.assertRaises(OverflowError):
+            zstd.compress_bound(-1)
+
+        with self.assertRaisesRegex(OverflowError, "input size is too large"):
+            zstd.compress_bound(2**64 - 1)
+
+        with self.assertRaises(TypeError):
+            zstd.compress_bound("1024")
@@ -0,0 +1,169 @@
+import io
+import os
+import unittest
+
+import zstandard as zstd
+
+
+@unittest.skipUnless(
+    "error_subclasses" in zstd.backend_features,
+    "error subclasses not available",
+)
+class TestExceptions(unittest.TestCase):
+    def setUp(self):
+        self.source = os.urandom(1024) + b"foobar" * 16384
+        self.frame = zstd.ZstdCompressor().compress(self.source)
+        self.truncated = self.frame[: len(self.frame) // 2]
+
+    def test_hierarchy(self):
+        self.assertTrue(issubclass(zstd.ZstdCompressionError, zstd.ZstdError))
+        self.assertTrue(
+            issubclass(zstd.ZstdDecompressionError, zstd.ZstdError)
+        )
+        self.assertTrue(
+            issubclass(zstd.ZstdTruncatedError, zstd.ZstdDecompressionError)
+        )
+        self.assertFalse(
+            issubclass(zstd.ZstdCompressionError, zstd.ZstdDecompressionError)
+        )
+
+    def test_compression_error(self):
+        cctx = zstd.ZstdCompressor()
+
+        with self.assertRaisesRegex(
+            zstd.ZstdCompressionError, "output buffer of 10 bytes is too small"
+        ):
+            cctx.compress_into(self.source, bytearray(10))
+
+    def test_decompression_error(self):
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaises(zstd.ZstdDecompressionError) as cm:
+            dctx.decompress(b"\x28\xb5\x2f\xfd\x00\x00\x00\x00\x00")
+
+        self.assertNotIsInstance(cm.exception, zstd.ZstdTruncatedError)
+
+    def test_output_limit_not_truncated(self):
+        frame = zstd.ZstdCompressor(write_content_size=False).compress(
+            self.source
+        )
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaises(zstd.ZstdDecompressionError) as cm:
+            dctx.decompress(frame, max_output_size=1024)
+
+        self.assertNotIsInstance(cm.exception, zstd.ZstdTruncatedError)
+
+    def test_truncated_decompress(self):
+        dctx = zstd.ZstdDecompressor()
+
+        # Messages are unchanged and the base class still catches them.
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "decompression error: did not decompress full frame"
+        ) as cm:
+            dctx.decompress(self.truncated)
+
+        self.assertIsInstance(cm.exception, zstd.ZstdTruncatedError)
+
+        with self.assertRaises(zstd.ZstdTruncatedError):
+            dctx.decompress_into(self.truncated, bytearray(len(self.source)))
+
+        with self.assertRaises(zstd.ZstdTruncatedError):
+            zstd.decompress(self.truncated)
+
+        with self.assertRaisesRegex(
+            zstd.ZstdTruncatedError, "chunk 0 did not decompress full frame"
+        ):
+            dctx.decompress_content_dict_chain([self.truncated])
+
+    def test_truncated_stream(self):
+        dctx = zstd.ZstdDecompressor()
+        source = self.frame + self.truncated
+
+        with self.assertRaisesRegex(
+            zstd.ZstdTruncatedError, "input ended with an incomplete frame"
+        ):
+            dctx.stream_reader(
+                io.BytesIO(source), read_across_frames=True
+            ).read()
+
+        with self.assertRaisesRegex(
+            zstd.ZstdTruncatedError, "input ended with an incomplete frame"
+        ):
+            list(dctx.read_to_iter(io.BytesIO(source), read_across_frames=True))
+
+    def test_error_code(self):
+        frame = bytearray(
+            zstd.ZstdCompressor(write_checksum=True).compress(self.source)
+        )
+        frame[-1] ^= 0xFF
+        dctx = zstd.ZstdDecompressor()
+
+        with self.assertRaises(zstd.ZstdDecompressionError) as cm:
+            dctx.decompress(bytes(frame))
+
+        self.assertEqual(
+            cm.exception.error_name, "Restored data doesn't match checksum"
+        )
+        # ZSTD_error_checksum_wrong as a size_t.
+        self.assertEqual(cm.exception.code, 2**64 - 22)
+        self.assertEqual(
+            str(cm.exception),
+            "decompression error: Restored data doesn't match checksum",
+        )
+
+        with self.assertRaises(zstd.ZstdDecompressionError) as cm:
+            dctx.decompressobj().decompress(bytes(frame))
+
+        self.assertEqual(cm.exception.code, 2**64 - 22)
+
+        with self.assertRaises(zstd.ZstdError) as cm:
+            zstd.get_frame_parameters(b"\x00" * 20)
+
+        self.assertEqual(cm.exception.error_name, "Unknown frame descriptor")
+        self.assertEqual(cm.exception.code, 2**64 - 10)
+
+    def test_error_code_details(self):
+        params = zstd.ZstdCompressionParameters(window_log=22)
+        frame = zstd.ZstdCompressor(compression_params=params).compress(
+            b"foobar" * 2**20
+        )
+        dctx = zstd.ZstdDecompressor(max_window_log=20)
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "frame requires a window of"
+        ) as cm:
+            dctx.decompress(frame)
+
+        self.assertEqual(
+            cm.exception.error_name,
+            "Frame requires too much memory for decoding",
+        )
+        self.assertEqual(cm.exception.code, 2**64 - 16)
+
+        with self.assertRaisesRegex(
+            zstd.ZstdCompressionError, "pledged 2 bytes but wrote 3"
+        ) as cm:
+            writer = zstd.ZstdCompressor().stream_writer(io.BytesIO(), size=2)
+            writer.write(b"foo")
+
+        self.assertEqual(cm.exception.error_name, "Src size is incorrect")
+        self.assertIsNotNone(cm.exception.code)
+
+    def test_no_error_code(self):
+        self.assertIsNone(zstd.ZstdError("foo").code)
+        self.assertIsNone(zstd.ZstdError("foo").error_name)
+
+        with self.assertRaises(zstd.ZstdTruncatedError) as cm:
+            zstd.ZstdDecompressor().decompress(self.truncated)
+
+        self.assertIsNone(cm.exception.code)
+        self.assertIsNone(cm.exception.error_name)
+
+        d = zstd.ZstdCompressionDict(
+            b"foobar" * 64, dict_type=zstd.DICT_TYPE_FULLDICT
+        )
+        with self.assertRaises(zstd.ZstdError) as cm:
+            zstd.ZstdCompressor(dict_data=d).compress(b"foo")
+
+        self.assertIsNone(cm.exception.code)
@@ -0,0 +1,107 @@
+import io
+import os
+import pathlib
+import tempfile
+import unittest
+import unittest.mock
+
+import zstandard as zstd
+
+
+class TestFileFunctions(unittest.TestCase):
+    def setUp(self):
+        self.td = tempfile.TemporaryDirectory()
+        self.addCleanup(self.td.cleanup)
+
+        self.source = b"".join(b"line %d\n" % i for i in range(20000))
+        self.src_path = os.path.join(self.td.name, "source")
+        self.zst_path = os.path.join(self.td.name, "source.zst")
+        self.out_path = os.path.join(self.td.name, "output")
+
+        with open(self.src_path, "wb") as fh:
+            fh.write(self.source)
+
+    def read(self, path):
+        with open(path, "rb") as fh:
+            return fh.read()
+
+    def test_round_trip(self):
+        r, w = zstd.compress_file(self.src_path, self.zst_path)
+
+        compressed = self.read(self.zst_path)
+        self.assertEqual(r, len(self.source))
+        self.assertEqual(w, len(compressed))
+        self.assertLess(w, r)
+
+        r, w = zstd.decompress_file(self.zst_path, self.out_path)
+
+        self.assertEqual(r, len(compressed))
+        self.assertEqual(w, len(self.source))
+        self.assertEqual(self.read(self.out_path), self.source)
+
+    def test_level(self):
+        zstd.compress_file(self.src_path, self.zst_path, level=19)
+
+        dest = io.BytesIO()
+        cctx = zstd.ZstdCompressor(level=19)
+        cctx.copy_stream(io.BytesIO(self.source), dest)
+        self.assertEqual(self.read(self.zst_path), dest.getvalue())
+
+    def test_threads(self):
+        with unittest.mock.patch.object(
+            zstd, "ZstdCompressor", wraps=zstd.ZstdCompressor
+        ) as compressor:
+            zstd.compress_file(self.src_path, self.zst_path, threads=2)
+
+        compressor.assert_called_once_with(level=3, threads=2)
+
+        zstd.decompress_file(self.zst_path, self.out_path)
+        self.assertEqual(self.read(self.out_path), self.source)
+
+    def test_multiple_frames(self):
+        cctx = zstd.ZstdCompressor()
+        with open(self.zst_path, "wb") as fh:
+            fh.write(cctx.compress(b"foo" * 100))
+            fh.write(cctx.compress(b"bar" * 100))
+
+        r, w = zstd.decompress_file(self.zst_path, self.out_path)
+        self.assertEqual(w, 600)
+        self.assertEqual(self.read(self.out_path), b"foo" * 100 + b"bar" * 100)
+
+    def test_replaces_destination(self):
+        with open(self.out_path, "wb") as fh:
+            fh.write(b"x" * 1000000)
+
+        zstd.compress_file(self.src_path, self.zst_path)
+        zstd.decompress_file(self.zst_path, self.out_path)
+        self.assertEqual(self.read(self.out_path), self.source)
+
+    def test_path_like(self):
+        r, _ = zstd.compress_file(
+            pathlib.Path(self.src_path), pathlib.Path(self.zst_path)
+        )
+        self.assertEqual(r, len(self.source))
+
+    def test_errors_close_files(self):
+        with open(self.zst_path, "wb") as fh:
+            fh.write(b"not a zstd frame")
+
+        opened = []
+        real_open = open
+
+        def tracking_open(*args, **kwargs):
+            fh = real_open(*args, **kwargs)
+            opened.append(fh)
+            return fh
+
+        with unittest.mock.patch("builtins.open", tracking_open):
+            with self.assertRaises(zstd.ZstdError):
+                zstd.decompress_file(self.zst_path, self.out_path)
+
+            with self.assertRaises(FileNotFoundError):
+                zstd.compress_file(
+                    os.path.join(self.td.name, "missing"), self.zst_path
+                )
+
+        self.assertEqual(len(opened), 2)
+        self.assertTrue(all(fh.closed for fh in opened))
@@ -0,0 +1,153 @@
+import io
+import struct
+import unittest
+
+import zstandard as zstd
+
+
+class TrickleReader:
+    """Returns at most one byte per read()."""
+
+    def __init__(self, data):
+        self.source = io.BytesIO(data)
+
+    def read(self, size):
+        return self.source.read(min(size, 1))
+
+
+@unittest.skipUnless(
+    "length_prefixed_framing" in zstd.backend_features,
+    "length prefixed framing not available",
+)
+class TestFramed(unittest.TestCase):
+    def test_round_trip(self):
+        messages = [b"foo" * 100, b"", b"bar"]
+
+        for length_bytes, fmt in ((4, "<I"), (8, "<Q")):
+            dest = io.BytesIO()
+            for message in messages:
+                start = dest.tell()
+                written = zstd.write_framed(
+                    dest, message, length_bytes=length_bytes
+                )
+                self.assertEqual(written, dest.tell() - start)
+
+            data = dest.getvalue()
+            (size,) = struct.unpack_from(fmt, data)
+            frame = data[length_bytes : length_bytes + size]
+            self.assertEqual(zstd.decompress(frame), messages[0])
+
+            source = io.BytesIO(data)
+            for message in messages:
+                self.assertEqual(
+                    zstd.read_framed(source, length_bytes=length_bytes),
+                    message,
+                )
+
+            self.assertIsNone(
+                zstd.read_framed(source, length_bytes=length_bytes)
+            )
+
+    def test_written_size(self):
+        dest = io.BytesIO()
+
+        written = zstd.write_framed(dest, b"foobar" * 10)
+        self.assertEqual(written, len(dest.getvalue()))
+        self.assertEqual(dest.getvalue()[4:], zstd.compress(b"foobar" * 10))
+
+    def test_leaves_following_data(self):
+        dest = io.BytesIO()
+        zstd.write_framed(dest, b"foo")
+        dest.write(b"trailer")
+
+        source = io.BytesIO(dest.getvalue())
+        self.assertEqual(zstd.read_framed(source), b"foo")
+        self.assertEqual(source.read(), b"trailer")
+
+    def test_short_reads(self):
+        dest = io.BytesIO()
+        zstd.write_framed(dest, b"foobar" * 100, length_bytes=8)
+
+        self.assertEqual(
+            zstd.read_framed(TrickleReader(dest.getvalue()), length_bytes=8),
+            b"foobar" * 100,
+        )
+
+    def test_level_and_dict(self):
+        samples = [b"message %d: " % i + b"foobar" * 8 for i in range(128)]
+        d = zstd.train_dictionary(8192, samples)
+        message = b"message 1000: " + b"foobar" * 8
+
+        dest = io.BytesIO()
+        zstd.write_framed(dest, message, level=19, dict=d)
+        self.assertEqual(
+            dest.getvalue()[4:],
+            zstd.ZstdCompressor(level=19, dict_data=d).compress(message),
+        )
+
+        source = io.BytesIO(dest.getvalue())
+        self.assertEqual(zstd.read_framed(source, dict=d), message)
+
+    def test_length_bytes(self):
+        for length_bytes in (0, 2, 16):
+            with self.assertRaisesRegex(
+                ValueError,
+                "length_bytes must be 4 or 8, not %d" % length_bytes,
+            ):
+                zstd.write_framed(io.BytesIO(), b"foo", length_bytes)
+
+            with self.assertRaisesRegex(ValueError, "length_bytes must be"):
+                zstd.read_framed(io.BytesIO(), length_bytes)
+
+    def test_truncated_prefix(self):
+        with self.assertRaisesRegex(
+            zstd.ZstdTruncatedError,
+            "length prefix is truncated: expected 8 bytes but read 3",
+        ):
+            zstd.read_framed(io.BytesIO(b"\x10\x00\x00"), length_bytes=8)
+
+    def test_truncated_frame(self):
+        dest = io.BytesIO()
+        zstd.write_framed(dest, b"foobar" * 10)
+        data = dest.getvalue()
+        size = len(data) - 4
+
+        with self.assertRaisesRegex(
+            zstd.ZstdTruncatedError,
+            "frame is truncated: length prefix declares %d bytes but read %d"
+            % (size, size - 1),
+        ) as cm:
+            zstd.read_framed(io.BytesIO(data[:-1]))
+
+        self.assertIsInstance(cm.exception, zstd.ZstdError)
+
+        # A prefix smaller than the frame it precedes.
+        short = struct.pack("<I", size - 1) + data[4:-1]
+        with self.assertRaisesRegex(
+            zstd.ZstdTruncatedError,
+            "length prefix declares %d bytes but the frame is longer"
+            % (size - 1),
+        ):
+            zstd.read_framed(io.BytesIO(short))
+
+    def test_prefix_mismatch(self):
+        frame = zstd.compress(b"foo")
+        data = struct.pack("<I", len(frame) * 2) + frame * 2
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "length prefix declares %d bytes but the frame is %d bytes"
+            % (len(frame) * 2, len(frame)),
+        ):
+            zstd.read_framed(io.BytesIO(data))
+
+        data = struct.pack("<I", 12) + b"garbage data"
+        with self.assertRaisesRegex(zstd.ZstdError, "frame is malformed"):
+            zstd.read_framed(io.BytesIO(data))
+
+    def test_bad_objects(self):
+        with self.assertRaisesRegex(ValueError, "write\\(\\) method"):
+            zstd.write_framed(b"", b"foo")
+
+        with self.assertRaisesRegex(ValueError, "read\\(\\) method"):
+            zstd.read_framed(b"")
@@ -0,0 +1,86 @@
+import unittest
+
+import zstandard as zstd
+
+
+SKIPPABLE = b"\x50\x2a\x4d\x18\x04\x00\x00\x00abcd"
+
+
+@unittest.skipUnless(
+    "iter_frames" in zstd.backend_features, "iter frames not available"
+)
+class TestIterFrames(unittest.TestCase):
+    def setUp(self):
+        cctx = zstd.ZstdCompressor(write_checksum=True)
+        self.sources = [b"foo" * 100, b"bar" * 200, b""]
+        self.frames = [cctx.compress(source) for source in self.sources]
+
+        # Three frames with a skippable frame between the first two.
+        self.data = self.frames[0] + SKIPPABLE + b"".join(self.frames[1:])
+
+        offset = len(self.frames[0])
+        self.offsets = [
+            0,
+            offset + len(SKIPPABLE),
+            offset + len(SKIPPABLE) + len(self.frames[1]),
+        ]
+
+    def test_frames(self):
+        frames = list(zstd.iter_frames(self.data))
+
+        self.assertEqual(
+            frames,
+            [
+                (offset, len(frame))
+                for offset, frame in zip(self.offsets, self.frames)
+            ],
+        )
+
+        # Each frame decompresses on its own.
+        dctx = zstd.ZstdDecompressor()
+        for (offset, length), source in zip(frames, self.sources):
+            frame = self.data[offset : offset + length]
+            self.assertEqual(dctx.decompress(frame), source)
+
+    def test_include_skippable(self):
+        frames = list(zstd.iter_frames(self.data, include_skippable=True))
+
+        self.assertEqual(
+            frames,
+            [
+                (0, len(self.frames[0]), False),
+                (len(self.frames[0]), len(SKIPPABLE), True),
+                (self.offsets[1], len(self.frames[1]), False),
+                (self.offsets[2], len(self.frames[2]), False),
+            ],
+        )
+
+    def test_buffer_types(self):
+        expected = list(zstd.iter_frames(self.data))
+
+        for data in (bytearray(self.data), memoryview(self.data)):
+            self.assertEqual(list(zstd.iter_frames(data)), expected)
+
+        self.assertEqual(list(zstd.iter_frames(b"")), [])
+
+    def test_truncated(self):
+        with self.assertRaisesRegex(
+            zstd.ZstdTruncatedError,
+            "frame at offset %d is truncated" % len(self.data),
+        ):
+            list(zstd.iter_frames(self.data + self.frames[0][:-1]))
+
+        with self.assertRaisesRegex(
+            zstd.ZstdTruncatedError, "frame at offset 0 is truncated"
+        ):
+            list(zstd.iter_frames(SKIPPABLE[:-1]))
+
+    def test_malformed(self):
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "frame at offset %d is malformed: Unknown frame descriptor"
+            % len(self.data),
+        ) as cm:
+            list(zstd.iter_frames(self.data + b"garbage data"))
+
+        self.assertEqual(cm.exception.error_name, "Unknown frame descriptor")
@@ -9,6 +9,33 @@ class TestModuleAttributes(unittest.TestCase):
 
         self.assertEqual(zstd.__version__, "0.25.0")
 
+    @unittest.skipUnless(
+        "compression_level_constants" in zstd.backend_features,
+        "compression level constants not available",
+    )
+    def test_runtime_version(self):
+        version = zstd.zstd_version()
+        self.assertEqual(version, "%d.%d.%d" % zstd.ZSTD_VERSION)
+
+        major, minor, release = map(int, version.split("."))
+        self.assertEqual(
+            zstd.VERSION_NUMBER, major * 10000 + minor * 100 + release
+        )
+
+    @unittest.skipUnless(
+        "has_multithread_support" in zstd.backend_features,
+        "has multithread support not available",
+    )
+    def test_has_multithread_support(self):
+        supported = zstd.has_multithread_support()
+        self.assertIsInstance(supported, bool)
+
+        # The bundled zstd is built with multithreading.
+        self.assertTrue(supported)
+
+        frame = zstd.ZstdCompressor(threads=2).compress(b"foobar" * 1024)
+        self.assertEqual(zstd.decompress(frame), b"foobar" * 1024)
+
     def test_features(self):
         self.assertIsInstance(zstd.backend_features, set)
 
@@ -22,6 +49,89 @@ class TestModuleAttributes(unittest.TestCase):
                 "buffer_types",
                 "multi_compress_to_buffer",
                 "multi_decompress_to_buffer",
+                "add_dictionary",
+                "allow_store",
+                "at_eof",
+                "block_api",
+                "buffer_dict_id",
+                "buffer_dictionary_samples",
+                "buffer_from_buffers",
+                "buffer_slicing",
+                "chunker_reset",
+                "closefd_on_failed_close",
+                "compress_bound",
+                "compress_into",
+                "compress_iter",
+                "compress_sequences",
+                "compression_level_constants",
+                "compression_reader_readinto_validation",
+                "compressor_format",
+                "compressor_reset",
+                "content_dict_chain_prefix",
+                "contiguous_buffer_inputs",
+                "copy_stream_progress",
+                "decoder_window_size",
+                "decompress_initial_size",
+                "decompress_into",
+                "decompress_stream",
+                "decompress_to_buffer",
+                "decompression_reader_readline",
+                "decompressobj_reset",
+                "decompressor_frame_content_size",
+                "dedicated_dict_search",
+                "dictionary_buffer_protocol",
+                "dictionary_equality",
+                "dictionary_merge",
+                "dictionary_training_algorithms",
+                "dictionary_training_stats",
+                "effective_compression_parameters",
+                "error_subclasses",
+                "flush_end_frame",
+                "frame_bound",
+                "frame_boundaries",
+                "frame_header_size_validation",
+                "frame_progression",
+                "from_level_validation",
+                "full_dictionary_validation",
+                "generate_sequences",
+                "growable_decompress_output",
+                "has_multithread_support",
+                "iter_frames",
+                "ldm_window_log",
+                "length_prefixed_framing",
+                "low_latency_stream_reader",
+                "max_frame_content_size",
+                "max_frames",
+                "max_window_log",
+                "multi_compress_dicts",
+                "multi_decompress_output",
+                "multi_decompress_size_validation",
+                "needs_input",
+                "next_input_hint",
+                "pledged_size_errors",
+                "precomputed_dictionary_level",
+                "prefix",
+                "read_across_frames",
+                "read_to_iter_exhaustion",
+                "require_checksum",
+                "seek_unsupported_operation",
+                "seekable_stream_reader",
+                "seekable_stream_writer",
+                "set_compression_params",
+                "set_parameter",
+                "skippable_frames",
+                "stable_buffers",
+                "strategy_validation",
+                "stream_reader_max_output_size",
+                "stream_reader_stops_at_frame_end",
+                "stream_writer_buffering",
+                "stream_writer_close_size",
+                "stream_writer_compression_params",
+                "stream_writer_hash",
+                "stream_writer_job_params",
+                "target_cblock_size",
+                "validate",
+                "verify_checksum",
             },
         }[zstd.backend]
 
@@ -31,6 +141,26 @@ class TestModuleAttributes(unittest.TestCase):
         self.assertEqual(zstd.MAX_COMPRESSION_LEVEL, 22)
         self.assertEqual(zstd.FRAME_HEADER, b"\x28\xb5\x2f\xfd")
 
+    @unittest.skipUnless(
+        "compression_level_constants" in zstd.backend_features,
+        "compression level constants not available",
+    )
+    def test_level_constants(self):
+        self.assertEqual(zstd.MIN_COMPRESSION_LEVEL, -131072)
+        self.assertEqual(zstd.DEFAULT_COMPRESSION_LEVEL, 3)
+
+        cctx = zstd.ZstdCompressor(level=zstd.MIN_COMPRESSION_LEVEL)
+        frame = cctx.compress(b"foobar" * 1024)
+        self.assertEqual(
+            zstd.ZstdDecompressor().decompress(frame), b"foobar" * 1024
+        )
+
+        cctx = zstd.ZstdCompressor(level=zstd.DEFAULT_COMPRESSION_LEVEL)
+        self.assertEqual(
+            cctx.compress(b"foobar" * 1024),
+            zstd.ZstdCompressor().compress(b"foobar" * 1024),
+        )
+
     def test_hasattr(self):
         attrs = (
             "CONTENTSIZE_UNKNOWN",
@@ -187,6 +187,33 @@ assert decompressed == data
         self.assertEqual(len(errors), 0, f"Errors occurred: {errors}")
         self.assertEqual(len(results), 10)
 
+    def test_one_shot_api_across_subinterpreters(self):
+        """Test module-level compress()/decompress() caches per interpreter."""
+        import zstandard
+
+        data = b"One-shot test data " * 100
+        frame = zstandard.compress(data)
+
+        interps = [interpreters.create() for _ in range(3)]
+        try:
+            # Alternate between interpreters on this thread so each call
+            # finds a cached context owned by another interpreter.
+            for _ in range(3):
+                for interp in interps:
+                    interp.exec(f"""
+import zstandard
+
+frame = zstandard.compress({data!r})
+assert frame == {frame!r}
+assert zstandard.decompress(frame) == {data!r}
+""")
+
+                self.assertEqual(zstandard.compress(data), frame)
+                self.assertEqual(zstandard.decompress(frame), data)
+        finally:
+            for interp in interps:
+                interp.close()
+
     def test_dictionary_in_subinterpreter(self):
         """Test compression dictionaries in a subinterpreter."""
         interp = interpreters.create()
@@ -68,12 +68,214 @@ class TestTrainDictionary(unittest.TestCase):
         self.assertIn(d.k, (50, 2000))
         self.assertEqual(d.d, 6)
 
+    @unittest.skipUnless(
+        "dictionary_training_algorithms" in zstd.backend_features,
+        "dictionary training algorithms not available",
+    )
+    def test_algorithm(self):
+        samples = generate_samples()
+        dict_size = get_optimal_dict_size_heuristically(samples)
+
+        for algorithm in ("cover", "fastcover"):
+            d = zstd.train_dictionary(
+                dict_size, samples, k=64, d=8, algorithm=algorithm
+            )
+            self.assertEqual(d.as_bytes()[0:4], b"\x37\xa4\x30\xec")
+            self.assertEqual(d.k, 64)
+            self.assertEqual(d.d, 8)
+
+        d = zstd.train_dictionary(8192, random_input_data(), algorithm="legacy")
+        self.assertEqual(d.as_bytes()[0:4], b"\x37\xa4\x30\xec")
+        self.assertEqual(d.k, 0)
+        self.assertEqual(d.d, 0)
+
+        with self.assertRaisesRegex(ValueError, "unknown algorithm: foo"):
+            zstd.train_dictionary(dict_size, samples, algorithm="foo")
+
+    @unittest.skipUnless(
+        "dictionary_training_algorithms" in zstd.backend_features,
+        "dictionary training algorithms not available",
+    )
+    def test_cover_optimize(self):
+        samples = generate_samples()
+        d = zstd.train_dictionary(
+            get_optimal_dict_size_heuristically(samples),
+            samples,
+            algorithm="cover",
+            steps=1,
+            d=8,
+        )
+
+        self.assertIn(d.k, (50, 2000))
+        self.assertEqual(d.d, 8)
+
+    @unittest.skipUnless(
+        "dictionary_training_algorithms" in zstd.backend_features,
+        "dictionary training algorithms not available",
+    )
+    def test_k_less_than_d(self):
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            r"segment size k \(8\) must be greater than dmer size d \(8\)",
+        ):
+            zstd.train_dictionary(8192, generate_samples(), k=8, d=8)
+
+
+    @unittest.skipUnless(
+        "dictionary_training_stats" in zstd.backend_features,
+        "dictionary training stats not available",
+    )
+    def test_return_stats(self):
+        samples = generate_samples()
+        dict_size = get_optimal_dict_size_heuristically(samples)
+
+        d, stats = zstd.train_dictionary(
+            dict_size, samples, k=64, d=8, return_stats=True
+        )
+        self.assertIsInstance(d, zstd.ZstdCompressionDict)
+        self.assertEqual(stats.dict_size, len(d))
+        self.assertLessEqual(stats.dict_size, dict_size)
+        self.assertEqual(stats.samples_used, len(samples))
+        self.assertEqual((stats.k, stats.d), (64, 8))
+
+        # Optimizing holds back samples after the split point for testing.
+        d, stats = zstd.train_dictionary(
+            dict_size, samples, steps=1, d=8, return_stats=True
+        )
+        self.assertEqual(stats.samples_used, int(len(samples) * 0.75))
+        self.assertEqual((stats.k, stats.d), (d.k, d.d))
+
+        d, stats = zstd.train_dictionary(
+            dict_size,
+            samples,
+            steps=1,
+            d=8,
+            split_point=0.5,
+            algorithm="cover",
+            return_stats=True,
+        )
+        self.assertEqual(stats.samples_used, len(samples) // 2)
+        self.assertEqual((stats.k, stats.d), (d.k, d.d))
+
+        d, stats = zstd.train_dictionary(
+            8192, random_input_data(), algorithm="legacy", return_stats=True
+        )
+        self.assertEqual(stats.dict_size, len(d))
+        self.assertIsNone(stats.k)
+        self.assertIsNone(stats.d)
+
+        self.assertIn("samples_used=", repr(stats))
+
+        # Without return_stats only the dictionary is returned.
+        d = zstd.train_dictionary(dict_size, samples, k=64, d=8)
+        self.assertIsInstance(d, zstd.ZstdCompressionDict)
+
+    @unittest.skipUnless(
+        "dictionary_training_stats" in zstd.backend_features,
+        "dictionary training stats not available",
+    )
+    def test_too_few_samples(self):
+        samples = [b"foobar%d" % i * 64 for i in range(4)]
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "cannot train dict: at least 5 samples are required; got 4",
+        ):
+            zstd.train_dictionary(8192, samples, k=64, d=8)
+
+        samples = [b"foobar%d" % i * 64 for i in range(6)]
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "at least 5 training samples are required; split point 0.75 "
+            "leaves 4 of 6 samples for training",
+        ):
+            zstd.train_dictionary(8192, samples)
+
+    @unittest.skipUnless(
+        "contiguous_buffer_inputs" in zstd.backend_features,
+        "contiguous buffer inputs not available",
+    )
+    def test_buffer_samples(self):
+        samples = generate_samples()
+        expected = zstd.train_dictionary(8192, samples, k=64, d=8)
+
+        for convert in (bytearray, memoryview):
+            d = zstd.train_dictionary(
+                8192, [convert(s) for s in samples], k=64, d=8
+            )
+            self.assertEqual(bytes(d.as_bytes()), bytes(expected.as_bytes()))
+
+        with self.assertRaisesRegex(
+            ValueError, "sample 1 does not support the buffer protocol"
+        ):
+            zstd.train_dictionary(8192, [b"foo", "foo"])
+
+        with self.assertRaisesRegex(ValueError, "sample 1 is not contiguous"):
+            zstd.train_dictionary(8192, [b"foo", memoryview(b"foobar")[::2]])
+
 
 class TestCompressionDict(unittest.TestCase):
     def test_bad_mode(self):
         with self.assertRaisesRegex(ValueError, "invalid dictionary load mode"):
             zstd.ZstdCompressionDict(b"foo", dict_type=42)
 
+    def test_rawcontent_round_trip(self):
+        d = zstd.ZstdCompressionDict(
+            b"dictcontent" * 64, dict_type=zstd.DICT_TYPE_RAWCONTENT
+        )
+        source = b"dictcontent" * 64 + b"extra"
+
+        frame = zstd.ZstdCompressor(dict_data=d).compress(source)
+        self.assertLess(len(frame), len(zstd.ZstdCompressor().compress(source)))
+        self.assertEqual(zstd.get_frame_parameters(frame).dict_id, 0)
+
+        dctx = zstd.ZstdDecompressor(dict_data=d)
+        self.assertEqual(dctx.decompress(frame), source)
+
+        # Content without the dictionary magic is also auto-detected as raw.
+        d = zstd.ZstdCompressionDict(b"dictcontent" * 64)
+        dctx = zstd.ZstdDecompressor(dict_data=d)
+        self.assertEqual(dctx.decompress(frame), source)
+
+    @unittest.skipUnless(
+        "full_dictionary_validation" in zstd.backend_features,
+        "full dictionary validation not available",
+    )
+    def test_fulldict_requires_magic(self):
+        d = zstd.ZstdCompressionDict(
+            b"dictcontent" * 64, dict_type=zstd.DICT_TYPE_FULLDICT
+        )
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "could not load compression dictionary: DICT_TYPE_FULLDICT "
+            "requires data beginning with the zstd dictionary magic number",
+        ):
+            zstd.ZstdCompressor(dict_data=d).compress(b"foobar")
+
+        frame = zstd.ZstdCompressor().compress(b"foobar")
+        with self.assertRaisesRegex(
+            zstd.ZstdError,
+            "could not load decompression dictionary: DICT_TYPE_FULLDICT "
+            "requires data beginning with the zstd dictionary magic number",
+        ):
+            zstd.ZstdDecompressor(dict_data=d).decompress(frame)
+
+        samples = generate_samples()
+        trained = zstd.train_dictionary(
+            get_optimal_dict_size_heuristically(samples), samples, k=64, d=8
+        )
+        d = zstd.ZstdCompressionDict(
+            trained.as_bytes(), dict_type=zstd.DICT_TYPE_FULLDICT
+        )
+        frame = zstd.ZstdCompressor(dict_data=d).compress(b"foobar" * 64)
+        self.assertEqual(
+            zstd.get_frame_parameters(frame).dict_id, trained.dict_id()
+        )
+        dctx = zstd.ZstdDecompressor(dict_data=d)
+        self.assertEqual(dctx.decompress(frame), b"foobar" * 64)
+
     def test_bad_precompute_compress(self):
         samples = generate_samples()
         d = zstd.train_dictionary(
@@ -105,3 +307,291 @@ class TestCompressionDict(unittest.TestCase):
             zstd.ZstdError, "unable to precompute dictionary"
         ):
             d.precompute_compress(level=1)
+
+    @unittest.skipUnless(
+        "dictionary_buffer_protocol" in zstd.backend_features,
+        "dictionary buffer protocol not available",
+    )
+    def test_as_bytes_memoryview(self):
+        samples = generate_samples()
+        d = zstd.train_dictionary(
+            get_optimal_dict_size_heuristically(samples), samples, k=64, d=8
+        )
+
+        view = d.as_bytes()
+        self.assertIsInstance(view, memoryview)
+        self.assertTrue(view.readonly)
+        self.assertEqual(len(view), len(d))
+        self.assertEqual(view.tobytes(), bytes(memoryview(d)))
+
+        # Round-tripping the raw bytes preserves the dictionary ID.
+        loaded = zstd.ZstdCompressionDict(view.tobytes())
+        self.assertEqual(loaded.dict_id(), d.dict_id())
+
+    def test_dict_id_precompute(self):
+        samples = generate_samples()
+        d = zstd.train_dictionary(
+            get_optimal_dict_size_heuristically(samples),
+            samples,
+            k=64,
+            d=8,
+            dict_id=42,
+        )
+
+        loaded = zstd.ZstdCompressionDict(bytes(d.as_bytes()))
+        loaded.precompute_compress(level=3)
+        self.assertEqual(loaded.dict_id(), 42)
+
+        cctx = zstd.ZstdCompressor(dict_data=loaded)
+        frame = cctx.compress(b"foobar" * 64)
+        self.assertEqual(
+            zstd.get_frame_parameters(frame).dict_id, loaded.dict_id()
+        )
+
+    def test_dict_id_rawcontent(self):
+        d = zstd.ZstdCompressionDict(
+            b"dictcontent" * 64, dict_type=zstd.DICT_TYPE_RAWCONTENT
+        )
+        d.precompute_compress(level=1)
+        self.assertEqual(d.dict_id(), 0)
+
+    @unittest.skipUnless(
+        "dictionary_equality" in zstd.backend_features,
+        "dictionary equality not available",
+    )
+    def test_repr(self):
+        samples = generate_samples()
+        d = zstd.train_dictionary(
+            get_optimal_dict_size_heuristically(samples),
+            samples,
+            k=64,
+            d=8,
+            dict_id=42,
+        )
+        self.assertEqual(
+            repr(d), "ZstdCompressionDict(dict_id=42, size=%d)" % len(d)
+        )
+
+        d = zstd.ZstdCompressionDict(
+            b"dictcontent", dict_type=zstd.DICT_TYPE_RAWCONTENT
+        )
+        self.assertEqual(repr(d), "ZstdCompressionDict(dict_id=0, size=11)")
+
+    @unittest.skipUnless(
+        "dictionary_equality" in zstd.backend_features,
+        "dictionary equality not available",
+    )
+    def test_equality(self):
+        samples = generate_samples()
+        d = zstd.train_dictionary(
+            get_optimal_dict_size_heuristically(samples), samples, k=64, d=8
+        )
+        same = zstd.ZstdCompressionDict(d.as_bytes().tobytes())
+        other = zstd.ZstdCompressionDict(b"dictcontent" * 64)
+
+        self.assertEqual(d, same)
+        self.assertEqual(hash(d), hash(same))
+        self.assertNotEqual(d, other)
+        self.assertNotEqual(d, d.as_bytes().tobytes())
+
+        # Precomputation and the declared type don't affect equality.
+        same.precompute_compress(level=3)
+        self.assertEqual(d, same)
+        self.assertEqual(
+            other,
+            zstd.ZstdCompressionDict(
+                b"dictcontent" * 64, dict_type=zstd.DICT_TYPE_RAWCONTENT
+            ),
+        )
+
+        # Dictionaries with identical content dedupe as cache keys.
+        cache = {d: "trained", other: "raw"}
+        self.assertEqual(cache[same], "trained")
+        self.assertEqual(len({d, same, other}), 2)
+
+
+def segments_buffer(samples):
+    offsets = []
+    offset = 0
+    for sample in samples:
+        offsets.append(struct.pack("=QQ", offset, len(sample)))
+        offset += len(sample)
+
+    return zstd.BufferWithSegments(b"".join(samples), b"".join(offsets))
+
+
+@unittest.skipUnless(
+    "buffer_dictionary_samples" in zstd.backend_features,
+    "buffer dictionary samples not available",
+)
+class TestTrainDictionary_buffer_with_segments(unittest.TestCase):
+    def test_matches_list(self):
+        samples = generate_samples()
+        dict_size = get_optimal_dict_size_heuristically(samples)
+
+        for algorithm in ("cover", "fastcover"):
+            expected = zstd.train_dictionary(
+                dict_size, samples, k=64, d=8, algorithm=algorithm
+            )
+            d = zstd.train_dictionary(
+                dict_size,
+                segments_buffer(samples),
+                k=64,
+                d=8,
+                algorithm=algorithm,
+            )
+
+            self.assertEqual(d.as_bytes(), expected.as_bytes())
+
+    def test_optimize_stats(self):
+        samples = generate_samples()
+
+        _, expected = zstd.train_dictionary(
+            8192, samples, steps=1, threads=1, return_stats=True
+        )
+        _, stats = zstd.train_dictionary(
+            8192,
+            segments_buffer(samples),
+            steps=1,
+            threads=1,
+            return_stats=True,
+        )
+
+        self.assertEqual(stats.samples_used, expected.samples_used)
+        self.assertEqual((stats.k, stats.d), (expected.k, expected.d))
+
+    def test_collection(self):
+        samples = generate_samples()
+        collection = zstd.BufferWithSegmentsCollection.from_buffers(samples)
+
+        # Collections are not a single buffer.
+        with self.assertRaisesRegex(
+            TypeError,
+            "samples must be a list of buffers or a BufferWithSegments",
+        ):
+            zstd.train_dictionary(8192, collection)
+
+    def test_gap(self):
+        data = b"foo" * 64 + b"bar" * 64
+        buffer = zstd.BufferWithSegments(
+            data,
+            struct.pack("=QQ", 0, 192) + struct.pack("=QQ", 196, 188),
+        )
+
+        with self.assertRaisesRegex(
+            ValueError,
+            "segment 1 starts at offset 196 but must start at 192; "
+            "segments must be contiguous and in order",
+        ):
+            zstd.train_dictionary(8192, buffer)
+
+    def test_out_of_order(self):
+        data = b"foo" * 64 + b"bar" * 64
+        buffer = zstd.BufferWithSegments(
+            data,
+            struct.pack("=QQ", 192, 192) + struct.pack("=QQ", 0, 192),
+        )
+
+        with self.assertRaisesRegex(
+            ValueError, "segment 0 starts at offset 192 but must start at 0"
+        ):
+            zstd.train_dictionary(8192, buffer)
+
+    def test_size_mismatch(self):
+        data = b"foo" * 64 + b"bar" * 64
+        buffer = zstd.BufferWithSegments(data, struct.pack("=QQ", 0, 192))
+
+        with self.assertRaisesRegex(
+            ValueError, "segment sizes total 192 bytes but buffer is 384 bytes"
+        ):
+            zstd.train_dictionary(8192, buffer)
+
+
+@unittest.skipUnless(
+    "dictionary_merge" in zstd.backend_features,
+    "dictionary merge not available",
+)
+class TestCompressionDict_merge(unittest.TestCase):
+    def setUp(self):
+        self.a = [
+            b"alpha %d: " % i + b"apple banana cherry " * (i % 5 + 1)
+            for i in range(200)
+        ]
+        self.b = [
+            b"beta %d: " % i + b"xylophone yak zebra " * (i % 5 + 1)
+            for i in range(200)
+        ]
+
+    def test_raw_content(self):
+        content_a = b"".join(self.a[:20])
+        content_b = b"".join(self.b[:20])
+        da = zstd.ZstdCompressionDict(
+            content_a, dict_type=zstd.DICT_TYPE_RAWCONTENT
+        )
+        db = zstd.ZstdCompressionDict(
+            content_b, dict_type=zstd.DICT_TYPE_RAWCONTENT
+        )
+
+        merged = zstd.ZstdCompressionDict.merge([da, db], level=3)
+        self.assertTrue(
+            bytes(merged.as_bytes()).endswith(content_a + content_b)
+        )
+        self.assertNotEqual(merged.dict_id(), 0)
+
+        # Frames using a source dictionary as a prefix reference content at
+        # the end of the dictionary, so they decompress with a merged
+        # dictionary ending with that content.
+        for source, sample, order in (
+            (db, self.b[50], [da, db]),
+            (da, self.a[50], [db, da]),
+        ):
+            frame = zstd.ZstdCompressor(dict_data=source).compress(sample)
+            merged = zstd.ZstdCompressionDict.merge(order)
+            dctx = zstd.ZstdDecompressor(dict_data=merged)
+            self.assertEqual(dctx.decompress(frame), sample)
+
+    def test_trained(self):
+        da = zstd.train_dictionary(2048, self.a)
+        db = zstd.train_dictionary(2048, self.b)
+
+        merged = zstd.ZstdCompressionDict.merge(
+            [da, db], level=3, dict_id=42, samples=self.a + self.b
+        )
+        self.assertEqual(merged.dict_id(), 42)
+        self.assertLess(len(merged), len(da) + len(db))
+
+        # The trained content follows each dictionary's header.
+        data = bytes(merged.as_bytes())
+        self.assertIn(bytes(da.as_bytes())[-256:], data)
+        self.assertTrue(data.endswith(bytes(db.as_bytes())[-256:]))
+
+        cctx = zstd.ZstdCompressor(dict_data=merged)
+        dctx = zstd.ZstdDecompressor(dict_data=merged)
+        for sample in (self.a[7], self.b[7]):
+            frame = cctx.compress(sample)
+            self.assertLess(len(frame), len(zstd.compress(sample)))
+            self.assertEqual(dctx.decompress(frame), sample)
+
+        loaded = zstd.ZstdCompressionDict(
+            data, dict_type=zstd.DICT_TYPE_FULLDICT
+        )
+        self.assertEqual(loaded.dict_id(), 42)
+
+    def test_invalid(self):
+        with self.assertRaisesRegex(
+            ValueError, "must pass at least 1 non-empty dictionary"
+        ):
+            zstd.ZstdCompressionDict.merge([])
+
+        with self.assertRaisesRegex(
+            TypeError, "dicts must only contain ZstdCompressionDict instances"
+        ):
+            zstd.ZstdCompressionDict.merge([b"foo"])
+
+        d = zstd.ZstdCompressionDict(
+            b"foobar" * 64, dict_type=zstd.DICT_TYPE_RAWCONTENT
+        )
+        with self.assertRaisesRegex(
+            TypeError, "sample 1 does not support the buffer protocol"
+        ):
+            zstd.ZstdCompressionDict.merge([d], samples=[b"foo", 42])
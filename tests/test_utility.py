@@ -1,3 +1,4 @@
+import threading
 import unittest
 
 import zstandard as zstd
@@ -13,9 +14,88 @@ class TestCompress(unittest.TestCase):
 
         zstd.compress(b"foobar" * 16384, level=7)
 
+    def test_matches_compressor(self):
+        source = b"foobar" * 16384
+
+        for level in (1, 19, 3, -5):
+            self.assertEqual(
+                zstd.compress(source, level=level),
+                zstd.ZstdCompressor(level=level).compress(source),
+            )
+
+    def test_invalid_level(self):
+        with self.assertRaisesRegex(ValueError, "level must be less than"):
+            zstd.compress(b"foobar", level=zstd.MAX_COMPRESSION_LEVEL + 1)
+
+        # The failure doesn't affect later calls.
+        self.assertEqual(
+            zstd.compress(b"foobar"), zstd.ZstdCompressor().compress(b"foobar")
+        )
+
+    def test_dict(self):
+        samples = [b"foo%dbar" % i * 64 for i in range(128)]
+        d = zstd.train_dictionary(8192, samples)
+        source = b"foo42bar" * 64
+
+        frame = zstd.compress(source, dict=d)
+        self.assertEqual(zstd.get_frame_parameters(frame).dict_id, d.dict_id())
+        self.assertEqual(
+            frame, zstd.ZstdCompressor(dict_data=d).compress(source)
+        )
+
+        # The dictionary doesn't leak into later calls.
+        self.assertEqual(
+            zstd.get_frame_parameters(zstd.compress(source)).dict_id, 0
+        )
+
+    def test_threads(self):
+        source = b"foobar" * 8192
+        expected = zstd.ZstdCompressor().compress(source)
+        results = []
+
+        def compress():
+            for _ in range(10):
+                results.append(zstd.compress(source))
+
+        threads = [threading.Thread(target=compress) for _ in range(4)]
+        for t in threads:
+            t.start()
+        for t in threads:
+            t.join()
+
+        self.assertEqual(results, [expected] * 40)
+
 
 class TestDecompress(unittest.TestCase):
     def test_simple(self):
         source = b"foobar" * 8192
         frame = zstd.compress(source)
         self.assertEqual(zstd.decompress(frame), source)
+
+    def test_max_output_size(self):
+        source = b"foobar" * 8192
+        frame = zstd.ZstdCompressor(write_content_size=False).compress(source)
+
+        with self.assertRaisesRegex(
+            zstd.ZstdError, "could not determine content size"
+        ):
+            zstd.decompress(frame)
+
+        self.assertEqual(
+            zstd.decompress(frame, max_output_size=len(source)), source
+        )
+
+    def test_dict(self):
+        samples = [b"foo%dbar" % i * 64 for i in range(128)]
+        d = zstd.train_dictionary(8192, samples)
+        source = b"foo42bar" * 64
+        frame = zstd.compress(source, dict=d)
+
+        self.assertEqual(zstd.decompress(frame, dict=d), source)
+
+        # A frame that references a dictionary fails without it, even after a
+        # call that loaded it.
+        with self.assertRaises(zstd.ZstdError):
+            zstd.decompress(frame)
+
+        self.assertEqual(zstd.decompress(zstd.compress(source)), source)
@@ -158,37 +158,87 @@ def open(
         return fh
 
 
-def compress(data: Buffer, level: int = 3) -> bytes:
+def compress(data: Buffer, level: int = 3, dict=None) -> bytes:
     """Compress source data using the zstd compression format.
 
     This performs one-shot compression using basic/default compression
     settings.
 
     This method is provided for convenience and is equivalent to calling
-    ``ZstdCompressor(level=level).compress(data)``.
+    ``ZstdCompressor(level=level, dict_data=dict).compress(data)``.
 
     If you find yourself calling this function in a tight loop,
     performance will be greater if you construct a single ``ZstdCompressor``
-    and repeatedly call ``compress()`` on it.
+    and repeatedly call ``compress()`` on it. The Rust backend avoids this
+    cost by reusing a compression context cached per thread.
     """
-    cctx = ZstdCompressor(level=level)  # type: ignore[name-defined]
+    cctx = ZstdCompressor(level=level, dict_data=dict)  # type: ignore[name-defined]
 
     return cctx.compress(data)
 
 
-def decompress(data: Buffer, max_output_size: int = 0) -> bytes:
+def decompress(data: Buffer, max_output_size: int = 0, dict=None) -> bytes:
     """Decompress a zstd frame into its original data.
 
     This performs one-shot decompression using basic/default compression
     settings.
 
     This method is provided for convenience and is equivalent to calling
-    ``ZstdDecompressor().decompress(data, max_output_size=max_output_size)``.
+    ``ZstdDecompressor(dict_data=dict).decompress(data,
+    max_output_size=max_output_size)``.
 
     If you find yourself calling this function in a tight loop, performance
     will be greater if you construct a single ``ZstdDecompressor`` and
-    repeatedly call ``decompress()`` on it.
+    repeatedly call ``decompress()`` on it. The Rust backend avoids this
+    cost by reusing a decompression context cached per thread.
     """
-    dctx = ZstdDecompressor()  # type: ignore[name-defined]
+    dctx = ZstdDecompressor(dict_data=dict)  # type: ignore[name-defined]
 
     return dctx.decompress(data, max_output_size=max_output_size)
+
+
+def compress_file(src_path, dst_path, level: int = 3, threads: int = 0):
+    """Compress the file at ``src_path`` into a new file at ``dst_path``.
+
+    This is provided for convenience and is equivalent to calling
+    ``ZstdCompressor(level=level, threads=threads).copy_stream()`` with both
+    files opened in binary mode. ``threads`` greater than 0 compresses with
+    that many worker threads. An existing file at ``dst_path`` is replaced.
+
+    Both files are closed before returning, including when an error is
+    raised. ``dst_path`` may then be left partially written.
+
+    Returns a tuple of the number of bytes read from ``src_path`` and written
+    to ``dst_path``.
+    """
+    cctx = ZstdCompressor(level=level, threads=threads)  # type: ignore[name-defined]
+
+    with builtins.open(src_path, "rb") as ifh:
+        with builtins.open(dst_path, "wb") as ofh:
+            return cctx.copy_stream(ifh, ofh)
+
+
+def decompress_file(src_path, dst_path):
+    """Decompress the zstd frames in the file at ``src_path`` into a new file.
+
+    This is provided for convenience and is equivalent to calling
+    ``ZstdDecompressor().copy_stream()`` with both files opened in binary
+    mode. Every frame in ``src_path`` is decompressed. An existing file at
+    ``dst_path`` is replaced.
+
+    Both files are closed before returning, including when an error is
+    raised. ``dst_path`` may then be left partially written.
+
+    Returns a tuple of the number of bytes read from ``src_path`` and written
+    to ``dst_path``.
+    """
+    dctx = ZstdDecompressor()  # type: ignore[name-defined]
+
+    with builtins.open(src_path, "rb") as ifh:
+        with builtins.open(dst_path, "wb") as ofh:
+            return dctx.copy_stream(ifh, ofh)
+
+
+if backend == "rust":
+    # Replace the pure Python wrappers with the cached-context versions.
+    from .backend_rust import compress, decompress  # type: ignore  # noqa: F811
@@ -5,8 +5,18 @@
 
 import io
 import os
-from collections.abc import Buffer
-from typing import Any, BinaryIO, Set, Tuple, Union
+from collections.abc import Buffer, Iterable, Iterator
+from typing import (
+    Any,
+    BinaryIO,
+    Callable,
+    Literal,
+    NamedTuple,
+    Set,
+    Tuple,
+    Union,
+    overload,
+)
 
 # Version
 __version__: str
@@ -28,6 +38,7 @@ CONTENTSIZE_ERROR: int
 CONTENTSIZE_UNKNOWN: int
 DECOMPRESSION_RECOMMENDED_INPUT_SIZE: int
 DECOMPRESSION_RECOMMENDED_OUTPUT_SIZE: int
+DEFAULT_COMPRESSION_LEVEL: int
 DICT_TYPE_AUTO: int
 DICT_TYPE_FULLDICT: int
 DICT_TYPE_RAWCONTENT: int
@@ -36,6 +47,8 @@ FLUSH_FRAME: int
 FORMAT_ZSTD1: int
 FORMAT_ZSTD1_MAGICLESS: int
 FRAME_HEADER: bytes
+FRAMEHEADERSIZE_MAX: int
+FRAMEHEADERSIZE_MIN: int
 HASHLOG_MAX: int
 HASHLOG_MIN: int
 LDM_BUCKETSIZELOG_MAX: int
@@ -45,10 +58,13 @@ MAGIC_NUMBER: int
 MAX_COMPRESSION_LEVEL: int
 MINMATCH_MAX: int
 MINMATCH_MIN: int
+MIN_COMPRESSION_LEVEL: int
 SEARCHLENGTH_MAX: int
 SEARCHLENGTH_MIN: int
 SEARCHLOG_MAX: int
 SEARCHLOG_MIN: int
+SEEKABLE_MAGIC_NUMBER: int
+SEEKABLE_MAX_FRAME_SIZE: int
 STRATEGY_BTLAZY2: int
 STRATEGY_BTOPT: int
 STRATEGY_BTULTRA: int
@@ -60,13 +76,21 @@ STRATEGY_LAZY: int
 STRATEGY_LAZY2: int
 TARGETLENGTH_MAX: int
 TARGETLENGTH_MIN: int
+VERSION_NUMBER: int
 WINDOWLOG_MAX: int
 WINDOWLOG_MIN: int
 ZSTD_VERSION: Tuple[int, int, int]
+VERSION_NUMBER: int
 
 
 # Exception
-class ZstdError(Exception): ...
+class ZstdError(Exception):
+    code: int | None
+    error_name: str | None
+
+class ZstdCompressionError(ZstdError): ...
+class ZstdDecompressionError(ZstdError): ...
+class ZstdTruncatedError(ZstdDecompressionError): ...
 
 
 # Buffer types
@@ -83,14 +107,24 @@ class BufferSegments:
 
 class BufferWithSegments:
     def __len__(self) -> int: ...
+    @overload
     def __getitem__(self, index: int) -> BufferSegment: ...
+    @overload
+    def __getitem__(self, index: slice) -> BufferWithSegments: ...
+    def __iter__(self) -> Iterator[memoryview]: ...
     def segments(self) -> BufferSegments: ...
     def tobytes(self) -> bytes: ...
+    def dict_id(self, index: int) -> int: ...
 
 
 class BufferWithSegmentsCollection:
+    @classmethod
+    def from_buffers(
+        cls, items: Iterable[Buffer]
+    ) -> BufferWithSegmentsCollection: ...
     def __len__(self) -> int: ...
     def __getitem__(self, index: int) -> BufferWithSegments: ...
+    def dict_id(self, index: int) -> int: ...
     def size(self) -> int: ...
 
 
@@ -102,8 +136,44 @@ class FrameParameters:
     has_checksum: bool
 
 
+# Frame progression
+class FrameProgression:
+    ingested: int
+    consumed: int
+    produced: int
+    flushed: int
+    current_job_id: int
+    nb_active_workers: int
+
+
 # Compression parameters
 class ZstdCompressionParameters:
+    def __init__(
+        self,
+        format: int = ...,
+        compression_level: int = ...,
+        window_log: int = ...,
+        hash_log: int = ...,
+        chain_log: int = ...,
+        search_log: int = ...,
+        min_match: int = ...,
+        target_length: int = ...,
+        strategy: int = ...,
+        write_content_size: int = ...,
+        write_checksum: int = ...,
+        write_dict_id: int = ...,
+        job_size: int = ...,
+        overlap_log: int = ...,
+        force_max_window: int = ...,
+        enable_ldm: int = ...,
+        ldm_hash_log: int = ...,
+        ldm_min_match: int = ...,
+        ldm_bucket_size_log: int = ...,
+        ldm_hash_rate_log: int = ...,
+        threads: int = ...,
+        target_cblock_size: int = ...,
+        enable_dedicated_dict_search: int = ...,
+    ) -> None: ...
     @classmethod
     def from_level(
         cls,
@@ -137,6 +207,8 @@ class ZstdCompressionParameters:
     ldm_bucket_size_log: int
     ldm_hash_rate_log: int
     threads: int
+    target_cblock_size: int
+    enable_dedicated_dict_search: int
 
 
 # Compression dictionary
@@ -148,13 +220,30 @@ class ZstdCompressionDict:
     ) -> None: ...
     
     def __len__(self) -> int: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
     def dict_id(self) -> int: ...
-    def as_bytes(self) -> bytes: ...
+    def as_bytes(self) -> Union[bytes, memoryview]: ...
     def precompute_compress(
         self,
         level: int = ...,
         compression_params: ZstdCompressionParameters = ...,
     ) -> None: ...
+    @classmethod
+    def merge(
+        cls,
+        dicts: Iterable[ZstdCompressionDict],
+        level: int = ...,
+        dict_id: int = ...,
+        samples: Iterable[Buffer] | None = ...,
+    ) -> ZstdCompressionDict: ...
+
+
+class DictionaryTrainingStats:
+    dict_size: int
+    samples_used: int
+    k: int | None
+    d: int | None
 
 
 # Compression reader
@@ -173,6 +262,8 @@ class ZstdCompressionReader(io.RawIOBase):
     def close(self) -> None: ...
     @property
     def closed(self) -> bool: ...
+    @property
+    def at_eof(self) -> bool: ...
     def flush(self) -> None: ...
     def tell(self) -> int: ...
 
@@ -185,15 +276,46 @@ class ZstdCompressionWriter(io.RawIOBase):
     def writable(self) -> bool: ...
     def seekable(self) -> bool: ...
     def write(self, data: Buffer) -> int: ...  # type: ignore[override]
-    def flush(self, flush_mode: int = ...) -> int: ...  # type: ignore[override]
+    def writelines(self, lines: Iterable[Buffer]) -> None: ...  # type: ignore[override]
+    def flush(  # type: ignore[override]
+        self, flush_mode: int = ..., end_frame: bool = ...
+    ) -> int: ...
+    def close(self) -> int: ...  # type: ignore[override]
+    @property
+    def closed(self) -> bool: ...
+    @property
+    def at_eof(self) -> bool: ...
+    def fileno(self) -> int: ...
+    def tell(self) -> int: ...
+    def frame_progression(self) -> FrameProgression: ...
+    def plaintext_digest(self) -> bytes: ...
+
+
+# Seekable compression writer
+class SeekableCompressionWriter:
+    def __enter__(self) -> SeekableCompressionWriter: ...
+    def __exit__(self, *args: Any) -> None: ...
+    def readable(self) -> bool: ...
+    def writable(self) -> bool: ...
+    def seekable(self) -> bool: ...
+    def write(self, data: Buffer) -> int: ...
+    def flush(self, flush_mode: int = ...) -> None: ...
     def close(self) -> None: ...
     @property
     def closed(self) -> bool: ...
     def fileno(self) -> int: ...
     def tell(self) -> int: ...
+    def frame_count(self) -> int: ...
+    def memory_size(self) -> int: ...
 
 
 # Compressor
+class Sequence(NamedTuple):
+    offset: int
+    match_length: int
+    lit_length: int
+    rep: int
+
 class ZstdCompressor:
     def __init__(
         self,
@@ -204,9 +326,32 @@ class ZstdCompressor:
         write_content_size: bool = ...,
         write_dict_id: bool = ...,
         threads: int = ...,
+        format: Union[int, str] = ...,
     ) -> None: ...
     
-    def compress(self, data: bytes) -> bytes: ...
+    def compress(
+        self,
+        data: bytes,
+        prefix: Buffer | None = ...,
+        allow_store: bool = ...,
+    ) -> bytes: ...
+    def reset(self, session_and_parameters: bool = ...) -> None: ...
+    def set_compression_params(
+        self, compression_params: ZstdCompressionParameters
+    ) -> None: ...
+    def set_parameter(self, param: int, value: int) -> int: ...
+    def compress_into(self, data: Buffer, output: Buffer) -> int: ...
+    def compress_block(self, data: Buffer) -> bytes: ...
+    def compress_sequences(
+        self,
+        sequences: Iterable[Tuple[int, int, int, int]],
+        src: Buffer,
+    ) -> bytes: ...
+    def generate_sequences(self, data: Buffer) -> list[Sequence]: ...
+    def compress_bound(self, input_len: int) -> int: ...
+    def write_skippable_frame(
+        self, data: Buffer, magic_variant: int = ...
+    ) -> bytes: ...
     def compressobj(self, size: int = ...) -> ZstdCompressionObj: ...
     def chunker(
         self,
@@ -220,6 +365,7 @@ class ZstdCompressor:
         size: int = ...,
         read_size: int = ...,
         write_size: int = ...,
+        progress: Callable[[int, int], None] = ...,
     ) -> Tuple[int, int]: ...
     def stream_reader(
         self,
@@ -235,7 +381,21 @@ class ZstdCompressor:
         write_size: int = ...,
         write_return_read: bool = ...,
         closefd: bool = ...,
+        min_flush_size: int = ...,
+        flush_on_close: bool = ...,
+        job_size: int = ...,
+        overlap_log: int = ...,
+        compression_params: ZstdCompressionParameters = ...,
+        hash: str | None = ...,
+        stable_buffers: bool = ...,
     ) -> ZstdCompressionWriter: ...
+    def seekable_stream_writer(
+        self,
+        writer: BinaryIO,
+        max_frame_size: int = ...,
+        write_size: int = ...,
+        closefd: bool = ...,
+    ) -> SeekableCompressionWriter: ...
     def read_to_iter(
         self,
         reader: BinaryIO,
@@ -243,12 +403,19 @@ class ZstdCompressor:
         read_size: int = ...,
         write_size: int = ...,
     ) -> ZstdCompressorIterator: ...
+    def compress_iter(
+        self,
+        chunks: Iterable[Buffer],
+        size: int = ...,
+        write_size: int = ...,
+    ) -> ZstdCompressorIterator: ...
     def frame_progression(self) -> Tuple[int, int, int]: ...
     def memory_size(self) -> int: ...
     def multi_compress_to_buffer(
         self,
         data: Any,
         threads: int = ...,
+        dicts: Any = ...,
     ) -> BufferWithSegmentsCollection: ...
 
 
@@ -256,6 +423,7 @@ class ZstdCompressor:
 class ZstdCompressionObj:
     def compress(self, data: bytes) -> bytes: ...
     def flush(self, flush_mode: int = ...) -> bytes: ...
+    def frame_progression(self) -> FrameProgression: ...
 
 
 # Compression chunker
@@ -263,6 +431,7 @@ class ZstdCompressionChunker:
     def compress(self, data: bytes) -> Any: ...
     def flush(self) -> Any: ...
     def finish(self) -> Any: ...
+    def reset(self, size: int | None = ...) -> None: ...
 
 
 # Compressor iterator
@@ -275,7 +444,10 @@ class ZstdCompressorIterator:
 class ZstdDecompressionReader(io.RawIOBase):
     def __enter__(self) -> ZstdDecompressionReader: ...
     def __exit__(self, *args: Any) -> None: ...
+    def __iter__(self) -> ZstdDecompressionReader: ...
+    def __next__(self) -> bytes: ...
     def readable(self) -> bool: ...
+    def read_skippable_frame(self) -> Tuple[int, bytes] | None: ...
     def writable(self) -> bool: ...
     def seekable(self) -> bool: ...
     def readline(self, size: int | None = ...) -> bytes: ...  # type: ignore[override]
@@ -308,13 +480,34 @@ class ZstdDecompressionWriter(io.RawIOBase):
     def tell(self) -> int: ...
 
 
+# Seekable decompression reader
+class SeekableDecompressionReader:
+    def __enter__(self) -> SeekableDecompressionReader: ...
+    def __exit__(self, *args: Any) -> None: ...
+    def readable(self) -> bool: ...
+    def writable(self) -> bool: ...
+    def seekable(self) -> bool: ...
+    def read(self, size: int = ...) -> bytes: ...
+    def readall(self) -> bytes: ...
+    def readinto(self, b: Buffer) -> int: ...
+    def seek(self, pos: int, whence: int = ...) -> int: ...
+    def tell(self) -> int: ...
+    def close(self) -> None: ...
+    @property
+    def closed(self) -> bool: ...
+    def frame_count(self) -> int: ...
+
+
 # Decompressor
 class ZstdDecompressor:
     def __init__(
         self,
         dict_data: ZstdCompressionDict = ...,
         max_window_size: int = ...,
-        format: int = ...,
+        format: Union[int, str] = ...,
+        max_window_log: int = ...,
+        verify_checksum: bool = ...,
+        require_checksum: bool = ...,
     ) -> None: ...
     
     def decompress(
@@ -323,7 +516,13 @@ class ZstdDecompressor:
         max_output_size: int = ...,
         read_across_frames: bool = ...,
         allow_extra_data: bool = ...,
+        prefix: Buffer | None = ...,
+        initial_size: int = ...,
     ) -> bytes: ...
+    def decompress_into(
+        self, data: Buffer, output: Buffer, max_output_size: int = ...
+    ) -> int: ...
+    def decompress_block(self, data: Buffer, original_size: int) -> bytes: ...
     def decompressobj(self, write_size: int = ...) -> ZstdDecompressionObj: ...
     def copy_stream(
         self,
@@ -332,13 +531,30 @@ class ZstdDecompressor:
         read_size: int = ...,
         write_size: int = ...,
     ) -> Tuple[int, int]: ...
+    def decompress_stream(
+        self,
+        source: Union[BinaryIO, Buffer],
+        dest: BinaryIO,
+        read_size: int = ...,
+        write_size: int = ...,
+        read_across_frames: bool = ...,
+    ) -> Tuple[int, int]: ...
     def stream_reader(
         self,
         source: Union[BinaryIO, bytes],
         read_size: int = ...,
         read_across_frames: bool = ...,
         closefd: bool = ...,
+        max_frames: int = ...,
+        low_latency: bool = ...,
+        max_output_size: int = ...,
+        max_frame_content_size: int = ...,
     ) -> ZstdDecompressionReader: ...
+    def seekable_stream_reader(
+        self,
+        source: Union[BinaryIO, bytes],
+        closefd: bool = ...,
+    ) -> SeekableDecompressionReader: ...
     def stream_writer(
         self,
         writer: BinaryIO,
@@ -352,40 +568,66 @@ class ZstdDecompressor:
         read_size: int = ...,
         write_size: int = ...,
         skip_bytes: int = ...,
+        read_across_frames: bool = ...,
+        frame_boundaries: bool = ...,
+        max_frames: int = ...,
     ) -> ZstdDecompressorIterator: ...
+    def frame_bound(self, data: Buffer) -> int: ...
+    def frame_content_size(self, source: Union[Buffer, BinaryIO]) -> int: ...
+    def validate(self, data: Buffer) -> bool: ...
     def memory_size(self) -> int: ...
+    def set_parameter(self, param: int, value: int) -> int: ...
+    def add_dictionary(self, dict: ZstdCompressionDict) -> None: ...
     def multi_decompress_to_buffer(
         self,
         frames: Any,
         decompressed_sizes: Any = ...,
         threads: int = ...,
+        output: BufferWithSegments | None = ...,
     ) -> BufferWithSegmentsCollection: ...
+    def decompress_to_buffer(
+        self,
+        data: Buffer,
+        decompressed_sizes: Any = ...,
+        max_output_size: int = ...,
+    ) -> BufferWithSegments: ...
 
 
 # Decompression object (for decompressobj interface)
 class ZstdDecompressionObj:
     def decompress(self, data: bytes) -> bytes: ...
     def flush(self, length: int = ...) -> bytes: ...
+    def reset(self) -> None: ...
+    def next_input_hint(self) -> int: ...
     @property
     def unused_data(self) -> bytes: ...
     @property
     def unconsumed_tail(self) -> bytes: ...
     @property
     def eof(self) -> bool: ...
+    @property
+    def needs_input(self) -> bool: ...
 
 
 # Decompressor iterator
 class ZstdDecompressorIterator:
     def __iter__(self) -> ZstdDecompressorIterator: ...
-    def __next__(self) -> bytes: ...
+    def __next__(self) -> bytes | tuple[bytes, bool]: ...
 
 
 # Module-level functions
-def compress(data: bytes, level: int = ...) -> bytes: ...
-def decompress(data: bytes, max_output_size: int = ...) -> bytes: ...
+def compress(
+    data: bytes, level: int = ..., dict: ZstdCompressionDict | None = ...
+) -> bytes: ...
+def decompress(
+    data: bytes,
+    max_output_size: int = ...,
+    dict: ZstdCompressionDict | None = ...,
+) -> bytes: ...
+@overload
 def train_dictionary(
     dict_size: int,
-    samples: list[bytes],
+    samples: Union[list[bytes], BufferWithSegments],
     k: int = ...,
     d: int = ...,
     f: int = ...,
@@ -396,10 +638,54 @@ def train_dictionary(
     level: int = ...,
     steps: int = ...,
     threads: int = ...,
+    algorithm: str = ...,
+    return_stats: Literal[False] = ...,
 ) -> ZstdCompressionDict: ...
-def frame_header_size(data: bytes) -> int: ...
+@overload
+def train_dictionary(
+    dict_size: int,
+    samples: Union[list[bytes], BufferWithSegments],
+    k: int = ...,
+    d: int = ...,
+    f: int = ...,
+    split_point: float = ...,
+    accel: int = ...,
+    notifications: int = ...,
+    dict_id: int = ...,
+    level: int = ...,
+    steps: int = ...,
+    threads: int = ...,
+    algorithm: str = ...,
+    *,
+    return_stats: Literal[True],
+) -> Tuple[ZstdCompressionDict, DictionaryTrainingStats]: ...
+def frame_header_size(data: Buffer) -> int: ...
 def frame_content_size(data: bytes) -> int: ...
 def get_frame_parameters(data: bytes) -> FrameParameters: ...
+@overload
+def iter_frames(
+    data: Buffer, include_skippable: Literal[False] = ...
+) -> Iterator[Tuple[int, int]]: ...
+@overload
+def iter_frames(
+    data: Buffer, include_skippable: Literal[True]
+) -> Iterator[Tuple[int, int, bool]]: ...
+def compress_bound(size: int) -> int: ...
+def write_framed(
+    dest: BinaryIO,
+    data: Buffer,
+    length_bytes: int = ...,
+    level: int = ...,
+    dict: ZstdCompressionDict | None = ...,
+) -> int: ...
+def read_framed(
+    source: BinaryIO,
+    length_bytes: int = ...,
+    max_output_size: int = ...,
+    dict: ZstdCompressionDict | None = ...,
+) -> bytes | None: ...
+def zstd_version() -> str: ...
+def has_multithread_support() -> bool: ...
 def estimate_decompression_context_size() -> int: ...
 def open(
     filename: Union[str, bytes, os.PathLike[Any], BinaryIO],
@@ -411,3 +697,13 @@ def open(
     newline: str = ...,
     closefd: bool = ...,
 ) -> Union[ZstdCompressionReader, ZstdDecompressionReader, ZstdCompressionWriter, io.TextIOWrapper]: ...
+def compress_file(
+    src_path: Union[str, bytes, os.PathLike[Any]],
+    dst_path: Union[str, bytes, os.PathLike[Any]],
+    level: int = ...,
+    threads: int = ...,
+) -> Tuple[int, int]: ...
+def decompress_file(
+    src_path: Union[str, bytes, os.PathLike[Any]],
+    dst_path: Union[str, bytes, os.PathLike[Any]],
+) -> Tuple[int, int]: ...
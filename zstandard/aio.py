@@ -0,0 +1,310 @@
+# Copyright (c) 2017-present, Gregory Szorc
+# All rights reserved.
+#
+# This software may be modified and distributed under the terms
+# of the BSD license. See the LICENSE file for details.
+
+"""asyncio adapters for streaming (de)compression.
+
+The adapters in this module wrap :py:meth:`ZstdCompressor.stream_writer` and
+:py:meth:`ZstdDecompressor.stream_reader` so they can be driven from
+coroutines. Compression and decompression run in an executor, where the zstd
+calls release the GIL and so do not stall the event loop. I/O against the
+wrapped stream is performed on the event loop.
+
+Each adapter accepts an ``executor`` argument, which is passed to
+``loop.run_in_executor()``. The default of ``None`` uses the event loop's
+default executor. A custom ``concurrent.futures.Executor`` may be injected to
+bound or isolate the threads used for (de)compression. The executor must run
+work on threads other than the event loop's: the decompression reader blocks
+its worker while the event loop reads from the source.
+"""
+
+import asyncio
+import inspect
+import io
+
+from . import FLUSH_BLOCK, ZstdCompressor, ZstdDecompressor
+
+__all__ = ["AsyncCompressionWriter", "AsyncDecompressionReader"]
+
+
+async def _maybe_await(value):
+    if inspect.isawaitable(value):
+        return await value
+
+    return value
+
+
+class AsyncCompressionWriter:
+    """Compress data written from coroutines to an asynchronous stream.
+
+    Data passed to :py:meth:`write` is compressed in ``executor`` by a
+    :py:class:`ZstdCompressionWriter` and the produced compressed chunks are
+    then written to ``writer`` on the event loop.
+
+    ``writer`` must have a ``write(data)`` method. If it returns an awaitable,
+    it is awaited. If ``writer`` has a ``drain()`` method, as
+    ``asyncio.StreamWriter`` does, it is awaited after each write.
+
+    Instances are async context managers. Exiting the context ends the zstd
+    frame and closes the adapter.
+
+    :param writer:
+       Stream receiving compressed data.
+    :param cctx:
+       ``ZstdCompressor`` to use for compression. If not specified, the
+       default ``ZstdCompressor`` is used.
+    :param executor:
+       ``concurrent.futures.Executor`` in which compression runs. ``None``
+       uses the event loop's default executor.
+    :param size:
+       Size in bytes of data that will be written, if known. Written to the
+       frame header.
+    :param write_size:
+       Size in bytes of chunks of compressed data buffered before being sent
+       to ``writer``.
+    :param closefd:
+       Whether to close ``writer`` when this adapter is closed. An awaitable
+       returned by ``writer.close()`` is awaited, as is ``wait_closed()``
+       if present.
+    """
+
+    def __init__(
+        self,
+        writer,
+        cctx=None,
+        executor=None,
+        size=None,
+        write_size=None,
+        closefd=True,
+    ):
+        cctx = cctx or ZstdCompressor()
+
+        kwargs = {"closefd": False}
+        if size is not None:
+            kwargs["size"] = size
+        if write_size is not None:
+            kwargs["write_size"] = write_size
+
+        self._writer = writer
+        self._executor = executor
+        self._closefd = closefd
+        self._sink = io.BytesIO()
+        self._compressor = cctx.stream_writer(self._sink, **kwargs)
+        self._lock = asyncio.Lock()
+        self._closed = False
+
+    async def __aenter__(self):
+        if self._closed:
+            raise ValueError("stream is closed")
+
+        return self
+
+    async def __aexit__(self, exc_type, exc_value, exc_tb):
+        await self.close()
+
+        return False
+
+    @property
+    def closed(self):
+        return self._closed
+
+    async def _run(self, fn, *args):
+        """Run ``fn`` in the executor then send the output it produced."""
+        if self._closed:
+            raise ValueError("stream is closed")
+
+        loop = asyncio.get_running_loop()
+        result = await loop.run_in_executor(self._executor, fn, *args)
+
+        data = self._sink.getvalue()
+        if data:
+            self._sink.seek(0)
+            self._sink.truncate()
+
+            await _maybe_await(self._writer.write(data))
+
+            drain = getattr(self._writer, "drain", None)
+            if drain is not None:
+                await drain()
+
+        return result
+
+    async def write(self, data):
+        """Compress ``data`` and send any compressed output.
+
+        Returns the number of bytes of ``data`` consumed.
+        """
+        data = memoryview(data)
+
+        async with self._lock:
+            await self._run(self._compressor.write, data)
+
+        return data.nbytes
+
+    async def flush(self, flush_mode=FLUSH_BLOCK):
+        """Flush compressed data to ``writer``.
+
+        Accepts the same ``flush_mode`` values as
+        :py:meth:`ZstdCompressionWriter.flush`.
+        """
+        async with self._lock:
+            await self._run(self._compressor.flush, flush_mode)
+
+    async def close(self):
+        """End the zstd frame, send it, and close the adapter.
+
+        Calling this on a closed adapter does nothing.
+        """
+        async with self._lock:
+            if self._closed:
+                return
+
+            try:
+                await self._run(self._compressor.close)
+            finally:
+                self._closed = True
+
+            if self._closefd:
+                close = getattr(self._writer, "close", None)
+                if close is not None:
+                    await _maybe_await(close())
+
+                wait_closed = getattr(self._writer, "wait_closed", None)
+                if wait_closed is not None:
+                    await wait_closed()
+
+    def tell(self):
+        """Return the number of compressed bytes produced so far."""
+        return self._compressor.tell()
+
+
+class _SourceBridge:
+    """File-like object reading an asynchronous stream from a worker thread.
+
+    ``read()`` is called by :py:class:`ZstdDecompressionReader` from the
+    executor. It schedules the read on the event loop and blocks until it
+    completes.
+    """
+
+    def __init__(self, reader):
+        self._reader = reader
+        self.loop = None
+
+    async def _read(self, size):
+        return await _maybe_await(self._reader.read(size))
+
+    def read(self, size=-1):
+        future = asyncio.run_coroutine_threadsafe(self._read(size), self.loop)
+
+        return future.result()
+
+
+class AsyncDecompressionReader:
+    """Decompress data read by coroutines from an asynchronous stream.
+
+    :py:meth:`read` runs a :py:class:`ZstdDecompressionReader` in
+    ``executor``. When it needs more input, the worker thread schedules a read
+    of ``reader`` on the event loop and waits for it.
+
+    ``reader`` must have a ``read(size)`` method. If it returns an awaitable,
+    as with ``asyncio.StreamReader``, it is awaited. An empty result signals
+    the end of input.
+
+    Instances are async context managers. Exiting the context closes the
+    adapter.
+
+    :param reader:
+       Stream providing compressed data.
+    :param dctx:
+       ``ZstdDecompressor`` to use for decompression. If not specified, the
+       default ``ZstdDecompressor`` is used.
+    :param executor:
+       ``concurrent.futures.Executor`` in which decompression runs. ``None``
+       uses the event loop's default executor. Work must not run on the event
+       loop's thread, or reads from ``reader`` would deadlock.
+    :param read_size:
+       Size in bytes of reads from ``reader``.
+    :param read_across_frames:
+       Whether to continue reading past the end of the first zstd frame.
+    :param closefd:
+       Whether to close ``reader`` when this adapter is closed. An awaitable
+       returned by ``reader.close()`` is awaited.
+    """
+
+    def __init__(
+        self,
+        reader,
+        dctx=None,
+        executor=None,
+        read_size=None,
+        read_across_frames=False,
+        closefd=True,
+    ):
+        dctx = dctx or ZstdDecompressor()
+
+        kwargs = {"read_across_frames": read_across_frames, "closefd": False}
+        if read_size is not None:
+            kwargs["read_size"] = read_size
+
+        self._reader = reader
+        self._executor = executor
+        self._closefd = closefd
+        self._source = _SourceBridge(reader)
+        self._decompressor = dctx.stream_reader(self._source, **kwargs)
+        self._lock = asyncio.Lock()
+        self._closed = False
+
+    async def __aenter__(self):
+        if self._closed:
+            raise ValueError("stream is closed")
+
+        return self
+
+    async def __aexit__(self, exc_type, exc_value, exc_tb):
+        await self.close()
+
+        return False
+
+    @property
+    def closed(self):
+        return self._closed
+
+    async def read(self, size=-1):
+        """Read up to ``size`` bytes of decompressed data.
+
+        A negative ``size`` reads until the end of input. Returns an empty
+        ``bytes`` once input is exhausted.
+        """
+        async with self._lock:
+            if self._closed:
+                raise ValueError("stream is closed")
+
+            loop = asyncio.get_running_loop()
+            self._source.loop = loop
+
+            return await loop.run_in_executor(
+                self._executor, self._decompressor.read, size
+            )
+
+    async def close(self):
+        """Close the adapter.
+
+        Calling this on a closed adapter does nothing.
+        """
+        async with self._lock:
+            if self._closed:
+                return
+
+            self._closed = True
+            self._decompressor.close()
+
+            if self._closefd:
+                close = getattr(self._reader, "close", None)
+                if close is not None:
+                    await _maybe_await(close())
+
+    def tell(self):
+        """Return the number of decompressed bytes read so far."""
+        return self._decompressor.tell()